@@ -13,6 +13,15 @@ chrome.storage.sync.get(['enabled', 'interceptAll'], (result) => {
   interceptAll = result.interceptAll === true;
 });
 
+// Token copied from ACCELARA Settings > Browser Extension
+// Kept in local storage so it stays on this device rather than syncing with the browser profile,
+// and read on every request since the service worker can wake before any startup load finishes
+function getToken() {
+  return new Promise((resolve) => {
+    chrome.storage.local.get(['apiToken'], (result) => resolve(result.apiToken || ''));
+  });
+}
+
 // Listen for download events
 chrome.downloads.onCreated.addListener((downloadItem) => {
   if (!isEnabled) return;
@@ -88,21 +97,31 @@ function sendToAccelara(data) {
 
 // Send download request via HTTP (fallback)
 function sendToAccelaraHTTP(data) {
-  fetch(`http://${ACCELARA_HOST}:${ACCELARA_PORT}/download`, {
+  getToken().then(token => fetch(`http://${ACCELARA_HOST}:${ACCELARA_PORT}/download`, {
     method: 'POST',
     headers: {
-      'Content-Type': 'application/json'
+      'Content-Type': 'application/json',
+      'Authorization': `Bearer ${token}`
     },
     body: JSON.stringify(data)
+  })).then(response => {
+    if (response.status === 401) {
+      notify('ACCELARA rejected the request. Copy the token from ACCELARA Settings > Browser Extension into the extension popup.');
+    } else if (!response.ok) {
+      console.error('ACCELARA returned an error:', response.status);
+    }
   }).catch(error => {
     console.error('Failed to send to ACCELARA:', error);
-    // Show notification
-    chrome.notifications.create({
-      type: 'basic',
-      iconUrl: 'icon48.png',
-      title: 'ACCELARA',
-      message: 'Failed to connect to ACCELARA. Make sure the app is running.'
-    });
+    notify('Failed to connect to ACCELARA. Make sure the app is running.');
+  });
+}
+
+function notify(message) {
+  chrome.notifications.create({
+    type: 'basic',
+    iconUrl: 'icon48.png',
+    title: 'ACCELARA',
+    message: message
   });
 }
 
@@ -116,8 +135,13 @@ chrome.runtime.onMessage.addListener((request, sender, sendResponse) => {
     interceptAll = request.value;
     chrome.storage.sync.set({ interceptAll: interceptAll });
     sendResponse({ success: true });
+  } else if (request.action === 'setToken') {
+    chrome.storage.local.set({ apiToken: (request.value || '').trim() });
+    sendResponse({ success: true });
   } else if (request.action === 'getStatus') {
-    sendResponse({ enabled: isEnabled, interceptAll: interceptAll });
+    getToken().then(token => {
+      sendResponse({ enabled: isEnabled, interceptAll: interceptAll, hasToken: token !== '' });
+    });
   } else if (request.action === 'sendDownload') {
     sendToAccelara(request.data);
     sendResponse({ success: true });
@@ -4,12 +4,15 @@ document.addEventListener('DOMContentLoaded', () => {
   const toggleEnabled = document.getElementById('toggleEnabled');
   const toggleInterceptAll = document.getElementById('toggleInterceptAll');
   const status = document.getElementById('status');
+  const tokenInput = document.getElementById('tokenInput');
+  let hasToken = false;
   
   // Load current status
   browser.runtime.sendMessage({ action: 'getStatus' }).then((response) => {
     if (response) {
       updateToggle(toggleEnabled, response.enabled);
       updateToggle(toggleInterceptAll, response.interceptAll);
+      hasToken = response.hasToken;
       updateStatus(response.enabled);
     }
   }).catch(() => {});
@@ -41,6 +44,21 @@ document.addEventListener('DOMContentLoaded', () => {
     }).catch(() => {});
   });
   
+  // Save the token when it's pasted or entered
+  tokenInput.addEventListener('change', () => {
+    browser.runtime.sendMessage({ action: 'setToken', value: tokenInput.value }).then((result) => {
+      if (result && result.success) {
+        hasToken = tokenInput.value.trim() !== '';
+        tokenInput.value = '';
+        return browser.runtime.sendMessage({ action: 'getStatus' }).then((response) => {
+          if (response) {
+            updateStatus(response.enabled);
+          }
+        });
+      }
+    }).catch(() => {});
+  });
+  
   function updateToggle(element, active) {
     if (active) {
       element.classList.add('active');
@@ -50,7 +68,11 @@ document.addEventListener('DOMContentLoaded', () => {
   }
   
   function updateStatus(enabled) {
-    if (enabled) {
+    tokenInput.placeholder = hasToken ? 'Token saved - paste to replace' : 'Paste token from ACCELARA';
+    if (!hasToken) {
+      status.textContent = '✗ Token not set - downloads will be rejected';
+      status.style.color = '#E53935';
+    } else if (enabled) {
       status.textContent = '✓ Interception enabled';
       status.style.color = '#4CAF50';
     } else {
@@ -0,0 +1,50 @@
+package main
+
+import (
+	"errors"
+	"net"
+	"net/http"
+	"net/url"
+	"strings"
+	"syscall"
+
+	"github.com/accelara/clidm/internal/downloader"
+)
+
+// Exit codes the app turns into actionable errors (see src-tauri/src/wrapper_exit.rs)
+const (
+	exitFailure  = 1
+	exitNetwork  = 2
+	exitDiskFull = 3
+	exitAuth     = 4
+	exitChecksum = 5
+	// An unknown flag or bad usage: sysexits' EX_USAGE, clear of the codes above
+	// (the flag package's own exit code is 2, which would read as a network error)
+	exitUsage = 64
+)
+
+// exitCode picks the exit code for a failed download
+func exitCode(err error) int {
+	message := strings.ToLower(err.Error())
+	var statusErr *downloader.HTTPStatusError
+	var urlErr *url.Error
+	var netErr net.Error
+
+	switch {
+	case errors.Is(err, downloader.ErrChecksumMismatch):
+		return exitChecksum
+	case errors.Is(err, syscall.ENOSPC),
+		strings.Contains(message, "no space left on device"),
+		strings.Contains(message, "not enough space on the disk"):
+		return exitDiskFull
+	case errors.As(err, &statusErr) && (statusErr.StatusCode == http.StatusUnauthorized ||
+		statusErr.StatusCode == http.StatusForbidden ||
+		statusErr.StatusCode == http.StatusProxyAuthRequired):
+		return exitAuth
+	case errors.As(err, &urlErr), errors.As(err, &netErr),
+		strings.HasPrefix(message, "connection lost"),
+		strings.HasPrefix(message, "download paused"):
+		return exitNetwork
+	}
+	return exitFailure
+}
@@ -10,27 +10,25 @@ import (
 	"path/filepath"
 	"regexp"
 	"strings"
-)
 
-func getHTTPInfo() {
-	// Note: flags are already parsed in main(), so we need to get the source from command line args
-	// or use a different approach. Let's use os.Args directly.
-	args := os.Args[1:]
-	source := ""
-	for i, arg := range args {
-		if arg == "--source" && i+1 < len(args) {
-			source = args[i+1]
-			break
-		}
-	}
+	"github.com/accelara/clidm/internal/downloader"
+)
 
+// getHTTPInfo probes source and prints its file name, size and range support
+// Request headers and credentials in opts are sent with every probe.
+func getHTTPInfo(source string, opts downloader.Options) {
 	if source == "" {
 		fmt.Fprintf(os.Stderr, "Error: source is required\n")
 		os.Exit(1)
 	}
 
+	transport := &http.Transport{Proxy: http.ProxyFromEnvironment}
+	if tlsConfig, err := downloader.TLSConfig(opts); err == nil && tlsConfig != nil {
+		transport.TLSClientConfig = tlsConfig
+	}
 	client := &http.Client{
-		Timeout: 15 * 1000000000, // 15 seconds
+		Transport: transport,
+		Timeout:   15 * 1000000000, // 15 seconds
 		CheckRedirect: func(req *http.Request, via []*http.Request) error {
 			// Follow redirects
 			return nil
@@ -43,6 +41,7 @@ func getHTTPInfo() {
 		fmt.Fprintf(os.Stderr, "Error: failed to create request: %s\n", err)
 		os.Exit(1)
 	}
+	downloader.ApplyRequestOptions(req, opts)
 
 	resp, err := client.Do(req)
 	if err != nil {
@@ -63,6 +62,7 @@ func getHTTPInfo() {
 		// Make a GET request to parse the HTML
 		getReq, err := http.NewRequest("GET", source, nil)
 		if err == nil {
+			downloader.ApplyRequestOptions(getReq, opts)
 			getResp, err := client.Do(getReq)
 			if err == nil {
 				defer getResp.Body.Close()
@@ -93,6 +93,7 @@ func getHTTPInfo() {
 							}
 							// Retry with the found URL
 							req, _ = http.NewRequest("HEAD", source, nil)
+							downloader.ApplyRequestOptions(req, opts)
 							resp, err = client.Do(req)
 							if err == nil {
 								break
@@ -16,17 +16,9 @@ import (
 	"github.com/anacrolix/torrent/storage"
 )
 
-func inspectTorrent() {
-	// Note: flags are already parsed in main(), so we need to get the source from command line args
-	args := os.Args[1:]
-	source := ""
-	for i, arg := range args {
-		if arg == "--source" && i+1 < len(args) {
-			source = args[i+1]
-			break
-		}
-	}
-
+// inspectTorrent prints the name, files, info hash and trackers of a torrent
+// With exportPath set, the torrent's metainfo is also written there as a .torrent file.
+func inspectTorrent(source, exportPath string) {
 	if source == "" {
 		fmt.Fprintf(os.Stderr, "Error: source is required\n")
 		os.Exit(1)
@@ -38,7 +30,7 @@ func inspectTorrent() {
 	// Load torrent from different sources
 	if strings.HasPrefix(source, "magnet:") {
 		// For magnet links, we need to download metadata first
-		result, err := inspectMagnetLink(source)
+		result, err := inspectMagnetLink(source, exportPath)
 		if err != nil {
 			fmt.Fprintf(os.Stderr, "Error: failed to inspect magnet link: %s\n", err)
 			os.Exit(1)
@@ -107,11 +99,20 @@ func inspectTorrent() {
 		})
 	}
 
+	if exportPath != "" {
+		if err := writeMetainfo(mi, exportPath); err != nil {
+			fmt.Fprintf(os.Stderr, "Error: %s\n", err)
+			os.Exit(1)
+		}
+	}
+
 	result := map[string]interface{}{
-		"name":      info.Name,
-		"totalSize": totalSize,
-		"fileCount": len(files),
-		"files":     files,
+		"name":         info.Name,
+		"totalSize":    totalSize,
+		"fileCount":    len(files),
+		"files":        files,
+		"infoHash":     mi.HashInfoBytes().HexString(),
+		"announceList": announceURLs(mi),
 	}
 
 	data, err := json.Marshal(result)
@@ -122,8 +123,38 @@ func inspectTorrent() {
 	fmt.Println(string(data))
 }
 
+// announceURLs flattens a torrent's announce tiers into one list without duplicates
+func announceURLs(mi *metainfo.MetaInfo) []string {
+	urls := []string{}
+	seen := make(map[string]bool)
+	for _, tier := range mi.UpvertedAnnounceList() {
+		for _, url := range tier {
+			if url != "" && !seen[url] {
+				seen[url] = true
+				urls = append(urls, url)
+			}
+		}
+	}
+	return urls
+}
+
+// writeMetainfo saves mi as a .torrent file at path
+func writeMetainfo(mi *metainfo.MetaInfo, path string) error {
+	file, err := os.Create(path)
+	if err != nil {
+		return fmt.Errorf("failed to create %s: %w", path, err)
+	}
+	if err := mi.Write(file); err != nil {
+		file.Close()
+		os.Remove(path)
+		return fmt.Errorf("failed to write torrent file: %w", err)
+	}
+	return file.Close()
+}
+
 // inspectMagnetLink downloads metadata from a magnet link and returns torrent info
-func inspectMagnetLink(magnetURL string) (map[string]interface{}, error) {
+// With exportPath set, the fetched metainfo is also written there as a .torrent file.
+func inspectMagnetLink(magnetURL, exportPath string) (map[string]interface{}, error) {
 	// Add panic recovery to prevent crashes
 	defer func() {
 		if r := recover(); r != nil {
@@ -256,11 +287,22 @@ func inspectMagnetLink(magnetURL string) (map[string]interface{}, error) {
 		})
 	}
 	
+	// The swarm's metainfo, with the magnet's trackers as its announce list
+	mi := t.Metainfo()
+	if exportPath != "" {
+		if err := writeMetainfo(&mi, exportPath); err != nil {
+			t.Drop()
+			return nil, err
+		}
+	}
+	
 	result := map[string]interface{}{
-		"name":      info.Name,
-		"totalSize": totalSize,
-		"fileCount": len(files),
-		"files":     files,
+		"name":         info.Name,
+		"totalSize":    totalSize,
+		"fileCount":    len(files),
+		"files":        files,
+		"infoHash":     t.InfoHash().HexString(),
+		"announceList": announceURLs(&mi),
 	}
 	
 	// Remove torrent from client before returning (cleanup)
@@ -8,6 +8,8 @@ import (
 	"os"
 	"os/signal"
 	"path/filepath"
+	"strconv"
+	"strings"
 	"syscall"
 	"time"
 
@@ -39,7 +41,26 @@ func (sr *StatusReporter) Report(status map[string]interface{}) {
 	fmt.Println(string(data))
 }
 
+// parseIndices parses a comma-separated list of file indices such as "0,2,5"
+func parseIndices(list string) ([]int, error) {
+	var indices []int
+	for _, part := range strings.Split(list, ",") {
+		part = strings.TrimSpace(part)
+		if part == "" {
+			continue
+		}
+		index, err := strconv.Atoi(part)
+		if err != nil || index < 0 {
+			return nil, fmt.Errorf("%q is not a file index", part)
+		}
+		indices = append(indices, index)
+	}
+	return indices, nil
+}
+
 func main() {
+	// Parse errors are reported with exitUsage rather than the flag package's exit code 2
+	flag.CommandLine.Init(os.Args[0], flag.ContinueOnError)
 	var (
 		source         = flag.String("source", "", "Source URL or torrent")
 		output         = flag.String("output", "", "Output path")
@@ -51,17 +72,36 @@ func main() {
 		btSequential   = flag.Bool("bt-sequential", false, "Sequential mode")
 		btKeepSeeding  = flag.Bool("bt-keep-seeding", false, "Keep seeding after download completes")
 		btPort         = flag.Int("bt-port", 0, "BitTorrent listen port (0 = use default/auto)")
+		maxPeers       = flag.Int("max-peers", 0, "Max established peer connections for this torrent (0 = default)")
+		btSelectFiles  = flag.String("bt-select-files", "", "Comma-separated indices of the torrent files to download (default all)")
 		connectTimeout = flag.Int("connect-timeout", 15, "Connect timeout")
 		readTimeout    = flag.Int("read-timeout", 60, "Read timeout")
 		retries        = flag.Int("retries", 5, "Retries")
 		sha256         = flag.String("sha256", "", "SHA256 hash")
+		auth           = flag.String("auth", "", "Authorization header value (Basic ... / Bearer ...)")
+		caCert         = flag.String("ca-cert", "", "PEM file of extra CA certificates to trust")
+		insecure       = flag.Bool("insecure", false, "Skip TLS certificate verification")
+		ifRange        = flag.String("if-range", "", "ETag or Last-Modified the existing parts were fetched against")
 		inspect        = flag.Bool("inspect", false, "Inspect torrent/metadata only")
+		exportTorrent  = flag.String("export-torrent", "", "With --inspect, also save the torrent's metainfo to this path")
 		httpInfo       = flag.Bool("http-info", false, "Get HTTP file info only")
+		assembleOnly   = flag.Bool("assemble-only", false, "Merge the part files of a stopped download into the output, without downloading")
 		speedTest      = flag.Bool("speedtest", false, "Run speed test")
 		testType       = flag.String("test-type", "full", "Speed test type: full, latency, download, upload")
 	)
+	var headers []string
+	flag.Func("header", "Extra request header \"Name: value\" (repeatable)", func(value string) error {
+		headers = append(headers, value)
+		return nil
+	})
 
-	flag.Parse()
+	if err := flag.CommandLine.Parse(os.Args[1:]); err != nil {
+		// The flag package has already printed the error and usage
+		if err == flag.ErrHelp {
+			os.Exit(0)
+		}
+		os.Exit(exitUsage)
+	}
 
 	// Handle speed test mode
 	if *speedTest {
@@ -73,19 +113,25 @@ func main() {
 
 	// Handle inspect mode
 	if *inspect {
-		inspectTorrent()
+		inspectTorrent(*source, *exportTorrent)
 		return
 	}
 
+	requestOpts := downloader.Options{Headers: headers, Auth: *auth, CACert: *caCert, Insecure: *insecure}
+	if _, err := downloader.TLSConfig(requestOpts); err != nil {
+		fmt.Fprintf(os.Stderr, "Error: %v\n", err)
+		os.Exit(1)
+	}
+
 	// Handle HTTP info mode
 	if *httpInfo {
-		getHTTPInfo()
+		getHTTPInfo(*source, requestOpts)
 		return
 	}
 
 	if *source == "" || *output == "" || *downloadID == "" {
 		fmt.Fprintf(os.Stderr, "Error: source, output, and download-id are required\n")
-		os.Exit(1)
+		os.Exit(exitUsage)
 	}
 
 	reporter := &StatusReporter{downloadID: *downloadID}
@@ -100,8 +146,23 @@ func main() {
 		btUploadLimitBytes, _ = utils.ParseBytes(*btUploadLimit)
 	}
 
+	selectFiles, err := parseIndices(*btSelectFiles)
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "Error: invalid --bt-select-files: %v\n", err)
+		os.Exit(exitUsage)
+	}
+
 	absOutPath, _ := filepath.Abs(*output)
 
+	// Handle assemble-only mode: the parts are already on disk
+	if *assembleOnly {
+		if err := downloader.AssembleParts(absOutPath, downloader.Options{Quiet: true, StatusReporter: reporter}); err != nil {
+			fmt.Fprintf(os.Stderr, "Error: %v\n", err)
+			os.Exit(1)
+		}
+		return
+	}
+
 	// Set up signal handling for graceful shutdown
 	ctx, cancel := context.WithCancel(context.Background())
 	defer cancel()
@@ -114,10 +175,17 @@ func main() {
 		ConnectTimeout: *connectTimeout,
 		ReadTimeout:    *readTimeout,
 		SHA256:         *sha256,
+		Headers:        headers,
+		Auth:           *auth,
+		CACert:         *caCert,
+		Insecure:       *insecure,
+		IfRange:        *ifRange,
 		BTUploadLimit:  btUploadLimitBytes,
 		BTSequential:   *btSequential,
 		BTKeepSeeding:  *btKeepSeeding,
 		BTPort:         *btPort,
+		BTMaxPeers:     *maxPeers,
+		BTSelectFiles:  selectFiles,
 		Quiet:          true,
 		StatusReporter: reporter,
 		DownloadID:     *downloadID,
@@ -156,7 +224,7 @@ func main() {
 					"status":  "error",
 					"message": err.Error(),
 				})
-				os.Exit(1)
+				os.Exit(exitCode(err))
 			}
 		case <-ctx.Done():
 			// Context cancelled - shutdown signal received
@@ -203,7 +271,7 @@ func main() {
 				})
 				// Also write to stderr for visibility in dev mode
 				fmt.Fprintf(os.Stderr, "HTTP download error: %v\n", err)
-				os.Exit(1)
+				os.Exit(exitCode(err))
 			}
 			// Success - report completion
 			reporter.Report(map[string]interface{}{
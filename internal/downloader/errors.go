@@ -0,0 +1,19 @@
+package downloader
+
+import (
+	"errors"
+	"fmt"
+)
+
+// ErrChecksumMismatch is returned when a finished file doesn't match the expected SHA256
+var ErrChecksumMismatch = errors.New("SHA256 mismatch")
+
+// HTTPStatusError is a response status the download can't continue from
+type HTTPStatusError struct {
+	StatusCode int
+	Status     string
+}
+
+func (e *HTTPStatusError) Error() string {
+	return fmt.Sprintf("unexpected HTTP status: %d %s", e.StatusCode, e.Status)
+}
@@ -5,11 +5,13 @@ import (
 	"encoding/hex"
 	"fmt"
 	"io"
+	"math/rand"
 	"net/http"
 	"net/url"
 	"os"
 	"path/filepath"
 	"runtime"
+	"sort"
 	"strconv"
 	"strings"
 	"sync"
@@ -28,6 +30,9 @@ type HTTPDownloader struct {
 	connectTimeout time.Duration
 	readTimeout    time.Duration
 	sha256         string
+	headers        []string
+	auth           string
+	ifRange        string
 	quiet          bool
 	reporter       StatusReporter
 	downloadID     string // For state persistence
@@ -75,6 +80,10 @@ func NewHTTPDownloader(sourceURL, outPath string, opts Options) *HTTPDownloader
 			transport.Proxy = http.ProxyURL(proxyURL)
 		}
 	}
+	// main validates the CA certificate up front, so an error can't happen here
+	if tlsConfig, err := TLSConfig(opts); err == nil && tlsConfig != nil {
+		transport.TLSClientConfig = tlsConfig
+	}
 
 	// Set client timeout to a very long value (or 0 for no timeout)
 	// We manage read deadlines manually per chunk, so we don't want the client
@@ -109,6 +118,9 @@ func NewHTTPDownloader(sourceURL, outPath string, opts Options) *HTTPDownloader
 		connectTimeout:      time.Duration(opts.ConnectTimeout) * time.Second,
 		readTimeout:         time.Duration(opts.ReadTimeout) * time.Second,
 		sha256:              opts.SHA256,
+		headers:             opts.Headers,
+		auth:                opts.Auth,
+		ifRange:             opts.IfRange,
 		quiet:               opts.Quiet,
 		reporter:            opts.StatusReporter,
 		downloadID:          opts.DownloadID,
@@ -126,9 +138,19 @@ func NewHTTPDownloader(sourceURL, outPath string, opts Options) *HTTPDownloader
 	return downloader
 }
 
+// newRequest builds a request for the source URL carrying the user's headers and credentials
+func (d *HTTPDownloader) newRequest(method string) (*http.Request, error) {
+	req, err := http.NewRequest(method, d.sourceURL, nil)
+	if err != nil {
+		return nil, err
+	}
+	ApplyRequestOptions(req, Options{Headers: d.headers, Auth: d.auth})
+	return req, nil
+}
+
 // resolveRedirects follows redirects and updates sourceURL to the final URL
 func (d *HTTPDownloader) resolveRedirects() error {
-	req, err := http.NewRequest("HEAD", d.sourceURL, nil)
+	req, err := d.newRequest("HEAD")
 	if err != nil {
 		return err
 	}
@@ -221,13 +243,15 @@ func (d *HTTPDownloader) handleConnectionFailure(err error) error {
 	if backoff > 30*time.Second {
 		backoff = 30 * time.Second
 	}
+	// Up to 25% jitter, so downloads that lost the same mirror don't all retry at once
+	backoff += time.Duration(rand.Int63n(int64(backoff / 4)))
 	
 	// Report retrying status
 	if d.reporter != nil {
 		d.reporter.Report(map[string]interface{}{
 			"type":    "http",
 			"status":  "downloading",
-			"message": fmt.Sprintf("Connection lost, retrying in %v... (attempt %d/%d)", backoff, failures, d.maxConnectionFailures),
+			"message": fmt.Sprintf("Connection lost, retrying in %v... (attempt %d/%d)", backoff.Round(100*time.Millisecond), failures, d.maxConnectionFailures),
 		})
 	}
 	
@@ -379,7 +403,7 @@ func (d *HTTPDownloader) Download() error {
 }
 
 func (d *HTTPDownloader) probe() error {
-	req, err := http.NewRequest("HEAD", d.sourceURL, nil)
+	req, err := d.newRequest("HEAD")
 	if err != nil {
 		return err
 	}
@@ -400,7 +424,7 @@ func (d *HTTPDownloader) probe() error {
 	// Check response status
 	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
 		// HEAD might not be supported, try GET with Range header instead
-		req, _ := http.NewRequest("GET", d.sourceURL, nil)
+		req, _ := d.newRequest("GET")
 		req.Header.Set("Range", "bytes=0-0")
 		resp2, err := d.client.Do(req)
 		if err != nil {
@@ -416,7 +440,7 @@ func (d *HTTPDownloader) probe() error {
 		}
 		
 		if resp2.StatusCode < 200 || resp2.StatusCode >= 300 {
-			return fmt.Errorf("unexpected HTTP status: %d %s", resp2.StatusCode, resp2.Status)
+			return &HTTPStatusError{StatusCode: resp2.StatusCode, Status: resp2.Status}
 		}
 		
 		// Use response from GET request
@@ -433,7 +457,7 @@ func (d *HTTPDownloader) probe() error {
 
 	// If still no size, try a small range request to get Content-Length
 	if d.totalSize == 0 {
-		req, _ := http.NewRequest("GET", d.sourceURL, nil)
+		req, _ := d.newRequest("GET")
 		req.Header.Set("Range", "bytes=0-0")
 		resp, err := d.client.Do(req)
 		if err == nil {
@@ -508,7 +532,7 @@ func (d *HTTPDownloader) downloadSingle() error {
 	}
 	defer file.Close()
 
-	req, _ := http.NewRequest("GET", d.sourceURL, nil)
+	req, _ := d.newRequest("GET")
 	resp, err := d.client.Do(req)
 	if err != nil {
 		// Handle connection errors with retry logic
@@ -528,7 +552,7 @@ func (d *HTTPDownloader) downloadSingle() error {
 
 	// Check response status
 	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
-		return fmt.Errorf("unexpected HTTP status: %d %s", resp.StatusCode, resp.Status)
+		return &HTTPStatusError{StatusCode: resp.StatusCode, Status: resp.Status}
 	}
 
 	// Update totalSize from response Content-Length if available and not already set
@@ -1117,8 +1141,12 @@ func (d *HTTPDownloader) downloadChunk(idx int, c chunk) error {
 	}
 	defer file.Close()
 
-	req, _ := http.NewRequest("GET", d.sourceURL, nil)
+	req, _ := d.newRequest("GET")
 	req.Header.Set("Range", fmt.Sprintf("bytes=%d-%d", start, c.end))
+	if d.ifRange != "" {
+		// A file that changed since the parts on disk were fetched comes back whole instead of as a range
+		req.Header.Set("If-Range", d.ifRange)
+	}
 
 	resp, err := d.client.Do(req)
 	if err != nil {
@@ -1157,6 +1185,15 @@ func (d *HTTPDownloader) downloadChunk(idx int, c chunk) error {
 		return fmt.Errorf("server rejected request (status %d): multi-connection may not be allowed", resp.StatusCode)
 	}
 	
+	// With If-Range a 200 means the parts on disk belong to an older version of the file;
+	// the single-connection fallback throws them away and starts over
+	if resp.StatusCode == http.StatusOK && d.ifRange != "" {
+		d.multiConnectionMutex.Lock()
+		d.multiConnectionFailed = true
+		d.multiConnectionMutex.Unlock()
+		return fmt.Errorf("file changed on the server (If-Range did not match): range requests may not be supported for the existing parts")
+	}
+	
 	// If we requested a range but got 200 OK instead of 206 Partial Content, server may not support ranges
 	if resp.StatusCode == http.StatusOK && start > c.start {
 		// We requested a range but got full content - server doesn't support ranges properly
@@ -1411,6 +1448,68 @@ func (d *HTTPDownloader) downloadChunk(idx int, c chunk) error {
 	return nil
 }
 
+// AssembleParts merges the part files a stopped segmented download left in its temp
+// directory into outPath, without contacting the server
+func AssembleParts(outPath string, opts Options) error {
+	fileName := filepath.Base(outPath)
+	d := &HTTPDownloader{
+		outPath:  outPath,
+		tempDir:  filepath.Join(filepath.Dir(outPath), fmt.Sprintf(".accelara-temp-%s", fileName)),
+		quiet:    opts.Quiet,
+		reporter: opts.StatusReporter,
+	}
+	chunks, err := partChunks(d.tempDir, fileName)
+	if err != nil {
+		return err
+	}
+	d.chunks = chunks
+	d.totalSize = chunks[len(chunks)-1].end + 1
+	return d.assemble()
+}
+
+// partChunks lists the chunks whose part files are in tempDir, in order
+// The parts have to cover the file from byte 0 without gaps or overlaps.
+func partChunks(tempDir, fileName string) ([]chunk, error) {
+	entries, err := os.ReadDir(tempDir)
+	if err != nil {
+		return nil, fmt.Errorf("failed to read temp directory: %w", err)
+	}
+	prefix := fileName + ".part."
+	var chunks []chunk
+	for _, entry := range entries {
+		name := entry.Name()
+		if entry.IsDir() || !strings.HasPrefix(name, prefix) {
+			continue
+		}
+		bounds := strings.Split(strings.TrimPrefix(name, prefix), ".")
+		if len(bounds) != 2 {
+			continue
+		}
+		start, startErr := strconv.ParseInt(bounds[0], 10, 64)
+		end, endErr := strconv.ParseInt(bounds[1], 10, 64)
+		if startErr != nil || endErr != nil || end < start {
+			continue
+		}
+		chunks = append(chunks, chunk{start: start, end: end})
+	}
+	if len(chunks) == 0 {
+		return nil, fmt.Errorf("no part files found in %s", tempDir)
+	}
+
+	sort.Slice(chunks, func(i, j int) bool { return chunks[i].start < chunks[j].start })
+	next := int64(0)
+	for _, c := range chunks {
+		if c.start > next {
+			return nil, fmt.Errorf("missing bytes %d-%d: the parts don't cover the whole file", next, c.start-1)
+		}
+		if c.start < next {
+			return nil, fmt.Errorf("overlapping parts at byte %d", c.start)
+		}
+		next = c.end + 1
+	}
+	return chunks, nil
+}
+
 func (d *HTTPDownloader) assemble() error {
 	if d.reporter != nil {
 		d.reporter.Report(map[string]interface{}{
@@ -1580,7 +1679,7 @@ func (d *HTTPDownloader) verifySHA256() error {
 	computed := hex.EncodeToString(hash.Sum(nil))
 
 	if strings.ToLower(computed) != strings.ToLower(d.sha256) {
-		return fmt.Errorf("SHA256 mismatch: expected %s, got %s", d.sha256, computed)
+		return fmt.Errorf("%w: expected %s, got %s", ErrChecksumMismatch, d.sha256, computed)
 	}
 
 	return nil
@@ -21,6 +21,13 @@ type Options struct {
 	BTSequential   bool
 	BTKeepSeeding  bool
 	BTPort         int // BitTorrent listen port (0 = use default/auto)
+	BTMaxPeers     int // Established peer connections for this torrent (0 = library default)
+	BTSelectFiles  []int // Indices of the torrent's files to download (empty = all)
+	Headers        []string // Extra request headers, each "Name: value"
+	Auth           string   // Authorization header value (Basic ... / Bearer ...)
+	CACert         string   // PEM file of extra CA certificates to trust
+	Insecure       bool     // Skip TLS certificate verification
+	IfRange        string   // ETag or Last-Modified the parts on disk were fetched against
 	Quiet          bool
 	StatusReporter StatusReporter
 	DownloadID     string // For state persistence
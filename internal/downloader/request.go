@@ -0,0 +1,51 @@
+package downloader
+
+import (
+	"crypto/tls"
+	"crypto/x509"
+	"fmt"
+	"net/http"
+	"os"
+	"strings"
+)
+
+// ApplyRequestOptions sets the user-supplied headers and credentials on req
+// Malformed headers (no "Name:" part) are skipped rather than failing the download.
+func ApplyRequestOptions(req *http.Request, opts Options) {
+	for _, header := range opts.Headers {
+		name, value, ok := strings.Cut(header, ":")
+		name = strings.TrimSpace(name)
+		if !ok || name == "" {
+			continue
+		}
+		req.Header.Add(name, strings.TrimSpace(value))
+	}
+	if opts.Auth != "" {
+		req.Header.Set("Authorization", opts.Auth)
+	}
+}
+
+// TLSConfig builds the TLS settings for opts' CA certificate and verification choice
+// Returns nil when neither is set, so Go's defaults apply.
+func TLSConfig(opts Options) (*tls.Config, error) {
+	if opts.CACert == "" && !opts.Insecure {
+		return nil, nil
+	}
+	config := &tls.Config{InsecureSkipVerify: opts.Insecure}
+	if opts.CACert != "" {
+		pem, err := os.ReadFile(opts.CACert)
+		if err != nil {
+			return nil, fmt.Errorf("failed to read CA certificate: %w", err)
+		}
+		// Trust the system roots as well, so redirects to public hosts still verify
+		pool, err := x509.SystemCertPool()
+		if err != nil || pool == nil {
+			pool = x509.NewCertPool()
+		}
+		if !pool.AppendCertsFromPEM(pem) {
+			return nil, fmt.Errorf("no certificates found in %s", opts.CACert)
+		}
+		config.RootCAs = pool
+	}
+	return config, nil
+}
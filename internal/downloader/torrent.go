@@ -62,6 +62,33 @@ func NewTorrentDownloader(source, outPath string, opts Options) *TorrentDownload
 	}
 }
 
+// wantedFiles returns the files to download: those at the selected indices, or all of them
+// Indices outside the torrent's file list are ignored.
+func (d *TorrentDownloader) wantedFiles(t *torrent.Torrent) []*torrent.File {
+	files := t.Files()
+	if len(d.opts.BTSelectFiles) == 0 {
+		return files
+	}
+	seen := make(map[int]bool)
+	var wanted []*torrent.File
+	for _, i := range d.opts.BTSelectFiles {
+		if i >= 0 && i < len(files) && !seen[i] {
+			seen[i] = true
+			wanted = append(wanted, files[i])
+		}
+	}
+	return wanted
+}
+
+// filesProgress sums the length and completed bytes of files
+func filesProgress(files []*torrent.File) (total, completed int64) {
+	for _, f := range files {
+		total += f.Length()
+		completed += f.BytesCompleted()
+	}
+	return total, completed
+}
+
 func (d *TorrentDownloader) Download() error {
 	cfg := torrent.NewDefaultClientConfig()
 	var dataDir string
@@ -86,6 +113,10 @@ func (d *TorrentDownloader) Download() error {
 	if d.downloadLimit > 0 {
 		cfg.DownloadRateLimiter = rate.NewLimiter(rate.Limit(d.downloadLimit), int(d.downloadLimit))
 	}
+	// The app splits one connection budget across all running torrents
+	if d.opts.BTMaxPeers > 0 {
+		cfg.EstablishedConnsPerTorrent = d.opts.BTMaxPeers
+	}
 
 	// Determine base port - use configured port if set, otherwise default to 42069
 	basePort := 42069
@@ -203,11 +234,24 @@ func (d *TorrentDownloader) Download() error {
 		return fmt.Errorf("failed to get torrent info after waiting for metadata")
 	}
 
+	// Files() is safe to call after GotInfo()
+	wanted := d.wantedFiles(t)
+	if len(wanted) == 0 {
+		return fmt.Errorf("none of the selected files are in the torrent")
+	}
+	selective := len(wanted) < len(t.Files())
+
 	// Now it's safe to call DownloadAll() since we have valid info
-	t.DownloadAll()
-	if d.sequential {
-		// Files() is safe to call after GotInfo()
+	if selective {
+		// Only the selected files: the rest are never requested
 		for _, f := range t.Files() {
+			f.SetPriority(torrent.PiecePriorityNone)
+		}
+	} else {
+		t.DownloadAll()
+	}
+	if d.sequential {
+		for _, f := range wanted {
 			f.SetPriority(torrent.PiecePriorityNow)
 		}
 	}
@@ -267,13 +311,13 @@ func (d *TorrentDownloader) Download() error {
 	// For multi-file torrents, enable parallel downloads by not using sequential mode
 	// unless explicitly requested
 	if !d.sequential {
-		// Set all files to normal priority to allow parallel downloads
-		for _, f := range t.Files() {
+		// Set all wanted files to normal priority to allow parallel downloads
+		for _, f := range wanted {
 			f.SetPriority(torrent.PiecePriorityNormal)
 		}
 	} else {
 		// Sequential mode - download files one by one
-		for _, f := range t.Files() {
+		for _, f := range wanted {
 			f.SetPriority(torrent.PiecePriorityNow)
 		}
 	}
@@ -312,8 +356,13 @@ func (d *TorrentDownloader) Download() error {
 			var completedBytes int64
 
 			if info != nil {
-				totalBytes = info.TotalLength()
-				completedBytes = t.BytesCompleted()
+				if selective {
+					// Done once the selected files are, whatever happens to the rest
+					totalBytes, completedBytes = filesProgress(wanted)
+				} else {
+					totalBytes = info.TotalLength()
+					completedBytes = t.BytesCompleted()
+				}
 				if totalBytes > 0 {
 					progress = float64(completedBytes) / float64(totalBytes)
 				}
@@ -9,7 +9,22 @@ const path = require('path');
 const fs = require('fs');
 const os = require('os');
 
-const dbPath = path.join(os.homedir(), '.accelara', 'accelara.db');
+// Same lookup as the app (src-tauri/src/paths.rs): ACCELARA_DATA_DIR, then the platform
+// data directory, then the legacy ~/.accelara for installs that haven't migrated yet
+function appDataDir() {
+  if (process.env.ACCELARA_DATA_DIR) {
+    return process.env.ACCELARA_DATA_DIR;
+  }
+  const platformDir = process.platform === 'darwin'
+    ? path.join(os.homedir(), 'Library', 'Application Support', 'accelara')
+    : process.platform === 'win32'
+      ? path.join(process.env.APPDATA || path.join(os.homedir(), 'AppData', 'Roaming'), 'accelara')
+      : path.join(process.env.XDG_DATA_HOME || path.join(os.homedir(), '.local', 'share'), 'accelara');
+  const legacyDir = path.join(os.homedir(), '.accelara');
+  return fs.existsSync(platformDir) || !fs.existsSync(legacyDir) ? platformDir : legacyDir;
+}
+
+const dbPath = path.join(appDataDir(), 'accelara.db');
 const dbDir = path.dirname(dbPath);
 
 async function clearDatabase() {
@@ -0,0 +1,342 @@
+use crate::{database, logger};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use serde_json::Value;
+use std::path::Path;
+
+/// Identifies an ACCELARA state bundle
+const STATE_FORMAT: &str = "accelara-state";
+
+/// Identifies a settings-and-history data export (no downloads list)
+const DATA_FORMAT: &str = "accelara-data";
+
+/// Bump when the bundle layout changes; import accepts this version and older
+pub const STATE_VERSION: u64 = 1;
+
+/// Placeholder written instead of secret values
+const REDACTED: &str = "<redacted>";
+
+/// Tables carried in a bundle, with the bundle key they're stored under
+const STATE_TABLES: &[(&str, &str)] = &[("downloads", "downloads"), ("download_history", "history")];
+
+/// Tables carried in a data export; in-flight downloads stay with the machine they run on
+const DATA_TABLES: &[(&str, &str)] = &[("download_history", "history"), ("speed_test_results", "speedTests")];
+
+/// Settings that are never exported (regenerated or machine-specific)
+const SKIPPED_SETTINGS: &[&str] = &["browserServerToken"];
+
+/// Whether a key looks like it holds a credential
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["token", "password", "secret", "cookie", "authorization", "apikey", "api_key"]
+        .iter()
+        .any(|s| key.contains(s))
+}
+
+/// Replace credential-looking values anywhere in a JSON value
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_secret_key(key) && !v.is_null() {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn sql_to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).to_string()),
+        // No table stores blobs; keep them out of the bundle rather than guess an encoding
+        ValueRef::Blob(_) => Value::Null,
+    }
+}
+
+fn json_to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => n.as_i64()
+            .map(SqlValue::Integer)
+            .or_else(|| n.as_f64().map(SqlValue::Real))
+            .unwrap_or(SqlValue::Null),
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// Dump a table as a list of `{column: value}` objects, with secrets in `metadata` redacted
+fn export_table(conn: &rusqlite::Connection, table: &str) -> Result<Vec<Value>, String> {
+    let columns = database::table_columns(conn, table)
+        .map_err(|e| format!("Failed to read {} columns: {}", table, e))?;
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {}", table))
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    
+    let rows = stmt.query_map([], |row| {
+        let mut object = serde_json::Map::new();
+        for (index, column) in columns.iter().enumerate() {
+            object.insert(column.clone(), sql_to_json(row.get_ref(index)?));
+        }
+        Ok(object)
+    })
+    .map_err(|e| format!("Failed to query {}: {}", table, e))?;
+    
+    let mut items = Vec::new();
+    for row in rows {
+        let mut row = row.map_err(|e| format!("Failed to read {} row: {}", table, e))?;
+        // metadata is JSON text holding the download options (headers, credentials, ...)
+        if let Some(Value::String(metadata)) = row.get("metadata") {
+            if let Ok(mut parsed) = serde_json::from_str::<Value>(metadata) {
+                redact(&mut parsed);
+                row.insert("metadata".to_string(), Value::String(parsed.to_string()));
+            }
+        }
+        items.push(Value::Object(row));
+    }
+    Ok(items)
+}
+
+/// Write settings, downloads and history to a portable JSON bundle at `path`
+pub fn export_state(path: &Path) -> Result<Value, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    export_bundle(&conn, path, STATE_FORMAT, STATE_TABLES)
+}
+
+/// Write settings, history and speed test results to a portable JSON file at `path`
+pub fn export_data(path: &Path) -> Result<Value, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    export_bundle(&conn, path, DATA_FORMAT, DATA_TABLES)
+}
+
+/// Load a bundle written by `export_state`
+/// With `merge`, existing rows and settings win; otherwise the bundle replaces them.
+pub fn import_state(path: &Path, merge: bool) -> Result<Value, String> {
+    // Make sure the schema is current before inserting rows shaped for it
+    database::init().map_err(|e| format!("Failed to prepare database: {}", e))?;
+    let mut conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    import_bundle(&mut conn, path, merge, STATE_FORMAT, STATE_TABLES)
+}
+
+/// Load a file written by `export_data`, merging into or replacing settings, history and speed tests
+pub fn import_data(path: &Path, merge: bool) -> Result<Value, String> {
+    database::init().map_err(|e| format!("Failed to prepare database: {}", e))?;
+    let mut conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    import_bundle(&mut conn, path, merge, DATA_FORMAT, DATA_TABLES)
+}
+
+/// Write the settings and `tables` to `path` as a bundle of the given format
+fn export_bundle(conn: &rusqlite::Connection, path: &Path, format: &str, tables: &[(&str, &str)]) -> Result<Value, String> {
+    let mut settings = serde_json::Map::new();
+    {
+        let mut stmt = conn.prepare("SELECT key, value FROM settings")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| format!("Failed to query settings: {}", e))?;
+        for (key, value) in rows.flatten() {
+            if SKIPPED_SETTINGS.contains(&key.as_str()) {
+                continue;
+            }
+            let mut value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            if is_secret_key(&key) {
+                value = Value::String(REDACTED.to_string());
+            } else {
+                redact(&mut value);
+            }
+            settings.insert(key, value);
+        }
+    }
+    
+    let mut bundle = serde_json::json!({
+        "format": format,
+        "version": STATE_VERSION,
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "exportedAt": chrono::Utc::now().timestamp(),
+        "settings": settings,
+    });
+    let mut counts = serde_json::json!({ "settings": bundle["settings"].as_object().map(|s| s.len()).unwrap_or(0) });
+    for (table, key) in tables {
+        let rows = export_table(conn, table)?;
+        counts[*key] = serde_json::json!(rows.len());
+        bundle[*key] = Value::Array(rows);
+    }
+    
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize state: {}", e))?;
+    std::fs::write(path, json)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    
+    logger::log_info("backup", &format!("Exported {} to {}", format, path.display()));
+    Ok(serde_json::json!({ "path": path, "counts": counts }))
+}
+
+/// Read a bundle of the given format from `path` into the settings and `tables`
+/// With `merge`, existing rows and settings win; otherwise the bundle replaces them.
+fn import_bundle(
+    conn: &mut rusqlite::Connection,
+    path: &Path,
+    merge: bool,
+    format: &str,
+    tables: &[(&str, &str)],
+) -> Result<Value, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let bundle: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("Not a valid state file: {}", e))?;
+    
+    if bundle.get("format").and_then(|v| v.as_str()) != Some(format) {
+        return Err(format!("Not an ACCELARA {} file", format));
+    }
+    let version = bundle.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version == 0 || version > STATE_VERSION {
+        return Err(format!("Unsupported state file version {} (this app reads up to {})", version, STATE_VERSION));
+    }
+    
+    let tx = conn.transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    
+    let mut counts = serde_json::json!({});
+    
+    // Settings - redacted values and regenerated keys keep whatever this machine has
+    let mut imported_settings = 0;
+    if !merge {
+        let placeholders: Vec<String> = (1..=SKIPPED_SETTINGS.len()).map(|i| format!("?{}", i)).collect();
+        tx.execute(
+            &format!("DELETE FROM settings WHERE key NOT IN ({})", placeholders.join(", ")),
+            rusqlite::params_from_iter(SKIPPED_SETTINGS.iter()),
+        )
+        .map_err(|e| format!("Failed to clear settings: {}", e))?;
+    }
+    if let Some(settings) = bundle.get("settings").and_then(|v| v.as_object()) {
+        let verb = if merge { "INSERT OR IGNORE" } else { "INSERT OR REPLACE" };
+        for (key, value) in settings {
+            if SKIPPED_SETTINGS.contains(&key.as_str()) || value.as_str() == Some(REDACTED) {
+                continue;
+            }
+            imported_settings += tx.execute(
+                &format!("{} INTO settings (key, value) VALUES (?1, ?2)", verb),
+                rusqlite::params![key, value.to_string()],
+            ).map_err(|e| format!("Failed to import setting {}: {}", key, e))?;
+        }
+    }
+    counts["settings"] = serde_json::json!(imported_settings);
+    
+    for (table, key) in tables {
+        // Only columns this schema knows about; older bundles simply lack the newer ones
+        let columns = database::table_columns(&tx, table)
+            .map_err(|e| format!("Failed to read {} columns: {}", table, e))?;
+        if !merge {
+            tx.execute(&format!("DELETE FROM {}", table), [])
+                .map_err(|e| format!("Failed to clear {}: {}", table, e))?;
+        }
+        
+        let mut imported = 0;
+        for row in bundle.get(*key).and_then(|v| v.as_array()).into_iter().flatten() {
+            let row = match row.as_object() {
+                Some(row) if row.get("id").map(|id| id.is_string()).unwrap_or(false) => row,
+                _ => continue,
+            };
+            let present: Vec<&String> = columns.iter().filter(|c| row.contains_key(*c)).collect();
+            let mut values: Vec<SqlValue> = present.iter().map(|c| json_to_sql(&row[*c])).collect();
+            
+            // Nothing is running for imported downloads yet
+            if *table == "downloads" {
+                if let Some(index) = present.iter().position(|c| c.as_str() == "status") {
+                    if let SqlValue::Text(status) = &values[index] {
+                        let status = match status.as_str() {
+                            "downloading" => "paused",
+                            "seeding" => "completed",
+                            other => other,
+                        };
+                        values[index] = SqlValue::Text(status.to_string());
+                    }
+                }
+            }
+            
+            let placeholders: Vec<String> = (1..=present.len()).map(|i| format!("?{}", i)).collect();
+            let column_list: Vec<&str> = present.iter().map(|c| c.as_str()).collect();
+            let verb = if merge { "INSERT OR IGNORE" } else { "INSERT OR REPLACE" };
+            imported += tx.execute(
+                &format!("{} INTO {} ({}) VALUES ({})", verb, table, column_list.join(", "), placeholders.join(", ")),
+                rusqlite::params_from_iter(values),
+            ).map_err(|e| format!("Failed to import {} row: {}", table, e))?;
+        }
+        counts[*key] = serde_json::json!(imported);
+    }
+    
+    tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+    
+    logger::log_info("backup", &format!("Imported {} from {} (merge: {})", format, path.display(), merge));
+    Ok(serde_json::json!({ "version": version, "merge": merge, "counts": counts }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        database::init_schema(&conn).unwrap();
+        conn
+    }
+    
+    fn table_rows(conn: &Connection, table: &str) -> Vec<Value> {
+        let mut rows = export_table(conn, table).unwrap();
+        rows.sort_by_key(|r| r["id"].as_str().unwrap_or_default().to_string());
+        rows
+    }
+    
+    #[test]
+    fn test_data_export_round_trip() {
+        let source = test_db();
+        source.execute_batch(
+            "INSERT INTO settings (key, value) VALUES ('maxConcurrentDownloads', '4'), ('theme', '\"dark\"');
+             INSERT INTO download_history (id, source, output, type, size, completed_at, notes) VALUES
+                ('h1', 'https://example.com/a.iso', '/tmp/a.iso', 'http', 1024, 1700000000, 'keep'),
+                ('h2', 'magnet:?xt=urn:btih:abc', '/tmp', 'magnet', 2048, 1700000100, NULL);
+             INSERT INTO speed_test_results (id, timestamp, download_speed, upload_speed, latency, location) VALUES
+                ('s1', 1700000000, 95.5, 20.25, '12ms', 'Nairobi');
+             INSERT INTO downloads (id, source, output, type, status) VALUES
+                ('active', 'https://example.com/b.iso', '/tmp/b.iso', 'http', 'downloading');"
+        ).unwrap();
+        
+        let path = std::env::temp_dir().join(format!("accelara-data-test-{}.json", std::process::id()));
+        export_bundle(&source, &path, DATA_FORMAT, DATA_TABLES).unwrap();
+        
+        let mut target = test_db();
+        let result = import_bundle(&mut target, &path, false, DATA_FORMAT, DATA_TABLES).unwrap();
+        assert_eq!(result["counts"]["history"], 2);
+        assert_eq!(result["counts"]["speedTests"], 1);
+        
+        for table in ["settings", "download_history", "speed_test_results"] {
+            let expected: Vec<Value> = if table == "settings" { settings_rows(&source) } else { table_rows(&source, table) };
+            let actual: Vec<Value> = if table == "settings" { settings_rows(&target) } else { table_rows(&target, table) };
+            assert_eq!(expected, actual, "{} differs after round trip", table);
+        }
+        // In-flight downloads aren't part of a data export
+        assert!(table_rows(&target, "downloads").is_empty());
+        
+        // A state bundle isn't accepted as a data export
+        assert!(import_bundle(&mut target, &path, true, STATE_FORMAT, STATE_TABLES).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    fn settings_rows(conn: &Connection) -> Vec<Value> {
+        let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key").unwrap();
+        let rows = stmt.query_map([], |row| Ok(serde_json::json!([row.get::<_, String>(0)?, row.get::<_, String>(1)?])))
+            .unwrap();
+        rows.map(|r| r.unwrap()).collect()
+    }
+}
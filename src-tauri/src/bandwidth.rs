@@ -0,0 +1,202 @@
+use crate::commands::{self, DOWNLOAD_PROCESSES};
+use crate::database;
+use crate::logger;
+use crate::peer_budget;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// No running download gets less than this (bytes/s), even if that puts the total over the cap
+const MIN_RATE_PER_DOWNLOAD: u64 = 32 * 1024;
+
+lazy_static::lazy_static! {
+    // Share of `globalRateLimit` each running download was started with
+    static ref ASSIGNED_SHARES: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// Parse a limit like "512KB", "1.5MB" or "2048" into bytes (the same format the Go binary accepts)
+pub fn parse_rate(limit: &str) -> Option<u64> {
+    let limit = limit.trim();
+    let split = limit.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(limit.len());
+    let (number, unit) = limit.split_at(split);
+    let value: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        "t" | "tb" => 1024 * 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some((value * multiplier as f64) as u64)
+}
+
+/// The `globalRateLimit` setting in bytes/s (None = no total cap)
+pub fn global_rate_limit() -> Option<u64> {
+    database::get_setting("globalRateLimit")
+        .and_then(|v| match v {
+            serde_json::Value::Number(n) => n.as_u64(),
+            serde_json::Value::String(s) => parse_rate(&s),
+            _ => None,
+        })
+        .filter(|limit| *limit > 0)
+}
+
+/// Split `total` bytes/s evenly across `active_ids`
+/// The bytes left over by the division go one each to the first ids in sorted order, so the
+/// shares add up to exactly `total`. Every download gets at least MIN_RATE_PER_DOWNLOAD.
+pub fn allocate_bandwidth(total: u64, active_ids: &[String]) -> HashMap<String, u64> {
+    let mut ids: Vec<&String> = active_ids.iter().collect();
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() {
+        return HashMap::new();
+    }
+
+    let share = total / ids.len() as u64;
+    let remainder = (total % ids.len() as u64) as usize;
+    ids.into_iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let extra = if i < remainder { 1 } else { 0 };
+            (id.clone(), (share + extra).max(MIN_RATE_PER_DOWNLOAD))
+        })
+        .collect()
+}
+
+/// Cap the `--limit` in a download's args at `share`, keeping a lower limit of its own
+pub fn apply_share(args: &mut Vec<String>, share: u64) {
+    match args.iter().position(|a| a == "--limit") {
+        Some(at) if at + 1 < args.len() => {
+            let own = parse_rate(&args[at + 1]).filter(|own| *own > 0);
+            if own.map(|own| own > share).unwrap_or(true) {
+                args[at + 1] = share.to_string();
+            }
+        }
+        _ => {
+            args.push("--limit".to_string());
+            args.push(share.to_string());
+        }
+    }
+}
+
+/// Remember the share a download was started with
+pub fn assign_share(download_id: &str, share: u64) {
+    if let Ok(mut shares) = ASSIGNED_SHARES.lock() {
+        shares.insert(download_id.to_string(), share);
+    }
+}
+
+/// Set up the background task that re-divides `globalRateLimit` as downloads start and finish
+pub fn setup_bandwidth_rebalancing(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+
+            let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+            let assigned = match ASSIGNED_SHARES.lock() {
+                Ok(mut shares) => {
+                    shares.retain(|id, _| running.contains(id));
+                    shares.clone()
+                }
+                Err(_) => continue,
+            };
+
+            // Downloads started without a share (or with one that's since gone) need a restart either way
+            let to_restart: Vec<String> = match global_rate_limit() {
+                Some(total) => {
+                    let shares = allocate_bandwidth(total, &running);
+                    running
+                        .iter()
+                        .filter(|id| match (assigned.get(*id), shares.get(*id)) {
+                            // Same 1.5x / 2/3 tolerance as peer limits - a restart costs a reconnect
+                            (Some(&current), Some(&target)) => peer_budget::needs_rebalance(current, target),
+                            _ => true,
+                        })
+                        .cloned()
+                        .collect()
+                }
+                None => {
+                    if let Ok(mut shares) = ASSIGNED_SHARES.lock() {
+                        shares.clear();
+                    }
+                    assigned.into_keys().collect()
+                }
+            };
+
+            for download_id in to_restart {
+                logger::log_info("bandwidth", &format!("Re-dividing global rate limit, restarting {}", download_id));
+                if let Err(e) = commands::restart_download_internal(download_id.clone(), app.clone()).await {
+                    logger::log_error("bandwidth", &format!("Failed to apply share to {}: {}", download_id, e));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("2048"), Some(2048));
+        assert_eq!(parse_rate("512KB"), Some(512 * 1024));
+        assert_eq!(parse_rate("1.5 mb"), Some(1536 * 1024));
+        assert_eq!(parse_rate("10M"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_rate("fast"), None);
+        assert_eq!(parse_rate("5XB"), None);
+    }
+
+    #[test]
+    fn test_even_split_adds_up_to_total() {
+        let total = 10 * 1024 * 1024;
+        let shares = allocate_bandwidth(total, &ids(&["a", "b", "c"]));
+        assert_eq!(shares.len(), 3);
+        assert_eq!(shares.values().sum::<u64>(), total);
+
+        // Rounding: the spare bytes go to the first ids, so no two shares differ by more than one
+        let max = *shares.values().max().unwrap();
+        let min = *shares.values().min().unwrap();
+        assert!(max - min <= 1);
+        assert_eq!(shares["a"], total / 3 + 1);
+        assert_eq!(shares["c"], total / 3);
+    }
+
+    #[test]
+    fn test_allocation_ignores_order_and_duplicates() {
+        let forward = allocate_bandwidth(1_000_001, &ids(&["x", "y"]));
+        let backward = allocate_bandwidth(1_000_001, &ids(&["y", "x", "y"]));
+        assert_eq!(forward, backward);
+        assert_eq!(forward["x"], 500_001);
+        assert!(allocate_bandwidth(1024, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_min_guarantee() {
+        // 64KB across four downloads would be 16KB each - nobody drops below the floor
+        let shares = allocate_bandwidth(64 * 1024, &ids(&["a", "b", "c", "d"]));
+        assert!(shares.values().all(|share| *share == MIN_RATE_PER_DOWNLOAD));
+    }
+
+    #[test]
+    fn test_apply_share_keeps_lower_own_limit() {
+        let mut args = ids(&["--source", "x", "--limit", "100KB"]);
+        apply_share(&mut args, 1024 * 1024);
+        assert_eq!(args[3], "100KB");
+
+        let mut args = ids(&["--source", "x", "--limit", "5MB"]);
+        apply_share(&mut args, 1024 * 1024);
+        assert_eq!(args[3], "1048576");
+
+        let mut args = ids(&["--source", "x"]);
+        apply_share(&mut args, 4096);
+        assert_eq!(args, ids(&["--source", "x", "--limit", "4096"]));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{logger, utils};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::process::Command as TokioCommand;
+
+/// Reported for a binary that runs but doesn't print a recognisable version
+pub const UNKNOWN_VERSION: &str = "unknown";
+
+/// `--version` should answer immediately; anything slower is treated as unsupported
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static::lazy_static! {
+    // Version of each probed binary, with the mtime it was read at; an updated binary is probed again
+    static ref VERSIONS: Mutex<HashMap<PathBuf, (SystemTime, String)>> = Mutex::new(HashMap::new());
+    // Flags each probed binary lists in its usage, cached the same way
+    static ref FLAGS: Mutex<HashMap<PathBuf, (SystemTime, HashSet<String>)>> = Mutex::new(HashMap::new());
+}
+
+/// The version number in `--version` output, e.g. "iris version v1.4.2 (linux/amd64)" -> "1.4.2"
+pub fn parse_version(output: &str) -> Option<String> {
+    output
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '(' || c == ')')
+        .map(|word| word.trim_start_matches('v').trim_start_matches('V'))
+        .find(|word| {
+            let mut parts = word.split('.');
+            let major = parts.next().unwrap_or("");
+            let minor = parts.next().unwrap_or("");
+            // The minor part may carry a suffix like "0-beta"
+            !major.is_empty()
+                && major.chars().all(|c| c.is_ascii_digit())
+                && minor.starts_with(|c: char| c.is_ascii_digit())
+        })
+        .map(|word| word.to_string())
+}
+
+/// Run `binary --version`; UNKNOWN_VERSION if it fails, times out or prints no version
+async fn probe_version(binary: &Path) -> String {
+    let output = tokio::time::timeout(VERSION_TIMEOUT, TokioCommand::new(binary)
+        .arg("--version")
+        .kill_on_drop(true)
+        .output())
+        .await;
+    match output {
+        Ok(Ok(output)) if output.status.success() => {
+            let text = format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+            parse_version(&text).unwrap_or_else(|| UNKNOWN_VERSION.to_string())
+        }
+        // Go's flag package exits non-zero with a usage message for flags it doesn't know
+        Ok(Ok(_)) => UNKNOWN_VERSION.to_string(),
+        Ok(Err(e)) => {
+            logger::log_warning("binaries", &format!("Failed to run {} --version: {}", binary.display(), e));
+            UNKNOWN_VERSION.to_string()
+        }
+        Err(_) => {
+            logger::log_warning("binaries", &format!("{} --version timed out", binary.display()));
+            UNKNOWN_VERSION.to_string()
+        }
+    }
+}
+
+/// Version of a binary, probed once per modification time
+pub async fn binary_version(binary: &Path) -> String {
+    let mtime = match std::fs::metadata(binary).and_then(|m| m.modified()) {
+        Ok(mtime) => mtime,
+        Err(_) => return UNKNOWN_VERSION.to_string(),
+    };
+    if let Some((cached_mtime, version)) = VERSIONS.lock().ok().and_then(|versions| versions.get(binary).cloned()) {
+        if cached_mtime == mtime {
+            return version;
+        }
+    }
+
+    let version = probe_version(binary).await;
+    if let Ok(mut versions) = VERSIONS.lock() {
+        versions.insert(binary.to_path_buf(), (mtime, version.clone()));
+    }
+    version
+}
+
+/// Flag names listed in Go flag-package usage output, e.g. "  -auth string" -> "auth"
+pub fn parse_flags(usage: &str) -> HashSet<String> {
+    usage
+        .lines()
+        .filter_map(|line| line.strip_prefix("  -"))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Run `binary -h` and collect the flags it lists; none if it can't be run
+async fn probe_flags(binary: &Path) -> HashSet<String> {
+    let output = tokio::time::timeout(VERSION_TIMEOUT, TokioCommand::new(binary)
+        .arg("-h")
+        .kill_on_drop(true)
+        .output())
+        .await;
+    match output {
+        // The usage goes to stderr, and older builds exit non-zero after printing it
+        Ok(Ok(output)) => parse_flags(&format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))),
+        Ok(Err(e)) => {
+            logger::log_warning("binaries", &format!("Failed to run {} -h: {}", binary.display(), e));
+            HashSet::new()
+        }
+        Err(_) => {
+            logger::log_warning("binaries", &format!("{} -h timed out", binary.display()));
+            HashSet::new()
+        }
+    }
+}
+
+/// Flags a binary accepts, probed once per modification time
+pub async fn supported_flags(binary: &Path) -> HashSet<String> {
+    let mtime = match std::fs::metadata(binary).and_then(|m| m.modified()) {
+        Ok(mtime) => mtime,
+        Err(_) => return HashSet::new(),
+    };
+    if let Some((cached_mtime, flags)) = FLAGS.lock().ok().and_then(|flags| flags.get(binary).cloned()) {
+        if cached_mtime == mtime {
+            return flags;
+        }
+    }
+
+    let flags = probe_flags(binary).await;
+    if let Ok(mut cache) = FLAGS.lock() {
+        cache.insert(binary.to_path_buf(), (mtime, flags.clone()));
+    }
+    flags
+}
+
+/// Remove `gated` options (each followed by its value) that `supported` doesn't list
+/// Returns the remaining args and the options that were dropped.
+pub fn strip_unsupported(args: Vec<String>, supported: &HashSet<String>, gated: &[&str]) -> (Vec<String>, Vec<String>) {
+    let mut kept = Vec::with_capacity(args.len());
+    let mut dropped = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if gated.contains(&arg.as_str()) && !supported.contains(arg.trim_start_matches('-')) {
+            args.next();
+            if !dropped.contains(&arg) {
+                dropped.push(arg);
+            }
+        } else {
+            kept.push(arg);
+        }
+    }
+    (kept, dropped)
+}
+
+/// `args` without the `gated` options an older `binary` would exit on, with a warning for each dropped
+pub async fn drop_unsupported_args(binary: &Path, args: Vec<String>, gated: &[&str]) -> Vec<String> {
+    if !args.iter().any(|arg| gated.contains(&arg.as_str())) {
+        return args;
+    }
+    let (args, dropped) = strip_unsupported(args, &supported_flags(binary).await, gated);
+    if !dropped.is_empty() {
+        logger::log_warning("binaries", &format!(
+            "{} doesn't support {}; sending the request without them", binary.display(), dropped.join(", ")));
+    }
+    args
+}
+
+/// `{path, version}` for a binary that may not have been found
+async fn binary_info(binary: Option<PathBuf>) -> serde_json::Value {
+    match binary {
+        Some(path) => serde_json::json!({
+            "path": path.to_string_lossy(),
+            "version": binary_version(&path).await,
+        }),
+        None => serde_json::json!({ "path": null, "version": null }),
+    }
+}
+
+/// Paths and versions of the bundled api-wrapper and iris binaries
+pub async fn binary_versions() -> serde_json::Value {
+    let (api_wrapper, iris) = tokio::join!(
+        binary_info(utils::find_go_binary()),
+        binary_info(utils::find_iris_binary()),
+    );
+    serde_json::json!({
+        "apiWrapper": api_wrapper,
+        "iris": iris,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("api-wrapper 1.2.3").as_deref(), Some("1.2.3"));
+        assert_eq!(parse_version("iris version v0.9.1 (linux/amd64)").as_deref(), Some("0.9.1"));
+        assert_eq!(parse_version("Version: 2.0-beta\n").as_deref(), Some("2.0-beta"));
+        assert_eq!(parse_version("flag provided but not defined: -version\nUsage of api-wrapper:"), None);
+        assert_eq!(parse_version("build 42"), None);
+    }
+
+    #[test]
+    fn test_parse_flags_and_strip_unsupported() {
+        let usage = "Usage of api-wrapper:\n  -auth string\n    \tAuthorization header value\n  -bt-sequential\n    \tSequential mode\n  -source string\n    \tSource URL or torrent\n";
+        let flags = parse_flags(usage);
+        assert_eq!(flags, ["auth", "bt-sequential", "source"].iter().map(|f| f.to_string()).collect::<HashSet<String>>());
+
+        let args: Vec<String> = ["--source", "http://x/a", "--header", "Cookie: a=1", "--header", "X-Y: 2", "--auth", "Bearer t"]
+            .iter().map(|a| a.to_string()).collect();
+        let (kept, dropped) = strip_unsupported(args.clone(), &flags, &["--header", "--auth"]);
+        assert_eq!(kept, vec!["--source", "http://x/a", "--auth", "Bearer t"]);
+        assert_eq!(dropped, vec!["--header"]);
+
+        let (kept, dropped) = strip_unsupported(args.clone(), &parse_flags("  -header value\n  -auth string\n"), &["--header", "--auth"]);
+        assert_eq!(kept, args);
+        assert!(dropped.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_version_cached_until_binary_changes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("accelara-binaries-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let counter = dir.join("runs");
+        let binary = dir.join("fake-wrapper");
+        let _ = std::fs::remove_file(&counter);
+        let write_binary = |version: &str, mtime: SystemTime| {
+            std::fs::write(&binary, format!("#!/bin/sh\necho run >> '{}'\necho \"fake-wrapper {}\"\n", counter.display(), version)).unwrap();
+            std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+            std::fs::File::options().write(true).open(&binary).unwrap().set_modified(mtime).unwrap();
+        };
+        let runs = || std::fs::read_to_string(&counter).map(|s| s.lines().count()).unwrap_or(0);
+
+        write_binary("1.0.0", SystemTime::UNIX_EPOCH + Duration::from_secs(1_000));
+        assert_eq!(binary_version(&binary).await, "1.0.0");
+        assert_eq!(binary_version(&binary).await, "1.0.0");
+        assert_eq!(runs(), 1);
+
+        // A replaced binary has a new mtime and is probed again
+        write_binary("1.1.0", SystemTime::UNIX_EPOCH + Duration::from_secs(2_000));
+        assert_eq!(binary_version(&binary).await, "1.1.0");
+        assert_eq!(runs(), 2);
+
+        assert_eq!(binary_version(&dir.join("missing")).await, UNKNOWN_VERSION);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
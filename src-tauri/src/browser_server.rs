@@ -1,17 +1,95 @@
 use axum::{
-    extract::{Json, State},
-    http::{Method, StatusCode},
-    response::Json as ResponseJson,
-    routing::post,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Json, Path, Query, Request, State,
+    },
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, post},
     Router,
 };
+use crate::commands;
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+use tokio::sync::broadcast;
 use tower::ServiceBuilder;
-use tower_http::cors::{CorsLayer, Any};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
-const BROWSER_SERVER_PORT: u16 = 8765;
+/// Port used when the `browserServerPort` setting is missing
+pub const DEFAULT_BROWSER_SERVER_PORT: u16 = 8765;
+
+/// How many ports after the configured one to try when it's already taken
+const PORT_FALLBACK_ATTEMPTS: u16 = 5;
+
+/// Maximum number of extension WebSocket clients connected at once
+const MAX_WS_CLIENTS: usize = 8;
+
+/// App events forwarded to WebSocket clients
+const FORWARDED_EVENTS: &[&str] = &["download-update", "download-complete"];
+
+static WS_CLIENTS: AtomicUsize = AtomicUsize::new(0);
+
+// Whether the server is bound and serving
+static SERVER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// Port the server actually bound to (0 until it's listening)
+static BOUND_PORT: AtomicU16 = AtomicU16::new(0);
+
+lazy_static::lazy_static! {
+    // Forwarded app events, serialized once and fanned out to every WebSocket client
+    static ref WS_EVENTS: broadcast::Sender<String> = broadcast::channel(256).0;
+}
+
+/// Setting holding the bearer token required by the extension and automation API
+const API_TOKEN_SETTING: &str = "browserServerToken";
+
+/// Setting holding the origins allowed to call the server (`*` suffix matches a prefix)
+const ALLOWED_ORIGINS_SETTING: &str = "browserAllowedOrigins";
+
+/// Origins allowed when the setting is missing: the browser extensions themselves
+pub const DEFAULT_ALLOWED_ORIGINS: &[&str] = &[
+    "chrome-extension://*",
+    "moz-extension://*",
+    "safari-web-extension://*",
+];
+
+/// Port configured in settings (the server may end up on a later one if it's taken)
+fn configured_port() -> u16 {
+    crate::database::get_setting("browserServerPort")
+        .and_then(|v| v.as_u64())
+        .and_then(|p| u16::try_from(p).ok())
+        .filter(|p| *p != 0)
+        .unwrap_or(DEFAULT_BROWSER_SERVER_PORT)
+}
+
+/// Port the browser integration server listens on
+/// Falls back to the configured port while the server isn't bound.
+pub fn server_port() -> u16 {
+    match BOUND_PORT.load(Ordering::SeqCst) {
+        0 => configured_port(),
+        port => port,
+    }
+}
+
+/// Whether the browser integration server is currently listening
+pub fn is_running() -> bool {
+    SERVER_RUNNING.load(Ordering::SeqCst)
+}
+
+/// A value from the extension that must never reach the logs (cookies, auth headers)
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+struct Redacted(String);
+
+impl std::fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
 
 #[derive(Debug, Deserialize)]
 struct BrowserDownloadRequest {
@@ -24,6 +102,12 @@ struct BrowserDownloadRequest {
     mime_type: Option<String>,
     #[serde(rename = "mimeType")]
     mime_type_alt: Option<String>,
+    /// The page's `Cookie` header value, so downloads behind a login work
+    #[serde(default)]
+    cookies: Option<Redacted>,
+    /// Other request headers the download needs
+    #[serde(default)]
+    headers: Option<HashMap<String, Redacted>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -34,62 +118,129 @@ struct BrowserDownloadResponse {
 
 /// Start the browser integration HTTP server
 pub fn start_browser_server(app: AppHandle) {
+    let enabled = crate::database::get_setting("browserServerEnabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    if !enabled {
+        eprintln!("[browser-server] Browser integration disabled in settings");
+        return;
+    }
+    if let Err(e) = ensure_api_token() {
+        eprintln!("[browser-server] {}", e);
+    }
+    
+    // Relay download events to connected extension clients
+    for event_name in FORWARDED_EVENTS {
+        app.listen_any(*event_name, move |event| {
+            // Skip the work entirely when nobody is listening
+            if WS_EVENTS.receiver_count() == 0 {
+                return;
+            }
+            let payload: serde_json::Value = serde_json::from_str(event.payload()).unwrap_or_default();
+            let message = serde_json::json!({ "event": event_name, "payload": payload });
+            let _ = WS_EVENTS.send(message.to_string());
+        });
+    }
+    
     let app_handle = Arc::new(app);
     
     tauri::async_runtime::spawn(async move {
-        // Build CORS layer
+        // Build CORS layer (Authorization has to be listed explicitly, a wildcard doesn't cover it)
+        let allowed_origins = stored_allowed_origins();
         let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods([Method::POST, Method::OPTIONS])
-            .allow_headers(Any);
+            .allow_origin(AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin.to_str().map(|o| origin_allowed(o, &allowed_origins)).unwrap_or(false)
+            }))
+            .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
+        
+        // Everything except /health can start or reveal downloads, so it needs the token
+        let protected = Router::new()
+            .route("/download", post(handle_download))
+            .route("/download/batch", post(handle_download_batch))
+            .route("/ws", get(handle_ws))
+            .route("/downloads", get(api_list_downloads).post(api_add_download))
+            .route("/downloads/:id", delete(api_remove_download))
+            .route("/downloads/:id/pause", post(api_pause_download))
+            .route("/downloads/:id/resume", post(api_resume_download))
+            .route_layer(middleware::from_fn_with_state(ApiGuard::from_settings(), require_token));
         
         // Build router with app handle in state
         let router = Router::new()
-            .route("/download", post(handle_download))
+            .route("/health", get(handle_health))
+            .merge(protected)
             .with_state(app_handle.clone())
             .layer(ServiceBuilder::new().layer(cors));
         
-        // Bind to localhost:8765
-        let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", BROWSER_SERVER_PORT)).await;
-        
-        match listener {
-            Ok(listener) => {
-                eprintln!("[browser-server] Browser integration server listening on http://localhost:{}", BROWSER_SERVER_PORT);
+        match bind_listener(configured_port()).await {
+            Ok((listener, port)) => {
+                eprintln!("[browser-server] Browser integration server listening on http://localhost:{}", port);
                 
                 // Run the server
+                BOUND_PORT.store(port, Ordering::SeqCst);
+                SERVER_RUNNING.store(true, Ordering::SeqCst);
+                let _ = app_handle.emit("browser-server-ready", serde_json::json!({ "port": port }));
                 if let Err(e) = axum::serve(listener, router).await {
                     eprintln!("[browser-server] Server error: {}", e);
                 }
+                SERVER_RUNNING.store(false, Ordering::SeqCst);
+                BOUND_PORT.store(0, Ordering::SeqCst);
             }
             Err(e) => {
-                if e.kind() == std::io::ErrorKind::AddrInUse {
-                    eprintln!("[browser-server] Port {} already in use, browser integration may not work", BROWSER_SERVER_PORT);
-                } else {
-                    eprintln!("[browser-server] Failed to start server: {}", e);
-                }
+                eprintln!("[browser-server] Failed to start server: {}", e);
             }
         }
     });
 }
 
-async fn handle_download(
-    State(app): State<Arc<AppHandle>>,
-    Json(payload): Json<BrowserDownloadRequest>,
-) -> Result<ResponseJson<BrowserDownloadResponse>, StatusCode> {
-    eprintln!("[browser-server] Received browser download request: {:?}", payload);
-    
+/// Bind to `port` on localhost, moving on to the next few ports while they're in use
+async fn bind_listener(port: u16) -> Result<(tokio::net::TcpListener, u16), String> {
+    let last = port.saturating_add(PORT_FALLBACK_ATTEMPTS);
+    for candidate in port..=last {
+        match tokio::net::TcpListener::bind(format!("127.0.0.1:{}", candidate)).await {
+            Ok(listener) => return Ok((listener, candidate)),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                eprintln!("[browser-server] Port {} already in use, trying the next one", candidate);
+            }
+            Err(e) => return Err(format!("Failed to bind port {}: {}", candidate, e)),
+        }
+    }
+    Err(format!("Ports {}-{} are all in use, browser integration may not work", port, last))
+}
+
+/// Liveness check used by the extension, the settings page and external automation
+async fn handle_health() -> ResponseJson<serde_json::Value> {
+    let mut health = crate::health::health_report().await;
+    health["ok"] = serde_json::json!(true);
+    health["version"] = serde_json::json!(env!("CARGO_PKG_VERSION"));
+    ResponseJson(health)
+}
+
+#[derive(Debug, Deserialize)]
+struct BrowserBatchRequest {
+    downloads: Vec<BrowserDownloadRequest>,
+}
+
+#[derive(Debug, Serialize)]
+struct BrowserBatchItemResult {
+    index: usize,
+    success: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BrowserBatchResponse {
+    success: bool,
+    results: Vec<BrowserBatchItemResult>,
+    error: Option<String>,
+}
+
+/// Turn an extension request into the payload of an `external-download` event
+fn to_external_download(payload: BrowserDownloadRequest) -> Result<serde_json::Value, String> {
     let source = payload.url
         .or(payload.source)
-        .ok_or_else(|| {
-            eprintln!("[browser-server] Missing url/source in request");
-            StatusCode::BAD_REQUEST
-        })?;
-    
-    // Show and focus the window
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.set_focus();
-    }
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| "Missing url/source in request".to_string())?;
     
     // Determine download type
     let download_type = if source.starts_with("magnet:") || payload.download_type == "magnet" {
@@ -98,16 +249,56 @@ async fn handle_download(
         "download"
     };
     
-    // Emit event to frontend to open download modal
-    let event_data = serde_json::json!({
+    // Passed on as the download's `headers` option; `cookies` wins over a Cookie in `headers`
+    let mut headers: serde_json::Map<String, serde_json::Value> = payload.headers
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(name, _)| !name.eq_ignore_ascii_case("cookie") || payload.cookies.is_none())
+        .map(|(name, value)| (name, serde_json::json!(value.0)))
+        .collect();
+    if let Some(Redacted(cookies)) = payload.cookies.filter(|c| !c.0.trim().is_empty()) {
+        headers.insert("Cookie".to_string(), serde_json::json!(cookies));
+    }
+    let has_header = |wanted: &str| headers.keys().any(|name| name.eq_ignore_ascii_case(wanted));
+    let has_cookies = has_header("cookie");
+    let has_auth = has_cookies || has_header("authorization");
+    
+    Ok(serde_json::json!({
         "type": download_type,
         "source": source,
         "filename": payload.filename,
         "referrer": payload.referrer,
         "mimeType": payload.mime_type.or(payload.mime_type_alt),
-    });
+        // Lets the modal say credentials are attached without having to look at (or log) them
+        "hasCookies": has_cookies,
+        "hasAuth": has_auth,
+        "headers": if headers.is_empty() { serde_json::Value::Null } else { serde_json::Value::Object(headers) },
+    }))
+}
+
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+async fn handle_download(
+    State(app): State<Arc<AppHandle>>,
+    Json(payload): Json<BrowserDownloadRequest>,
+) -> Result<ResponseJson<BrowserDownloadResponse>, StatusCode> {
+    eprintln!("[browser-server] Received browser download request: {:?}", payload);
+    
+    let event_data = to_external_download(payload).map_err(|e| {
+        eprintln!("[browser-server] {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
     
-    if let Err(e) = app.emit("external-download", event_data) {
+    // Show and focus the window
+    focus_main_window(&app);
+    
+    // Emit event to frontend to open download modal
+    if let Err(e) = app.emit("external-download", event_data.clone()) {
         eprintln!("[browser-server] Failed to emit external-download event: {}", e);
         return Ok(ResponseJson(BrowserDownloadResponse {
             success: false,
@@ -115,7 +306,7 @@ async fn handle_download(
         }));
     }
     
-    eprintln!("[browser-server] Successfully processed {} download: {}", download_type, source);
+    eprintln!("[browser-server] Successfully processed {} download: {}", event_data["type"], event_data["source"]);
     
     Ok(ResponseJson(BrowserDownloadResponse {
         success: true,
@@ -123,3 +314,344 @@ async fn handle_download(
     }))
 }
 
+/// Handle "download all links": one request and one `external-downloads` event for the whole set
+async fn handle_download_batch(
+    State(app): State<Arc<AppHandle>>,
+    Json(payload): Json<BrowserBatchRequest>,
+) -> ResponseJson<BrowserBatchResponse> {
+    eprintln!("[browser-server] Received batch of {} download(s)", payload.downloads.len());
+    
+    let mut results = Vec::new();
+    let mut downloads = Vec::new();
+    for (index, request) in payload.downloads.into_iter().enumerate() {
+        match to_external_download(request) {
+            Ok(event_data) => {
+                downloads.push(event_data);
+                results.push(BrowserBatchItemResult { index, success: true, error: None });
+            }
+            Err(e) => results.push(BrowserBatchItemResult { index, success: false, error: Some(e) }),
+        }
+    }
+    
+    if downloads.is_empty() {
+        return ResponseJson(BrowserBatchResponse {
+            success: false,
+            results,
+            error: Some("No valid downloads in batch".to_string()),
+        });
+    }
+    
+    focus_main_window(&app);
+    
+    if let Err(e) = app.emit("external-downloads", serde_json::json!({ "downloads": downloads })) {
+        eprintln!("[browser-server] Failed to emit external-downloads event: {}", e);
+        // Nothing reached the UI, so none of the items succeeded
+        for result in results.iter_mut().filter(|r| r.success) {
+            result.success = false;
+            result.error = Some(format!("Failed to process download: {}", e));
+        }
+        return ResponseJson(BrowserBatchResponse {
+            success: false,
+            results,
+            error: Some(format!("Failed to process downloads: {}", e)),
+        });
+    }
+    
+    ResponseJson(BrowserBatchResponse {
+        success: true,
+        results,
+        error: None,
+    })
+}
+
+/// Upgrade to a WebSocket that streams download progress to the extension
+async fn handle_ws(ws: WebSocketUpgrade) -> Response {
+    // Reserve a client slot up front so concurrent upgrades can't exceed the limit
+    if WS_CLIENTS.fetch_add(1, Ordering::SeqCst) >= MAX_WS_CLIENTS {
+        WS_CLIENTS.fetch_sub(1, Ordering::SeqCst);
+        eprintln!("[browser-server] Rejecting WebSocket client, {} already connected", MAX_WS_CLIENTS);
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    
+    ws.on_upgrade(|socket| async move {
+        stream_events(socket).await;
+        WS_CLIENTS.fetch_sub(1, Ordering::SeqCst);
+    })
+}
+
+async fn stream_events(mut socket: WebSocket) {
+    let mut events = WS_EVENTS.subscribe();
+    eprintln!("[browser-server] WebSocket client connected");
+    
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(message) => {
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client missed some updates - later ones supersede them anyway
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                // Clients don't send anything meaningful; pings are answered by axum
+                Some(Ok(_)) => continue,
+            },
+        }
+    }
+    
+    eprintln!("[browser-server] WebSocket client disconnected");
+}
+
+type ApiResult = Result<ResponseJson<serde_json::Value>, (StatusCode, ResponseJson<serde_json::Value>)>;
+
+fn api_error(status: StatusCode, message: &str) -> (StatusCode, ResponseJson<serde_json::Value>) {
+    (status, ResponseJson(serde_json::json!({ "error": message })))
+}
+
+fn stored_api_token() -> Option<String> {
+    crate::database::get_setting(API_TOKEN_SETTING)
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .filter(|t| !t.is_empty())
+}
+
+fn stored_allowed_origins() -> Vec<String> {
+    crate::database::get_setting(ALLOWED_ORIGINS_SETTING)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_else(|| DEFAULT_ALLOWED_ORIGINS.iter().map(|o| o.to_string()).collect())
+}
+
+/// Return the server token, generating and storing one on first run
+pub fn ensure_api_token() -> Result<String, String> {
+    if let Some(token) = stored_api_token() {
+        return Ok(token);
+    }
+    let token = nanoid::nanoid!(32);
+    crate::database::set_setting(API_TOKEN_SETTING, &serde_json::json!(token))
+        .map_err(|e| format!("Failed to store API token: {}", e))?;
+    Ok(token)
+}
+
+/// Whether `origin` matches an allowlist entry exactly or by a trailing `*` prefix
+fn origin_allowed(origin: &str, allowed: &[String]) -> bool {
+    allowed.iter().any(|entry| match entry.strip_suffix('*') {
+        Some(prefix) => origin.starts_with(prefix),
+        None => origin == entry,
+    })
+}
+
+/// Where the token middleware reads the expected token and origin allowlist from
+/// Looked up per request so regenerating the token or editing the allowlist applies immediately.
+#[derive(Clone, Copy)]
+struct ApiGuard {
+    token: fn() -> Option<String>,
+    allowed_origins: fn() -> Vec<String>,
+}
+
+impl ApiGuard {
+    fn from_settings() -> Self {
+        ApiGuard { token: stored_api_token, allowed_origins: stored_allowed_origins }
+    }
+}
+
+/// Require `Authorization: Bearer <token>` (or `?token=` for WebSocket clients, which can't set headers)
+/// and, when the request carries an Origin, that it is on the allowlist
+async fn require_token(State(guard): State<ApiGuard>, request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    if let Some(origin) = headers.get(header::ORIGIN) {
+        let allowed = origin.to_str().map(|o| origin_allowed(o, &(guard.allowed_origins)())).unwrap_or(false);
+        if !allowed {
+            return api_error(StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+        }
+    }
+    
+    let provided = headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.to_string())
+        .or_else(|| request.uri().query().and_then(|q| {
+            // Tokens only use URL-safe characters, so no percent-decoding is needed
+            q.split('&').find_map(|pair| pair.strip_prefix("token=")).map(|t| t.to_string())
+        }));
+    
+    match ((guard.token)(), provided) {
+        (Some(expected), Some(provided)) if expected == provided => next.run(request).await,
+        _ => api_error(StatusCode::UNAUTHORIZED, "Missing or invalid API token").into_response(),
+    }
+}
+
+/// Map a command error onto the API: unknown IDs are 404, everything else 400
+fn command_error(e: String) -> (StatusCode, ResponseJson<serde_json::Value>) {
+    let status = if e.starts_with("Download not found") { StatusCode::NOT_FOUND } else { StatusCode::BAD_REQUEST };
+    api_error(status, &e)
+}
+
+/// GET /downloads?status=a,b&type=http&sortBy=progress&order=asc&limit=50&offset=0
+/// Same shape as the `query_downloads` command.
+async fn api_list_downloads(
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult {
+    let list = |key: &str| params.get(key).map(|v| v.split(',').map(|s| s.trim().to_string()).collect());
+    let number = |key: &str| params.get(key).and_then(|v| v.parse::<i64>().ok());
+    let query = commands::DownloadQuery {
+        status: list("status"),
+        exclude_status: list("excludeStatus"),
+        download_type: list("type"),
+        sort_by: params.get("sortBy").cloned(),
+        order: params.get("order").cloned(),
+        limit: number("limit"),
+        offset: number("offset"),
+    };
+    
+    commands::query_downloads(Some(query)).await
+        .map(ResponseJson)
+        .map_err(command_error)
+}
+
+/// POST /downloads with the same body as the `start_download` command's config
+async fn api_add_download(
+    State(app): State<Arc<AppHandle>>,
+    Json(config): Json<commands::DownloadConfig>,
+) -> ApiResult {
+    commands::start_download(config, (*app).clone()).await
+        .map(ResponseJson)
+        .map_err(command_error)
+}
+
+async fn api_pause_download(
+    State(app): State<Arc<AppHandle>>,
+    Path(id): Path<String>,
+) -> ApiResult {
+    commands::pause_download(id, (*app).clone()).await
+        .map(|_| ResponseJson(serde_json::Value::Null))
+        .map_err(command_error)
+}
+
+async fn api_resume_download(
+    State(app): State<Arc<AppHandle>>,
+    Path(id): Path<String>,
+) -> ApiResult {
+    commands::resume_download(id, (*app).clone()).await
+        .map(|_| ResponseJson(serde_json::Value::Null))
+        .map_err(command_error)
+}
+
+async fn api_remove_download(
+    State(app): State<Arc<AppHandle>>,
+    Path(id): Path<String>,
+) -> ApiResult {
+    commands::remove_download(id, (*app).clone()).await
+        .map(|_| ResponseJson(serde_json::Value::Null))
+        .map_err(command_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    fn guarded_router() -> Router {
+        let guard = ApiGuard {
+            token: || Some("secret-token".to_string()),
+            allowed_origins: || vec!["chrome-extension://*".to_string()],
+        };
+        Router::new()
+            .route("/download", post(|| async { "accepted" }))
+            .route_layer(middleware::from_fn_with_state(guard, require_token))
+    }
+
+    fn download_request(authorization: Option<&str>, origin: Option<&str>) -> Request {
+        let mut builder = axum::http::Request::builder().method(Method::POST).uri("/download");
+        if let Some(authorization) = authorization {
+            builder = builder.header(header::AUTHORIZATION, authorization);
+        }
+        if let Some(origin) = origin {
+            builder = builder.header(header::ORIGIN, origin);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_download_rejected_without_token() {
+        let response = guarded_router().oneshot(download_request(None, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = guarded_router().oneshot(download_request(Some("Bearer wrong"), None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_download_accepted_with_token() {
+        let response = guarded_router()
+            .oneshot(download_request(Some("Bearer secret-token"), Some("chrome-extension://abcdef")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_download_rejected_from_unknown_origin() {
+        let response = guarded_router()
+            .oneshot(download_request(Some("Bearer secret-token"), Some("https://evil.example")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_origin_allowlist_matching() {
+        let allowed = vec!["moz-extension://*".to_string(), "http://localhost:5173".to_string()];
+        assert!(origin_allowed("moz-extension://1234", &allowed));
+        assert!(origin_allowed("http://localhost:5173", &allowed));
+        assert!(!origin_allowed("http://localhost:5174", &allowed));
+        assert!(!origin_allowed("https://example.com", &allowed));
+    }
+
+    fn parse_request(body: serde_json::Value) -> BrowserDownloadRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_request_with_cookies() {
+        let request = parse_request(serde_json::json!({
+            "type": "download",
+            "url": "https://files.example.com/report.pdf",
+            "referrer": "https://files.example.com/",
+            "cookies": "session=abc123; theme=dark",
+            "headers": { "Cookie": "stale=1", "X-Requested-With": "XMLHttpRequest" },
+        }));
+        assert!(!format!("{:?}", request).contains("abc123"));
+
+        let event = to_external_download(request).unwrap();
+        assert_eq!(event["source"], "https://files.example.com/report.pdf");
+        assert_eq!(event["headers"]["Cookie"], "session=abc123; theme=dark");
+        assert_eq!(event["headers"]["X-Requested-With"], "XMLHttpRequest");
+        assert_eq!(event["headers"].as_object().unwrap().len(), 2);
+        assert_eq!(event["hasCookies"], true);
+        assert_eq!(event["hasAuth"], true);
+    }
+
+    #[test]
+    fn test_request_without_cookies() {
+        let event = to_external_download(parse_request(serde_json::json!({
+            "type": "download",
+            "url": "https://files.example.com/report.pdf",
+        }))).unwrap();
+        assert!(event["headers"].is_null());
+        assert_eq!(event["hasCookies"], false);
+        assert_eq!(event["hasAuth"], false);
+
+        let event = to_external_download(parse_request(serde_json::json!({
+            "type": "download",
+            "url": "https://files.example.com/report.pdf",
+            "cookies": "  ",
+            "headers": { "Authorization": "Bearer t" },
+        }))).unwrap();
+        assert_eq!(event["hasCookies"], false);
+        assert_eq!(event["hasAuth"], true);
+    }
+}
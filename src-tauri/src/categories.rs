@@ -0,0 +1,102 @@
+use rusqlite::Connection;
+use std::collections::BTreeSet;
+
+/// Trim a category name; blank means "no category"
+pub fn normalize_category(category: &str) -> Option<String> {
+    let category = category.trim();
+    if category.is_empty() {
+        None
+    } else {
+        Some(category.to_string())
+    }
+}
+
+/// Folder for a new download that has no explicit output: the category's folder from the
+/// `categoryFolders` setting when one is configured, otherwise `default_path`
+pub fn resolve_download_folder(
+    category: Option<&str>,
+    category_folders: Option<&serde_json::Value>,
+    default_path: &str,
+) -> String {
+    category
+        .and_then(normalize_category)
+        .and_then(|category| {
+            category_folders?
+                .get(&category)?
+                .as_str()
+                .map(|folder| folder.trim().to_string())
+        })
+        .filter(|folder| !folder.is_empty())
+        .unwrap_or_else(|| default_path.to_string())
+}
+
+/// Every category in use by a download or history entry, plus those with a configured folder, sorted
+pub fn list_categories(conn: &Connection, category_folders: Option<&serde_json::Value>) -> rusqlite::Result<Vec<String>> {
+    let mut categories = BTreeSet::new();
+    
+    let mut stmt = conn.prepare(
+        "SELECT category FROM downloads WHERE category IS NOT NULL
+         UNION
+         SELECT category FROM download_history WHERE category IS NOT NULL"
+    )?;
+    for category in stmt.query_map([], |row| row.get::<_, String>(0))? {
+        if let Some(category) = normalize_category(&category?) {
+            categories.insert(category);
+        }
+    }
+    
+    if let Some(folders) = category_folders.and_then(|f| f.as_object()) {
+        categories.extend(folders.keys().filter_map(|k| normalize_category(k)));
+    }
+    
+    Ok(categories.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+    
+    fn folders() -> serde_json::Value {
+        serde_json::json!({
+            "Movies": "/media/movies",
+            "Music": "  ",
+            "Books": 42,
+        })
+    }
+    
+    #[test]
+    fn test_routes_to_category_folder() {
+        assert_eq!(resolve_download_folder(Some("Movies"), Some(&folders()), "~/Downloads"), "/media/movies");
+        assert_eq!(resolve_download_folder(Some("  Movies "), Some(&folders()), "~/Downloads"), "/media/movies");
+    }
+    
+    #[test]
+    fn test_falls_back_to_default_download_path() {
+        let default_path = "~/Downloads";
+        // No category, unknown category, blank folder, non-string folder, no setting at all
+        assert_eq!(resolve_download_folder(None, Some(&folders()), default_path), default_path);
+        assert_eq!(resolve_download_folder(Some("Games"), Some(&folders()), default_path), default_path);
+        assert_eq!(resolve_download_folder(Some("Music"), Some(&folders()), default_path), default_path);
+        assert_eq!(resolve_download_folder(Some("Books"), Some(&folders()), default_path), default_path);
+        assert_eq!(resolve_download_folder(Some(""), Some(&folders()), default_path), default_path);
+        assert_eq!(resolve_download_folder(Some("Movies"), None, default_path), default_path);
+    }
+    
+    #[test]
+    fn test_list_categories() {
+        let conn = Connection::open_in_memory().unwrap();
+        database::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO downloads (id, source, output, type, status, category) VALUES
+                ('a', 's', 'o', 'http', 'paused', 'Software'),
+                ('b', 's', 'o', 'http', 'paused', NULL);
+             INSERT INTO download_history (id, source, output, type, category) VALUES
+                ('c', 's', 'o', 'http', 'Movies'),
+                ('d', 's', 'o', 'http', 'Software');"
+        ).unwrap();
+        
+        let categories = list_categories(&conn, Some(&folders())).unwrap();
+        assert_eq!(categories, vec!["Books", "Movies", "Music", "Software"]);
+    }
+}
@@ -0,0 +1,189 @@
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Read size when hashing; large enough to be fast, small enough to notice a cancel quickly
+const HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+lazy_static::lazy_static! {
+    // Cancel flags of running verifications, by download ID
+    static ref RUNNING: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+/// Register a verification so it can be cancelled; returns its cancel flag
+pub fn start(download_id: &str) -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    if let Ok(mut running) = RUNNING.lock() {
+        running.insert(download_id.to_string(), flag.clone());
+    }
+    flag
+}
+
+pub fn finish(download_id: &str) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.remove(download_id);
+    }
+}
+
+/// Ask a running verification to stop; false if none was running
+pub fn cancel(download_id: &str) -> bool {
+    match RUNNING.lock().ok().and_then(|running| running.get(download_id).cloned()) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Hash algorithms a download's expected checksum can be given in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// The option key the expected hash is stored under, also reported to the UI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+/// The hash to check a download against, from its options (or metadata)
+/// When several are given the strongest one is used.
+pub fn expected_hash(options: &serde_json::Value) -> Option<(HashAlgorithm, String)> {
+    [HashAlgorithm::Sha256, HashAlgorithm::Sha1, HashAlgorithm::Md5]
+        .into_iter()
+        .find_map(|algorithm| {
+            options.get(algorithm.as_str())
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .map(|hash| (algorithm, hash))
+        })
+}
+
+/// Hash of a file as lowercase hex, streamed so large files don't fill memory
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm, cancelled: &AtomicBool) -> Result<String, String> {
+    match algorithm {
+        HashAlgorithm::Md5 => digest_file::<Md5>(path, cancelled),
+        HashAlgorithm::Sha1 => digest_file::<Sha1>(path, cancelled),
+        HashAlgorithm::Sha256 => digest_file::<Sha256>(path, cancelled),
+    }
+}
+
+/// SHA-256 of a file as lowercase hex
+pub fn sha256_file(path: &Path, cancelled: &AtomicBool) -> Result<String, String> {
+    hash_file(path, HashAlgorithm::Sha256, cancelled)
+}
+
+fn digest_file<D: Digest>(path: &Path, cancelled: &AtomicBool) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err("Checksum verification cancelled".to_string());
+        }
+        let n = file.read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// All files under `dir`, sorted so results are stable
+pub fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        if let Ok(entries) = std::fs::read_dir(&current) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_known_fixture() {
+        let path = std::env::temp_dir().join(format!("accelara-checksum-{}.txt", std::process::id()));
+        std::fs::write(&path, b"hello world").unwrap();
+        
+        let hash = sha256_file(&path, &AtomicBool::new(false)).unwrap();
+        assert_eq!(hash, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+        
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_md5_and_sha1_known_fixture() {
+        let path = std::env::temp_dir().join(format!("accelara-checksum-legacy-{}.txt", std::process::id()));
+        std::fs::write(&path, b"hello world").unwrap();
+        
+        let not_cancelled = AtomicBool::new(false);
+        assert_eq!(hash_file(&path, HashAlgorithm::Md5, &not_cancelled).unwrap(), "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert_eq!(hash_file(&path, HashAlgorithm::Sha1, &not_cancelled).unwrap(), "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+        assert!(hash_file(&path, HashAlgorithm::Md5, &AtomicBool::new(true)).is_err());
+        
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_expected_hash_prefers_strongest() {
+        let options = serde_json::json!({ "md5": "ABC", "sha1": " def " });
+        assert_eq!(expected_hash(&options), Some((HashAlgorithm::Sha1, "def".to_string())));
+        
+        let options = serde_json::json!({ "md5": "abc", "sha256": "" });
+        assert_eq!(expected_hash(&options), Some((HashAlgorithm::Md5, "abc".to_string())));
+        
+        assert_eq!(expected_hash(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn test_sha256_cancelled() {
+        let path = std::env::temp_dir().join(format!("accelara-checksum-cancel-{}.txt", std::process::id()));
+        std::fs::write(&path, b"data").unwrap();
+        
+        assert!(sha256_file(&path, &AtomicBool::new(true)).is_err());
+        
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_cancel_registered_verification() {
+        let flag = start("test-download");
+        assert!(cancel("test-download"));
+        assert!(flag.load(Ordering::SeqCst));
+        finish("test-download");
+        assert!(!cancel("test-download"));
+    }
+}
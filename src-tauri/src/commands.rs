@@ -1,5 +1,14 @@
+use crate::bandwidth;
+use crate::binaries;
+use crate::categories;
+use crate::credentials;
 use crate::database;
 use crate::download;
+use crate::peer_budget;
+use crate::process_cleanup;
+use crate::queue;
+use crate::schedule;
+use crate::speed_test;
 use crate::utils;
 use crate::updater;
 use serde::{Deserialize, Serialize};
@@ -24,6 +33,37 @@ pub struct DownloadConfig {
     pub source: String,
     pub output: Option<String>,
     pub options: Option<serde_json::Value>,
+    /// Set by the frontend after the user confirmed a download above the size threshold
+    #[serde(default)]
+    pub confirmed: bool,
+    /// Category to file the download under; picks the folder when `output` is not given
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Other URLs serving the same file, tried in order if the download fails (HTTP only)
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// Raw .torrent contents, base64 or a base64 data URL, for torrents dropped or sent as bytes
+    #[serde(default, rename = "torrentData", alias = "torrent_data")]
+    pub torrent_data: Option<String>,
+}
+
+// Helper to read the connect/read timeout settings for wrapper probes
+// Returns the args to forward to the Go binary and the overall deadline for the probe
+async fn probe_timeouts() -> (Vec<String>, std::time::Duration) {
+    let settings = get_settings().await.unwrap_or_default();
+    let connect_timeout = settings.get("connectTimeout").and_then(|v| v.as_u64()).unwrap_or(15);
+    let read_timeout = settings.get("readTimeout").and_then(|v| v.as_u64()).unwrap_or(60);
+    
+    let args = vec![
+        "--connect-timeout".to_string(),
+        connect_timeout.to_string(),
+        "--read-timeout".to_string(),
+        read_timeout.to_string(),
+    ];
+    
+    // Give the wrapper a little slack beyond its own timeouts before we give up on it
+    let deadline = std::time::Duration::from_secs(connect_timeout + read_timeout + 5);
+    (args, deadline)
 }
 
 // Handler 1: inspect-torrent
@@ -51,14 +91,22 @@ pub async fn inspect_torrent(source: String) -> Result<serde_json::Value, String
     logger::log_info("inspect_torrent", &format!("Verified binary path: {}", verified_binary.display()));
     
     let working_dir = utils::get_working_directory();
+    let (timeout_args, deadline) = probe_timeouts().await;
     
-    let output = TokioCommand::new(&verified_binary)
+    let output = tokio::time::timeout(deadline, TokioCommand::new(&verified_binary)
         .args(&["--inspect", "--source", &source])
+        .args(&timeout_args)
         .current_dir(&working_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .output()
+        .kill_on_drop(true)
+        .output())
         .await
+        .map_err(|_| {
+            let error = format!("Torrent inspection timed out after {}s", deadline.as_secs());
+            logger::log_error("inspect_torrent", &error);
+            error
+        })?
         .map_err(|e| format!("Failed to spawn process: {}", e))?;
     
     if !output.status.success() {
@@ -69,13 +117,33 @@ pub async fn inspect_torrent(source: String) -> Result<serde_json::Value, String
     let stdout = String::from_utf8_lossy(&output.stdout);
     
     // Parse JSON output
-    serde_json::from_str(&stdout)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))
+    let mut info: serde_json::Value = serde_json::from_str(&stdout)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    
+    // Number the files so the frontend can build a selection for set_torrent_file_selection
+    if let Some(files) = info.get_mut("files").and_then(|f| f.as_array_mut()) {
+        for (index, file) in files.iter_mut().enumerate() {
+            let name = file.get("path")
+                .and_then(|p| p.as_str())
+                .map(|p| p.rsplit('/').next().unwrap_or(p).to_string());
+            file["index"] = serde_json::json!(index);
+            file["name"] = serde_json::json!(name);
+        }
+    }
+    
+    Ok(info)
 }
 
 // Handler 2: get-http-info
 #[command]
-pub async fn get_http_info(source: String) -> Result<serde_json::Value, String> {
+pub async fn get_http_info(source: String, options: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
+    // The wrapper sees through download pages; our own HEAD adds the final URL and reliable range support
+    let (info, head) = tokio::join!(wrapper_http_info(&source, &options), probe_head(&source, &options));
+    Ok(normalize_http_info(info?, head.ok().as_ref(), &source))
+}
+
+// Helper to run the Go binary's --http-info probe
+async fn wrapper_http_info(source: &str, options: &Option<serde_json::Value>) -> Result<serde_json::Value, String> {
     let go_binary = utils::find_go_binary()
         .ok_or_else(|| "Go binary (api-wrapper) not found".to_string())?;
     
@@ -83,14 +151,31 @@ pub async fn get_http_info(source: String) -> Result<serde_json::Value, String>
         .map_err(|e| format!("Binary verification failed: {}", e))?;
     
     let working_dir = utils::get_working_directory();
+    let (timeout_args, deadline) = probe_timeouts().await;
+    let mut auth_args = Vec::new();
+    if let Some(opts) = options {
+        for (name, value) in custom_headers(opts) {
+            auth_args.push("--header".to_string());
+            auth_args.push(format!("{}: {}", name, value));
+        }
+        if let Some(auth) = auth_header_value(opts) {
+            auth_args.push("--auth".to_string());
+            auth_args.push(auth);
+        }
+    }
+    let auth_args = binaries::drop_unsupported_args(&verified_binary, auth_args, &["--header", "--auth"]).await;
     
-    let output = TokioCommand::new(&verified_binary)
-        .args(&["--http-info", "--source", &source])
+    let output = tokio::time::timeout(deadline, TokioCommand::new(&verified_binary)
+        .args(&["--http-info", "--source", source])
+        .args(&timeout_args)
+        .args(&auth_args)
         .current_dir(&working_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .output()
+        .kill_on_drop(true)
+        .output())
         .await
+        .map_err(|_| format!("HTTP info request timed out after {}s", deadline.as_secs()))?
         .map_err(|e| format!("Failed to spawn process: {}", e))?;
     
     if !output.status.success() {
@@ -105,6 +190,121 @@ pub async fn get_http_info(source: String) -> Result<serde_json::Value, String>
         .map_err(|e| format!("Failed to parse JSON: {}", e))
 }
 
+/// What a HEAD request (after redirects) says about a file
+#[derive(Debug, Clone, PartialEq)]
+struct HeadInfo {
+    final_url: String,
+    accept_ranges: bool,
+    content_length: Option<u64>,
+    suggested_filename: Option<String>,
+}
+
+// Helper to HEAD a URL and read the headers that matter for chunking (header names are case-insensitive)
+async fn probe_head(source: &str, options: &Option<serde_json::Value>) -> Result<HeadInfo, String> {
+    let response = head_request(source, options, "get_http_info").await?;
+    if !response.status().is_success() {
+        return Err(format!("Server responded with {}", response.status()));
+    }
+    
+    let header = |name: reqwest::header::HeaderName| {
+        response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.trim().to_string())
+    };
+    Ok(HeadInfo {
+        final_url: response.url().to_string(),
+        accept_ranges: header(reqwest::header::ACCEPT_RANGES)
+            .map(|v| v.split(',').any(|unit| unit.trim().eq_ignore_ascii_case("bytes")))
+            .unwrap_or(false),
+        content_length: header(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse().ok()),
+        suggested_filename: header(reqwest::header::CONTENT_DISPOSITION).and_then(|v| content_disposition_filename(&v)),
+    })
+}
+
+/// The wrapper's --http-info output with `acceptRanges`, `contentLength`, `suggestedFilename` and
+/// `finalUrl` always present, preferring our own HEAD probe where we have one
+fn normalize_http_info(mut info: serde_json::Value, head: Option<&HeadInfo>, source: &str) -> serde_json::Value {
+    if !info.is_object() {
+        info = serde_json::json!({});
+    }
+    
+    let accept_ranges = head.map(|h| h.accept_ranges).unwrap_or(false)
+        || info.get("acceptRanges").and_then(|v| v.as_bool()).unwrap_or(false);
+    let content_length = head.and_then(|h| h.content_length)
+        .or_else(|| info.get("totalSize").and_then(|v| v.as_u64()))
+        .filter(|len| *len > 0);
+    let final_url = head.map(|h| h.final_url.clone()).unwrap_or_else(|| source.to_string());
+    
+    info["acceptRanges"] = serde_json::json!(accept_ranges);
+    // Surface whether the server honours Range requests so it can be stored with the download
+    info["supportsRange"] = serde_json::json!(accept_ranges);
+    info["contentLength"] = serde_json::json!(content_length);
+    info["suggestedFilename"] = serde_json::json!(head.and_then(|h| h.suggested_filename.clone()));
+    info["finalUrl"] = serde_json::json!(final_url);
+    info
+}
+
+// Helper to format a stored file selection as the comma-separated list the wrapper expects
+fn select_files_arg(selected: &serde_json::Value) -> Option<String> {
+    let indices: Vec<String> = selected.as_array()?
+        .iter()
+        .filter_map(|v| v.as_u64())
+        .map(|i| i.to_string())
+        .collect();
+    if indices.is_empty() {
+        None
+    } else {
+        Some(indices.join(","))
+    }
+}
+
+/// Options with the download's `rateLimitOverride` (see `set_download_rate_limit`) as its limit
+/// The override is kept alongside so an active bandwidth schedule rule can only tighten it.
+fn with_rate_limit_override(options: Option<serde_json::Value>, metadata: &serde_json::Value) -> Option<serde_json::Value> {
+    match metadata.get("rateLimitOverride").and_then(|v| v.as_str()) {
+        Some(limit) => {
+            let mut options = options.filter(|o| o.is_object()).unwrap_or_else(|| serde_json::json!({}));
+            options["rateLimit"] = serde_json::json!(limit);
+            options["rateLimitOverride"] = serde_json::json!(limit);
+            Some(options)
+        }
+        None => options,
+    }
+}
+
+/// A limit once the active schedule rule's cap (empty = the rule doesn't cap it) is applied
+/// The cap replaces the download's own limit, except for a per-download override where the
+/// stricter of the two wins (an empty override is unlimited, so the cap does).
+fn limit_with_rule(own: Option<String>, overridden: bool, rule_cap: Option<&str>) -> Option<String> {
+    let cap = match rule_cap.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(cap) => cap,
+        None => return own,
+    };
+    let own_rate = own.as_deref().and_then(bandwidth::parse_rate).filter(|r| *r > 0);
+    let cap_rate = bandwidth::parse_rate(cap).filter(|r| *r > 0);
+    match (overridden, own_rate, cap_rate) {
+        (true, Some(own_rate), Some(cap_rate)) if own_rate <= cap_rate => own,
+        (true, Some(_), None) => own,
+        _ => Some(cap.to_string()),
+    }
+}
+
+/// Fail with a clear message when the disk can't hold the rest of a download
+/// Unknown sizes (total 0) and filesystems we can't measure are let through.
+fn check_free_space(folder: &std::path::Path, total: i64, downloaded: i64) -> Result<(), String> {
+    let remaining = (total - downloaded).max(0) as u64;
+    if remaining == 0 {
+        return Ok(());
+    }
+    match utils::available_space(folder) {
+        Some(available) if available < remaining => Err(format!(
+            "Disk full: {} more needed but only {} free in {}. Free up space and resume",
+            format_bytes(remaining),
+            format_bytes(available),
+            folder.display()
+        )),
+        _ => Ok(()),
+    }
+}
+
 // Helper function to build command args for downloads
 fn build_command_args(
     source: &str,
@@ -161,8 +361,14 @@ fn build_command_args(
         
         // Rate limit (download speed limit) - Go uses --limit, not --rate-limit
         // Supports both rateLimit/rate_limit and limit
-        if let Some(rate_limit) = get_str("rateLimit", "rate_limit")
-            .or_else(|| get_str("limit", "limit")) {
+        // An active bandwidth schedule rule's cap takes precedence over the download's own limit
+        let scheduled = schedule::active_rule();
+        let rate_limit = limit_with_rule(
+            get_str("rateLimit", "rate_limit").or_else(|| get_str("limit", "limit")),
+            opts.get("rateLimitOverride").is_some(),
+            scheduled.as_ref().map(|rule| rule.rate_limit.as_str()),
+        );
+        if let Some(rate_limit) = rate_limit {
             if !rate_limit.is_empty() {
                 args.push("--limit".to_string());
                 args.push(rate_limit);
@@ -170,7 +376,12 @@ fn build_command_args(
         }
         
         // BitTorrent upload limit (supports both btUploadLimit and bt_upload_limit)
-        if let Some(bt_upload_limit) = get_str("btUploadLimit", "bt_upload_limit") {
+        let bt_upload_limit = limit_with_rule(
+            get_str("btUploadLimit", "bt_upload_limit"),
+            false,
+            scheduled.as_ref().map(|rule| rule.upload_limit.as_str()),
+        );
+        if let Some(bt_upload_limit) = bt_upload_limit {
             if !bt_upload_limit.is_empty() {
                 args.push("--bt-upload-limit".to_string());
                 args.push(bt_upload_limit);
@@ -193,6 +404,14 @@ fn build_command_args(
             }
         }
         
+        // BitTorrent file selection (indices into the torrent's file list)
+        if let Some(selected) = opts.get("selectedFiles").or_else(|| opts.get("selected_files")) {
+            if let Some(select_files) = select_files_arg(selected) {
+                args.push("--bt-select-files".to_string());
+                args.push(select_files);
+            }
+        }
+        
         // BitTorrent port (supports both btPort and bt_port)
         if let Some(bt_port) = get_u64("btPort", "bt_port") {
             args.push("--bt-port".to_string());
@@ -217,7 +436,36 @@ fn build_command_args(
             args.push(retries.to_string());
         }
         
+        // Custom CA certificate for private/self-signed servers
+        if let Some(ca_cert) = get_str("caCertPath", "ca_cert_path") {
+            if !ca_cert.is_empty() {
+                args.push("--ca-cert".to_string());
+                args.push(utils::expand_path(&ca_cert));
+            }
+        }
+        
+        // Skipping certificate verification is only ever a per-download opt-in
+        if get_bool("insecureSkipVerify", "insecure_skip_verify").unwrap_or(false) {
+            use crate::logger;
+            logger::log_warning("build_command_args", &format!("TLS certificate verification disabled for download {}", download_id));
+            args.push("--insecure".to_string());
+        }
+        
+        // Custom request headers, one --header "Name: value" each
+        for (name, value) in custom_headers(opts) {
+            args.push("--header".to_string());
+            args.push(format!("{}: {}", name, value));
+        }
+        
+        // Credentials go as the Authorization value itself (Basic ... / Bearer ...)
+        if let Some(auth) = auth_header_value(opts) {
+            args.push("--auth".to_string());
+            args.push(auth);
+        }
+        
         // SHA256 hash verification
+        // md5/sha1 aren't passed on: the wrapper only has --sha256 and exits on unknown flags,
+        // so those are checked by download::verify_completed_checksum once the download finishes
         if let Some(sha256) = get_str("sha256", "sha256") {
             if !sha256.is_empty() {
                 args.push("--sha256".to_string());
@@ -229,9 +477,448 @@ fn build_command_args(
     args
 }
 
+/// Where derived output paths go, from the `createSubfolderPerDownload` and `torrentSubfolder` settings
+#[derive(Debug, Default)]
+pub struct OutputLayout {
+    /// HTTP: put each file in its own folder named after it (`folder/name/name.ext`)
+    pub subfolder_per_download: bool,
+    /// Torrents: collect them in this subfolder of `folder` (empty = directly in `folder`)
+    pub torrent_subfolder: String,
+}
+
+impl OutputLayout {
+    pub fn from_settings() -> Self {
+        OutputLayout {
+            subfolder_per_download: database::get_setting("createSubfolderPerDownload")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            torrent_subfolder: database::get_setting("torrentSubfolder")
+                .and_then(|v| v.as_str().map(|s| s.trim().to_string()))
+                // A plain folder name only - no escaping the download folder
+                .filter(|s| !s.contains("..") && !s.contains('/') && !s.contains('\\'))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Derive the output path for a source inside `folder`
+/// For torrents, use the directory (Go will create torrent name folder inside)
+/// For HTTP, generate filename from source
+pub fn derive_output_path(source: &str, folder: &str) -> String {
+    derive_output_path_with(source, folder, &OutputLayout::from_settings())
+}
+
+fn derive_output_path_with(source: &str, folder: &str, layout: &OutputLayout) -> String {
+    if is_torrent_source(source) {
+        // Torrent: output is the directory where torrent files will be saved
+        if layout.torrent_subfolder.is_empty() {
+            folder.to_string()
+        } else {
+            format!("{}/{}", folder, layout.torrent_subfolder)
+        }
+    } else {
+        // HTTP: output is the file path
+        let filename = std::path::Path::new(source)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("download");
+        if layout.subfolder_per_download {
+            let stem = std::path::Path::new(filename)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(filename);
+            format!("{}/{}/{}", folder, stem, filename)
+        } else {
+            format!("{}/{}", folder, filename)
+        }
+    }
+}
+
+// Helper to read the TLS options (custom CA, skip verification) from a download's options
+fn tls_options(options: &Option<serde_json::Value>) -> (Option<String>, bool) {
+    let opts = match options {
+        Some(opts) => opts,
+        None => return (None, false),
+    };
+    let ca_cert = opts.get("caCertPath")
+        .or_else(|| opts.get("ca_cert_path"))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string());
+    let insecure = opts.get("insecureSkipVerify")
+        .or_else(|| opts.get("insecure_skip_verify"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    (ca_cert, insecure)
+}
+
+// Helper to read the custom request headers from `options.headers`, sorted by name
+// Headers that can't be sent as given (empty or ':' in the name, line breaks anywhere) are skipped.
+fn custom_headers(opts: &serde_json::Value) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = opts.get("headers")
+        .and_then(|h| h.as_object())
+        .map(|map| map.iter()
+            .filter_map(|(name, value)| Some((name.trim().to_string(), value.as_str()?.trim().to_string())))
+            .filter(|(name, value)| {
+                !name.is_empty()
+                    && name.chars().all(|c| c.is_ascii_graphic() && c != ':')
+                    && !value.contains(|c: char| c == '\r' || c == '\n' || c == '\0')
+            })
+            .collect())
+        .unwrap_or_default();
+    headers.sort();
+    headers
+}
+
+// Helper to turn `options.auth` into an Authorization header value
+// Accepts {type: "basic", username, password}, {type: "bearer", token} or a bare token string.
+fn auth_header_value(opts: &serde_json::Value) -> Option<String> {
+    use base64::Engine;
+    
+    let auth = opts.get("auth")?;
+    let header = match auth {
+        serde_json::Value::String(token) => format!("Bearer {}", token.trim()),
+        serde_json::Value::Object(_) => {
+            let field = |key: &str| auth.get(key).and_then(|v| v.as_str()).unwrap_or("");
+            match field("type").to_lowercase().as_str() {
+                "basic" => format!("Basic {}", base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", field("username"), field("password")))),
+                "bearer" => format!("Bearer {}", field("token").trim()),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(header).filter(|h| !h.ends_with(' ') && !h.contains(|c: char| c == '\r' || c == '\n'))
+}
+
+// Helper to mask header values and credentials in wrapper args before they're logged
+// Header names stay visible so a log still shows which headers were sent.
+fn redact_args(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    for (i, arg) in args.iter().enumerate() {
+        let value = match i.checked_sub(1).map(|prev| args[prev].as_str()) {
+            Some("--header") => match arg.split_once(':') {
+                Some((name, _)) => format!("{}: <redacted>", name),
+                None => "<redacted>".to_string(),
+            },
+            Some("--auth") => "<redacted>".to_string(),
+            _ => arg.clone(),
+        };
+        redacted.push(value);
+    }
+    redacted
+}
+
+// Helper to get a download's stored options, with any headers/auth kept in the keyring put back
+fn stored_options(download_id: &str, metadata: &serde_json::Value) -> Option<serde_json::Value> {
+    credentials::reveal(download_id, metadata.get("options").cloned())
+}
+
+// Helper to send a HEAD request with the connect timeout, TLS options and headers downloads use
+// `context` is only used for log messages
+async fn head_request(
+    source: &str,
+    options: &Option<serde_json::Value>,
+    context: &str,
+) -> Result<reqwest::Response, String> {
+    let settings = get_settings().await.unwrap_or_default();
+    let connect_timeout = settings.get("connectTimeout").and_then(|v| v.as_u64()).unwrap_or(15);
+    
+    let (ca_cert, insecure) = tls_options(options);
+    let builder = reqwest::Client::builder()
+        .user_agent("ACCELARA")
+        .connect_timeout(std::time::Duration::from_secs(connect_timeout))
+        .timeout(std::time::Duration::from_secs(connect_timeout + 15));
+    let client = utils::apply_tls_options(builder, ca_cert.as_deref(), insecure, context)?
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    
+    // Same headers and credentials as the download itself, or protected sources answer 401/403
+    let mut request = client.head(source);
+    if let Some(opts) = options {
+        for (name, value) in custom_headers(opts) {
+            request = request.header(name, value);
+        }
+        if let Some(auth) = auth_header_value(opts) {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
+    }
+    request.send().await
+        .map_err(|e| format!("Request failed: {}", e))
+}
+
+// Helper to fetch the HTTP validators (ETag/Last-Modified/Content-Length) of a source
+// Returns None if the server couldn't be reached; missing headers are stored as null
+async fn fetch_http_validators(source: &str, options: &Option<serde_json::Value>) -> Option<serde_json::Value> {
+    let response = head_request(source, options, "fetch_http_validators").await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    
+    let header = |name: reqwest::header::HeaderName| {
+        response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string())
+    };
+    
+    Some(serde_json::json!({
+        "etag": header(reqwest::header::ETAG),
+        "lastModified": header(reqwest::header::LAST_MODIFIED),
+        "contentLength": header(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse::<u64>().ok()),
+    }))
+}
+
+// Helper to decide whether the remote resource changed between two validator sets
+// The strongest validator present on both sides wins: ETag, then Last-Modified, then size
+fn source_changed(stored: &serde_json::Value, current: &serde_json::Value) -> bool {
+    for key in ["etag", "lastModified", "contentLength"] {
+        let stored_value = stored.get(key).filter(|v| !v.is_null());
+        let current_value = current.get(key).filter(|v| !v.is_null());
+        if let (Some(a), Some(b)) = (stored_value, current_value) {
+            return a != b;
+        }
+    }
+    false
+}
+
+// Helper to pick the value sent as If-Range (an ETag if we have one, otherwise Last-Modified)
+fn if_range_value(validators: &serde_json::Value) -> Option<String> {
+    validators.get("etag")
+        .and_then(|v| v.as_str())
+        // Weak ETags aren't allowed in If-Range
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| validators.get("lastModified").and_then(|v| v.as_str()))
+        .map(|v| v.to_string())
+}
+
+// Helper to find the size of a source before downloading it
+// Uses the probe info the frontend already attached if present, otherwise asks the Go binary
+async fn probe_source_size(config: &DownloadConfig) -> Option<u64> {
+    let known_size = config.options.as_ref()
+        .and_then(|opts| opts.get("httpInfo").or_else(|| opts.get("torrentInfo")))
+        .and_then(|info| info.get("totalSize"))
+        .and_then(|v| v.as_u64());
+    if known_size.is_some() {
+        return known_size;
+    }
+    
+    let info = if is_torrent_source(&config.source) {
+        inspect_torrent(config.source.clone()).await.ok()?
+    } else {
+        get_http_info(config.source.clone(), config.options.clone()).await.ok()?
+    };
+    info.get("totalSize").and_then(|v| v.as_u64())
+}
+
+/// Folder the .torrent files sent as `torrentData` are kept in, until their download is removed
+fn torrent_data_dir() -> PathBuf {
+    use crate::paths;
+    
+    paths::temp_dir().join("torrents")
+}
+
+/// Decode `torrentData` (plain base64 or a `data:...;base64,` URL), which must hold a bencoded dictionary
+pub fn decode_torrent_data(data: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    
+    let data = data.trim();
+    let encoded = match data.strip_prefix("data:") {
+        Some(url) => url.split_once(',')
+            .filter(|(header, _)| header.ends_with(";base64"))
+            .map(|(_, body)| body)
+            .ok_or_else(|| "Torrent data URL must be base64-encoded".to_string())?,
+        None => data,
+    };
+    let encoded: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)
+        .map_err(|e| format!("Invalid torrent data: {}", e))?;
+    if bytes.first() != Some(&b'd') || bytes.last() != Some(&b'e') {
+        return Err("Torrent data is not a bencoded dictionary".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Replace a config's `torrentData` with a saved .torrent file as its source
+/// Returns the file written, so the caller can delete it if the download isn't created after all.
+fn materialize_torrent_data(config: &mut DownloadConfig) -> Result<Option<PathBuf>, String> {
+    let data = match config.torrent_data.take() {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    let bytes = decode_torrent_data(&data)?;
+    
+    let dir = torrent_data_dir();
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.torrent", nanoid::nanoid!(12)));
+    fs::write(&path, &bytes)
+        .map_err(|e| format!("Failed to save torrent data: {}", e))?;
+    
+    config.source = path.to_string_lossy().to_string();
+    Ok(Some(path))
+}
+
+/// Delete a .torrent saved from `torrentData` once no download uses it any more
+/// The wrapper reads the file again on every resume, so it has to outlive the first start.
+pub fn remove_saved_torrent_data(conn: &rusqlite::Connection, source: &str) {
+    let path = std::path::Path::new(source);
+    if !path.starts_with(torrent_data_dir()) {
+        return;
+    }
+    let users: i64 = conn.query_row("SELECT COUNT(*) FROM downloads WHERE source = ?1", [source], |row| row.get(0))
+        .unwrap_or(1);
+    if users == 0 {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn is_torrent_source(source: &str) -> bool {
+    source.starts_with("magnet:") ||
+        source.ends_with(".torrent") ||
+        std::path::Path::new(source).extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("torrent"))
+            .unwrap_or(false)
+}
+
+/// Summary of a torrent's inspect output kept in metadata as `torrentMeta`
+fn torrent_meta(info: &serde_json::Value, source: &str) -> serde_json::Value {
+    let file_count = info.get("fileCount").and_then(|v| v.as_u64())
+        .or_else(|| info.get("files").and_then(|f| f.as_array()).map(|f| f.len() as u64));
+    let trackers = utils::parse_magnet(source).map(|m| m.trackers).unwrap_or_default();
+    serde_json::json!({
+        "name": info.get("name").and_then(|v| v.as_str()).filter(|n| !n.is_empty()),
+        "totalSize": info.get("totalSize").and_then(|v| v.as_u64()),
+        "fileCount": file_count,
+        "trackers": trackers,
+    })
+}
+
+/// A readable name for a download: the torrent's or file's own name, then the magnet's `dn`,
+/// then the last segment of the source URL or path
+pub fn derive_display_name(source: &str, metadata: &serde_json::Value) -> String {
+    let non_empty = |v: Option<&serde_json::Value>| -> Option<String> {
+        v.and_then(|v| v.as_str())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    };
+    
+    let known = non_empty(metadata.pointer("/torrentMeta/name"))
+        .or_else(|| non_empty(metadata.pointer("/options/torrentInfo/name")))
+        .or_else(|| non_empty(metadata.pointer("/torrentInfo/name")))
+        .or_else(|| non_empty(metadata.get("fileName")));
+    if let Some(name) = known {
+        return name;
+    }
+    
+    if source.starts_with("magnet:") {
+        return match utils::parse_magnet(source) {
+            Some(magnet) => magnet.name
+                .or(magnet.info_hash_v1)
+                .or(magnet.info_hash_v2)
+                .unwrap_or_else(|| source.to_string()),
+            None => source.to_string(),
+        };
+    }
+    
+    // URLs and local paths: the last non-empty segment, without any query or fragment
+    let path = source.split(['?', '#']).next().unwrap_or(source);
+    path.rsplit(['/', '\\'])
+        .find(|segment| !segment.is_empty() && !segment.ends_with(':'))
+        .map(|segment| segment.to_string())
+        .unwrap_or_else(|| source.to_string())
+}
+
+/// Inspect a newly added torrent once and keep its name, size and file count in metadata
+/// Runs in the background: resolving a magnet's metadata can take a while.
+async fn record_torrent_meta(download_id: String, source: String, app: tauri::AppHandle) {
+    use crate::logger;
+    
+    let info = match inspect_torrent(source.clone()).await {
+        Ok(info) => info,
+        Err(e) => {
+            logger::log_warning("start_download", &format!("Couldn't inspect torrent {}: {}", download_id, e));
+            return;
+        }
+    };
+    let meta = torrent_meta(&info, &source);
+    
+    let conn = match database::get_connection() {
+        Ok(conn) => conn,
+        Err(_) => return,
+    };
+    let metadata_str: Option<String> = match conn.query_row(
+        "SELECT metadata FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| row.get(0),
+    ) {
+        Ok(metadata_str) => metadata_str,
+        // Removed while we were inspecting
+        Err(_) => return,
+    };
+    let mut metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    metadata["torrentMeta"] = meta.clone();
+    let _ = conn.execute(
+        "UPDATE downloads SET metadata = ? WHERE id = ?",
+        rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+    );
+    
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "displayName": derive_display_name(&source, &metadata),
+        "torrentMeta": meta,
+    }));
+}
+
 // Handler 3: start-download
 #[command]
 pub async fn start_download(
+    mut config: DownloadConfig,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    // Dropped .torrent contents become a file the wrapper (and the size probe below) can read
+    let saved_torrent = materialize_torrent_data(&mut config)?;
+    let discard_saved_torrent = || {
+        if let Some(path) = &saved_torrent {
+            let _ = fs::remove_file(path);
+        }
+    };
+    
+    // Ask the user to confirm huge downloads before creating them
+    if !config.confirmed {
+        let settings = get_settings().await.unwrap_or_default();
+        let confirm_large = settings.get("confirmLargeDownloads").and_then(|v| v.as_bool()).unwrap_or(false);
+        let threshold = settings.get("largeDownloadThreshold").and_then(|v| v.as_u64()).unwrap_or(0);
+        
+        if confirm_large && threshold > 0 {
+            if let Some(size) = probe_source_size(&config).await {
+                if size > threshold {
+                    use crate::logger;
+                    logger::log_info("start_download", &format!("{} is {} bytes (threshold {}), asking for confirmation", config.source, size, threshold));
+                    // The frontend sends the torrent data again once confirmed
+                    discard_saved_torrent();
+                    return Ok(serde_json::json!({
+                        "needsConfirmation": true,
+                        "size": size,
+                        "threshold": threshold,
+                    }));
+                }
+            }
+        }
+    }
+    
+    let download_id = start_download_internal(config, app).await
+        .inspect_err(|_| discard_saved_torrent())?;
+    Ok(serde_json::json!({
+        "downloadId": download_id,
+    }))
+}
+
+/// Create a paused download and return its ID (shared by start_download and batch adds)
+pub async fn start_download_internal(
     config: DownloadConfig,
     app: tauri::AppHandle,
 ) -> Result<String, String> {
@@ -248,34 +935,19 @@ pub async fn start_download(
     // Determine output path
     // For torrents, output should be a directory (the Go code uses it as DataDir)
     // For HTTP, output should be a file path
+    let category = config.category.as_deref().and_then(categories::normalize_category);
     let output_path = if let Some(output) = &config.output {
         output.clone()
     } else {
-        // Use default download path from settings
+        // Use the category's folder, or the default download path from settings
         let settings = get_settings().await.unwrap_or_default();
         let default_path = settings
             .get("defaultDownloadPath")
             .and_then(|v| v.as_str())
             .unwrap_or("~/Downloads");
+        let folder = categories::resolve_download_folder(category.as_deref(), settings.get("categoryFolders"), default_path);
         
-        // For torrents, use the directory as-is (Go will create torrent name folder inside)
-        // For HTTP, generate filename from source
-        if config.source.starts_with("magnet:") || 
-           config.source.ends_with(".torrent") ||
-           std::path::Path::new(&config.source).extension()
-               .and_then(|e| e.to_str())
-               .map(|e| e.eq_ignore_ascii_case("torrent"))
-               .unwrap_or(false) {
-            // Torrent: output is the directory where torrent files will be saved
-            default_path.to_string()
-        } else {
-            // HTTP: output is the file path
-            let filename = std::path::Path::new(&config.source)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("download");
-            format!("{}/{}", default_path, filename)
-        }
+        derive_output_path(&config.source, &folder)
     };
     
     // Determine download type
@@ -293,6 +965,41 @@ pub async fn start_download(
         "http"
     };
     
+    // Torrents can download into a working folder and move to a library folder when finished
+    let torrent_dir_option = |key: &str| -> Option<String> {
+        config.options.as_ref()
+            .and_then(|opts| opts.get(key))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.to_string())
+    };
+    let is_torrent = download_type == "torrent" || download_type == "magnet";
+    let complete_dir = if is_torrent { torrent_dir_option("completeDir") } else { None };
+    let output_path = match torrent_dir_option("incompleteDir") {
+        Some(incomplete_dir) if is_torrent => incomplete_dir,
+        _ => output_path,
+    };
+    if let Some(complete_dir) = &complete_dir {
+        let complete_dir = PathBuf::from(utils::expand_path(complete_dir));
+        std::fs::create_dir_all(&complete_dir)
+            .map_err(|e| format!("Failed to create {}: {}", complete_dir.display(), e))?;
+        utils::check_dir_writable(&complete_dir)?;
+    }
+    
+    // Fail early on a custom CA that can't be used rather than when the wrapper starts
+    if let (Some(ca_cert), _) = tls_options(&config.options) {
+        utils::validate_ca_cert(&ca_cert)?;
+    }
+    
+    // Subfolders added by the output layout settings are created here; the chosen folder itself must exist
+    let download_folder = utils::download_folder(&output_path, download_type);
+    if !download_folder.exists() && download_folder.parent().map(|p| p.is_dir()).unwrap_or(false) {
+        let _ = std::fs::create_dir(&download_folder);
+    }
+    
+    // Catch read-only or missing folders now instead of as a cryptic failure mid-download
+    utils::check_dir_writable(&download_folder)?;
+    
     // Save to database with paused status
     let conn = database::get_connection()
         .map_err(|e| format!("Database error: {}", e))?;
@@ -302,6 +1009,21 @@ pub async fn start_download(
         "pause_reason": "Paused - click resume to start",
         "options": config.options,
     });
+    // Headers/auth go to the keyring rather than into the database where possible
+    if let Some(options) = metadata.get_mut("options").filter(|o| o.is_object()) {
+        credentials::protect(&download_id, options);
+    }
+    
+    // Per-download flag to open the file once it finishes (still gated by allowAutoOpen)
+    if let Some(open_on_complete) = config.options.as_ref()
+        .and_then(|opts| opts.get("openOnComplete"))
+        .and_then(|v| v.as_bool())
+    {
+        metadata["openOnComplete"] = serde_json::json!(open_on_complete);
+    }
+    if let Some(complete_dir) = &complete_dir {
+        metadata["completeDir"] = serde_json::json!(complete_dir);
+    }
     
     // If HTTP info is provided in options, store it in metadata
     if let Some(opts) = &config.options {
@@ -311,12 +1033,46 @@ pub async fn start_download(
             if let Some(file_name) = http_info.get("fileName").and_then(|v| v.as_str()) {
                 metadata["fileName"] = serde_json::json!(file_name);
             }
+            // Remember whether the server supports Range requests (needed to resume)
+            if let Some(supports_range) = http_info.get("supportsRange")
+                .or_else(|| http_info.get("acceptRanges"))
+                .and_then(|v| v.as_bool()) {
+                metadata["supportsRange"] = serde_json::json!(supports_range);
+            }
+        }
+    }
+    
+    // Torrents are listed by name rather than by their magnet link or .torrent path
+    let torrent_info_name = config.options.as_ref()
+        .and_then(|opts| opts.get("torrentInfo"))
+        .filter(|info| info.get("name").and_then(|n| n.as_str()).map(|n| !n.is_empty()).unwrap_or(false));
+    let needs_inspection = match torrent_info_name {
+        Some(info) if is_torrent => {
+            metadata["torrentMeta"] = torrent_meta(info, &config.source);
+            false
         }
+        _ => is_torrent,
+    };
+    
+    // Remember the resource validators so a resume can tell whether the file changed on the server
+    if download_type == "http" {
+        if let Some(validators) = fetch_http_validators(&config.source, &config.options).await {
+            metadata["validators"] = validators;
+        }
+    }
+    
+    // Fallback URLs for the monitor to switch to if this one fails
+    let mirrors: Vec<&String> = config.mirrors.iter()
+        .filter(|m| !m.trim().is_empty() && **m != config.source)
+        .collect();
+    if download_type == "http" && !mirrors.is_empty() {
+        metadata["mirrors"] = serde_json::json!(mirrors);
+        metadata["mirrorIndex"] = serde_json::json!(0);
     }
     
     conn.execute(
-        "INSERT INTO downloads (id, source, output, type, status, progress, downloaded, total, speed, metadata, started_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        "INSERT INTO downloads (id, source, output, type, status, progress, downloaded, total, speed, metadata, started_at, updated_at, category)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         rusqlite::params![
             download_id,
             config.source,
@@ -330,6 +1086,7 @@ pub async fn start_download(
             serde_json::to_string(&metadata).unwrap(),
             SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
             SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            category,
         ],
     )
     .map_err(|e| format!("Failed to insert download: {}", e))?;
@@ -351,6 +1108,7 @@ pub async fn start_download(
         "total": 0,
         "speed": 0,
         "pause_reason": "Paused - click resume to start",
+        "category": category,
     });
     
     if let Some(name) = file_name {
@@ -359,10 +1117,15 @@ pub async fn start_download(
     if let Some(info) = http_info {
         event_data["httpInfo"] = info;
     }
+    event_data["displayName"] = serde_json::json!(derive_display_name(&config.source, &metadata));
     
     app.emit("download-update", event_data)
     .map_err(|e| format!("Failed to emit event: {}", e))?;
     
+    if needs_inspection {
+        tauri::async_runtime::spawn(record_torrent_meta(download_id.clone(), config.source.clone(), app.clone()));
+    }
+    
     use crate::logger;
     logger::log_info("start_download", &format!("Created download {} with status 'paused' (type: {})", download_id, download_type));
     logger::log_info("start_download", &format!("Source: {}, Output: {}", config.source, output_path));
@@ -389,6 +1152,8 @@ pub async fn remove_download(
     download_id: String,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
+    queue::dequeue(&download_id);
+    
     // Stop process if running
     stop_download(download_id.clone()).await?;
     
@@ -396,18 +1161,24 @@ pub async fn remove_download(
     let conn = database::get_connection()
         .map_err(|e| format!("Database error: {}", e))?;
     
-    let output: Result<String, _> = conn.query_row(
-        "SELECT output FROM downloads WHERE id = ?1",
+    let row: Option<(String, String)> = conn.query_row(
+        "SELECT output, source FROM downloads WHERE id = ?1",
         [&download_id],
-        |row| row.get(0),
-    );
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).ok();
     
     // Delete from database
     conn.execute("DELETE FROM downloads WHERE id = ?1", [&download_id])
         .map_err(|e| format!("Failed to delete download: {}", e))?;
     
+    if let Some((_, source)) = &row {
+        remove_saved_torrent_data(&conn, source);
+    }
+    crate::download_log::clear(&download_id);
+    credentials::forget(&download_id);
+    
     // Try to delete partial files if they exist
-    if let Ok(output_path) = output {
+    if let Some((output_path, _)) = row {
         if let Some(path) = std::path::Path::new(&output_path).parent() {
             if path.exists() {
                 // Delete .accelara-temp-* directories
@@ -439,20 +1210,40 @@ pub async fn pause_download(
     download_id: String,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    // Kill the process (SIGTERM on Unix)
-    let mut processes = DOWNLOAD_PROCESSES.lock().await;
+    pause_download_internal(download_id, app, "Paused by user").await
+}
+
+/// Internal pause function shared by the pause command and system-initiated pauses
+/// (e.g. before the machine goes to sleep). `reason` is stored as the pause_reason.
+pub async fn pause_download_internal(
+    download_id: String,
+    app: tauri::AppHandle,
+    reason: &str,
+) -> Result<(), String> {
+    if stop_and_mark_paused(&download_id, reason).await? {
+        // Emit update event
+        app.emit("download-update", serde_json::json!({
+            "downloadId": download_id,
+            "download_id": download_id,
+            "status": "paused",
+            "pause_reason": reason,
+        }))
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    }
     
-    if let Some(mut child) = processes.remove(&download_id) {
-        #[cfg(unix)]
-        {
-            if let Some(pid) = child.id() {
-                let _ = std::process::Command::new("kill")
-                    .arg("-TERM")
-                    .arg(pid.to_string())
-                    .output();
-            }
-        }
-        let _ = child.kill().await;
+    Ok(())
+}
+
+/// Take a download out of the queue, stop its process and mark it paused
+/// Returns false if the download has no metadata row to update.
+async fn stop_and_mark_paused(download_id: &str, reason: &str) -> Result<bool, String> {
+    // A queued download isn't running yet - just take it out of line
+    queue::dequeue(download_id);
+    
+    // Ask the process to exit so it can flush its chunk state
+    let child = DOWNLOAD_PROCESSES.lock().await.remove(download_id);
+    if let Some(mut child) = child {
+        terminate_child(&mut child).await;
     }
     
     // Update database
@@ -461,7 +1252,7 @@ pub async fn pause_download(
     
     let download: Result<Option<String>, _> = conn.query_row(
         "SELECT metadata FROM downloads WHERE id = ?1",
-        [&download_id],
+        [download_id],
         |row| row.get::<_, Option<String>>(0),
     );
     
@@ -469,35 +1260,62 @@ pub async fn pause_download(
         let mut metadata: serde_json::Value = serde_json::from_str(&metadata_str)
             .unwrap_or_else(|_| serde_json::json!({}));
         
-        metadata["pause_reason"] = serde_json::json!("Paused by user");
+        metadata["pause_reason"] = serde_json::json!(reason);
         metadata["paused_at"] = serde_json::json!(SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs());
         
         conn.execute(
-            "UPDATE downloads SET status = ?, metadata = ? WHERE id = ?",
+            // Pausing a scheduled download also calls off its scheduled start
+            "UPDATE downloads SET status = ?, metadata = ?, scheduled_at = NULL WHERE id = ?",
             rusqlite::params!["paused", serde_json::to_string(&metadata).unwrap(), download_id],
         )
         .map_err(|e| format!("Failed to update download: {}", e))?;
-        
-        // Emit update event
-        app.emit("download-update", serde_json::json!({
-            "downloadId": download_id,
-            "download_id": download_id,
-            "status": "paused",
-            "pause_reason": "Paused by user",
-        }))
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
+        return Ok(true);
     }
     
-    Ok(())
+    Ok(false)
 }
 
-/// Auto-resume downloads that were in "downloading" state when app exited
-pub async fn auto_resume_downloads(app: tauri::AppHandle) {
-    // Get download IDs synchronously (before any await)
-    let download_ids: Vec<String> = {
+/// Stop a download process gracefully: SIGTERM on Unix, wait briefly, then kill
+async fn terminate_child(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.id() {
+            let _ = std::process::Command::new("kill")
+                .arg("-TERM")
+                .arg(pid.to_string())
+                .output();
+        }
+        // Give it a moment to exit cleanly before forcing it
+        let _ = tokio::time::timeout(
+            tokio::time::Duration::from_secs(2),
+            child.wait(),
+        ).await;
+    }
+    let _ = child.kill().await;
+}
+
+/// Restart a running download's process so it picks up new arguments (e.g. a new rate limit)
+/// Progress is preserved by the Go binary's resume-from-disk behavior
+pub async fn restart_download_internal(
+    download_id: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let child = DOWNLOAD_PROCESSES.lock().await.remove(&download_id);
+    if let Some(mut child) = child {
+        terminate_child(&mut child).await;
+    }
+    // The restart reports the stored progress as its starting point, so store the latest first
+    download::persist_cached_progress(&download_id).await;
+    resume_download_internal(download_id, app).await
+}
+
+/// Auto-resume downloads that were in "downloading" state when app exited
+pub async fn auto_resume_downloads(app: tauri::AppHandle) {
+    // Get download IDs synchronously (before any await)
+    let download_ids: Vec<String> = {
         let conn = match database::get_connection() {
             Ok(conn) => conn,
             Err(e) => {
@@ -509,7 +1327,7 @@ pub async fn auto_resume_downloads(app: tauri::AppHandle) {
         // Find all downloads that were in "downloading" or "paused" state
         // (paused downloads should also be resumed if they have progress)
         let mut stmt = match conn.prepare(
-            "SELECT id FROM downloads WHERE status IN ('downloading', 'paused') ORDER BY started_at ASC"
+            "SELECT id FROM downloads WHERE status IN ('downloading', 'paused', 'queued') ORDER BY started_at ASC"
         ) {
             Ok(stmt) => stmt,
             Err(e) => {
@@ -544,25 +1362,27 @@ pub async fn auto_resume_downloads(app: tauri::AppHandle) {
     
     if download_ids.is_empty() {
         eprintln!("[auto-resume] No downloads to resume");
-        return;
+    } else {
+        eprintln!("[auto-resume] Found {} download(s) to resume", download_ids.len());
     }
     
-    eprintln!("[auto-resume] Found {} download(s) to resume", download_ids.len());
-    
     // Resume each download with a small delay between them
     for (index, download_id) in download_ids.iter().enumerate() {
         if index > 0 {
-            // Small delay between resuming multiple downloads
-            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            // Staggered (with jitter) so downloads from the same host don't start in lockstep
+            tokio::time::sleep(utils::start_stagger()).await;
         }
         
         eprintln!("[auto-resume] Resuming download: {}", download_id);
         
-        // Call resume_download logic directly (not as a command)
-        match resume_download_internal(download_id.clone(), app.clone()).await {
-            Ok(_) => {
+        // Goes through the queue so at most maxConcurrentDownloads start; the rest wait their turn
+        match queue::enqueue(download_id.clone(), app.clone()).await {
+            Ok(true) => {
                 eprintln!("[auto-resume] Successfully resumed download: {}", download_id);
             }
+            Ok(false) => {
+                eprintln!("[auto-resume] Queued download: {}", download_id);
+            }
             Err(e) => {
                 eprintln!("[auto-resume] Failed to resume download {}: {}", download_id, e);
                 // Update status to "paused" so user can manually resume
@@ -575,13 +1395,154 @@ pub async fn auto_resume_downloads(app: tauri::AppHandle) {
             }
         }
     }
+    
+    resume_seeding_torrents(app).await;
+}
+
+/// Restart torrents that were seeding when the app exited, if `resumeSeedingOnStartup` is on
+/// Their processes died with the app, so without this they show as seeding but share nothing.
+async fn resume_seeding_torrents(app: tauri::AppHandle) {
+    use crate::logger;
+    
+    let enabled = database::get_setting("resumeSeedingOnStartup")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    
+    let seeding: Vec<(String, Option<String>)> = match database::get_connection() {
+        Ok(conn) => match conn.prepare("SELECT id, metadata FROM downloads WHERE status = 'seeding' ORDER BY started_at ASC") {
+            Ok(mut stmt) => stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                .unwrap_or_default(),
+            Err(e) => {
+                logger::log_error("auto-resume", &format!("Failed to query seeding torrents: {}", e));
+                return;
+            }
+        },
+        Err(e) => {
+            logger::log_error("auto-resume", &format!("Database error: {}", e));
+            return;
+        }
+    };
+    if seeding.is_empty() {
+        return;
+    }
+    
+    if !enabled {
+        // Nothing is sharing them any more - say so instead of pretending
+        if let Ok(conn) = database::get_connection() {
+            let _ = conn.execute("UPDATE downloads SET status = 'completed' WHERE status = 'seeding'", []);
+        }
+        logger::log_info("auto-resume", &format!("Marked {} seeding torrent(s) as completed (resumeSeedingOnStartup is off)", seeding.len()));
+        return;
+    }
+    
+    for (download_id, metadata_str) in seeding {
+        // The wrapper only keeps seeding when asked to
+        let mut metadata: serde_json::Value = metadata_str
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_else(|| serde_json::json!({}));
+        if !metadata.get("options").map(|o| o.is_object()).unwrap_or(false) {
+            metadata["options"] = serde_json::json!({});
+        }
+        metadata["options"]["btKeepSeeding"] = serde_json::json!(true);
+        if let Ok(conn) = database::get_connection() {
+            let _ = conn.execute(
+                "UPDATE downloads SET metadata = ?1 WHERE id = ?2",
+                rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+            );
+        }
+        
+        match resume_download_internal(download_id.clone(), app.clone()).await {
+            Ok(_) => logger::log_info("auto-resume", &format!("Resumed seeding: {}", download_id)),
+            Err(e) => {
+                logger::log_error("auto-resume", &format!("Failed to resume seeding {}: {}", download_id, e));
+                if let Ok(conn) = database::get_connection() {
+                    let _ = conn.execute(
+                        "UPDATE downloads SET status = 'completed' WHERE id = ?1",
+                        [&download_id],
+                    );
+                }
+            }
+        }
+        tokio::time::sleep(utils::start_stagger()).await;
+    }
+}
+
+// Helper to re-probe an HTTP download's source before resuming it
+// Ok(Some(value)) is the If-Range value to pass on; Err means the source changed and the download was paused
+async fn check_source_unchanged(download_id: &str, app: &tauri::AppHandle) -> Result<Option<String>, String> {
+    let (source, download_type, metadata_str, downloaded): (String, String, Option<String>, i64) = {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        conn.query_row(
+            "SELECT source, type, metadata, downloaded FROM downloads WHERE id = ?1",
+            [download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|_| "Download not found".to_string())?
+    };
+    
+    if download_type != "http" {
+        return Ok(None);
+    }
+    
+    let mut metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    let stored = match metadata.get("validators") {
+        Some(validators) if !validators.is_null() => validators.clone(),
+        _ => return Ok(None),
+    };
+    
+    // Nothing recorded yet: no need to ask the server. Parts found on disk still get If-Range.
+    if downloaded == 0 {
+        return Ok(if_range_value(&stored));
+    }
+    
+    let current = match fetch_http_validators(&source, &stored_options(&download_id, &metadata)).await {
+        Some(current) => current,
+        // Server unreachable - let the wrapper retry as usual, If-Range still protects the splice
+        None => return Ok(if_range_value(&stored)),
+    };
+    
+    if !source_changed(&stored, &current) {
+        return Ok(if_range_value(&stored));
+    }
+    
+    use crate::logger;
+    logger::log_warning("resume_download", &format!("Source of {} changed on the server, not reusing chunks", download_id));
+    
+    metadata["pause_reason"] = serde_json::json!("File changed on the server - restart the download to fetch the new version");
+    metadata["source_changed"] = serde_json::json!(true);
+    if let Ok(conn) = database::get_connection() {
+        let _ = conn.execute(
+            "UPDATE downloads SET status = 'paused', metadata = ?1 WHERE id = ?2",
+            rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+        );
+    }
+    
+    let _ = app.emit("download-source-changed", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "previous": stored,
+        "current": current,
+    }));
+    
+    Err("The file changed on the server since the download started".to_string())
 }
 
 /// Internal resume function (extracted from resume_download command)
-async fn resume_download_internal(
+pub async fn resume_download_internal(
     download_id: String,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
+    // Make sure the remote file is still the one our chunks belong to (checked before taking the process lock)
+    let if_range = if DOWNLOAD_PROCESSES.lock().await.contains_key(&download_id) {
+        None
+    } else {
+        check_source_unchanged(&download_id, &app).await?
+    };
+    
     // Check if process exists (shouldn't after app restart)
     let mut processes = DOWNLOAD_PROCESSES.lock().await;
     
@@ -625,7 +1586,7 @@ async fn resume_download_internal(
         )),
     );
     
-    let (source, output, _download_type, metadata_str_opt, existing_progress, existing_downloaded, existing_total) = download
+    let (source, output, _download_type, metadata_str_opt, mut existing_progress, mut existing_downloaded, existing_total) = download
         .map_err(|_| "Download not found".to_string())?;
     
     // The Go binary will automatically check for existing files and resume
@@ -637,10 +1598,65 @@ async fn resume_download_internal(
         serde_json::json!({})
     };
     
-    let options = metadata.get("options").cloned();
+    let options = with_rate_limit_override(stored_options(&download_id, &metadata), &metadata);
+    
+    // The CA file may have been moved since the download was added
+    if let (Some(ca_cert), _) = tls_options(&options) {
+        utils::validate_ca_cert(&ca_cert)?;
+    }
+    
+    // The folder may have been unmounted or had its permissions changed since
+    let download_folder = utils::download_folder(&output, &_download_type);
+    utils::check_dir_writable(&download_folder)?;
+    
+    // The DB value can lag behind (or be zeroed) after a crash - trust the files if they're further along
+    let torrent_name = if _download_type == "http" { None } else { torrent_content_name(&source, &metadata) };
+    let (on_disk, _) = bytes_on_disk(&_download_type, std::path::Path::new(&utils::expand_path(&output)), torrent_name.as_deref());
+    if let Some((downloaded, progress)) = reconcile_downloaded(existing_downloaded, existing_total, on_disk) {
+        crate::logger::log_info("resume_download", &format!(
+            "[{}] Found {} bytes on disk but only {} recorded, restoring from disk",
+            download_id, downloaded, existing_downloaded
+        ));
+        existing_downloaded = downloaded;
+        existing_progress = progress;
+        conn.execute(
+            "UPDATE downloads SET progress = ?, downloaded = ? WHERE id = ?",
+            rusqlite::params![existing_progress, existing_downloaded, download_id],
+        )
+        .map_err(|e| format!("Failed to update download: {}", e))?;
+    }
+    
+    // Refuse up front rather than letting the wrapper fail partway with ENOSPC
+    check_free_space(&download_folder, existing_total, existing_downloaded)?;
     
     // Build command args
-    let args = build_command_args(&source, &output, &download_id, &options);
+    let mut args = build_command_args(&source, &output, &download_id, &options);
+    
+    // Torrents share the global peer budget with the other torrents already running
+    if (_download_type == "torrent" || _download_type == "magnet") && peer_budget::wrapper_limits_peers().await {
+        let others: Vec<String> = processes.keys().filter(|id| **id != download_id).cloned().collect();
+        let running = peer_budget::running_torrents(&others).len() + 1;
+        let max_peers = peer_budget::per_torrent_budget(peer_budget::max_torrent_connections(), running);
+        args.push("--max-peers".to_string());
+        args.push(max_peers.to_string());
+        peer_budget::assign_budget(&download_id, max_peers);
+    }
+    
+    // Likewise its share of the total speed cap, alongside everything already running
+    if let Some(total) = bandwidth::global_rate_limit() {
+        let mut active: Vec<String> = processes.keys().filter(|id| **id != download_id).cloned().collect();
+        active.push(download_id.clone());
+        let share = bandwidth::allocate_bandwidth(total, &active).get(&download_id).copied().unwrap_or(total);
+        bandwidth::apply_share(&mut args, share);
+        bandwidth::assign_share(&download_id, share);
+    }
+    
+    // Let the wrapper send If-Range so a server-side change mid-resume yields a full response instead of a bad splice.
+    // Only with parts on disk: a fresh start has nothing to splice onto.
+    if let Some(value) = if_range.filter(|_| _download_type == "http" && on_disk > 0) {
+        args.push("--if-range".to_string());
+        args.push(value);
+    }
     
     // Get expanded output path for logging and checking
     let expanded_output = args.iter().skip(3).next().cloned().unwrap_or_else(|| "N/A".to_string());
@@ -793,6 +1809,7 @@ async fn resume_download_internal(
     // Update database FIRST
     let mut updated_metadata = metadata.clone();
     updated_metadata["auto_paused"] = serde_json::json!(false);
+    updated_metadata["sleep_paused"] = serde_json::json!(false);
     updated_metadata["pause_reason"] = serde_json::Value::Null;
     updated_metadata["paused_at"] = serde_json::Value::Null;
     
@@ -824,10 +1841,13 @@ async fn resume_download_internal(
     use crate::logger;
     logger::log_info("resume_download", &format!("Spawning Go binary: {}", verified_binary.display()));
     logger::log_info("resume_download", &format!("Working directory: {}", working_dir.display()));
-    logger::log_info("resume_download", &format!("Command args: {:?}", args));
+    // An older helper exits on flags it doesn't know, which would fail e.g. every download with browser cookies
+    let args = binaries::drop_unsupported_args(&verified_binary, args, &["--header", "--auth"]).await;
+    logger::log_info("resume_download", &format!("Command args: {:?}", redact_args(&args)));
     
     let child = TokioCommand::new(&verified_binary)
         .args(&args)
+        .env(process_cleanup::DOWNLOAD_ID_ENV, &download_id)
         .current_dir(&working_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -839,6 +1859,7 @@ async fn resume_download_internal(
         })?;
     
     logger::log_info("resume_download", &format!("✓ Go binary spawned successfully for download: {}", download_id));
+    process_cleanup::record_spawn(child.id(), &download_id);
     
     // Store process
     processes.insert(download_id.clone(), child);
@@ -849,6 +1870,7 @@ async fn resume_download_internal(
     if let Some(mut child) = processes_for_monitor.remove(&download_id) {
         let stdout = child.stdout.take();
         let stderr = child.stderr.take();
+        let pid = child.id();
         processes_for_monitor.insert(download_id.clone(), child);
         drop(processes_for_monitor);
         
@@ -858,7 +1880,7 @@ async fn resume_download_internal(
         let app_clone = app.clone();
         let download_id_clone = download_id.clone();
         tokio::spawn(async move {
-            download::monitor_download_process_with_streams(app_clone, download_id_clone, stdout, stderr).await;
+            download::monitor_download_process_with_streams(app_clone, download_id_clone, stdout, stderr, pid).await;
         });
     } else {
         logger::log_error("resume_download", &format!("Failed to retrieve process from map for monitoring: {}", download_id));
@@ -875,78 +1897,211 @@ pub async fn resume_download(
 ) -> Result<(), String> {
     use crate::logger;
     logger::log_info("resume_download", &format!("Resume requested for download: {}", download_id));
-    let result = resume_download_internal(download_id.clone(), app).await;
-    if let Err(ref e) = result {
-        logger::log_error("resume_download", &format!("Failed to resume download {}: {}", download_id, e));
-    } else {
-        logger::log_info("resume_download", &format!("Successfully initiated resume for download: {}", download_id));
+    // Starts now if a slot is free, otherwise waits in the queue
+    match queue::enqueue(download_id.clone(), app).await {
+        Ok(true) => {
+            logger::log_info("resume_download", &format!("Successfully initiated resume for download: {}", download_id));
+            Ok(())
+        }
+        Ok(false) => {
+            logger::log_info("resume_download", &format!("Download queued: {}", download_id));
+            Ok(())
+        }
+        Err(e) => {
+            logger::log_error("resume_download", &format!("Failed to resume download {}: {}", download_id, e));
+            Err(e)
+        }
     }
-    result
 }
 
 // Handler 8: get-active-downloads
 #[command]
 pub async fn get_active_downloads() -> Result<Vec<serde_json::Value>, String> {
+    let query = DownloadQuery {
+        exclude_status: Some(vec!["completed".to_string(), "cancelled".to_string()]),
+        ..Default::default()
+    };
+    query_downloads_internal(&query).map(|(downloads, _)| downloads)
+}
+
+/// Filter, sort and paging options for `query_downloads`
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadQuery {
+    #[serde(default)]
+    pub status: Option<Vec<String>>,
+    #[serde(default)]
+    pub exclude_status: Option<Vec<String>>,
+    #[serde(default, rename = "type")]
+    pub download_type: Option<Vec<String>>,
+    /// progress | speed | size | startedAt | updatedAt (default: startedAt)
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    /// asc | desc (default: desc)
+    #[serde(default)]
+    pub order: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+// Columns selected for a downloads row, in the order download_row_to_json reads them
+const DOWNLOAD_COLUMNS: &str = "id, source, output, type, status, progress, downloaded, total, speed, error, metadata, started_at, updated_at, batch_id, batch_name, notes, category, scheduled_at";
+
+// Helper to convert a downloads row to the JSON the frontend expects
+fn download_row_to_json(row: &rusqlite::Row) -> rusqlite::Result<serde_json::Value> {
+    // metadata can be NULL, so handle it as Option
+    let metadata_str: Option<String> = row.get(10).ok();
+    let metadata: serde_json::Value = if let Some(ref s) = metadata_str {
+        serde_json::from_str(s).unwrap_or_else(|_| serde_json::json!({}))
+    } else {
+        serde_json::json!({})
+    };
+    
+    Ok(serde_json::json!({
+        "id": row.get::<_, String>(0)?,
+        "source": row.get::<_, String>(1)?,
+        "output": row.get::<_, String>(2)?,
+        "type": row.get::<_, String>(3)?,
+        "status": row.get::<_, String>(4)?,
+        "progress": row.get::<_, f64>(5)?,
+        "downloaded": row.get::<_, i64>(6)?,
+        "total": row.get::<_, i64>(7)?,
+        "speed": row.get::<_, i64>(8)?,
+        "error": row.get::<_, Option<String>>(9)?,
+        "metadata": metadata,
+        "startedAt": row.get::<_, Option<i64>>(11)?,
+        "updatedAt": row.get::<_, Option<i64>>(12)?,
+        "batchId": row.get::<_, Option<String>>(13)?,
+        "batchName": row.get::<_, Option<String>>(14)?,
+        "notes": row.get::<_, Option<String>>(15)?,
+        "category": row.get::<_, Option<String>>(16)?,
+        "scheduledAt": row.get::<_, Option<i64>>(17)?,
+        "isSeeding": row.get::<_, String>(4)? == "seeding",
+        "displayName": derive_display_name(&row.get::<_, String>(1)?, &metadata),
+    }))
+}
+
+/// Run a download query, returning the requested page and the total number of matches
+pub fn query_downloads_internal(query: &DownloadQuery) -> Result<(Vec<serde_json::Value>, i64), String> {
     let conn = database::get_connection()
         .map_err(|e| format!("Database error: {}", e))?;
     
-    let mut stmt = conn.prepare(
-        "SELECT * FROM downloads WHERE status NOT IN ('completed', 'cancelled') ORDER BY started_at DESC"
+    // Only placeholders are built from user input; column names come from a fixed whitelist
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+    let mut add_list = |column: &str, negate: bool, values: &Option<Vec<String>>| {
+        if let Some(values) = values.as_ref().filter(|v| !v.is_empty()) {
+            let placeholders: Vec<String> = values.iter().map(|v| {
+                params.push(v.clone());
+                format!("?{}", params.len())
+            }).collect();
+            conditions.push(format!("{} {}IN ({})", column, if negate { "NOT " } else { "" }, placeholders.join(", ")));
+        }
+    };
+    add_list("status", false, &query.status);
+    add_list("status", true, &query.exclude_status);
+    add_list("type", false, &query.download_type);
+    
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    
+    let sort_column = match query.sort_by.as_deref() {
+        Some("progress") => "progress",
+        Some("speed") => "speed",
+        Some("size") | Some("total") => "total",
+        Some("updatedAt") | Some("updated_at") => "updated_at",
+        Some("startedAt") | Some("started_at") | None => "started_at",
+        Some(other) => return Err(format!("Unsupported sort field: {}", other)),
+    };
+    let order = match query.order.as_deref() {
+        Some(o) if o.eq_ignore_ascii_case("asc") => "ASC",
+        Some(o) if o.eq_ignore_ascii_case("desc") => "DESC",
+        None => "DESC",
+        Some(other) => return Err(format!("Unsupported sort order: {}", other)),
+    };
+    
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM downloads {}", where_clause),
+        rusqlite::params_from_iter(params.iter()),
+        |row| row.get(0),
     )
-    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    .map_err(|e| format!("Failed to count downloads: {}", e))?;
+    
+    // LIMIT -1 means no limit in SQLite
+    let limit = query.limit.filter(|l| *l >= 0).unwrap_or(-1);
+    let offset = query.offset.filter(|o| *o >= 0).unwrap_or(0);
+    let sql = format!(
+        "SELECT {} FROM downloads {} ORDER BY {} {}, id {} LIMIT {} OFFSET {}",
+        DOWNLOAD_COLUMNS, where_clause, sort_column, order, order, limit, offset
+    );
     
-    let rows = stmt.query_map([], |row| {
-        // Column order: id(0), source(1), output(2), type(3), status(4), progress(5), 
-        // downloaded(6), total(7), speed(8), error(9), metadata(10), started_at(11), updated_at(12)
-        // metadata can be NULL, so handle it as Option
-        let metadata_str: Option<String> = row.get(10).ok();
-        let metadata: serde_json::Value = if let Some(ref s) = metadata_str {
-            serde_json::from_str(s).unwrap_or_else(|_| serde_json::json!({}))
-        } else {
-            serde_json::json!({})
-        };
-        
-        Ok(serde_json::json!({
-            "id": row.get::<_, String>(0)?,
-            "source": row.get::<_, String>(1)?,
-            "output": row.get::<_, String>(2)?,
-            "type": row.get::<_, String>(3)?,
-            "status": row.get::<_, String>(4)?,
-            "progress": row.get::<_, f64>(5)?,
-            "downloaded": row.get::<_, i64>(6)?,
-            "total": row.get::<_, i64>(7)?,
-            "speed": row.get::<_, i64>(8)?,
-            "error": row.get::<_, Option<String>>(9)?,
-            "metadata": metadata,
-            "startedAt": row.get::<_, Option<i64>>(11)?,
-            "updatedAt": row.get::<_, Option<i64>>(12)?,
-        }))
-    })
-    .map_err(|e| format!("Failed to query: {}", e))?;
+    let mut stmt = conn.prepare(&sql)
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), download_row_to_json)
+        .map_err(|e| format!("Failed to query: {}", e))?;
     
     let mut downloads = Vec::new();
     for row in rows {
         downloads.push(row.map_err(|e| format!("Failed to process row: {}", e))?);
     }
     
-    Ok(downloads)
+    Ok((downloads, total))
+}
+
+/// Default number of entries returned by `get_download_history`
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Merge history rows with the seeding torrents, newest first, capped at `limit`
+/// A seeding entry replaces the history row with the same id. Entries without a
+/// completion time (torrents seeding since before history recorded them) sort first,
+/// and ties are broken by id so the order is stable between calls.
+fn merge_history_items(
+    history: Vec<serde_json::Value>,
+    seeding: Vec<serde_json::Value>,
+    limit: usize,
+) -> Vec<serde_json::Value> {
+    let mut by_id: std::collections::HashMap<String, serde_json::Value> = std::collections::HashMap::new();
+    for item in history.into_iter().chain(seeding) {
+        if let Some(id) = item.get("id").and_then(|v| v.as_str()) {
+            by_id.insert(id.to_string(), item);
+        }
+    }
+    
+    let mut items: Vec<serde_json::Value> = by_id.into_values().collect();
+    items.sort_by(|a, b| {
+        let completed = |v: &serde_json::Value| v.get("completedAt").and_then(|c| c.as_i64());
+        let id = |v: &serde_json::Value| v.get("id").and_then(|i| i.as_str()).unwrap_or("").to_string();
+        match (completed(a), completed(b)) {
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (x, y) => y.cmp(&x),
+        }
+        .then_with(|| id(a).cmp(&id(b)))
+    });
+    items.truncate(limit);
+    items
 }
 
 // Handler 9: get-download-history
 #[command]
-pub async fn get_download_history() -> Result<Vec<serde_json::Value>, String> {
+pub async fn get_download_history(limit: Option<usize>) -> Result<Vec<serde_json::Value>, String> {
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
     let conn = database::get_connection()
         .map_err(|e| format!("Database error: {}", e))?;
     
     // Get history items
     let mut stmt = conn.prepare(
-        "SELECT * FROM download_history ORDER BY completed_at DESC LIMIT 100"
+        "SELECT id, source, output, type, size, metadata, completed_at, notes, category FROM download_history ORDER BY completed_at DESC LIMIT ?1"
     )
     .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
-    let mut history_map: std::collections::HashMap<String, serde_json::Value> = std::collections::HashMap::new();
-    
-    let rows = stmt.query_map([], |row| {
+    let rows = stmt.query_map([limit as i64], |row| {
         // Column order: id(0), source(1), output(2), type(3), size(4), metadata(5), completed_at(6)
         // metadata can be NULL, so handle it as Option
         let metadata_str: Option<String> = row.get(5).ok();
@@ -959,29 +2114,25 @@ pub async fn get_download_history() -> Result<Vec<serde_json::Value>, String> {
         Ok(serde_json::json!({
             "id": row.get::<_, String>(0)?,
             "source": row.get::<_, String>(1)?,
+            "displayName": derive_display_name(&row.get::<_, String>(1)?, &metadata),
             "output": row.get::<_, String>(2)?,
             "type": row.get::<_, String>(3)?,
             "size": row.get::<_, Option<i64>>(4)?,
             "completedAt": row.get::<_, Option<i64>>(6)?,
             "metadata": metadata,
+            "notes": row.get::<_, Option<String>>(7)?,
+            "category": row.get::<_, Option<String>>(8)?,
             "isSeeding": false,
         }))
     })
     .map_err(|e| format!("Failed to query: {}", e))?;
 
-    // Process history rows - use iterator for efficiency
-    // HashMap automatically handles duplicates by overwriting
-    for row in rows {
-        if let Ok(item) = row {
-            if let Some(id) = item.get("id").and_then(|v| v.as_str()) {
-                history_map.insert(id.to_string(), item);
-            }
-        }
-    }
+    let history: Vec<serde_json::Value> = rows.filter_map(|r| r.ok()).collect();
     
     // Also include active seeding torrents
     let mut stmt2 = conn.prepare(
-        "SELECT d.*, h.completed_at FROM downloads d
+        "SELECT d.id, d.source, d.output, d.type, d.status, d.progress, d.downloaded, d.total, d.speed,
+                d.error, d.metadata, d.started_at, d.updated_at, h.completed_at, d.notes, d.category FROM downloads d
          LEFT JOIN download_history h ON d.id = h.id
          WHERE d.status = 'seeding' AND d.type = 'torrent'
          ORDER BY h.completed_at DESC, d.started_at DESC"
@@ -991,7 +2142,7 @@ pub async fn get_download_history() -> Result<Vec<serde_json::Value>, String> {
     let rows2 = stmt2.query_map([], |row| {
         // Column order from JOIN: d.id(0), d.source(1), d.output(2), d.type(3), d.status(4), 
         // d.progress(5), d.downloaded(6), d.total(7), d.speed(8), d.error(9), d.metadata(10), 
-        // d.started_at(11), d.updated_at(12), h.completed_at(13)
+        // d.started_at(11), d.updated_at(12), h.completed_at(13), d.notes(14), d.category(15)
         // metadata can be NULL, so handle it as Option
         let metadata_str: Option<String> = row.get(10).ok();
         let metadata: serde_json::Value = if let Some(ref s) = metadata_str {
@@ -1006,6 +2157,7 @@ pub async fn get_download_history() -> Result<Vec<serde_json::Value>, String> {
         Ok(serde_json::json!({
             "id": row.get::<_, String>(0)?,
             "source": row.get::<_, String>(1)?,
+            "displayName": derive_display_name(&row.get::<_, String>(1)?, &metadata),
             "output": row.get::<_, String>(2)?,
             "type": row.get::<_, String>(3)?,
             "status": "seeding",
@@ -1015,21 +2167,16 @@ pub async fn get_download_history() -> Result<Vec<serde_json::Value>, String> {
             "speed": row.get::<_, i64>(8)?,
             "completedAt": completed_at,
             "metadata": metadata,
+            "notes": row.get::<_, Option<String>>(14)?,
+            "category": row.get::<_, Option<String>>(15)?,
             "isSeeding": true,
         }))
     })
     .map_err(|e| format!("Failed to query: {}", e))?;
     
-    // Process seeding rows - HashMap automatically handles duplicates (overwrites)
-    for row in rows2 {
-        if let Ok(item) = row {
-            if let Some(id) = item.get("id").and_then(|v| v.as_str()) {
-                history_map.insert(id.to_string(), item);
-            }
-        }
-    }
+    let seeding: Vec<serde_json::Value> = rows2.filter_map(|r| r.ok()).collect();
     
-    Ok(history_map.values().cloned().collect())
+    Ok(merge_history_items(history, seeding, limit))
 }
 
 // Handler 10: clear-download-history
@@ -1109,7 +2256,7 @@ fn calculate_dir_size(path: PathBuf) -> Result<u64, std::io::Error> {
     Ok(total)
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub fn format_bytes(bytes: u64) -> String {
     if bytes == 0 {
         return "0 B".to_string();
     }
@@ -1180,10 +2327,12 @@ pub async fn save_speed_test_result(result: serde_json::Value) -> Result<String,
     
     let latency = result.get("latency").and_then(|v| serde_json::to_string(v).ok());
     let location = result.get("location").and_then(|v| serde_json::to_string(v).ok());
+    // Host of a custom speed test (absent for the general internet test)
+    let target = result.get("target").and_then(|v| v.as_str());
     
     conn.execute(
-        "INSERT INTO speed_test_results (id, timestamp, download_speed, upload_speed, latency, location)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO speed_test_results (id, timestamp, download_speed, upload_speed, latency, location, target)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         rusqlite::params![
             test_id,
             result.get("timestamp").and_then(|v| v.as_i64()).unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64),
@@ -1191,6 +2340,7 @@ pub async fn save_speed_test_result(result: serde_json::Value) -> Result<String,
             result.get("uploadSpeed").and_then(|v| v.as_f64()).unwrap_or(0.0),
             latency,
             location,
+            target,
         ],
     )
     .map_err(|e| format!("Failed to save speed test result: {}", e))?;
@@ -1207,12 +2357,12 @@ pub async fn get_speed_test_results(limit: Option<usize>) -> Result<Vec<serde_js
         .map_err(|e| format!("Database error: {}", e))?;
     
     let mut stmt = conn.prepare(
-        &format!("SELECT * FROM speed_test_results ORDER BY timestamp DESC LIMIT {}", limit)
+        &format!("SELECT id, timestamp, download_speed, upload_speed, latency, location, target FROM speed_test_results ORDER BY timestamp DESC LIMIT {}", limit)
     )
     .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
     let rows = stmt.query_map([], |row| {
-        // Column order: id(0), timestamp(1), download_speed(2), upload_speed(3), latency(4), location(5)
+        // Column order: id(0), timestamp(1), download_speed(2), upload_speed(3), latency(4), location(5), target(6)
         // latency and location are TEXT, but may be NULL
         let latency_str: Option<String> = row.get(4).ok();
         let location_str: Option<String> = row.get(5).ok();
@@ -1233,6 +2383,7 @@ pub async fn get_speed_test_results(limit: Option<usize>) -> Result<Vec<serde_js
                     Some(serde_json::Value::String(s))
                 })
             }),
+            "target": row.get::<_, Option<String>>(6)?,
         }))
     })
     .map_err(|e| format!("Failed to query: {}", e))?;
@@ -1271,9 +2422,20 @@ pub async fn start_speed_test(
     
     let _test_type = test_type.unwrap_or_else(|| "full".to_string());
     
-    // Find iris binary
-    let iris_binary = utils::find_iris_binary()
-        .ok_or_else(|| "Iris binary not found".to_string())?;
+    // Find iris binary, falling back to the built-in (less accurate) test without it
+    let iris_binary = match utils::find_iris_binary() {
+        Some(binary) => binary,
+        None => {
+            use crate::logger;
+            logger::log_warning("start_speed_test", "Iris binary not found, using built-in speed test");
+            speed_test::spawn(app, test_id.clone(), speed_test::SpeedTestTargets::default(), true);
+            return Ok(serde_json::json!({
+                "testId": test_id,
+                "success": true,
+                "fallback": true,
+            }));
+        }
+    };
     
     let verified_binary = utils::verify_binary_path(&iris_binary)
         .map_err(|e| format!("Binary verification failed: {}", e))?;
@@ -1283,11 +2445,13 @@ pub async fn start_speed_test(
     // Spawn iris process
     let child = TokioCommand::new(&verified_binary)
         .args(&["--json", "--quiet"])
+        .env(process_cleanup::DOWNLOAD_ID_ENV, &test_id)
         .current_dir(&working_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| format!("Failed to spawn iris process: {}", e))?;
+    process_cleanup::record_spawn(child.id(), &test_id);
     
     // Store process
     let mut processes = SPEED_TEST_PROCESSES.lock().await;
@@ -1309,30 +2473,35 @@ pub async fn start_speed_test(
 
 // Handler 17: stop-speed-test
 #[command]
-pub async fn stop_speed_test(test_id: String) -> Result<(), String> {
-    let mut processes = SPEED_TEST_PROCESSES.lock().await;
-    
-    if let Some(mut child) = processes.remove(&test_id) {
-        child.kill().await
-            .map_err(|e| format!("Failed to kill process: {}", e))?;
+pub async fn stop_speed_test(test_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    // An aborted built-in test can't report anything itself
+    if speed_test::cancel(&test_id) {
+        speed_test::emit_cancelled(&app, &test_id);
+        return Ok(());
     }
     
-    Ok(())
+    // The iris monitor sees the cancellation once the process exits
+    download::cancel_speed_test_process(&test_id).await
 }
 
 // Handler 18: get-settings
 #[command]
 pub async fn get_settings() -> Result<serde_json::Value, String> {
-    let conn = database::get_connection()
-        .map_err(|e| format!("Database error: {}", e))?;
-    
-    let mut stmt = conn.prepare("SELECT key, value FROM settings")
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let mut settings = default_settings();
+    for (key, value) in stored_settings()? {
+        settings[&key] = value;
+    }
     
-    let mut settings = serde_json::json!({
+    Ok(settings)
+}
+
+// Helper returning the settings the app falls back to when a key isn't stored
+fn default_settings() -> serde_json::Value {
+    serde_json::json!({
         "concurrency": 8,
         "chunkSize": "4MB",
         "rateLimit": null,
+        "globalRateLimit": null,
         "uploadLimit": null,
         "sequentialMode": false,
         "keepSeeding": false,
@@ -1342,30 +2511,68 @@ pub async fn get_settings() -> Result<serde_json::Value, String> {
         "retries": 5,
         "torrentPort": 42069,
         "autoCheckForUpdates": true,
+        "allowPrerelease": false,
         "updateCheckInterval": 24,
+        "confirmLargeDownloads": false,
+        "largeDownloadThreshold": 10u64 * 1024 * 1024 * 1024, // 10GB
+        "maxTorrentConnections": peer_budget::DEFAULT_MAX_TORRENT_CONNECTIONS,
+        "quietHours": null,
+        "bandwidthSchedule": [],
+        "categoryFolders": {},
+        "stallTimeout": 60,
+        "autoRestartStalled": false,
+        "updateCaCertPath": "",
+        "logLevel": "info",
+        "logFormat": "text",
+        "logMaxSizeMb": crate::logger::DEFAULT_LOG_MAX_SIZE_MB,
+        "logKeepArchives": crate::logger::DEFAULT_LOG_KEEP_ARCHIVES,
+        "speedUnitBase": download::DEFAULT_SPEED_UNIT_BASE,
+        "speedTestCalibration": 1.0,
+        "notifyOnComplete": true,
+        "completionSound": "default",
+        "allowAutoOpen": false,
+        "closeBehavior": "minimizeToTray",
+        "browserServerEnabled": true,
+        "browserServerPort": crate::browser_server::DEFAULT_BROWSER_SERVER_PORT,
+        "browserAllowedOrigins": crate::browser_server::DEFAULT_ALLOWED_ORIGINS,
+        "createSubfolderPerDownload": false,
+        "torrentSubfolder": "",
+        "resumeSeedingOnStartup": false,
+        "autoCleanupCompletedDays": download::DEFAULT_AUTO_CLEANUP_COMPLETED_DAYS,
+        "globalSpeedEventIntervalMs": download::DEFAULT_GLOBAL_SPEED_EVENT_INTERVAL_MS,
+        "startStaggerMs": utils::DEFAULT_START_STAGGER_MS,
+        "maxConcurrentDownloads": queue::DEFAULT_MAX_CONCURRENT,
         "defaultDownloadPath": dirs::download_dir()
             .unwrap_or_else(|| dirs::home_dir().unwrap().join("Downloads"))
             .to_string_lossy()
             .to_string(),
-    });
+    })
+}
+
+// Helper returning the settings explicitly stored in the database
+fn stored_settings() -> Result<Vec<(String, serde_json::Value)>, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
     let rows = stmt.query_map([], |row| {
         Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
     })
     .map_err(|e| format!("Failed to query: {}", e))?;
     
+    let mut stored = Vec::new();
     for row in rows {
         let (key, value): (String, String) = row.map_err(|e| format!("Failed to process row: {}", e))?;
         
         // Try to parse as JSON, fallback to string
-        if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(&value) {
-            settings[&key] = json_value;
-        } else {
-            settings[&key] = serde_json::Value::String(value);
-        }
+        let json_value = serde_json::from_str::<serde_json::Value>(&value)
+            .unwrap_or(serde_json::Value::String(value));
+        stored.push((key, json_value));
     }
     
-    Ok(settings)
+    Ok(stored)
 }
 
 // Handler 19: save-settings
@@ -1385,6 +2592,10 @@ pub async fn save_settings(settings: serde_json::Value) -> Result<(), String> {
             )
             .map_err(|e| format!("Failed to save setting: {}", e))?;
         }
+        
+        if ["logLevel", "logFormat", "logMaxSizeMb", "logKeepArchives"].iter().any(|k| obj.contains_key(*k)) {
+            crate::logger::apply_settings();
+        }
     }
     
     Ok(())
@@ -1435,7 +2646,11 @@ pub async fn select_download_folder(app: tauri::AppHandle) -> Result<Option<Stri
         });
     
     match rx.await {
-        Ok(Some(path)) => Ok(Some(path)),
+        Ok(Some(path)) => {
+            // Reject folders we can't write to right away
+            utils::check_dir_writable(&PathBuf::from(&path))?;
+            Ok(Some(path))
+        }
         Ok(None) => Ok(None),
         Err(_) => Ok(None), // User cancelled
     }
@@ -1444,59 +2659,277 @@ pub async fn select_download_folder(app: tauri::AppHandle) -> Result<Option<Stri
 // Handler 22: open-folder
 #[command]
 pub async fn open_folder(folder_path: String) -> Result<(), String> {
-    // This will be handled by the shell plugin
-    // For now, use system command
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(&folder_path)
-            .output()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
+    utils::open_path(&folder_path)
+}
+
+/// The file (or, for multi-file torrents, the folder) a download produced
+/// Errors when it's no longer on disk, e.g. after the user deleted or moved it.
+fn resolve_open_path(output: &str, download_type: &str, source: &str, metadata: &serde_json::Value) -> Result<PathBuf, String> {
+    let mut path = PathBuf::from(utils::expand_path(output));
+    if download_type == "torrent" || download_type == "magnet" {
+        // Torrents land in <output>/<name>; without a name the output folder itself is opened
+        if let Some(name) = torrent_content_name(source, metadata) {
+            path = path.join(name);
+        }
     }
+    if !path.exists() {
+        return Err(format!("File not found: {} (it may have been moved or deleted)", path.display()));
+    }
+    Ok(path)
+}
+
+// Handler 90: open-file
+#[command]
+pub async fn open_file(download_id: String) -> Result<(), String> {
+    let (source, output, download_type, metadata_str): (String, String, String, Option<String>) = {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        conn.query_row(
+            "SELECT source, output, type, metadata FROM downloads WHERE id = ?1",
+            [&download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|_| "Download not found".to_string())?
+    };
+    let metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
     
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("explorer")
-            .arg(&folder_path)
-            .output()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
+    let path = resolve_open_path(&output, &download_type, &source, &metadata)?;
+    utils::open_path(&path.to_string_lossy())
+}
+
+/// Whether a download scheduled for `scheduled_at` (epoch seconds) should have started by `now`
+pub fn is_due(scheduled_at: Option<i64>, now: i64) -> bool {
+    scheduled_at.map(|at| at <= now).unwrap_or(false)
+}
+
+/// Record a scheduled start for a download
+/// Returns true if `timestamp` has already passed, in which case nothing is stored and the
+/// caller should start the download right away.
+pub fn schedule_download_internal(download_id: &str, timestamp: i64, now: i64) -> Result<bool, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let status: String = conn.query_row(
+        "SELECT status FROM downloads WHERE id = ?1",
+        [download_id],
+        |row| row.get(0),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    match status.as_str() {
+        "downloading" | "seeding" => return Err("Download is already running".to_string()),
+        "completed" | "cancelled" => return Err(format!("Download is {}", status)),
+        _ => {}
     }
     
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(&folder_path)
-            .output()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
+    if is_due(Some(timestamp), now) {
+        conn.execute("UPDATE downloads SET scheduled_at = NULL WHERE id = ?1", [download_id])
+            .map_err(|e| format!("Failed to update download: {}", e))?;
+        return Ok(true);
     }
     
-    Ok(())
+    conn.execute(
+        "UPDATE downloads SET status = 'scheduled', scheduled_at = ?1 WHERE id = ?2",
+        rusqlite::params![timestamp, download_id],
+    )
+    .map_err(|e| format!("Failed to update download: {}", e))?;
+    Ok(false)
 }
 
-// Handler 23: get-system-theme
+// Handler 91: schedule-download
 #[command]
-pub async fn get_system_theme() -> Result<String, String> {
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        // Use defaults read to check macOS appearance
-        let output = Command::new("defaults")
-            .args(&["read", "-g", "AppleInterfaceStyle"])
-            .output();
-        
-        match output {
-            Ok(result) if result.status.success() => {
-                let theme = String::from_utf8_lossy(&result.stdout).trim().to_lowercase();
-                if theme == "dark" {
-                    return Ok("dark".to_string());
-                }
-            }
-            _ => {
-                // If command fails or returns nothing, it's light mode
-                // (dark mode sets AppleInterfaceStyle, light mode doesn't)
-            }
-        }
-        Ok("light".to_string())
+pub async fn schedule_download(
+    download_id: String,
+    timestamp: i64,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    
+    if schedule_download_internal(&download_id, timestamp, now)? {
+        logger::log_info("schedule_download", &format!("Scheduled time already passed, starting {}", download_id));
+        let started = queue::enqueue(download_id, app).await?;
+        return Ok(serde_json::json!({ "scheduled": false, "started": started }));
+    }
+    
+    // A scheduled download waits for its time, not for a slot
+    queue::dequeue(&download_id);
+    logger::log_info("schedule_download", &format!("Scheduled {} to start at {}", download_id, timestamp));
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "status": "scheduled",
+        "scheduledAt": timestamp,
+    }));
+    Ok(serde_json::json!({ "scheduled": true, "scheduledAt": timestamp }))
+}
+
+// Handler 92: get-binary-versions
+// Paths and `--version` of the api-wrapper and iris binaries, for gating features on what they support
+#[command]
+pub async fn get_binary_versions() -> Result<serde_json::Value, String> {
+    Ok(binaries::binary_versions().await)
+}
+
+// Handler 93: refresh-binary-paths
+// Search for the binaries again instead of reusing the paths found at first use
+#[command]
+pub async fn refresh_binary_paths() -> Result<serde_json::Value, String> {
+    utils::clear_binary_paths();
+    Ok(serde_json::json!({
+        "apiWrapper": utils::find_go_binary().map(|p| p.to_string_lossy().to_string()),
+        "iris": utils::find_iris_binary().map(|p| p.to_string_lossy().to_string()),
+    }))
+}
+
+// Handler 94: get-download-logs
+// The last wrapper stderr lines of one download, oldest first
+#[command]
+pub async fn get_download_logs(download_id: String) -> Result<Vec<serde_json::Value>, String> {
+    use crate::download_log;
+    
+    Ok(download_log::lines(&download_id))
+}
+
+/// Check a new output file name: a single, non-empty path component
+pub fn validate_output_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("File name cannot be empty".to_string());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("File name cannot contain path separators".to_string());
+    }
+    if name == "." || name == ".." || name.chars().any(|c| c.is_control()) {
+        return Err(format!("Invalid file name: {:?}", name));
+    }
+    Ok(name.to_string())
+}
+
+/// Move an HTTP download's partial data to a new output path
+/// The wrapper finds its chunks by file name (`.accelara-temp-<name>/<name>.part.<start>.<end>`),
+/// so both the temp directory and the chunks in it are renamed.
+fn move_partial_output(old_path: &std::path::Path, new_path: &std::path::Path) -> Result<(), String> {
+    if old_path.exists() {
+        fs::rename(old_path, new_path)
+            .map_err(|e| format!("Failed to rename {}: {}", old_path.display(), e))?;
+    }
+    
+    let (old_temp, new_temp) = match (utils::chunk_temp_dir(old_path), utils::chunk_temp_dir(new_path)) {
+        (Some(old_temp), Some(new_temp)) if old_temp.is_dir() => (old_temp, new_temp),
+        _ => return Ok(()),
+    };
+    fs::rename(&old_temp, &new_temp)
+        .map_err(|e| format!("Failed to rename {}: {}", old_temp.display(), e))?;
+    
+    let old_prefix = format!("{}.part.", old_path.file_name().unwrap_or_default().to_string_lossy());
+    let new_prefix = format!("{}.part.", new_path.file_name().unwrap_or_default().to_string_lossy());
+    for chunk in utils::list_chunk_files(&new_temp) {
+        let chunk_name = chunk.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if let Some(range) = chunk_name.strip_prefix(&old_prefix) {
+            fs::rename(&chunk, new_temp.join(format!("{}{}", new_prefix, range)))
+                .map_err(|e| format!("Failed to rename {}: {}", chunk.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Change the file name of a download that isn't running, returning its new output path
+pub fn rename_download_output_internal(download_id: &str, new_name: &str) -> Result<String, String> {
+    let new_name = validate_output_name(new_name)?;
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let (status, output, download_type, metadata): (String, String, String, Option<String>) = conn.query_row(
+        "SELECT status, output, type, metadata FROM downloads WHERE id = ?1",
+        [download_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    match status.as_str() {
+        "downloading" | "seeding" => return Err("Cannot rename a download while it is running - pause it first".to_string()),
+        "completed" | "cancelled" => return Err(format!("Cannot rename a download that is {}", status)),
+        _ => {}
+    }
+    
+    let new_output = std::path::Path::new(&output).with_file_name(&new_name).to_string_lossy().to_string();
+    if new_output == output {
+        return Ok(output);
+    }
+    
+    let old_path = PathBuf::from(utils::expand_path(&output));
+    let new_path = old_path.with_file_name(&new_name);
+    if new_path.exists() {
+        return Err(format!("A file named {} already exists", new_name));
+    }
+    move_partial_output(&old_path, &new_path)?;
+    
+    // The display name comes from metadata.fileName, so keep it in step with the output
+    let mut metadata: serde_json::Value = metadata
+        .and_then(|m| serde_json::from_str(&m).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    if download_type == "http" && metadata.is_object() {
+        metadata["fileName"] = serde_json::json!(new_name);
+    }
+    
+    conn.execute(
+        "UPDATE downloads SET output = ?1, metadata = ?2 WHERE id = ?3",
+        rusqlite::params![new_output, metadata.to_string(), download_id],
+    )
+    .map_err(|e| format!("Failed to update download: {}", e))?;
+    Ok(new_output)
+}
+
+// Handler 95: rename-download-output
+// Replace the file name derived from the URL (e.g. `download.php?id=123`) before the download runs
+#[command]
+pub async fn rename_download_output(
+    download_id: String,
+    new_name: String,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    let output = rename_download_output_internal(&download_id, &new_name)?;
+    let file_name = std::path::Path::new(&output).file_name().unwrap_or_default().to_string_lossy().to_string();
+    logger::log_info("rename_download_output", &format!("Renamed output of {} to {}", download_id, output));
+    
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "output": output,
+        "fileName": file_name,
+        "displayName": file_name,
+    }));
+    Ok(serde_json::json!({ "output": output, "fileName": file_name }))
+}
+
+// Handler 23: get-system-theme
+#[command]
+pub async fn get_system_theme() -> Result<String, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+        // Use defaults read to check macOS appearance
+        let output = Command::new("defaults")
+            .args(&["read", "-g", "AppleInterfaceStyle"])
+            .output();
+        
+        match output {
+            Ok(result) if result.status.success() => {
+                let theme = String::from_utf8_lossy(&result.stdout).trim().to_lowercase();
+                if theme == "dark" {
+                    return Ok("dark".to_string());
+                }
+            }
+            _ => {
+                // If command fails or returns nothing, it's light mode
+                // (dark mode sets AppleInterfaceStyle, light mode doesn't)
+            }
+        }
+        Ok("light".to_string())
     }
     
     #[cfg(target_os = "windows")]
@@ -1569,6 +3002,9 @@ pub async fn show_window(app: tauri::AppHandle) -> Result<(), String> {
 // Handler 25: quit-app
 #[command]
 pub async fn quit_app(app: tauri::AppHandle) -> Result<(), String> {
+    // Save the latest progress while it still matches what's on disk
+    download::flush_progress_cache().await;
+    
     // Stop all downloads before quitting
     let mut processes = DOWNLOAD_PROCESSES.lock().await;
     for (download_id, mut child) in processes.drain() {
@@ -1601,15 +3037,9 @@ pub async fn quit_app(app: tauri::AppHandle) -> Result<(), String> {
 // Handler 26: get-log-path
 #[command]
 pub async fn get_log_path() -> Result<String, String> {
-    use dirs::home_dir;
+    use crate::paths;
     
-    if let Some(home) = home_dir() {
-        let log_dir = home.join(".accelara");
-        let log_path = log_dir.join("accelara.log");
-        Ok(log_path.to_string_lossy().to_string())
-    } else {
-        Err("Could not determine home directory".to_string())
-    }
+    Ok(paths::log_path().to_string_lossy().to_string())
 }
 
 // Handler 28: open-debug-log-window
@@ -1637,36 +3067,71 @@ pub async fn open_debug_log_window(app: tauri::AppHandle) -> Result<(), String>
 
 // Handler 27: get-recent-logs
 #[command]
-pub async fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, String> {
-    use dirs::home_dir;
+pub async fn get_recent_logs(
+    lines: Option<usize>,
+    level: Option<String>,
+    include_archive: Option<bool>,
+) -> Result<Vec<String>, String> {
+    use crate::logger::{self, LogLevel};
+    use crate::paths;
     use std::fs;
     use std::io::{BufRead, BufReader};
     
     let num_lines = lines.unwrap_or(50);
-    
-    if let Some(home) = home_dir() {
-        let log_dir = home.join(".accelara");
-        let log_path = log_dir.join("accelara.log");
-        
-        if !log_path.exists() {
-            return Ok(vec!["No log file found yet.".to_string()]);
-        }
-        
-        if let Ok(file) = fs::File::open(&log_path) {
-            let reader = BufReader::new(file);
-            let all_lines: Vec<String> = reader.lines().filter_map(|l| l.ok()).collect();
-            let start = if all_lines.len() > num_lines {
-                all_lines.len() - num_lines
-            } else {
-                0
-            };
-            Ok(all_lines[start..].to_vec())
-        } else {
-            Err("Failed to read log file".to_string())
+    // Only keep lines at or above this level (lines without a level, like session headers, are dropped)
+    let min_level = match level {
+        Some(name) => Some(LogLevel::parse(&name).ok_or_else(|| format!("Unknown log level: {}", name))?),
+        None => None,
+    };
+    let log_path = paths::log_path();
+    
+    // Right after a rotation the current file is short, so optionally start from the latest archive
+    let mut files = Vec::new();
+    if include_archive.unwrap_or(false) {
+        let archive = logger::archive_path(&log_path, 1);
+        if archive.exists() {
+            files.push(archive);
         }
-    } else {
-        Err("Could not determine home directory".to_string())
     }
+    if log_path.exists() {
+        files.push(log_path);
+    }
+    if files.is_empty() {
+        return Ok(vec!["No log file found yet.".to_string()]);
+    }
+    
+    let mut all_lines: Vec<String> = Vec::new();
+    for path in files {
+        let file = fs::File::open(&path)
+            .map_err(|_| "Failed to read log file".to_string())?;
+        all_lines.extend(BufReader::new(file).lines()
+            .filter_map(|l| l.ok())
+            .filter(|l| match min_level {
+                Some(min) => logger::line_level(l).map(|lvl| lvl >= min).unwrap_or(false),
+                None => true,
+            }));
+    }
+    let start = if all_lines.len() > num_lines {
+        all_lines.len() - num_lines
+    } else {
+        0
+    };
+    Ok(all_lines[start..].to_vec())
+}
+
+// Handler 87: start-log-streaming
+#[command]
+pub async fn start_log_streaming(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    // Every debug window shares one watcher; a second call just reports it's already running
+    let started = crate::log_stream::start(app);
+    Ok(serde_json::json!({ "started": started }))
+}
+
+// Handler 88: stop-log-streaming
+#[command]
+pub async fn stop_log_streaming() -> Result<serde_json::Value, String> {
+    let stopped = crate::log_stream::stop();
+    Ok(serde_json::json!({ "stopped": stopped }))
 }
 
 // Handler 26: check-for-updates
@@ -1687,12 +3152,15 @@ pub async fn check_for_updates() -> Result<updater::UpdateCheckResult, String> {
 
 // Handler 27: download-update
 #[command]
-pub async fn download_update(asset_url: String, filename: String) -> Result<String, String> {
+pub async fn download_update(asset_url: String, filename: String, app: tauri::AppHandle) -> Result<serde_json::Value, String> {
     use crate::logger;
     logger::log_info("download_update", &format!("Starting download: {}", filename));
-    let path = updater::download_update(&asset_url, &filename).await?;
-    logger::log_info("download_update", &format!("Download complete: {}", path.display()));
-    Ok(path.to_string_lossy().to_string())
+    let download = updater::download_update(&asset_url, &filename, &app).await?;
+    logger::log_info("download_update", &format!("Download complete: {} (verified: {})", download.path.display(), download.verified));
+    Ok(serde_json::json!({
+        "path": download.path.to_string_lossy(),
+        "verified": download.verified,
+    }))
 }
 
 // Handler 28: install-update
@@ -1793,3 +3261,2819 @@ pub async fn restart_app(app: tauri::AppHandle) -> Result<(), String> {
     
     Ok(())
 }
+
+// Handler 30: relocate-paths
+#[command]
+pub async fn relocate_paths(old_prefix: String, new_prefix: String) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    // "/a/b/" and "/a/b" name the same folder
+    let old_prefix = old_prefix.trim_end_matches(['/', '\\']).to_string();
+    if old_prefix.is_empty() {
+        return Err("Old path prefix cannot be empty or the filesystem root".to_string());
+    }
+    
+    // The new location must already exist - we only rewrite DB rows, we don't move files
+    let new_prefix = utils::expand_path(&new_prefix).trim_end_matches(['/', '\\']).to_string();
+    if new_prefix.is_empty() {
+        return Err("New location cannot be the filesystem root".to_string());
+    }
+    if !std::path::Path::new(&new_prefix).exists() {
+        return Err(format!("New location does not exist: {}", new_prefix));
+    }
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    // Only replace whole leading path segments: "/a/Down" must leave "/a/Downloads/x" alone
+    let downloads_updated = conn.execute(
+        "UPDATE downloads SET output = ?2 || substr(output, length(?1) + 1)
+         WHERE output = ?1
+            OR (substr(output, 1, length(?1)) = ?1 AND substr(output, length(?1) + 1, 1) IN ('/', '\\'))",
+        rusqlite::params![old_prefix, new_prefix],
+    )
+    .map_err(|e| format!("Failed to update downloads: {}", e))?;
+    
+    let history_updated = conn.execute(
+        "UPDATE download_history SET output = ?2 || substr(output, length(?1) + 1)
+         WHERE output = ?1
+            OR (substr(output, 1, length(?1)) = ?1 AND substr(output, length(?1) + 1, 1) IN ('/', '\\'))",
+        rusqlite::params![old_prefix, new_prefix],
+    )
+    .map_err(|e| format!("Failed to update download history: {}", e))?;
+    
+    logger::log_info("relocate_paths", &format!("Relocated {} -> {} ({} download(s), {} history entr(ies))",
+        old_prefix, new_prefix, downloads_updated, history_updated));
+    
+    Ok(serde_json::json!({
+        "downloadsUpdated": downloads_updated,
+        "historyUpdated": history_updated,
+    }))
+}
+
+/// Look for a file that has gone missing from `path` in the parent directory
+/// and in its sibling directories (e.g. after the containing folder was renamed)
+fn find_relocated_file(path: &std::path::Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?;
+    let parent = path.parent()?;
+    
+    let mut search_dirs = Vec::new();
+    if parent.exists() {
+        search_dirs.push(parent.to_path_buf());
+        if let Ok(entries) = fs::read_dir(parent) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    search_dirs.push(entry.path());
+                }
+            }
+        }
+    }
+    if let Some(grandparent) = parent.parent() {
+        if let Ok(entries) = fs::read_dir(grandparent) {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                if dir.is_dir() && dir != parent {
+                    search_dirs.push(dir);
+                }
+            }
+        }
+    }
+    
+    search_dirs
+        .into_iter()
+        .map(|dir| dir.join(file_name))
+        .find(|candidate| candidate.exists())
+}
+
+// Handler 31: scan-and-fix-paths
+#[command]
+pub async fn scan_and_fix_paths() -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let mut fixed = 0u64;
+    let mut unfixable = Vec::new();
+    
+    for table in ["downloads", "download_history"] {
+        let query = if table == "downloads" {
+            "SELECT id, output FROM downloads WHERE status = 'completed'"
+        } else {
+            "SELECT id, output FROM download_history"
+        };
+        
+        let entries: Vec<(String, String)> = {
+            let mut stmt = conn.prepare(query)
+                .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+                .map_err(|e| format!("Failed to query: {}", e))?;
+            let entries: Vec<(String, String)> = rows.filter_map(|r| r.ok()).collect();
+            entries
+        };
+        
+        for (id, output) in entries {
+            let expanded_output = utils::expand_path(&output);
+            let output_path = std::path::Path::new(&expanded_output);
+            if output_path.exists() {
+                continue;
+            }
+            
+            match find_relocated_file(output_path) {
+                Some(found) => {
+                    let new_output = found.to_string_lossy().to_string();
+                    conn.execute(
+                        &format!("UPDATE {} SET output = ?1 WHERE id = ?2", table),
+                        rusqlite::params![new_output, id],
+                    )
+                    .map_err(|e| format!("Failed to update path: {}", e))?;
+                    logger::log_info("scan_and_fix_paths", &format!("[{}] {} -> {}", id, output, new_output));
+                    fixed += 1;
+                }
+                None => {
+                    unfixable.push(serde_json::json!({
+                        "id": id,
+                        "output": output,
+                    }));
+                }
+            }
+        }
+    }
+    
+    Ok(serde_json::json!({
+        "fixed": fixed,
+        "unfixable": unfixable.len(),
+        "unfixableEntries": unfixable,
+    }))
+}
+
+// Handler 32: assemble-download
+#[command]
+pub async fn assemble_download(
+    download_id: String,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    if DOWNLOAD_PROCESSES.lock().await.contains_key(&download_id) {
+        return Err("Download is still running. Pause it before assembling.".to_string());
+    }
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let (source, output, download_type, total): (String, String, String, i64) = conn.query_row(
+        "SELECT source, output, type, total FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    if download_type != "http" {
+        return Err("Only HTTP downloads are assembled from chunks".to_string());
+    }
+    
+    let expanded_output = utils::expand_path(&output);
+    let output_path = std::path::Path::new(&expanded_output);
+    let temp_dir = utils::chunk_temp_dir(output_path)
+        .ok_or_else(|| format!("Invalid output path: {}", expanded_output))?;
+    
+    if !temp_dir.exists() {
+        return Err(format!("No chunk directory found at {}", temp_dir.display()));
+    }
+    
+    let chunks = utils::list_chunk_files(&temp_dir);
+    if chunks.is_empty() {
+        return Err(format!("No chunk files found in {}", temp_dir.display()));
+    }
+    if let Some(empty) = chunks.iter().find(|c| fs::metadata(c).map(|m| m.len() == 0).unwrap_or(true)) {
+        return Err(format!("Chunk file is empty or unreadable: {}", empty.display()));
+    }
+    
+    // The wrapper only sees the parts (and removes them as it merges); a missing last part would
+    // still make a contiguous, short file, so check they add up to the whole download first
+    let chunk_bytes: u64 = chunks.iter().filter_map(|c| fs::metadata(c).ok()).map(|m| m.len()).sum();
+    if total > 0 && chunk_bytes != total as u64 {
+        return Err(format!("Chunks hold {} of {} bytes - resume the download to fetch the rest", chunk_bytes, total));
+    }
+    
+    logger::log_info("assemble_download", &format!("[{}] Assembling {} chunk(s) from {}", download_id, chunks.len(), temp_dir.display()));
+    
+    let go_binary = utils::find_go_binary()
+        .ok_or_else(|| "Go binary (api-wrapper) not found".to_string())?;
+    let verified_binary = utils::verify_binary_path(&go_binary)
+        .map_err(|e| format!("Binary verification failed: {}", e))?;
+    
+    let child = TokioCommand::new(&verified_binary)
+        .args(&[
+            "--assemble-only",
+            "--source", &source,
+            "--output", &expanded_output,
+            "--download-id", &download_id,
+        ])
+        .env(process_cleanup::DOWNLOAD_ID_ENV, &download_id)
+        .current_dir(utils::get_working_directory())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn process: {}", e))?;
+    process_cleanup::record_spawn(child.id(), &download_id);
+    let result = child.wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run process: {}", e))?;
+    
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        logger::log_error("assemble_download", &format!("[{}] Assembly failed: {}", download_id, stderr.trim()));
+        return Err(format!("Assembly failed (chunks may be corrupt): {}", stderr.trim()));
+    }
+    
+    // Verify the final file actually exists before marking the download completed
+    let size = fs::metadata(output_path)
+        .ok()
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .ok_or_else(|| format!("Assembly reported success but {} does not exist", expanded_output))?;
+    
+    conn.execute(
+        "UPDATE downloads SET status = 'completed', error = NULL, progress = 1.0, downloaded = ?1 WHERE id = ?2",
+        rusqlite::params![size as i64, download_id],
+    )
+    .map_err(|e| format!("Failed to update download: {}", e))?;
+    download::record_in_history(&download_id);
+    
+    logger::log_info("assemble_download", &format!("[{}] ✓ Assembled {} ({} bytes)", download_id, expanded_output, size));
+    
+    let _ = app.emit("download-complete", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "success": true,
+    }));
+    
+    Ok(serde_json::json!({
+        "output": expanded_output,
+        "size": size,
+        "chunks": chunks.len(),
+    }))
+}
+
+// Handler 33: load-workspace
+#[command]
+pub async fn load_workspace(path: String) -> Result<serde_json::Value, String> {
+    use crate::workspace;
+    
+    let workspace_path = workspace::resolve_workspace_path(&path);
+    let (config, warnings) = workspace::load_workspace(&workspace_path)?;
+    
+    Ok(serde_json::json!({
+        "path": workspace_path.to_string_lossy(),
+        "config": config,
+        "warnings": warnings,
+    }))
+}
+
+// Handler 34: run-workspace
+#[command]
+pub async fn run_workspace(path: String, app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    use crate::workspace;
+    
+    let workspace_path = workspace::resolve_workspace_path(&path);
+    let (config, warnings) = workspace::load_workspace(&workspace_path)?;
+    let options = config.download_options();
+    
+    let mut added = Vec::new();
+    let mut failed = Vec::new();
+    
+    for source in &config.sources {
+        // Per-source output wins, then the workspace folder, then the app default
+        let output = match (source.output(), &config.output) {
+            (Some(output), _) => Some(derive_output_path(source.url(), output)),
+            (None, Some(folder)) => Some(derive_output_path(source.url(), folder)),
+            (None, None) => None,
+        };
+        
+        let download_config = DownloadConfig {
+            source: source.url().to_string(),
+            output,
+            options: Some(options.clone()),
+            confirmed: true,
+            category: None,
+            mirrors: Vec::new(),
+            torrent_data: None,
+        };
+        
+        match start_download_internal(download_config, app.clone()).await {
+            Ok(download_id) => added.push(download_id),
+            Err(e) => {
+                logger::log_error("run_workspace", &format!("Failed to add {}: {}", source.url(), e));
+                failed.push(serde_json::json!({
+                    "source": source.url(),
+                    "error": e,
+                }));
+            }
+        }
+    }
+    
+    logger::log_info("run_workspace", &format!("Added {} download(s) from {}", added.len(), workspace_path.display()));
+    
+    Ok(serde_json::json!({
+        "added": added,
+        "failed": failed,
+        "warnings": warnings,
+    }))
+}
+
+// Handler 35: is-resumable
+#[command]
+pub async fn is_resumable(download_id: String) -> Result<serde_json::Value, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let (download_type, metadata_str): (String, Option<String>) = conn.query_row(
+        "SELECT type, metadata FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    let metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    
+    let (resumable, reason) = if download_type == "torrent" || download_type == "magnet" {
+        (true, "Torrents can always be resumed")
+    } else {
+        let supports_range = metadata.get("supportsRange")
+            .or_else(|| metadata.get("httpInfo").and_then(|i| i.get("acceptRanges")))
+            .and_then(|v| v.as_bool());
+        match supports_range {
+            Some(true) => (true, "Server supports range requests"),
+            Some(false) => (false, "This server doesn't support resuming; pausing will restart from zero"),
+            None => (false, "Server resume support is unknown; pausing may restart from zero"),
+        }
+    };
+    
+    Ok(serde_json::json!({
+        "resumable": resumable,
+        "reason": reason,
+    }))
+}
+
+// Handler 36: find-orphaned-processes
+#[command]
+pub async fn find_orphaned_processes() -> Result<Vec<process_cleanup::OrphanProcess>, String> {
+    Ok(process_cleanup::find_orphaned_processes().await)
+}
+
+// Handler 37: kill-orphaned-processes
+#[command]
+pub async fn kill_orphaned_processes() -> Result<Vec<process_cleanup::OrphanProcess>, String> {
+    Ok(process_cleanup::kill_orphaned_processes().await)
+}
+
+// Handler 38: get-app-paths
+#[command]
+pub async fn get_app_paths() -> Result<serde_json::Value, String> {
+    use crate::paths;
+    
+    let settings = get_settings().await.unwrap_or_default();
+    let default_download_path = settings
+        .get("defaultDownloadPath")
+        .and_then(|v| v.as_str())
+        .map(utils::expand_path)
+        .unwrap_or_default();
+    
+    Ok(serde_json::json!({
+        "dataDir": paths::app_data_dir().to_string_lossy(),
+        "dbPath": paths::db_path().to_string_lossy(),
+        "logPath": paths::log_path().to_string_lossy(),
+        "defaultDownloadPath": default_download_path,
+        "tempDir": paths::temp_dir().to_string_lossy(),
+        "updateCacheDir": paths::update_cache_dir().to_string_lossy(),
+    }))
+}
+
+// Handler 39: get-download-speed-history
+#[command]
+pub async fn get_download_speed_history(download_id: String) -> Result<Vec<serde_json::Value>, String> {
+    // Samples cover the last minute at one-second resolution; empty once the download stops
+    Ok(download::get_speed_history(&download_id)
+        .into_iter()
+        .map(|(timestamp, speed)| serde_json::json!({
+            "timestamp": timestamp,
+            "speed": speed,
+        }))
+        .collect())
+}
+
+// Handler 40: restart-download-from-scratch
+#[command]
+pub async fn restart_download_from_scratch(download_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    use crate::logger;
+    
+    if DOWNLOAD_PROCESSES.lock().await.contains_key(&download_id) {
+        pause_download_internal(download_id.clone(), app.clone(), "Restarting from scratch").await?;
+    }
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let (source, output, download_type, metadata_str): (String, String, String, Option<String>) = conn.query_row(
+        "SELECT source, output, type, metadata FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    if download_type != "http" {
+        return Err("Only HTTP downloads can be restarted from scratch".to_string());
+    }
+    
+    // Throw away the old chunks and any partial output
+    let output_path = PathBuf::from(utils::expand_path(&output));
+    if let Some(temp_dir) = utils::chunk_temp_dir(&output_path) {
+        if temp_dir.exists() {
+            fs::remove_dir_all(&temp_dir)
+                .map_err(|e| format!("Failed to remove chunk directory {}: {}", temp_dir.display(), e))?;
+        }
+    }
+    if output_path.is_file() {
+        let _ = fs::remove_file(&output_path);
+    }
+    
+    let mut metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    metadata["source_changed"] = serde_json::json!(false);
+    let options = stored_options(&download_id, &metadata);
+    metadata["validators"] = fetch_http_validators(&source, &options).await.unwrap_or(serde_json::Value::Null);
+    
+    conn.execute(
+        "UPDATE downloads SET progress = 0, downloaded = 0, total = 0, speed = 0, metadata = ?1 WHERE id = ?2",
+        rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+    )
+    .map_err(|e| format!("Failed to reset download: {}", e))?;
+    drop(conn);
+    
+    logger::log_info("restart_download_from_scratch", &format!("Reset {} and restarting", download_id));
+    queue::enqueue(download_id, app).await.map(|_| ())
+}
+
+// Helper to pick an output path that no other download or existing file uses
+// "file.iso" becomes "file (2).iso", "file (3).iso", ...
+fn unique_output_path(output: &str) -> Result<String, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let taken = |candidate: &str| -> bool {
+        let in_db = conn.query_row(
+            "SELECT COUNT(*) FROM downloads WHERE output = ?1",
+            [candidate],
+            |row| row.get::<_, i64>(0),
+        ).unwrap_or(0) > 0;
+        in_db || PathBuf::from(utils::expand_path(candidate)).exists()
+    };
+    
+    let path = std::path::Path::new(output);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("download");
+    let extension = path.extension().and_then(|e| e.to_str());
+    let parent = path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    
+    for n in 2..1000 {
+        let file_name = match extension {
+            Some(ext) => format!("{} ({}).{}", stem, n, ext),
+            None => format!("{} ({})", stem, n),
+        };
+        let candidate = if parent.is_empty() {
+            file_name
+        } else {
+            format!("{}/{}", parent, file_name)
+        };
+        if !taken(&candidate) {
+            return Ok(candidate);
+        }
+    }
+    
+    Err(format!("Could not find a free output name for {}", output))
+}
+
+// Handler 41: duplicate-download
+#[command]
+pub async fn duplicate_download(
+    download_id: String,
+    option_overrides: Option<serde_json::Value>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let (source, output, metadata_str, category): (String, String, Option<String>, Option<String>) = {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        conn.query_row(
+            "SELECT source, output, metadata, category FROM downloads WHERE id = ?1",
+            [&download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|_| "Download not found".to_string())?
+    };
+    
+    let metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    
+    // Start from the original options and apply the overrides on top
+    let mut options = match stored_options(&download_id, &metadata) {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    match option_overrides {
+        Some(serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                options.insert(key, value);
+            }
+        }
+        Some(serde_json::Value::Null) | None => {}
+        Some(_) => return Err("Option overrides must be an object".to_string()),
+    }
+    
+    let config = DownloadConfig {
+        source,
+        output: Some(unique_output_path(&output)?),
+        options: Some(serde_json::Value::Object(options)),
+        // The original was already confirmed (or below the threshold)
+        confirmed: true,
+        category,
+        mirrors: metadata.get("mirrors")
+            .and_then(|m| serde_json::from_value(m.clone()).ok())
+            .unwrap_or_default(),
+        torrent_data: None,
+    };
+    
+    start_download_internal(config, app).await
+}
+
+// Handler 42: query-downloads
+#[command]
+pub async fn query_downloads(query: Option<DownloadQuery>) -> Result<serde_json::Value, String> {
+    let query = query.unwrap_or_default();
+    let (downloads, total) = query_downloads_internal(&query)?;
+    
+    Ok(serde_json::json!({
+        "items": downloads,
+        "total": total,
+        "offset": query.offset.unwrap_or(0),
+        "limit": query.limit,
+    }))
+}
+
+// Handler 43: ensure-download-folder
+#[command]
+pub async fn ensure_download_folder(path: String, create: Option<bool>) -> Result<serde_json::Value, String> {
+    let folder = PathBuf::from(utils::expand_path(&path));
+    let existed = folder.exists();
+    
+    // Only create the folder when the user agreed to it
+    if !existed && create.unwrap_or(false) {
+        fs::create_dir_all(&folder)
+            .map_err(|e| format!("Failed to create download folder {}: {}", folder.display(), e))?;
+    }
+    
+    let writable = utils::check_dir_writable(&folder);
+    Ok(serde_json::json!({
+        "path": folder.to_string_lossy(),
+        "exists": folder.exists(),
+        "created": !existed && folder.exists(),
+        "writable": writable.is_ok(),
+        "error": writable.err(),
+    }))
+}
+
+// Handler 44: add-downloads-batch
+#[command]
+pub async fn add_downloads_batch(
+    sources: Vec<String>,
+    output_folder: Option<String>,
+    options: Option<serde_json::Value>,
+    batch_name: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let batch_id = format!("batch-{}", nanoid::nanoid!(9));
+    let batch_name = batch_name.filter(|n| !n.trim().is_empty());
+    
+    let mut download_ids = Vec::new();
+    let mut errors = Vec::new();
+    
+    for source in sources {
+        let config = DownloadConfig {
+            output: output_folder.as_deref().map(|folder| derive_output_path(&source, folder)),
+            source: source.clone(),
+            options: options.clone(),
+            confirmed: true,
+            category: None,
+            mirrors: Vec::new(),
+            torrent_data: None,
+        };
+        
+        match start_download_internal(config, app.clone()).await {
+            Ok(download_id) => {
+                let conn = database::get_connection()
+                    .map_err(|e| format!("Database error: {}", e))?;
+                conn.execute(
+                    "UPDATE downloads SET batch_id = ?1, batch_name = ?2 WHERE id = ?3",
+                    rusqlite::params![batch_id, batch_name, download_id],
+                )
+                .map_err(|e| format!("Failed to assign batch: {}", e))?;
+                download_ids.push(download_id);
+            }
+            Err(e) => errors.push(serde_json::json!({ "source": source, "error": e })),
+        }
+    }
+    
+    let _ = app.emit("batch-update", serde_json::json!({
+        "batchId": batch_id,
+        "batchName": batch_name,
+        "action": "add",
+        "succeeded": download_ids,
+        "failed": errors,
+    }));
+    
+    Ok(serde_json::json!({
+        "batchId": batch_id,
+        "downloadIds": download_ids,
+        "errors": errors,
+    }))
+}
+
+// Helper to list the downloads of a batch, optionally limited to some statuses
+fn batch_download_ids(batch_id: &str, statuses: &[&str]) -> Result<Vec<String>, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut stmt = conn.prepare("SELECT id, status FROM downloads WHERE batch_id = ?1 ORDER BY started_at")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let rows = stmt.query_map([batch_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("Failed to query: {}", e))?;
+    
+    let ids: Vec<String> = rows
+        .filter_map(|r| r.ok())
+        .filter(|(_, status)| statuses.is_empty() || statuses.contains(&status.as_str()))
+        .map(|(id, _)| id)
+        .collect();
+    Ok(ids)
+}
+
+// Helper to emit the single summary event for a batch operation
+fn emit_batch_result(app: &tauri::AppHandle, batch_id: &str, action: &str, succeeded: Vec<String>, failed: Vec<serde_json::Value>) -> serde_json::Value {
+    let result = serde_json::json!({
+        "batchId": batch_id,
+        "action": action,
+        "succeeded": succeeded,
+        "failed": failed,
+    });
+    let _ = app.emit("batch-update", result.clone());
+    result
+}
+
+// Handler 45: pause-batch
+#[command]
+pub async fn pause_batch(batch_id: String, app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let (mut succeeded, mut failed) = (Vec::new(), Vec::new());
+    for download_id in batch_download_ids(&batch_id, &["downloading"])? {
+        match pause_download_internal(download_id.clone(), app.clone(), "Paused by user").await {
+            Ok(_) => succeeded.push(download_id),
+            Err(e) => failed.push(serde_json::json!({ "downloadId": download_id, "error": e })),
+        }
+    }
+    Ok(emit_batch_result(&app, &batch_id, "pause", succeeded, failed))
+}
+
+// Handler 46: resume-batch
+#[command]
+pub async fn resume_batch(batch_id: String, app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let (mut succeeded, mut failed) = (Vec::new(), Vec::new());
+    for (index, download_id) in batch_download_ids(&batch_id, &["paused", "error"])?.into_iter().enumerate() {
+        if index > 0 {
+            // Batches often come from one mirror - spread the starts out
+            tokio::time::sleep(utils::start_stagger()).await;
+        }
+        match queue::enqueue(download_id.clone(), app.clone()).await {
+            Ok(_) => succeeded.push(download_id),
+            Err(e) => failed.push(serde_json::json!({ "downloadId": download_id, "error": e })),
+        }
+    }
+    Ok(emit_batch_result(&app, &batch_id, "resume", succeeded, failed))
+}
+
+// Handler 47: remove-batch
+#[command]
+pub async fn remove_batch(batch_id: String, app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let (mut succeeded, mut failed) = (Vec::new(), Vec::new());
+    for download_id in batch_download_ids(&batch_id, &[])? {
+        match remove_download(download_id.clone(), app.clone()).await {
+            Ok(_) => succeeded.push(download_id),
+            Err(e) => failed.push(serde_json::json!({ "downloadId": download_id, "error": e })),
+        }
+    }
+    Ok(emit_batch_result(&app, &batch_id, "remove", succeeded, failed))
+}
+
+// Handler 48: get-torrent-peer-stats
+#[command]
+pub async fn get_torrent_peer_stats() -> Result<serde_json::Value, String> {
+    Ok(peer_budget::peer_stats())
+}
+
+// Helper to load a torrent download's source and type
+fn torrent_source(download_id: &str) -> Result<String, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let (source, download_type): (String, String) = conn.query_row(
+        "SELECT source, type FROM downloads WHERE id = ?1",
+        [download_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    if download_type != "torrent" && download_type != "magnet" {
+        return Err("Download is not a torrent".to_string());
+    }
+    Ok(source)
+}
+
+// Handler 49: export-torrent
+#[command]
+pub async fn export_torrent(download_id: String, dest_path: String) -> Result<String, String> {
+    use crate::logger;
+    
+    let source = torrent_source(&download_id)?;
+    let dest = PathBuf::from(utils::expand_path(&dest_path));
+    if let Some(parent) = dest.parent() {
+        utils::check_dir_writable(parent)?;
+    }
+    
+    // A local .torrent file already is the metainfo
+    let local = PathBuf::from(utils::expand_path(&source));
+    if !source.starts_with("magnet:") && local.is_file() {
+        fs::copy(&local, &dest)
+            .map_err(|e| format!("Failed to copy torrent file: {}", e))?;
+        return Ok(dest.to_string_lossy().to_string());
+    }
+    
+    // Otherwise have the wrapper fetch the metainfo (from the URL or the swarm) and write it out
+    let go_binary = utils::find_go_binary()
+        .ok_or_else(|| "Go binary (api-wrapper) not found".to_string())?;
+    let verified_binary = utils::verify_binary_path(&go_binary)
+        .map_err(|e| format!("Binary verification failed: {}", e))?;
+    let (timeout_args, deadline) = probe_timeouts().await;
+    let dest_str = dest.to_string_lossy().to_string();
+    
+    let output = tokio::time::timeout(deadline, TokioCommand::new(&verified_binary)
+        .args(&["--inspect", "--source", &source, "--export-torrent", &dest_str])
+        .args(&timeout_args)
+        .current_dir(utils::get_working_directory())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output())
+        .await
+        .map_err(|_| format!("Fetching torrent metadata timed out after {}s", deadline.as_secs()))?
+        .map_err(|e| format!("Failed to spawn process: {}", e))?;
+    
+    if !output.status.success() || !dest.is_file() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to export torrent: {}", stderr.trim()));
+    }
+    
+    logger::log_info("export_torrent", &format!("Exported torrent of {} to {}", download_id, dest.display()));
+    Ok(dest_str)
+}
+
+// Handler 50: get-magnet-link
+#[command]
+pub async fn get_magnet_link(download_id: String) -> Result<String, String> {
+    let source = torrent_source(&download_id)?;
+    if source.starts_with("magnet:") {
+        return Ok(source);
+    }
+    
+    // Build the link from the wrapper's view of the .torrent
+    let info = inspect_torrent(source).await?;
+    let info_hash = info.get("infoHash")
+        .or_else(|| info.get("infoHashV1"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Torrent info hash not available".to_string())?;
+    
+    let mut link = reqwest::Url::parse(&format!("magnet:?xt=urn:btih:{}", info_hash))
+        .map_err(|e| format!("Failed to build magnet link: {}", e))?;
+    {
+        let mut query = link.query_pairs_mut();
+        if let Some(name) = info.get("name").and_then(|v| v.as_str()) {
+            query.append_pair("dn", name);
+        }
+        let trackers = info.get("announceList")
+            .or_else(|| info.get("trackers"))
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for tracker in trackers.iter().filter_map(|t| t.as_str()) {
+            query.append_pair("tr", tracker);
+        }
+    }
+    
+    Ok(link.to_string())
+}
+
+// Handler 51: ping-browser-server
+#[command]
+pub async fn ping_browser_server() -> Result<serde_json::Value, String> {
+    use crate::browser_server;
+    
+    let port = browser_server::server_port();
+    let url = format!("http://localhost:{}", port);
+    
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(3))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    
+    let (reachable, version, error) = match client.get(format!("{}/health", url)).send().await {
+        Ok(response) if response.status().is_success() => {
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            let ok = body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
+            (ok, body.get("version").cloned(), None)
+        }
+        Ok(response) => (false, None, Some(format!("Server responded with {}", response.status()))),
+        Err(e) => (false, None, Some(format!("Server not reachable: {}", e))),
+    };
+    
+    Ok(serde_json::json!({
+        "reachable": reachable,
+        "url": url,
+        "port": port,
+        "version": version,
+        "error": error,
+    }))
+}
+
+// Handler 52: set-download-notes
+#[command]
+pub async fn set_download_notes(download_id: String, notes: Option<String>) -> Result<(), String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    // Empty notes are stored as NULL
+    let notes = notes.filter(|n| !n.trim().is_empty());
+    
+    // The same ID is used in both tables, so keep the history copy in sync too
+    let updated = conn.execute(
+        "UPDATE downloads SET notes = ?1 WHERE id = ?2",
+        rusqlite::params![notes, download_id],
+    )
+    .map_err(|e| format!("Failed to update notes: {}", e))?
+        + conn.execute(
+            "UPDATE download_history SET notes = ?1 WHERE id = ?2",
+            rusqlite::params![notes, download_id],
+        )
+        .map_err(|e| format!("Failed to update notes: {}", e))?;
+    
+    if updated == 0 {
+        return Err("Download not found".to_string());
+    }
+    Ok(())
+}
+
+// Handler 53: get-infohash
+#[command]
+pub async fn get_infohash(source: String) -> Result<serde_json::Value, String> {
+    // Magnet links carry their hashes and trackers, so those are known without fetching anything
+    let magnet = if source.starts_with("magnet:") {
+        Some(utils::parse_magnet(&source).ok_or_else(|| "Invalid magnet link".to_string())?)
+    } else {
+        None
+    };
+    
+    // The wrapper computes the hashes of .torrent files and fetches the metadata of magnets
+    let info = inspect_torrent(source).await?;
+    let field = |keys: &[&str]| -> Option<String> {
+        keys.iter()
+            .find_map(|key| info.get(*key).and_then(|v| v.as_str()))
+            .map(|s| s.to_ascii_lowercase())
+    };
+    
+    let info_hash_v1 = field(&["infoHashV1", "infoHash"])
+        .or_else(|| magnet.as_ref().and_then(|m| m.info_hash_v1.clone()));
+    let info_hash_v2 = field(&["infoHashV2"])
+        .or_else(|| magnet.as_ref().and_then(|m| m.info_hash_v2.clone()));
+    
+    if info_hash_v1.is_none() && info_hash_v2.is_none() {
+        return Err("Could not determine the torrent's info hash".to_string());
+    }
+    
+    // Prefer the announce list from the metainfo, falling back to the magnet's trackers
+    let mut announce_list: Vec<String> = info.get("announceList")
+        .or_else(|| info.get("trackers"))
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    if announce_list.is_empty() {
+        if let Some(m) = &magnet {
+            announce_list = m.trackers.clone();
+        }
+    }
+    
+    Ok(serde_json::json!({
+        "infoHashV1": info_hash_v1,
+        "infoHashV2": info_hash_v2,
+        "hybrid": info_hash_v1.is_some() && info_hash_v2.is_some(),
+        "name": info.get("name").cloned().or_else(|| magnet.as_ref().and_then(|m| m.name.clone()).map(serde_json::Value::String)),
+        "totalSize": info.get("totalSize").cloned(),
+        "announceList": announce_list,
+    }))
+}
+
+// Handler 54: get-settings-with-sources
+#[command]
+pub async fn get_settings_with_sources() -> Result<Vec<serde_json::Value>, String> {
+    let defaults = default_settings();
+    let stored: HashMap<String, serde_json::Value> = stored_settings()?.into_iter().collect();
+    
+    let mut keys: Vec<String> = defaults.as_object()
+        .map(|d| d.keys().cloned().collect())
+        .unwrap_or_default();
+    keys.extend(stored.keys().filter(|k| defaults.get(k.as_str()).is_none()).cloned());
+    keys.sort();
+    
+    Ok(keys.into_iter().map(|key| {
+        let (value, is_default) = match stored.get(&key) {
+            Some(value) => (value.clone(), false),
+            None => (defaults.get(&key).cloned().unwrap_or(serde_json::Value::Null), true),
+        };
+        serde_json::json!({
+            "key": key,
+            "value": value,
+            "isDefault": is_default,
+            "default": defaults.get(&key),
+        })
+    }).collect())
+}
+
+// Helper to tell the UI which settings went back to their defaults
+async fn emit_settings_changed(app: &tauri::AppHandle, keys: Vec<String>) {
+    let settings = get_settings().await.unwrap_or_default();
+    let _ = app.emit("settings-changed", serde_json::json!({
+        "keys": keys,
+        "settings": settings,
+    }));
+}
+
+// Handler 55: reset-setting
+#[command]
+pub async fn reset_setting(key: String, app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    conn.execute("DELETE FROM settings WHERE key = ?1", [&key])
+        .map_err(|e| format!("Failed to reset setting: {}", e))?;
+    drop(conn);
+    
+    emit_settings_changed(&app, vec![key.clone()]).await;
+    Ok(default_settings().get(&key).cloned().unwrap_or(serde_json::Value::Null))
+}
+
+// Handler 56: reset-all-settings
+#[command]
+pub async fn reset_all_settings(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let keys: Vec<String> = stored_settings()?.into_iter().map(|(key, _)| key).collect();
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    conn.execute("DELETE FROM settings", [])
+        .map_err(|e| format!("Failed to reset settings: {}", e))?;
+    drop(conn);
+    
+    emit_settings_changed(&app, keys).await;
+    Ok(default_settings())
+}
+
+// Helper to find the files a download produced on disk
+// Torrent outputs are the shared download folder, so only the torrent's own entry inside it counts
+fn download_data_paths(output: &str, download_type: &str, metadata: &serde_json::Value) -> Vec<PathBuf> {
+    let output_path = PathBuf::from(utils::expand_path(output));
+    let mut paths = Vec::new();
+    
+    if download_type == "torrent" || download_type == "magnet" {
+        if let Some(name) = utils::torrent_name(metadata) {
+            paths.push(output_path.join(name));
+        }
+    } else {
+        if let Some(temp_dir) = utils::chunk_temp_dir(&output_path) {
+            paths.push(temp_dir);
+        }
+        paths.push(output_path);
+    }
+    
+    paths.into_iter().filter(|p| p.exists()).collect()
+}
+
+// Handler 57: remove-downloads-by-status
+#[command]
+pub async fn remove_downloads_by_status(
+    status: String,
+    delete_files: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    if status == "downloading" || status == "seeding" {
+        return Err(format!("Refusing to bulk-remove active downloads ({})", status));
+    }
+    let delete_files = delete_files.unwrap_or(false);
+    
+    let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let rows: Vec<(String, String, String, Option<String>)> = {
+        let mut stmt = conn.prepare("SELECT id, output, type, metadata FROM downloads WHERE status = ?1")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt.query_map([&status], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .map_err(|e| format!("Failed to query: {}", e))?;
+        let rows = rows.filter_map(|r| r.ok()).collect();
+        rows
+    };
+    
+    let mut removed_ids = Vec::new();
+    let mut bytes_freed: u64 = 0;
+    
+    for (download_id, output, download_type, metadata_str) in rows {
+        // A row can say paused/error while its process is still winding down
+        if running.contains(&download_id) {
+            continue;
+        }
+        
+        if delete_files {
+            let metadata: serde_json::Value = metadata_str
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_else(|| serde_json::json!({}));
+            for path in download_data_paths(&output, &download_type, &metadata) {
+                let size = calculate_dir_size(path.clone()).unwrap_or(0);
+                let result = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+                match result {
+                    Ok(_) => bytes_freed += size,
+                    Err(e) => logger::log_warning("remove_downloads_by_status",
+                        &format!("Failed to delete {}: {}", path.display(), e)),
+                }
+            }
+        }
+        
+        conn.execute("DELETE FROM downloads WHERE id = ?1", [&download_id])
+            .map_err(|e| format!("Failed to delete download: {}", e))?;
+        removed_ids.push(download_id);
+    }
+    
+    logger::log_info("remove_downloads_by_status",
+        &format!("Removed {} '{}' download(s), freed {} bytes", removed_ids.len(), status, bytes_freed));
+    
+    let _ = app.emit("downloads-removed", serde_json::json!({
+        "downloadIds": removed_ids,
+        "status": status,
+    }));
+    
+    Ok(serde_json::json!({
+        "removed": removed_ids.len(),
+        "bytesFreed": bytes_freed,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomSpeedTestConfig {
+    pub download_url: String,
+    #[serde(default)]
+    pub upload_url: Option<String>,
+}
+
+// Handler 58: start-custom-speed-test
+#[command]
+pub async fn start_custom_speed_test(
+    config: CustomSpeedTestConfig,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let targets = speed_test::SpeedTestTargets::custom(
+        &config.download_url,
+        config.upload_url.as_deref().filter(|u| !u.is_empty()),
+    )?;
+    
+    let test_id = format!("test_{}_{}", 
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        nanoid::nanoid!(9)
+    );
+    let target = targets.target.clone();
+    
+    speed_test::spawn(app, test_id.clone(), targets, false);
+    
+    Ok(serde_json::json!({
+        "testId": test_id,
+        "success": true,
+        "target": target,
+    }))
+}
+
+// Handler 59: check-for-updates-now
+#[command]
+pub async fn check_for_updates_now(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    use crate::update_manager;
+    
+    match update_manager::check_now(&app).await {
+        Some(result) => Ok(serde_json::json!({
+            "started": true,
+            "result": result,
+        })),
+        // Another check is already running; its update-check-complete event will follow
+        None => Ok(serde_json::json!({
+            "started": false,
+            "result": null,
+        })),
+    }
+}
+
+// Handler 60: get-update-status
+#[command]
+pub async fn get_update_status() -> Result<serde_json::Value, String> {
+    use crate::update_manager;
+    
+    Ok(update_manager::get_update_status())
+}
+
+// Handler 61: hide-window
+#[command]
+pub async fn hide_window(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+    if let Some(window) = app.get_webview_window("main") {
+        window.hide().map_err(|e| format!("Failed to hide window: {}", e))?;
+    }
+    Ok(())
+}
+
+// Handler 62: get-release-notes-html
+#[command]
+pub async fn get_release_notes_html(tag: String) -> Result<String, String> {
+    use crate::updater;
+    
+    updater::get_release_notes_html(&tag).await
+}
+
+// Handler 63: health-check
+#[command]
+pub async fn health_check() -> Result<serde_json::Value, String> {
+    use crate::health;
+    
+    Ok(health::health_report().await)
+}
+
+// Handler 64: relocate-completed-downloads
+#[command]
+pub async fn relocate_completed_downloads(
+    new_dir: String,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    let target_dir = PathBuf::from(utils::expand_path(&new_dir));
+    std::fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create {}: {}", target_dir.display(), e))?;
+    utils::check_dir_writable(&target_dir)?;
+    
+    let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+    let downloads: Vec<(String, String, String, Option<String>)> = {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT id, output, type, metadata FROM downloads WHERE status = 'completed' ORDER BY updated_at ASC"
+        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .map_err(|e| format!("Failed to query downloads: {}", e))?;
+        rows.filter_map(|r| r.ok())
+            .filter(|(id, _, _, _)| !running.contains(id))
+            .collect()
+    };
+    
+    let total = downloads.len();
+    let mut moved = Vec::new();
+    let mut failed = Vec::new();
+    let mut missing = Vec::new();
+    
+    for (index, (download_id, output, download_type, metadata_str)) in downloads.into_iter().enumerate() {
+        let metadata: serde_json::Value = metadata_str
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        let is_torrent = download_type == "torrent" || download_type == "magnet";
+        
+        // HTTP output is the file itself; torrent output is the folder holding the torrent's data
+        let output_path = PathBuf::from(utils::expand_path(&output));
+        let src = if is_torrent {
+            utils::torrent_name(&metadata).map(|name| output_path.join(name))
+        } else {
+            Some(output_path)
+        };
+        let src = match src {
+            Some(src) if src.exists() => src,
+            _ => {
+                missing.push(serde_json::json!({ "downloadId": download_id, "path": output }));
+                continue;
+            }
+        };
+        
+        let name = src.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| download_id.clone());
+        if src.parent() == Some(target_dir.as_path()) {
+            // Already there
+            continue;
+        }
+        
+        let _ = app.emit("relocate-progress", serde_json::json!({
+            "downloadId": download_id,
+            "index": index,
+            "total": total,
+            "name": name,
+        }));
+        
+        // Cross-device moves copy the data, so keep them off the async executor
+        let dest = utils::unique_path_in(&target_dir, &name);
+        let (move_src, move_dest) = (src.clone(), dest.clone());
+        let result = tokio::task::spawn_blocking(move || utils::move_path(&move_src, &move_dest))
+            .await
+            .map_err(|e| format!("Move task failed: {}", e))
+            .and_then(|r| r);
+        
+        if let Err(e) = result {
+            logger::log_error("relocate", &format!("Failed to move {}: {}", src.display(), e));
+            failed.push(serde_json::json!({ "downloadId": download_id, "path": src, "error": e }));
+            continue;
+        }
+        
+        let new_output = if is_torrent {
+            target_dir.to_string_lossy().to_string()
+        } else {
+            dest.to_string_lossy().to_string()
+        };
+        let mut metadata = metadata;
+        if is_torrent && dest.file_name() != src.file_name() {
+            // Renamed to avoid a collision
+            metadata["name"] = serde_json::json!(dest.file_name().map(|n| n.to_string_lossy().to_string()));
+        }
+        if let Ok(conn) = database::get_connection() {
+            let metadata_json = serde_json::to_string(&metadata).unwrap();
+            let _ = conn.execute(
+                "UPDATE downloads SET output = ?1, metadata = ?2 WHERE id = ?3",
+                rusqlite::params![new_output, metadata_json, download_id],
+            );
+            let _ = conn.execute(
+                "UPDATE download_history SET output = ?1, metadata = ?2 WHERE id = ?3",
+                rusqlite::params![new_output, metadata_json, download_id],
+            );
+        }
+        
+        moved.push(serde_json::json!({ "downloadId": download_id, "from": src, "to": dest }));
+    }
+    
+    logger::log_info("relocate", &format!("Relocated {} download(s) to {} ({} failed, {} missing)",
+        moved.len(), target_dir.display(), failed.len(), missing.len()));
+    let _ = app.emit("relocate-complete", serde_json::json!({
+        "moved": moved.len(),
+        "failed": failed.len(),
+        "missing": missing.len(),
+    }));
+    
+    Ok(serde_json::json!({
+        "moved": moved,
+        "failed": failed,
+        "missing": missing,
+    }))
+}
+
+// Handler 65: preview-download
+// Shows where a download would be saved without starting it, and which settings decided that
+#[command]
+pub async fn preview_download(
+    source: String,
+    output_folder: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let folder = match output_folder {
+        Some(folder) => folder,
+        None => database::get_setting("defaultDownloadPath")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "~/Downloads".to_string()),
+    };
+    let layout = OutputLayout::from_settings();
+    let output = derive_output_path_with(&source, &folder, &layout);
+    let is_torrent = is_torrent_source(&source);
+    
+    let explanation = if is_torrent {
+        if layout.torrent_subfolder.is_empty() {
+            "Torrent data is saved in a folder named after the torrent inside the download folder".to_string()
+        } else {
+            format!("Torrent data is saved in a folder named after the torrent inside the \"{}\" subfolder", layout.torrent_subfolder)
+        }
+    } else if layout.subfolder_per_download {
+        "The file is saved in its own folder, named after the file, inside the download folder".to_string()
+    } else {
+        "The file is saved directly in the download folder".to_string()
+    };
+    
+    Ok(serde_json::json!({
+        "source": source,
+        "type": if is_torrent { "torrent" } else { "http" },
+        "folder": folder,
+        "output": output,
+        "downloadFolder": utils::download_folder(&output, if is_torrent { "torrent" } else { "http" }),
+        "createSubfolderPerDownload": layout.subfolder_per_download,
+        "torrentSubfolder": layout.torrent_subfolder,
+        "explanation": explanation,
+    }))
+}
+
+// Handler 66: export-state
+#[command]
+pub async fn export_state(path: String) -> Result<serde_json::Value, String> {
+    use crate::backup;
+    
+    let path = PathBuf::from(utils::expand_path(&path));
+    tokio::task::spawn_blocking(move || backup::export_state(&path))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+// Handler 67: import-state
+#[command]
+pub async fn import_state(path: String, merge: Option<bool>) -> Result<serde_json::Value, String> {
+    use crate::backup;
+    
+    let path = PathBuf::from(utils::expand_path(&path));
+    let merge = merge.unwrap_or(true);
+    tokio::task::spawn_blocking(move || backup::import_state(&path, merge))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+// Handler 68: get-queue-state
+#[command]
+pub async fn get_queue_state() -> Result<serde_json::Value, String> {
+    Ok(queue::queue_state().await)
+}
+
+// Handler 69: verify-download-checksum
+// Hashes the finished output and compares it to the expected sha256/sha1/md5 from the download's options
+#[command]
+pub async fn verify_download_checksum(download_id: String) -> Result<serde_json::Value, String> {
+    use crate::checksum;
+    
+    let (output, download_type, metadata_str): (String, String, Option<String>) = {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        conn.query_row(
+            "SELECT output, type, metadata FROM downloads WHERE id = ?1",
+            [&download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| "Download not found".to_string())?
+    };
+    let metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let expected = metadata.get("options")
+        .and_then(checksum::expected_hash)
+        .or_else(|| checksum::expected_hash(&metadata));
+    
+    // Optional per-file hashes for torrents: {"relative/path": "sha256"}
+    let expected_files = metadata.get("options")
+        .and_then(|opts| opts.get("fileChecksums"))
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    
+    let output_path = PathBuf::from(utils::expand_path(&output));
+    let target = if download_type == "torrent" || download_type == "magnet" {
+        utils::torrent_name(&metadata)
+            .map(|name| output_path.join(name))
+            .ok_or_else(|| "Torrent name unknown - can't locate its files".to_string())?
+    } else {
+        output_path
+    };
+    if !target.exists() {
+        return Err(format!("Downloaded data not found: {}", target.display()));
+    }
+    
+    let cancelled = checksum::start(&download_id);
+    let result = tokio::task::spawn_blocking(move || -> Result<serde_json::Value, String> {
+        if target.is_dir() {
+            // Multi-file torrent: one hash per file, keyed by path relative to the torrent folder
+            let mut files = serde_json::Map::new();
+            let mut all_matched = true;
+            for file in checksum::list_files(&target) {
+                let computed = checksum::sha256_file(&file, &cancelled)?;
+                let relative = file.strip_prefix(&target).unwrap_or(&file).to_string_lossy().to_string();
+                let expected = expected_files.get(&relative)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.trim().to_lowercase());
+                let matched = expected.as_ref().map(|e| *e == computed);
+                if matched == Some(false) {
+                    all_matched = false;
+                }
+                files.insert(relative, serde_json::json!({
+                    "matched": matched,
+                    "computed": computed,
+                    "expected": expected,
+                }));
+            }
+            Ok(serde_json::json!({
+                // Only meaningful when per-file hashes were provided
+                "matched": if expected_files.is_empty() { None } else { Some(all_matched) },
+                "computed": null,
+                "expected": null,
+                "files": files,
+            }))
+        } else {
+            let algorithm = expected.as_ref().map(|(algorithm, _)| *algorithm).unwrap_or(checksum::HashAlgorithm::Sha256);
+            let expected = expected.map(|(_, hash)| hash);
+            let computed = checksum::hash_file(&target, algorithm, &cancelled)?;
+            Ok(serde_json::json!({
+                "matched": expected.as_ref().map(|e| *e == computed),
+                "computed": computed,
+                "expected": expected,
+                "algorithm": algorithm.as_str(),
+            }))
+        }
+    })
+    .await
+    .map_err(|e| format!("Checksum task failed: {}", e));
+    checksum::finish(&download_id);
+    
+    result?
+}
+
+// Handler 70: cancel-checksum-verification
+#[command]
+pub async fn cancel_checksum_verification(download_id: String) -> Result<bool, String> {
+    use crate::checksum;
+    
+    Ok(checksum::cancel(&download_id))
+}
+
+// Handler 71: set-torrent-file-selection
+#[command]
+pub async fn set_torrent_file_selection(
+    download_id: String,
+    selected_indices: Vec<usize>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    use crate::logger;
+    
+    torrent_source(&download_id)?;
+    
+    let mut selected = selected_indices;
+    selected.sort_unstable();
+    selected.dedup();
+    if selected.is_empty() {
+        return Err("Select at least one file".to_string());
+    }
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let metadata_str: Option<String> = conn.query_row(
+        "SELECT metadata FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| row.get(0),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    let mut metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    if !metadata.get("options").map(|o| o.is_object()).unwrap_or(false) {
+        metadata["options"] = serde_json::json!({});
+    }
+    
+    // Reject indices past the end of the file list when we know how long it is
+    let file_count = metadata["options"].get("torrentInfo")
+        .or_else(|| metadata.get("torrentInfo"))
+        .and_then(|info| info.get("files"))
+        .and_then(|files| files.as_array())
+        .map(|files| files.len());
+    if let (Some(count), Some(&last)) = (file_count, selected.last()) {
+        if last >= count {
+            return Err(format!("File index {} is out of range (torrent has {} files)", last, count));
+        }
+    }
+    
+    metadata["options"]["selectedFiles"] = serde_json::json!(selected);
+    conn.execute(
+        "UPDATE downloads SET metadata = ? WHERE id = ?",
+        rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+    )
+    .map_err(|e| format!("Failed to update download: {}", e))?;
+    drop(conn);
+    
+    logger::log_info("set_torrent_file_selection", &format!("Download {} now selects files {:?}", download_id, selected));
+    
+    // A running torrent only picks up the new selection when its process is restarted
+    let running = DOWNLOAD_PROCESSES.lock().await.contains_key(&download_id);
+    if running {
+        restart_download_internal(download_id.clone(), app.clone()).await?;
+    }
+    
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "selectedFiles": selected,
+    }));
+    
+    Ok(())
+}
+
+// Handler 72: get-browser-server-status
+#[command]
+pub async fn get_browser_server_status() -> Result<serde_json::Value, String> {
+    use crate::browser_server;
+    
+    Ok(serde_json::json!({
+        "running": browser_server::is_running(),
+        "port": browser_server::server_port(),
+    }))
+}
+
+// Handler 73: get-browser-token
+#[command]
+pub async fn get_browser_token() -> Result<String, String> {
+    use crate::browser_server;
+    
+    browser_server::ensure_api_token()
+}
+
+// Handler 74: cancel-update-download
+#[command]
+pub async fn cancel_update_download() -> Result<(), String> {
+    use crate::logger;
+    
+    logger::log_info("cancel_update_download", "Cancelling update download");
+    updater::cancel_update_download();
+    Ok(())
+}
+
+// Handler 75: get-download-stats
+#[command]
+pub async fn get_download_stats() -> Result<serde_json::Value, String> {
+    use crate::stats;
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    stats::download_stats(&conn, now)
+        .map_err(|e| format!("Failed to compute download stats: {}", e))
+}
+
+// Handler 76: search-downloads
+#[command]
+pub async fn search_downloads(query: String, limit: Option<usize>) -> Result<Vec<serde_json::Value>, String> {
+    use crate::search;
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    search::search_downloads(&conn, &query, limit.unwrap_or(search::DEFAULT_SEARCH_LIMIT))
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+// Handler 77: set-download-category
+#[command]
+pub async fn set_download_category(download_id: String, category: String, app: tauri::AppHandle) -> Result<(), String> {
+    let category = categories::normalize_category(&category);
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let updated = conn.execute(
+        "UPDATE downloads SET category = ?1 WHERE id = ?2",
+        rusqlite::params![category, download_id],
+    )
+    .map_err(|e| format!("Failed to update download: {}", e))?;
+    let updated_history = conn.execute(
+        "UPDATE download_history SET category = ?1 WHERE id = ?2",
+        rusqlite::params![category, download_id],
+    )
+    .map_err(|e| format!("Failed to update history: {}", e))?;
+    if updated == 0 && updated_history == 0 {
+        return Err("Download not found".to_string());
+    }
+    
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "category": category,
+    }));
+    
+    Ok(())
+}
+
+// Handler 78: get-categories
+#[command]
+pub async fn get_categories() -> Result<Vec<String>, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let category_folders = database::get_setting("categoryFolders");
+    categories::list_categories(&conn, category_folders.as_ref())
+        .map_err(|e| format!("Failed to list categories: {}", e))
+}
+
+// Handler 79: relocate-download
+#[command]
+pub async fn relocate_download(download_id: String, new_dir: String, app: tauri::AppHandle) -> Result<(), String> {
+    use crate::logger;
+    
+    if DOWNLOAD_PROCESSES.lock().await.contains_key(&download_id) {
+        return Err("Stop the download before moving its files".to_string());
+    }
+    
+    // Completed downloads may only be left in history once removed from the list
+    let (output, download_type, metadata_str, status): (String, String, Option<String>, Option<String>) = {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        conn.query_row(
+            "SELECT output, type, metadata, status FROM downloads WHERE id = ?1",
+            [&download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, Some(row.get(3)?))),
+        )
+        .or_else(|_| conn.query_row(
+            "SELECT output, type, metadata, NULL FROM download_history WHERE id = ?1",
+            [&download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        ))
+        .map_err(|_| "Download not found".to_string())?
+    };
+    if let Some(status) = status.filter(|s| s != "completed") {
+        return Err(format!("Only completed downloads can be moved (this one is {})", status));
+    }
+    
+    let metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let is_torrent = download_type == "torrent" || download_type == "magnet";
+    
+    // HTTP output is the file itself; torrent output is the folder holding the torrent's data
+    let output_path = PathBuf::from(utils::expand_path(&output));
+    let src = if is_torrent {
+        utils::torrent_name(&metadata)
+            .map(|name| output_path.join(name))
+            .ok_or_else(|| "Torrent name unknown - can't locate its files".to_string())?
+    } else {
+        output_path
+    };
+    if !src.exists() {
+        return Err(format!("Downloaded data not found: {}", src.display()));
+    }
+    
+    let target_dir = PathBuf::from(utils::expand_path(&new_dir));
+    std::fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create {}: {}", target_dir.display(), e))?;
+    utils::check_dir_writable(&target_dir)?;
+    
+    let name = src.file_name()
+        .ok_or_else(|| format!("Invalid download path: {}", src.display()))?;
+    let dest = target_dir.join(name);
+    if src == dest {
+        return Ok(());
+    }
+    if dest.exists() {
+        return Err(format!("{} already exists in {}", name.to_string_lossy(), target_dir.display()));
+    }
+    
+    // Cross-device moves copy the data, so keep them off the async executor
+    let (move_src, move_dest) = (src.clone(), dest.clone());
+    tokio::task::spawn_blocking(move || utils::move_path(&move_src, &move_dest))
+        .await
+        .map_err(|e| format!("Move task failed: {}", e))??;
+    
+    let new_output = if is_torrent { target_dir.clone() } else { dest.clone() };
+    let new_output = new_output.to_string_lossy().to_string();
+    {
+        let conn = database::get_connection()
+            .map_err(|e| format!("Database error: {}", e))?;
+        conn.execute(
+            "UPDATE downloads SET output = ?1 WHERE id = ?2",
+            rusqlite::params![new_output, download_id],
+        )
+        .map_err(|e| format!("Failed to update download: {}", e))?;
+        conn.execute(
+            "UPDATE download_history SET output = ?1 WHERE id = ?2",
+            rusqlite::params![new_output, download_id],
+        )
+        .map_err(|e| format!("Failed to update history: {}", e))?;
+    }
+    
+    logger::log_info("relocate_download", &format!("Moved {} to {}", src.display(), dest.display()));
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "output": new_output,
+    }));
+    
+    Ok(())
+}
+
+// Handler 80: export-data
+#[command]
+pub async fn export_data(path: String) -> Result<serde_json::Value, String> {
+    use crate::backup;
+    
+    let path = PathBuf::from(utils::expand_path(&path));
+    tokio::task::spawn_blocking(move || backup::export_data(&path))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+// Handler 81: import-data
+#[command]
+pub async fn import_data(path: String, merge: bool) -> Result<serde_json::Value, String> {
+    use crate::backup;
+    
+    let path = PathBuf::from(utils::expand_path(&path));
+    tokio::task::spawn_blocking(move || backup::import_data(&path, merge))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+// Handler 82: set-download-rate-limit
+#[command]
+pub async fn set_download_rate_limit(
+    download_id: String,
+    limit: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    use crate::logger;
+    
+    let limit = limit.map(|l| l.trim().to_string());
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let metadata_str: Option<String> = conn.query_row(
+        "SELECT metadata FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| row.get(0),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    
+    let mut metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    if !metadata.is_object() {
+        metadata = serde_json::json!({});
+    }
+    
+    match &limit {
+        // An empty override means unlimited for this download
+        Some(limit) => metadata["rateLimitOverride"] = serde_json::json!(limit),
+        None => {
+            if let Some(obj) = metadata.as_object_mut() {
+                obj.remove("rateLimitOverride");
+            }
+            // Back to the global limit as it is now, not as it was when the download was added
+            let global = database::get_setting("rateLimit").filter(|v| v.is_string());
+            if let Some(options) = metadata.get_mut("options").and_then(|o| o.as_object_mut()) {
+                options.remove("rateLimit");
+                options.remove("rate_limit");
+                options.insert("limit".to_string(), global.unwrap_or(serde_json::Value::Null));
+            }
+        }
+    }
+    
+    conn.execute(
+        "UPDATE downloads SET metadata = ? WHERE id = ?",
+        rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+    )
+    .map_err(|e| format!("Failed to update download: {}", e))?;
+    drop(conn);
+    
+    logger::log_info("set_download_rate_limit", &format!(
+        "Download {} rate limit: {}",
+        download_id,
+        match limit.as_deref() {
+            Some("") => "unlimited",
+            Some(limit) => limit,
+            None => "global setting",
+        }
+    ));
+    
+    // The Go binary only reads --limit at startup; the restart resumes from the files on disk
+    let running = DOWNLOAD_PROCESSES.lock().await.contains_key(&download_id);
+    if running {
+        restart_download_internal(download_id.clone(), app.clone()).await?;
+    }
+    
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "rateLimitOverride": limit,
+    }));
+    
+    Ok(())
+}
+
+/// `{ available, required, sufficient }` for the filesystem holding `path` (or its nearest existing parent)
+fn disk_space_report(path: &std::path::Path, required_bytes: u64) -> Result<serde_json::Value, String> {
+    let available = utils::available_space(path)
+        .ok_or_else(|| format!("Failed to read free space for {}", path.display()))?;
+    Ok(serde_json::json!({
+        "available": available,
+        "required": required_bytes,
+        "sufficient": available >= required_bytes,
+    }))
+}
+
+// Handler 83: check-disk-space
+#[command]
+pub async fn check_disk_space(path: String, required_bytes: u64) -> Result<serde_json::Value, String> {
+    let path = PathBuf::from(utils::expand_path(&path));
+    tokio::task::spawn_blocking(move || disk_space_report(&path, required_bytes))
+        .await
+        .map_err(|e| format!("Disk space check failed: {}", e))?
+}
+
+lazy_static::lazy_static! {
+    // Held for a whole pause-all/resume-all, so two bulk operations can't interleave
+    static ref BULK_LOCK: Mutex<()> = Mutex::new(());
+}
+
+fn download_ids_with_status(sql: &str) -> Result<Vec<String>, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut stmt = conn.prepare(sql)
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let ids = stmt.query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(ids)
+}
+
+/// Pause every downloading or queued download; returns (paused, failed) ids
+/// The ids are collected up front and queued ones are paused first, so stopping a running
+/// download can't hand its slot to something that's about to be paused anyway.
+async fn pause_all_internal(reason: &str) -> Result<(Vec<String>, Vec<String>), String> {
+    use crate::logger;
+    
+    let _bulk = BULK_LOCK.lock().await;
+    let ids = download_ids_with_status(
+        "SELECT id FROM downloads WHERE status IN ('downloading', 'queued')
+         ORDER BY status = 'downloading', started_at ASC"
+    )?;
+    for id in &ids {
+        queue::dequeue(id);
+    }
+    
+    let mut paused = Vec::new();
+    let mut failed = Vec::new();
+    for id in ids {
+        match stop_and_mark_paused(&id, reason).await {
+            Ok(_) => paused.push(id),
+            Err(e) => {
+                logger::log_error("pause_all_downloads", &format!("Failed to pause {}: {}", id, e));
+                failed.push(id);
+            }
+        }
+    }
+    Ok((paused, failed))
+}
+
+// Handler 84: pause-all-downloads
+#[command]
+pub async fn pause_all_downloads(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let (paused, failed) = pause_all_internal("Paused by user").await?;
+    
+    let summary = serde_json::json!({
+        "action": "pause",
+        "status": "paused",
+        "downloadIds": paused,
+        "failed": failed,
+    });
+    let _ = app.emit("downloads-bulk-update", &summary);
+    Ok(summary)
+}
+
+// Handler 85: resume-all-downloads
+#[command]
+pub async fn resume_all_downloads(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    use crate::logger;
+    
+    let _bulk = BULK_LOCK.lock().await;
+    let ids = download_ids_with_status("SELECT id FROM downloads WHERE status = 'paused' ORDER BY started_at ASC")?;
+    
+    // The queue starts what fits under maxConcurrentDownloads and holds the rest
+    let mut started = Vec::new();
+    let mut queued = Vec::new();
+    let mut failed = Vec::new();
+    for id in ids {
+        match queue::enqueue(id.clone(), app.clone()).await {
+            Ok(true) => started.push(id),
+            Ok(false) => queued.push(id),
+            Err(e) => {
+                logger::log_error("resume_all_downloads", &format!("Failed to resume {}: {}", id, e));
+                failed.push(id);
+            }
+        }
+    }
+    
+    let summary = serde_json::json!({
+        "action": "resume",
+        "downloadIds": started.iter().chain(queued.iter()).collect::<Vec<_>>(),
+        "started": started,
+        "queued": queued,
+        "failed": failed,
+    });
+    let _ = app.emit("downloads-bulk-update", &summary);
+    Ok(summary)
+}
+
+/// Bytes a download actually has on disk, plus the (name, size) of each HTTP chunk
+/// HTTP downloads are measured from their `.accelara-temp-*` chunks (or the finished file once
+/// assembled); torrents from their content under the output folder.
+fn bytes_on_disk(
+    download_type: &str,
+    output_path: &std::path::Path,
+    torrent_name: Option<&str>,
+) -> (u64, Vec<(String, u64)>) {
+    if download_type == "http" {
+        return match utils::chunk_temp_dir(output_path).filter(|dir| dir.is_dir()) {
+            Some(temp_dir) => {
+                let chunks: Vec<(String, u64)> = utils::list_chunk_files(&temp_dir)
+                    .iter()
+                    .map(|chunk| (
+                        chunk.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                        fs::metadata(chunk).map(|m| m.len()).unwrap_or(0),
+                    ))
+                    .collect();
+                (chunks.iter().map(|(_, size)| size).sum(), chunks)
+            }
+            None => (fs::metadata(output_path).ok().filter(|m| m.is_file()).map(|m| m.len()).unwrap_or(0), Vec::new()),
+        };
+    }
+    
+    // Only the torrent's own content - the output folder is usually shared with other downloads
+    let content = match torrent_name {
+        Some(name) if !name.is_empty() => output_path.join(name),
+        _ => output_path.to_path_buf(),
+    };
+    let size = if content.exists() {
+        calculate_dir_size(content).unwrap_or(0)
+    } else {
+        0
+    };
+    (size, Vec::new())
+}
+
+/// The folder or file name a torrent's content is saved under, if we know it yet
+fn torrent_content_name(source: &str, metadata: &serde_json::Value) -> Option<String> {
+    utils::torrent_name(metadata).or_else(|| {
+        utils::parse_magnet(source)
+            .and_then(|m| m.name)
+            .filter(|n| !n.contains("..") && !n.contains('/') && !n.contains('\\'))
+    })
+}
+
+/// What a download actually occupies on disk, to catch a `downloaded` value that drifted after a crash
+fn disk_usage_report(
+    download_type: &str,
+    output_path: &std::path::Path,
+    torrent_name: Option<&str>,
+    expected_total: u64,
+) -> serde_json::Value {
+    let (on_disk, chunks) = bytes_on_disk(download_type, output_path, torrent_name);
+    let chunks: Vec<serde_json::Value> = chunks.into_iter()
+        .map(|(name, size)| serde_json::json!({ "name": name, "size": size }))
+        .collect();
+    
+    let percent_on_disk = if expected_total > 0 {
+        Some(on_disk as f64 / expected_total as f64 * 100.0)
+    } else {
+        None
+    };
+    serde_json::json!({
+        "onDisk": on_disk,
+        "expectedTotal": expected_total,
+        "chunks": chunks,
+        "percentOnDisk": percent_on_disk,
+    })
+}
+
+/// The (downloaded, progress) to restore when the files on disk are ahead of the database
+/// None when the database value is already at least what's on disk. Torrent folders can hold
+/// stray files, so the on-disk value never goes past the known total.
+fn reconcile_downloaded(db_downloaded: i64, total: i64, on_disk: u64) -> Option<(i64, f64)> {
+    let on_disk = if total > 0 { (on_disk as i64).min(total) } else { on_disk as i64 };
+    if on_disk <= db_downloaded {
+        return None;
+    }
+    let progress = if total > 0 { on_disk as f64 / total as f64 } else { 0.0 };
+    Some((on_disk, progress))
+}
+
+// Handler 86: get-download-disk-usage
+#[command]
+pub async fn get_download_disk_usage(download_id: String) -> Result<serde_json::Value, String> {
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let (source, output, download_type, total, metadata_str): (String, String, String, Option<i64>, Option<String>) = conn.query_row(
+        "SELECT source, output, type, total, metadata FROM downloads WHERE id = ?1",
+        [&download_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+    )
+    .map_err(|_| "Download not found".to_string())?;
+    drop(conn);
+    
+    let metadata: serde_json::Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    let torrent_name = if download_type == "http" {
+        None
+    } else {
+        torrent_content_name(&source, &metadata)
+    };
+    let expected_total = total.filter(|t| *t > 0).map(|t| t as u64)
+        .or_else(|| metadata.pointer("/torrentMeta/totalSize").and_then(|v| v.as_u64()))
+        .unwrap_or(0);
+    let output_path = PathBuf::from(utils::expand_path(&output));
+    
+    tokio::task::spawn_blocking(move || {
+        disk_usage_report(&download_type, &output_path, torrent_name.as_deref(), expected_total)
+    })
+    .await
+    .map_err(|e| format!("Disk usage check failed: {}", e))
+}
+
+/// Work out what kind of source this is, or why it can't be downloaded
+/// Returns "magnet", "torrent" or "http". Local .torrent files must exist and be readable.
+fn classify_source(source: &str) -> Result<&'static str, String> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err("Source is empty".to_string());
+    }
+    
+    if source.starts_with("magnet:") {
+        let magnet = utils::parse_magnet(source)
+            .ok_or_else(|| "Malformed magnet link".to_string())?;
+        if magnet.info_hash_v1.is_none() && magnet.info_hash_v2.is_none() {
+            return Err("Magnet link has no valid xt=urn:btih: info hash".to_string());
+        }
+        return Ok("magnet");
+    }
+    
+    if let Ok(url) = reqwest::Url::parse(source) {
+        match url.scheme() {
+            "http" | "https" => {}
+            // Windows paths like C:\file.torrent parse as a URL with a one-letter scheme
+            scheme if scheme.len() == 1 => return classify_torrent_file(source),
+            "file" => {
+                let path = url.to_file_path().map_err(|_| format!("Invalid file URL: {}", source))?;
+                return classify_torrent_file(&path.to_string_lossy());
+            }
+            scheme => return Err(format!("Unsupported URL scheme: {}", scheme)),
+        }
+        if url.host_str().map(|h| h.is_empty()).unwrap_or(true) {
+            return Err("URL has no host".to_string());
+        }
+        let is_torrent = url.path().to_lowercase().ends_with(".torrent");
+        return Ok(if is_torrent { "torrent" } else { "http" });
+    }
+    
+    if source.contains("://") {
+        return Err(format!("Malformed URL: {}", source));
+    }
+    classify_torrent_file(source)
+}
+
+// Helper for local paths: only .torrent files can be added, and they have to be readable
+fn classify_torrent_file(path: &str) -> Result<&'static str, String> {
+    if !is_torrent_source(path) {
+        return Err("Not a URL, magnet link or .torrent file".to_string());
+    }
+    let expanded = utils::expand_path(path);
+    if !std::path::Path::new(&expanded).is_file() {
+        return Err(format!("Torrent file not found: {}", expanded));
+    }
+    fs::File::open(&expanded)
+        .map_err(|e| format!("Torrent file is not readable: {}", e))?;
+    Ok("torrent")
+}
+
+// Helper to pull the file name out of a Content-Disposition header
+fn content_disposition_filename(header: &str) -> Option<String> {
+    header.split(';')
+        .map(|part| part.trim())
+        .find_map(|part| part.strip_prefix("filename="))
+        .map(|name| name.trim_matches('"').to_string())
+        .filter(|name| !name.is_empty())
+}
+
+// Handler 89: validate-source
+#[command]
+pub async fn validate_source(source: String, probe: Option<bool>) -> Result<serde_json::Value, String> {
+    let source_type = match classify_source(&source) {
+        Ok(source_type) => source_type,
+        Err(reason) => {
+            return Ok(serde_json::json!({
+                "valid": false,
+                "type": null,
+                "reason": reason,
+            }));
+        }
+    };
+    
+    let mut result = serde_json::json!({
+        "valid": true,
+        "type": source_type,
+        "reason": null,
+    });
+    
+    // Optionally make sure the server is actually there, and report what it says about the file
+    if source_type == "http" && probe.unwrap_or(false) {
+        match head_request(source.trim(), &None, "validate_source").await {
+            Ok(response) if response.status().is_success() => {
+                let header = |name: reqwest::header::HeaderName| {
+                    response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string())
+                };
+                let file_name = header(reqwest::header::CONTENT_DISPOSITION)
+                    .and_then(|h| content_disposition_filename(&h))
+                    .unwrap_or_else(|| derive_display_name(source.trim(), &serde_json::json!({})));
+                result["size"] = serde_json::json!(header(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse::<u64>().ok()));
+                result["fileName"] = serde_json::json!(file_name);
+                result["resumable"] = serde_json::json!(header(reqwest::header::ACCEPT_RANGES)
+                    .map(|v| v.eq_ignore_ascii_case("bytes"))
+                    .unwrap_or(false));
+            }
+            Ok(response) => {
+                result["valid"] = serde_json::json!(false);
+                result["reason"] = serde_json::json!(format!("Server responded with {}", response.status()));
+            }
+            Err(e) => {
+                result["valid"] = serde_json::json!(false);
+                result["reason"] = serde_json::json!(e);
+            }
+        }
+    }
+    
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn item(id: &str, completed_at: Option<i64>, seeding: bool) -> serde_json::Value {
+        serde_json::json!({ "id": id, "completedAt": completed_at, "isSeeding": seeding })
+    }
+    
+    fn ids(items: &[serde_json::Value]) -> Vec<&str> {
+        items.iter().map(|i| i["id"].as_str().unwrap()).collect()
+    }
+    
+    #[test]
+    fn test_merge_history_orders_and_limits() {
+        let history: Vec<_> = (0..100).map(|i| item(&format!("h{:03}", i), Some(i), false)).collect();
+        let seeding = vec![
+            item("s-new", Some(1000), true),
+            item("s-unknown", None, true),
+            // Seeding entry for a download that is also in history replaces it
+            item("h050", Some(50), true),
+        ];
+        
+        let merged = merge_history_items(history, seeding, DEFAULT_HISTORY_LIMIT);
+        assert_eq!(merged.len(), DEFAULT_HISTORY_LIMIT);
+        assert_eq!(&ids(&merged)[..3], &["s-unknown", "s-new", "h099"]);
+        assert_eq!(merged.iter().filter(|i| i["id"] == "h050").count(), 1);
+        assert_eq!(merged.iter().find(|i| i["id"] == "h050").unwrap()["isSeeding"], true);
+        // The oldest entries fall off once seeding items push past the limit
+        assert!(!ids(&merged).contains(&"h000"));
+        assert!(!ids(&merged).contains(&"h001"));
+        
+        let completed: Vec<i64> = merged.iter().filter_map(|i| i["completedAt"].as_i64()).collect();
+        assert!(completed.windows(2).all(|w| w[0] >= w[1]));
+    }
+    
+    #[test]
+    fn test_merge_history_ties_are_stable() {
+        let history = vec![item("b", Some(5), false), item("a", Some(5), false), item("c", None, false)];
+        let seeding = vec![item("d", None, true)];
+        let merged = merge_history_items(history, seeding, 3);
+        assert_eq!(ids(&merged), vec!["c", "d", "a"]);
+    }
+    
+    #[test]
+    fn test_rate_limit_override_replaces_download_limit() {
+        let options = Some(serde_json::json!({ "limit": "5MB", "concurrency": 4 }));
+        
+        let metadata = serde_json::json!({ "rateLimitOverride": "256KB" });
+        let args = build_command_args("http://x/a", "/tmp/a", "id", &with_rate_limit_override(options.clone(), &metadata));
+        let limit_at = args.iter().position(|a| a == "--limit").unwrap();
+        assert_eq!(args[limit_at + 1], "256KB");
+        
+        // Empty override: unlimited, so no --limit at all
+        let metadata = serde_json::json!({ "rateLimitOverride": "" });
+        let args = build_command_args("http://x/a", "/tmp/a", "id", &with_rate_limit_override(options.clone(), &metadata));
+        assert!(!args.iter().any(|a| a == "--limit"));
+        
+        // No override: the download's own options are untouched
+        assert_eq!(with_rate_limit_override(options.clone(), &serde_json::json!({})), options);
+    }
+    
+    #[test]
+    fn test_rate_limit_override_with_active_schedule_rule() {
+        // The stricter of an override and the rule's cap wins
+        assert_eq!(limit_with_rule(Some("256KB".to_string()), true, Some("1MB")).as_deref(), Some("256KB"));
+        assert_eq!(limit_with_rule(Some("5MB".to_string()), true, Some("1MB")).as_deref(), Some("1MB"));
+        // An unlimited override still gets the rule's cap
+        assert_eq!(limit_with_rule(Some(String::new()), true, Some("1MB")).as_deref(), Some("1MB"));
+        // Rules without a download cap (unlimited or upload-only) leave the limit alone
+        assert_eq!(limit_with_rule(Some("256KB".to_string()), true, Some("")).as_deref(), Some("256KB"));
+        assert_eq!(limit_with_rule(Some("5MB".to_string()), false, Some("")).as_deref(), Some("5MB"));
+        assert_eq!(limit_with_rule(None, false, None), None);
+        // Without an override the rule's cap replaces the download's own limit
+        assert_eq!(limit_with_rule(Some("256KB".to_string()), false, Some("1MB")).as_deref(), Some("1MB"));
+    }
+    
+    #[test]
+    fn test_disk_space_report() {
+        let dir = std::env::temp_dir().join(format!("accelara-space-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let report = disk_space_report(&dir, 1).unwrap();
+        assert_eq!(report["sufficient"], true);
+        assert_eq!(report["required"], 1);
+        assert!(report["available"].as_u64().unwrap() >= 1);
+        
+        let report = disk_space_report(&dir, u64::MAX).unwrap();
+        assert_eq!(report["sufficient"], false);
+        
+        // Folders that don't exist yet are measured at the nearest existing parent
+        let missing = dir.join("not").join("created").join("yet");
+        let report = disk_space_report(&missing, 1).unwrap();
+        assert_eq!(report["sufficient"], true);
+        assert!(!missing.exists());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pause_all_pauses_every_running_download() {
+        database::init_test_database();
+        
+        let ids = ["bulk-pause-1", "bulk-pause-2", "bulk-pause-3"];
+        {
+            let conn = database::get_connection().unwrap();
+            let mut processes = DOWNLOAD_PROCESSES.lock().await;
+            for id in ids {
+                conn.execute(
+                    "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at)
+                     VALUES (?1, 'http://x/a', '/tmp/a', 'http', 'downloading', '{}', 1)",
+                    [id],
+                ).unwrap();
+                let child = tokio::process::Command::new("sleep").arg("30").spawn().unwrap();
+                processes.insert(id.to_string(), child);
+            }
+        }
+        
+        let (paused, failed) = pause_all_internal("Paused by user").await.unwrap();
+        assert!(failed.is_empty());
+        for id in ids {
+            assert!(paused.iter().any(|p| p == id), "{} not paused", id);
+        }
+        
+        let conn = database::get_connection().unwrap();
+        let processes = DOWNLOAD_PROCESSES.lock().await;
+        for id in ids {
+            let status: String = conn.query_row("SELECT status FROM downloads WHERE id = ?1", [id], |row| row.get(0)).unwrap();
+            assert_eq!(status, "paused");
+            assert!(!processes.contains_key(id));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relocate_paths_respects_path_segments() {
+        database::init_test_database();
+        
+        let base = std::env::temp_dir().join(format!("accelara-relocate-{}", std::process::id()));
+        let new_dir = base.join("Moved");
+        std::fs::create_dir_all(&new_dir).unwrap();
+        let base = base.to_string_lossy().to_string();
+        let new_dir = new_dir.to_string_lossy().to_string();
+        
+        let rows = [
+            ("relocate-1", format!("{}/Down/a.iso", base)),
+            ("relocate-2", format!("{}/Downloads/b.iso", base)),
+            ("relocate-3", format!("{}/Down", base)),
+        ];
+        {
+            let conn = database::get_connection().unwrap();
+            for (id, output) in &rows {
+                conn.execute(
+                    "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at)
+                     VALUES (?1, 'http://x/a', ?2, 'http', 'paused', '{}', 1)",
+                    rusqlite::params![id, output],
+                ).unwrap();
+            }
+        }
+        
+        let result = relocate_paths(format!("{}/Down/", base), format!("{}/", new_dir)).await.unwrap();
+        assert_eq!(result["downloadsUpdated"], 2);
+        
+        let conn = database::get_connection().unwrap();
+        let output = |id: &str| -> String {
+            conn.query_row("SELECT output FROM downloads WHERE id = ?1", [id], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(output("relocate-1"), format!("{}/a.iso", new_dir));
+        assert_eq!(output("relocate-2"), format!("{}/Downloads/b.iso", base));
+        assert_eq!(output("relocate-3"), new_dir);
+        
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_derive_display_name() {
+        let empty = serde_json::json!({});
+        
+        // Magnet: dn, falling back to the info hash
+        assert_eq!(
+            derive_display_name("magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=ubuntu-24.04.iso", &empty),
+            "ubuntu-24.04.iso"
+        );
+        assert_eq!(
+            derive_display_name("magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a", &empty),
+            "c12fe1c06bba254a9dc9f519b335aa7c1367a88a"
+        );
+        
+        // .torrent file: its base name until the torrent itself has been inspected
+        assert_eq!(derive_display_name("/home/me/Downloads/debian.torrent", &empty), "debian.torrent");
+        let inspected = serde_json::json!({ "torrentMeta": { "name": "debian-12.5.0-amd64-netinst.iso", "fileCount": 1 } });
+        assert_eq!(derive_display_name("/home/me/Downloads/debian.torrent", &inspected), "debian-12.5.0-amd64-netinst.iso");
+        
+        // HTTP: last path segment without query or fragment
+        assert_eq!(derive_display_name("https://example.com/files/archive.zip?token=abc#top", &empty), "archive.zip");
+        assert_eq!(derive_display_name("https://example.com/files/", &empty), "files");
+        assert_eq!(derive_display_name("https://example.com", &empty), "example.com");
+        let named = serde_json::json!({ "fileName": "report.pdf" });
+        assert_eq!(derive_display_name("https://example.com/download?id=7", &named), "report.pdf");
+    }
+    
+    #[test]
+    fn test_torrent_meta_summary() {
+        let info = serde_json::json!({
+            "name": "album",
+            "totalSize": 1024,
+            "files": [{ "path": "a.flac" }, { "path": "b.flac" }],
+        });
+        let meta = torrent_meta(&info, "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&tr=udp%3A%2F%2Ftracker.example.org%3A1337");
+        assert_eq!(meta["name"], "album");
+        assert_eq!(meta["totalSize"], 1024);
+        assert_eq!(meta["fileCount"], 2);
+        assert_eq!(meta["trackers"], serde_json::json!(["udp://tracker.example.org:1337"]));
+    }
+
+    #[test]
+    fn test_disk_usage_report_http_chunks() {
+        let dir = std::env::temp_dir().join(format!("accelara-usage-http-{}", std::process::id()));
+        let output = dir.join("video.mp4");
+        let temp_dir = utils::chunk_temp_dir(&output).unwrap();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::write(temp_dir.join("video.mp4.part.0"), vec![0u8; 300]).unwrap();
+        std::fs::write(temp_dir.join("video.mp4.part.1"), vec![0u8; 200]).unwrap();
+        // Not a chunk
+        std::fs::write(temp_dir.join("state.json"), b"{}").unwrap();
+        
+        let report = disk_usage_report("http", &output, None, 1000);
+        assert_eq!(report["onDisk"], 500);
+        assert_eq!(report["expectedTotal"], 1000);
+        assert_eq!(report["percentOnDisk"], 50.0);
+        assert_eq!(report["chunks"], serde_json::json!([
+            { "name": "video.mp4.part.0", "size": 300 },
+            { "name": "video.mp4.part.1", "size": 200 },
+        ]));
+        
+        // Once assembled, the finished file is what counts
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+        std::fs::write(&output, vec![0u8; 1000]).unwrap();
+        let report = disk_usage_report("http", &output, None, 1000);
+        assert_eq!(report["onDisk"], 1000);
+        assert_eq!(report["chunks"], serde_json::json!([]));
+        
+        // Nothing on disk and an unknown size
+        std::fs::remove_file(&output).unwrap();
+        let report = disk_usage_report("http", &output, None, 0);
+        assert_eq!(report["onDisk"], 0);
+        assert!(report["percentOnDisk"].is_null());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn test_disk_usage_report_torrent_content() {
+        let dir = std::env::temp_dir().join(format!("accelara-usage-torrent-{}", std::process::id()));
+        let content = dir.join("album");
+        std::fs::create_dir_all(content.join("disc1")).unwrap();
+        std::fs::write(content.join("disc1").join("01.flac"), vec![0u8; 400]).unwrap();
+        std::fs::write(content.join("cover.jpg"), vec![0u8; 100]).unwrap();
+        // Something else in the shared output folder
+        std::fs::write(dir.join("unrelated.iso"), vec![0u8; 5000]).unwrap();
+        
+        let report = disk_usage_report("torrent", &dir, Some("album"), 2000);
+        assert_eq!(report["onDisk"], 500);
+        assert_eq!(report["percentOnDisk"], 25.0);
+        
+        let report = disk_usage_report("magnet", &dir, Some("not-started"), 2000);
+        assert_eq!(report["onDisk"], 0);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_bytes_on_disk_layouts() {
+        let dir = std::env::temp_dir().join(format!("accelara-on-disk-{}", std::process::id()));
+        
+        // HTTP: chunks in the temp directory
+        let output = dir.join("archive.zip");
+        let temp_dir = utils::chunk_temp_dir(&output).unwrap();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::write(temp_dir.join("archive.zip.part.0"), vec![0u8; 700]).unwrap();
+        std::fs::write(temp_dir.join("archive.zip.part.1"), vec![0u8; 100]).unwrap();
+        let (size, chunks) = bytes_on_disk("http", &output, None);
+        assert_eq!(size, 800);
+        assert_eq!(chunks.len(), 2);
+        
+        // Single-file torrent saved straight into the output folder
+        std::fs::write(dir.join("debian.iso"), vec![0u8; 1500]).unwrap();
+        assert_eq!(bytes_on_disk("torrent", &dir, Some("debian.iso")).0, 1500);
+        
+        // Multi-file torrent in its own folder
+        std::fs::create_dir_all(dir.join("album").join("cd2")).unwrap();
+        std::fs::write(dir.join("album").join("01.flac"), vec![0u8; 250]).unwrap();
+        std::fs::write(dir.join("album").join("cd2").join("01.flac"), vec![0u8; 250]).unwrap();
+        assert_eq!(bytes_on_disk("magnet", &dir, Some("album")).0, 500);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn test_reconcile_downloaded() {
+        // Crash left the DB at 0 while the chunks are 80% done
+        assert_eq!(reconcile_downloaded(0, 1000, 800), Some((800, 0.8)));
+        // DB already ahead or equal: keep it
+        assert_eq!(reconcile_downloaded(900, 1000, 800), None);
+        assert_eq!(reconcile_downloaded(800, 1000, 800), None);
+        // Stray files in a torrent folder can't push past the total
+        assert_eq!(reconcile_downloaded(100, 1000, 5000), Some((1000, 1.0)));
+        // Unknown total: take the bytes, leave progress for the wrapper to report
+        assert_eq!(reconcile_downloaded(0, 0, 300), Some((300, 0.0)));
+    }
+    
+    #[test]
+    fn test_classify_magnet_links() {
+        assert_eq!(classify_source("magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=x"), Ok("magnet"));
+        // Base32 hashes from older clients
+        assert_eq!(classify_source("magnet:?xt=urn:btih:YEX6BQDLXISUVHPZ7EM3GNNKPQJWPKEK"), Ok("magnet"));
+        assert!(classify_source("magnet:?dn=no-hash").is_err());
+        assert!(classify_source("magnet:?xt=urn:btih:not-a-hash").is_err());
+        assert!(classify_source("magnet:").is_err());
+    }
+    
+    #[test]
+    fn test_classify_urls_and_paths() {
+        assert_eq!(classify_source("https://example.com/file.zip"), Ok("http"));
+        assert_eq!(classify_source("  http://example.com/  "), Ok("http"));
+        assert_eq!(classify_source("https://example.com/linux.TORRENT"), Ok("torrent"));
+        assert!(classify_source("").is_err());
+        assert!(classify_source("ftp://example.com/file.zip").unwrap_err().contains("ftp"));
+        assert!(classify_source("http://").is_err());
+        assert!(classify_source("https//example.com/file.zip").is_err());
+        assert!(classify_source("just some text").is_err());
+        assert!(classify_source("/definitely/not/here.torrent").unwrap_err().contains("not found"));
+        
+        let path = std::env::temp_dir().join(format!("accelara-validate-{}.torrent", std::process::id()));
+        std::fs::write(&path, b"d4:infod4:name1:xee").unwrap();
+        assert_eq!(classify_source(&path.to_string_lossy()), Ok("torrent"));
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[test]
+    fn test_content_disposition_filename() {
+        assert_eq!(content_disposition_filename("attachment; filename=\"report 2026.pdf\""), Some("report 2026.pdf".to_string()));
+        assert_eq!(content_disposition_filename("attachment; filename=data.csv"), Some("data.csv".to_string()));
+        assert_eq!(content_disposition_filename("inline"), None);
+    }
+    
+    // Local server standing in for a file host: /ranged supports Range, /plain doesn't, /moved redirects
+    async fn serve_test_files() -> String {
+        use axum::http::header;
+        use axum::routing::get;
+        
+        let router = axum::Router::new()
+            .route("/ranged", get(|| async {
+                ([
+                    (header::ACCEPT_RANGES, "Bytes"),
+                    (header::CONTENT_LENGTH, "1234"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"data.bin\""),
+                ], vec![0u8; 1234])
+            }))
+            .route("/plain", get(|| async { vec![0u8; 10] }))
+            .route("/moved", get(|| async { axum::response::Redirect::temporary("/ranged") }))
+            .route("/protected", get(|headers: axum::http::HeaderMap| async move {
+                let authorized = headers.get(header::AUTHORIZATION).map(|v| v == "Bearer abc").unwrap_or(false)
+                    && headers.get("x-api-key").map(|v| v == "k=1;2").unwrap_or(false);
+                if authorized { (axum::http::StatusCode::OK, vec![0u8; 42]) } else { (axum::http::StatusCode::UNAUTHORIZED, Vec::new()) }
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        format!("http://{}", addr)
+    }
+    
+    #[tokio::test]
+    async fn test_probe_head_with_and_without_ranges() {
+        database::init_test_database();
+        let base = serve_test_files().await;
+        
+        let ranged = probe_head(&format!("{}/ranged", base), &None).await.unwrap();
+        assert!(ranged.accept_ranges);
+        assert_eq!(ranged.content_length, Some(1234));
+        assert_eq!(ranged.suggested_filename.as_deref(), Some("data.bin"));
+        
+        let plain = probe_head(&format!("{}/plain", base), &None).await.unwrap();
+        assert!(!plain.accept_ranges);
+        assert_eq!(plain.suggested_filename, None);
+        
+        let moved = probe_head(&format!("{}/moved", base), &None).await.unwrap();
+        assert_eq!(moved.final_url, format!("{}/ranged", base));
+        assert!(moved.accept_ranges);
+        
+        assert!(probe_head(&format!("{}/missing", base), &None).await.is_err());
+    }
+    
+    #[test]
+    fn test_normalize_http_info_always_has_fields() {
+        let wrapper = serde_json::json!({ "fileName": "a.iso", "totalSize": 2048, "acceptRanges": false });
+        
+        // No probe: fall back to what the wrapper reported
+        let info = normalize_http_info(wrapper.clone(), None, "http://x/a.iso");
+        assert_eq!(info["acceptRanges"], false);
+        assert_eq!(info["contentLength"], 2048);
+        assert!(info["suggestedFilename"].is_null());
+        assert_eq!(info["finalUrl"], "http://x/a.iso");
+        assert_eq!(info["fileName"], "a.iso");
+        
+        let head = HeadInfo {
+            final_url: "http://mirror/a.iso".to_string(),
+            accept_ranges: true,
+            content_length: Some(4096),
+            suggested_filename: Some("a-1.0.iso".to_string()),
+        };
+        let info = normalize_http_info(wrapper, Some(&head), "http://x/a.iso");
+        assert_eq!(info["acceptRanges"], true);
+        assert_eq!(info["supportsRange"], true);
+        assert_eq!(info["contentLength"], 4096);
+        assert_eq!(info["suggestedFilename"], "a-1.0.iso");
+        assert_eq!(info["finalUrl"], "http://mirror/a.iso");
+    }
+    
+    #[test]
+    fn test_resolve_open_path() {
+        let dir = std::env::temp_dir().join(format!("accelara-open-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("Some Album [FLAC]")).unwrap();
+        let file = dir.join("report (final) #2.pdf");
+        std::fs::write(&file, b"%PDF").unwrap();
+        let output = file.to_string_lossy().to_string();
+        let empty = serde_json::json!({});
+        
+        // Spaces and special characters come back untouched - open_path passes them as a single argument
+        assert_eq!(resolve_open_path(&output, "http", "https://x/r.pdf", &empty).unwrap(), file.canonicalize().unwrap());
+        
+        // Multi-file torrent: its folder inside the output directory
+        let folder = dir.to_string_lossy().to_string();
+        let metadata = serde_json::json!({ "torrentMeta": { "name": "Some Album [FLAC]" } });
+        assert_eq!(
+            resolve_open_path(&folder, "torrent", "/t/album.torrent", &metadata).unwrap(),
+            dir.join("Some Album [FLAC]").canonicalize().unwrap()
+        );
+        
+        // Deleted by the user
+        std::fs::remove_file(&file).unwrap();
+        let err = resolve_open_path(&output, "http", "https://x/r.pdf", &empty).unwrap_err();
+        assert!(err.contains("File not found"));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn test_is_due() {
+        assert!(is_due(Some(1_000), 1_000));
+        assert!(is_due(Some(999), 1_000));
+        assert!(!is_due(Some(1_001), 1_000));
+        assert!(!is_due(None, 1_000));
+    }
+    
+    #[test]
+    fn test_schedule_download_defers_or_starts_now() {
+        database::init_test_database();
+        let conn = database::get_connection().unwrap();
+        for id in ["schedule-future", "schedule-past"] {
+            conn.execute(
+                "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at)
+                 VALUES (?1, 'http://x/a', '/tmp/a', 'http', 'paused', '{}', 1)",
+                [id],
+            ).unwrap();
+        }
+        let row = |id: &str| -> (String, Option<i64>) {
+            conn.query_row("SELECT status, scheduled_at FROM downloads WHERE id = ?1", [id], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        };
+        
+        // A future time is stored and the download waits for it
+        assert_eq!(schedule_download_internal("schedule-future", 5_000, 1_000), Ok(false));
+        assert_eq!(row("schedule-future"), ("scheduled".to_string(), Some(5_000)));
+        
+        // A time that has already passed means start immediately, nothing is stored
+        assert_eq!(schedule_download_internal("schedule-past", 500, 1_000), Ok(true));
+        assert_eq!(row("schedule-past"), ("paused".to_string(), None));
+        
+        // Not due yet, then picked up exactly once when it is
+        assert!(!download::take_due_scheduled_downloads(4_999).contains(&"schedule-future".to_string()));
+        assert!(download::take_due_scheduled_downloads(5_000).contains(&"schedule-future".to_string()));
+        assert_eq!(row("schedule-future"), ("paused".to_string(), None));
+        assert!(!download::take_due_scheduled_downloads(6_000).contains(&"schedule-future".to_string()));
+        
+        assert!(schedule_download_internal("schedule-missing", 5_000, 1_000).is_err());
+    }
+    
+    #[test]
+    fn test_decode_torrent_data() {
+        let torrent = b"d4:infod4:name4:testee".to_vec();
+        assert_eq!(decode_torrent_data("ZDQ6aW5mb2Q0Om5hbWU0OnRlc3RlZQ=="), Ok(torrent.clone()));
+        assert_eq!(decode_torrent_data("data:application/x-bittorrent;base64,ZDQ6aW5mb2Q0\nOm5hbWU0OnRlc3RlZQ=="), Ok(torrent));
+        
+        // A bencoded list, not a dictionary
+        assert!(decode_torrent_data("bDQ6c3BhbWU=").is_err());
+        assert!(decode_torrent_data("not base64!").is_err());
+        assert!(decode_torrent_data("data:application/x-bittorrent,d4:infoe").is_err());
+    }
+    
+    #[test]
+    fn test_torrent_data_saved_and_classified_as_torrent() {
+        database::init_test_database();
+        let mut config: DownloadConfig = serde_json::from_value(serde_json::json!({
+            "source": "dropped.torrent",
+            "torrentData": "ZDQ6aW5mb2Q0Om5hbWU0OnRlc3RlZQ==",
+        })).unwrap();
+        
+        let saved = materialize_torrent_data(&mut config).unwrap().unwrap();
+        assert_eq!(config.source, saved.to_string_lossy());
+        assert!(config.torrent_data.is_none());
+        assert!(is_torrent_source(&config.source));
+        assert_eq!(classify_source(&config.source), Ok("torrent"));
+        assert_eq!(fs::read(&saved).unwrap(), b"d4:infod4:name4:testee");
+        
+        // Kept while a download still uses it
+        let conn = database::get_connection().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata) VALUES ('torrent-data-1', ?1, '/tmp', 'torrent', 'paused', '{}')",
+            [&config.source],
+        ).unwrap();
+        remove_saved_torrent_data(&conn, &config.source);
+        assert!(saved.exists());
+        conn.execute("DELETE FROM downloads WHERE id = 'torrent-data-1'", []).unwrap();
+        remove_saved_torrent_data(&conn, &config.source);
+        assert!(!saved.exists());
+        
+        // Without torrentData the config is left alone
+        let mut plain: DownloadConfig = serde_json::from_value(serde_json::json!({ "source": "https://x/a.iso" })).unwrap();
+        assert_eq!(materialize_torrent_data(&mut plain), Ok(None));
+        assert_eq!(plain.source, "https://x/a.iso");
+    }
+    
+    #[test]
+    fn test_header_and_auth_args() {
+        let options = Some(serde_json::json!({
+            "headers": {
+                "X-Token": "a\"b; c=d e/ü",
+                "Accept": " */* ",
+                "Referer": "https://example.com/page?x=1&y=2",
+                "Bad Name": "skipped",
+                "X-Split": "v\r\nInjected: 1",
+                "X-Number": 5,
+            },
+            "auth": { "type": "basic", "username": "user@example.com", "password": "p:ss wörd" },
+        }));
+        let args = build_command_args("http://x/a", "/tmp/a", "id", &options);
+        let headers: Vec<&String> = args.windows(2).filter(|w| w[0] == "--header").map(|w| &w[1]).collect();
+        assert_eq!(headers, vec![
+            "Accept: */*",
+            "Referer: https://example.com/page?x=1&y=2",
+            "X-Token: a\"b; c=d e/ü",
+        ]);
+        
+        let auth = args.windows(2).find(|w| w[0] == "--auth").map(|w| w[1].clone());
+        // base64 of "user@example.com:p:ss wörd"
+        assert_eq!(auth.as_deref(), Some("Basic dXNlckBleGFtcGxlLmNvbTpwOnNzIHfDtnJk"));
+        
+        let bearer = serde_json::json!({ "auth": { "type": "bearer", "token": " abc " } });
+        assert_eq!(auth_header_value(&bearer).as_deref(), Some("Bearer abc"));
+        assert_eq!(auth_header_value(&serde_json::json!({ "auth": "abc" })).as_deref(), Some("Bearer abc"));
+        assert_eq!(auth_header_value(&serde_json::json!({ "auth": { "type": "digest" } })), None);
+        assert_eq!(auth_header_value(&serde_json::json!({ "auth": { "type": "bearer", "token": "" } })), None);
+        assert!(!build_command_args("http://x/a", "/tmp/a", "id", &None).contains(&"--header".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_probe_sends_headers_and_auth() {
+        database::init_test_database();
+        let base = serve_test_files().await;
+        let url = format!("{}/protected", base);
+        
+        assert!(probe_head(&url, &None).await.is_err());
+        let options = Some(serde_json::json!({
+            "headers": { "X-Api-Key": "k=1;2" },
+            "auth": { "type": "bearer", "token": "abc" },
+        }));
+        assert!(probe_head(&url, &options).await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_wrapper_http_info_sends_headers_and_auth() {
+        // Goes through the real helper binary; nothing to check when it hasn't been (re)built
+        let Some(go_binary) = utils::find_go_binary() else { return };
+        if !binaries::supported_flags(&go_binary).await.contains("header") {
+            return;
+        }
+        database::init_test_database();
+        let base = serve_test_files().await;
+        let url = format!("{}/protected", base);
+        
+        let denied = wrapper_http_info(&url, &None).await.unwrap();
+        assert_eq!(denied["totalSize"], 0);
+        let options = Some(serde_json::json!({
+            "headers": { "X-Api-Key": "k=1;2" },
+            "auth": { "type": "bearer", "token": "abc" },
+        }));
+        let info = wrapper_http_info(&url, &options).await.unwrap();
+        assert_eq!(info["totalSize"], 42);
+    }
+    
+    #[test]
+    fn test_redact_args() {
+        let args: Vec<String> = ["--source", "http://x/a", "--header", "Cookie: session=abc", "--auth", "Bearer t", "--limit", "1MB"]
+            .iter().map(|a| a.to_string()).collect();
+        assert_eq!(redact_args(&args), vec![
+            "--source", "http://x/a", "--header", "Cookie: <redacted>", "--auth", "<redacted>", "--limit", "1MB",
+        ]);
+    }
+    
+    #[test]
+    fn test_rename_download_output() {
+        database::init_test_database();
+        let conn = database::get_connection().unwrap();
+        let dir = std::env::temp_dir().join(format!("accelara-rename-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("download.php");
+        
+        conn.execute(
+            "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at)
+             VALUES ('rename-paused', 'http://x/download.php?id=123', ?1, 'http', 'paused', '{\"fileName\":\"download.php\"}', 1)",
+            [output.to_string_lossy()],
+        ).unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at)
+             VALUES ('rename-running', 'http://x/b', '/tmp/b', 'http', 'downloading', '{}', 1)",
+            [],
+        ).unwrap();
+        
+        // Chunks of the paused download move along with it
+        let temp_dir = utils::chunk_temp_dir(&output).unwrap();
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("download.php.part.0.99"), vec![0u8; 100]).unwrap();
+        
+        let renamed = rename_download_output_internal("rename-paused", " report.pdf ").unwrap();
+        assert_eq!(renamed, dir.join("report.pdf").to_string_lossy());
+        let new_temp = utils::chunk_temp_dir(&dir.join("report.pdf")).unwrap();
+        assert!(new_temp.join("report.pdf.part.0.99").is_file());
+        assert!(!temp_dir.exists());
+        
+        let (stored_output, metadata): (String, String) = conn.query_row(
+            "SELECT output, metadata FROM downloads WHERE id = 'rename-paused'", [], |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(stored_output, renamed);
+        assert!(metadata.contains("\"fileName\":\"report.pdf\""));
+        
+        // Only a bare file name is accepted
+        for bad in ["", "   ", "../report.pdf", "sub/report.pdf", "sub\\report.pdf", "..", "a\nb"] {
+            assert!(rename_download_output_internal("rename-paused", bad).is_err(), "{:?} was accepted", bad);
+        }
+        
+        let err = rename_download_output_internal("rename-running", "b.bin").unwrap_err();
+        assert!(err.contains("pause it first"), "{}", err);
+        assert!(rename_download_output_internal("rename-missing", "c.bin").is_err());
+        
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
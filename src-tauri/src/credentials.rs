@@ -0,0 +1,114 @@
+use crate::logger;
+use keyring::Entry;
+use serde_json::Value;
+
+/// Keyring service name the secrets are stored under (one entry per download ID)
+const KEYRING_SERVICE: &str = "accelara";
+
+/// Download options that can carry secrets
+const SECRET_OPTIONS: [&str; 2] = ["headers", "auth"];
+
+/// Set on stored options whose secrets were moved to the keyring
+const IN_KEYRING: &str = "credentialsInKeyring";
+
+/// Remove the secret-bearing options, returning them as one object (None if there were none)
+pub fn take_secrets(options: &mut Value) -> Option<Value> {
+    let map = options.as_object_mut()?;
+    let secrets: serde_json::Map<String, Value> = SECRET_OPTIONS
+        .iter()
+        .filter_map(|key| map.remove(*key).map(|value| (key.to_string(), value)))
+        .filter(|(_, value)| !value.is_null())
+        .collect();
+    if secrets.is_empty() {
+        None
+    } else {
+        Some(Value::Object(secrets))
+    }
+}
+
+/// Put secrets taken by `take_secrets` back into the options
+pub fn restore_secrets(options: &mut Value, secrets: Value) {
+    if let (Some(map), Value::Object(secrets)) = (options.as_object_mut(), secrets) {
+        map.extend(secrets);
+    }
+}
+
+fn entry(download_id: &str) -> Result<Entry, String> {
+    Entry::new(KEYRING_SERVICE, download_id).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+/// Move a download's headers/auth out of `options` into the OS keyring, before the options are saved
+/// Without a usable keyring they stay in the options (and so in the database) as given.
+pub fn protect(download_id: &str, options: &mut Value) {
+    let secrets = match take_secrets(options) {
+        Some(secrets) => secrets,
+        None => return,
+    };
+    let stored = entry(download_id)
+        .and_then(|entry| entry.set_password(&secrets.to_string()).map_err(|e| format!("Failed to store credentials: {}", e)));
+    match stored {
+        Ok(()) => options[IN_KEYRING] = Value::Bool(true),
+        Err(e) => {
+            logger::log_warning("credentials", &format!("{} - keeping headers/auth of {} in the database", e, download_id));
+            restore_secrets(options, secrets);
+        }
+    }
+}
+
+/// A download's stored options with the secrets from the keyring put back, for building requests
+pub fn reveal(download_id: &str, options: Option<Value>) -> Option<Value> {
+    let mut options = options?;
+    let in_keyring = options.as_object_mut()
+        .and_then(|map| map.remove(IN_KEYRING))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !in_keyring {
+        return Some(options);
+    }
+
+    let secrets = entry(download_id)
+        .and_then(|entry| entry.get_password().map_err(|e| format!("Failed to read credentials: {}", e)))
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| format!("Stored credentials are corrupt: {}", e)));
+    match secrets {
+        Ok(secrets) => restore_secrets(&mut options, secrets),
+        Err(e) => logger::log_warning("credentials", &format!("{} - continuing {} without headers/auth", e, download_id)),
+    }
+    Some(options)
+}
+
+/// Delete a removed download's keyring entry, if it has one
+pub fn forget(download_id: &str) {
+    if let Ok(entry) = entry(download_id) {
+        let _ = entry.delete_credential();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_and_restore_secrets() {
+        let mut options = serde_json::json!({
+            "concurrency": 4,
+            "headers": { "X-Api-Key": "secret" },
+            "auth": { "type": "bearer", "token": "abc" },
+        });
+        let secrets = take_secrets(&mut options).unwrap();
+        assert_eq!(options, serde_json::json!({ "concurrency": 4 }));
+        assert_eq!(secrets["headers"]["X-Api-Key"], "secret");
+
+        restore_secrets(&mut options, secrets);
+        assert_eq!(options["auth"]["token"], "abc");
+        assert_eq!(options["concurrency"], 4);
+
+        assert_eq!(take_secrets(&mut serde_json::json!({ "concurrency": 4, "auth": null })), None);
+    }
+
+    #[test]
+    fn test_reveal_leaves_plain_options_alone() {
+        let options = serde_json::json!({ "headers": { "Accept": "*/*" } });
+        assert_eq!(reveal("credentials-test", Some(options.clone())), Some(options));
+        assert_eq!(reveal("credentials-test", None), None);
+    }
+}
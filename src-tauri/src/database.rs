@@ -1,12 +1,215 @@
+use crate::paths;
 use rusqlite::{Connection, Result};
+use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
-use dirs::home_dir;
+use std::sync::Mutex;
+use std::time::Duration;
 
-pub fn init() -> Result<()> {
+/// Idle connections kept around for reuse; extra ones are closed when returned
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+/// How long a connection waits on a lock held by another one before failing with "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static::lazy_static! {
+    // Idle connections, tagged with the database file they were opened on
+    static ref POOL: Mutex<Vec<(PathBuf, Connection)>> = Mutex::new(Vec::new());
+}
+
+/// A pooled connection; derefs to `Connection` and goes back to the pool when dropped
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    path: PathBuf,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+    
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection taken")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection taken")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            // Don't hand out a connection stuck in a transaction someone forgot to finish
+            if !conn.is_autocommit() {
+                return;
+            }
+            if let Ok(mut pool) = POOL.lock() {
+                if pool.len() < MAX_IDLE_CONNECTIONS {
+                    pool.push((std::mem::take(&mut self.path), conn));
+                }
+            }
+        }
+    }
+}
+
+fn open_connection(path: &PathBuf) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+/// Schema version stored in `PRAGMA user_version` once every migration has run
+pub const CURRENT_SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Ordered schema changes; step N brings a database from version N to N + 1.
+/// Never edit or reorder a released step - append a new one.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    // 1: databases created before `error`/`metadata`/timestamps were part of the schema
+    |conn| {
+        add_column_if_missing(conn, "downloads", "error", "TEXT")?;
+        add_column_if_missing(conn, "downloads", "metadata", "TEXT")?;
+        add_column_if_missing(conn, "downloads", "started_at", "INTEGER")?;
+        add_column_if_missing(conn, "downloads", "updated_at", "INTEGER")?;
+        add_column_if_missing(conn, "download_history", "size", "INTEGER")?;
+        add_column_if_missing(conn, "download_history", "metadata", "TEXT")?;
+        add_column_if_missing(conn, "download_history", "completed_at", "INTEGER")?;
+        add_column_if_missing(conn, "speed_test_results", "latency", "TEXT")?;
+        add_column_if_missing(conn, "speed_test_results", "location", "TEXT")
+    },
+    // 2: batches, notes and speed test targets
+    |conn| {
+        add_column_if_missing(conn, "downloads", "batch_id", "TEXT")?;
+        add_column_if_missing(conn, "downloads", "batch_name", "TEXT")?;
+        add_column_if_missing(conn, "downloads", "notes", "TEXT")?;
+        add_column_if_missing(conn, "download_history", "notes", "TEXT")?;
+        add_column_if_missing(conn, "speed_test_results", "target", "TEXT")
+    },
+    // 3: categories
+    |conn| {
+        add_column_if_missing(conn, "downloads", "category", "TEXT")?;
+        add_column_if_missing(conn, "download_history", "category", "TEXT")
+    },
+    // 4: indexes for the active/seeding lookups and the history listing
+    |conn| {
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_downloads_status ON downloads(status);
+             CREATE INDEX IF NOT EXISTS idx_downloads_type ON downloads(type);
+             CREATE INDEX IF NOT EXISTS idx_history_completed_at ON download_history(completed_at);"
+        )
+    },
+    // 5: deferred starts
+    |conn| add_column_if_missing(conn, "downloads", "scheduled_at", "INTEGER"),
+];
+
+/// How `init` found the database
+#[derive(Debug, Clone, PartialEq)]
+pub enum DatabaseInit {
+    /// Opened (or created) normally
+    Opened,
+    /// The file was corrupt or not a database; it was moved to `backup` and a fresh one created
+    Recovered { backup: PathBuf, reason: String },
+}
+
+/// Open the database and bring its schema up to date
+/// A corrupt file (e.g. after a power loss) doesn't stop the app from starting: it's set aside
+/// as `accelara.db.corrupt.<timestamp>` and replaced with an empty database.
+pub fn init() -> Result<DatabaseInit> {
     let db_path = get_db_path();
+    let error = match init_connection() {
+        Ok(()) => return Ok(DatabaseInit::Opened),
+        Err(e) if is_corruption(&e) => e,
+        Err(e) => return Err(e),
+    };
+    
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let backup = match set_aside_corrupt_database(&db_path, timestamp) {
+        Ok(backup) => backup,
+        Err(e) => {
+            crate::logger::log_error("database", &format!("Database is corrupt ({}) but couldn't be moved aside: {}", error, e));
+            return Err(error);
+        }
+    };
+    init_connection()?;
+    Ok(DatabaseInit::Recovered { backup, reason: error.to_string() })
+}
+
+fn init_connection() -> Result<()> {
+    let conn = get_connection()?;
+    
+    // WAL lets readers (UI queries) run alongside the progress writer; it's persistent, so once is enough
+    conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get::<_, String>(0))?;
     
-    let conn = Connection::open(&db_path)?;
+    init_schema(&conn)
+}
+
+/// Whether an error means the database file itself is damaged (SQLITE_CORRUPT / SQLITE_NOTADB)
+pub fn is_corruption(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _)
+            if matches!(e.code, rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+    )
+}
+
+/// Move a corrupt database (and its WAL/shared-memory files) to `<name>.corrupt.<timestamp>`
+/// The WAL has to go too, or it would be replayed into the fresh database.
+fn set_aside_corrupt_database(db_path: &std::path::Path, timestamp: u64) -> std::io::Result<PathBuf> {
+    // Pooled connections still have the old file open
+    if let Ok(mut pool) = POOL.lock() {
+        pool.retain(|(path, _)| path != db_path);
+    }
+    
+    let backup = db_path.with_file_name(format!(
+        "{}.corrupt.{}",
+        db_path.file_name().unwrap_or_default().to_string_lossy(),
+        timestamp,
+    ));
+    std::fs::rename(db_path, &backup)?;
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_os_string();
+        sidecar.push(suffix);
+        let mut sidecar_backup = backup.as_os_str().to_os_string();
+        sidecar_backup.push(suffix);
+        if std::path::Path::new(&sidecar).exists() {
+            std::fs::rename(&sidecar, &sidecar_backup)?;
+        }
+    }
+    Ok(backup)
+}
+
+/// Create the tables and bring them up to the current schema version
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    create_tables(conn)?;
+    run_migrations(conn)
+}
+
+/// Apply the migrations a database hasn't seen yet, each in its own transaction
+/// Steps are idempotent (columns are only added when missing), since databases from before
+/// versioning already have some of them.
+pub fn run_migrations(conn: &Connection) -> Result<()> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
     
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+        let target = index as i64 + 1;
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+        let result = migration(conn)
+            .and_then(|_| conn.execute_batch(&format!("PRAGMA user_version = {}", target)));
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Create any missing tables with the current schema
+fn create_tables(conn: &Connection) -> Result<()> {
     // Create downloads table with correct column order
     // Column order: id(0), source(1), output(2), type(3), status(4), progress(5), 
     // downloaded(6), total(7), speed(8), error(9), metadata(10), started_at(11), updated_at(12)
@@ -67,19 +270,242 @@ pub fn init() -> Result<()> {
         [],
     )?;
     
+    // Columns added after the initial schema come from MIGRATIONS (appended, so existing column indexes stay valid)
     Ok(())
 }
 
-pub fn get_connection() -> Result<Connection> {
+/// Names of a table's columns, in order
+pub fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .collect();
+    Ok(columns)
+}
+
+/// Add a column to an existing table unless it's already there
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = table_columns(conn, table)?.iter().any(|name| name == column);
+    
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+/// Get a database connection, reusing an idle one from the pool when possible
+pub fn get_connection() -> Result<PooledConnection> {
     let db_path = get_db_path();
-    Connection::open(&db_path)
+    
+    let idle = POOL.lock().ok().and_then(|mut pool| {
+        let index = pool.iter().position(|(path, _)| *path == db_path)?;
+        Some(pool.swap_remove(index).1)
+    });
+    let conn = match idle {
+        Some(conn) => conn,
+        None => open_connection(&db_path)?,
+    };
+    
+    Ok(PooledConnection { conn: Some(conn), path: db_path })
 }
 
 fn get_db_path() -> PathBuf {
-    let mut path = home_dir().expect("Failed to get home directory");
-    path.push(".accelara");
-    std::fs::create_dir_all(&path).expect("Failed to create .accelara directory");
-    path.push("accelara.db");
-    path
+    paths::db_path()
+}
+
+
+/// Store a single setting, JSON-encoded like `save_settings` does
+pub fn set_setting(key: &str, value: &serde_json::Value) -> Result<()> {
+    let conn = get_connection()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+        rusqlite::params![key, value.to_string()],
+    )?;
+    Ok(())
 }
 
+/// Read a single setting stored by `save_settings` (values are JSON-encoded)
+/// Returns None if the key isn't set, so callers can apply their own default
+pub fn get_setting(key: &str) -> Option<serde_json::Value> {
+    let conn = get_connection().ok()?;
+    let value = conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [key],
+        |row| row.get::<_, String>(0),
+    ).ok()?;
+    
+    // Try to parse as JSON, fallback to string (same as get_settings)
+    Some(serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value)))
+}
+
+/// Point the global database at a per-process temp directory, for tests that go through `get_connection`
+/// Every such test shares it, so they must use their own ids rather than assume an empty database.
+#[cfg(test)]
+pub fn init_test_database() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let dir = std::env::temp_dir().join(format!("accelara-db-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var(paths::DATA_DIR_ENV, &dir);
+        init().unwrap();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_concurrent_connections_do_not_lock() {
+        init_test_database();
+        
+        let handles: Vec<_> = (0..100).map(|i| {
+            std::thread::spawn(move || -> Result<()> {
+                let conn = get_connection()?;
+                conn.execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                    rusqlite::params![format!("test-{}", i), i.to_string()],
+                )?;
+                conn.query_row("SELECT COUNT(*) FROM settings", [], |row| row.get::<_, i64>(0))?;
+                Ok(())
+            })
+        }).collect();
+        
+        for handle in handles {
+            handle.join().unwrap().expect("database call failed");
+        }
+        
+        let count: i64 = get_connection().unwrap()
+            .query_row("SELECT COUNT(*) FROM settings WHERE key LIKE 'test-%'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 100);
+        assert!(POOL.lock().unwrap().len() <= MAX_IDLE_CONNECTIONS);
+    }
+    
+    #[test]
+    fn test_migrations_upgrade_old_schema() {
+        let path = std::env::temp_dir().join(format!("accelara-migrate-test-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let conn = Connection::open(&path).unwrap();
+        
+        // Shape of an early install: no error/metadata columns and no version
+        conn.execute_batch(
+            "CREATE TABLE downloads (
+                id TEXT PRIMARY KEY,
+                source TEXT NOT NULL,
+                output TEXT NOT NULL,
+                type TEXT NOT NULL,
+                status TEXT NOT NULL,
+                progress REAL DEFAULT 0,
+                downloaded INTEGER DEFAULT 0,
+                total INTEGER DEFAULT 0,
+                speed INTEGER DEFAULT 0
+            );
+            INSERT INTO downloads (id, source, output, type, status) VALUES ('old', 'http://x/a', '/tmp/a', 'http', 'paused');"
+        ).unwrap();
+        
+        create_tables(&conn).unwrap();
+        run_migrations(&conn).unwrap();
+        
+        let columns = table_columns(&conn, "downloads").unwrap();
+        for column in ["error", "metadata", "started_at", "updated_at", "batch_id", "batch_name", "notes", "scheduled_at"] {
+            assert!(columns.iter().any(|c| c == column), "missing column {}", column);
+        }
+        assert!(table_columns(&conn, "download_history").unwrap().iter().any(|c| c == "notes"));
+        assert!(table_columns(&conn, "speed_test_results").unwrap().iter().any(|c| c == "target"));
+        
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, CURRENT_SCHEMA_VERSION);
+        
+        // Existing rows survive and the new columns are readable
+        let error: Option<String> = conn.query_row("SELECT error FROM downloads WHERE id = 'old'", [], |row| row.get(0)).unwrap();
+        assert_eq!(error, None);
+        
+        // Running again is a no-op
+        run_migrations(&conn).unwrap();
+        
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    fn query_plan(conn: &Connection, sql: &str) -> String {
+        let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql)).unwrap();
+        let details: Vec<String> = stmt.query_map([], |row| row.get::<_, String>(3))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        details.join("\n")
+    }
+    
+    #[test]
+    fn test_status_queries_use_indexes() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        
+        let statuses = ["completed", "completed", "completed", "cancelled", "downloading", "paused", "seeding", "queued"];
+        let tx = conn.transaction().unwrap();
+        for i in 0..5000 {
+            let download_type = if i % 3 == 0 { "torrent" } else { "http" };
+            tx.execute(
+                "INSERT INTO downloads (id, source, output, type, status, started_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![format!("d{}", i), format!("http://x/{}", i), format!("/tmp/{}", i), download_type, statuses[i % statuses.len()], i as i64],
+            ).unwrap();
+            tx.execute(
+                "INSERT INTO download_history (id, source, output, type, completed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![format!("h{}", i), format!("http://x/{}", i), format!("/tmp/h{}", i), download_type, i as i64],
+            ).unwrap();
+        }
+        tx.commit().unwrap();
+        conn.execute_batch("ANALYZE").unwrap();
+        
+        // Active downloads count (get_active_downloads excludes completed/cancelled)
+        let plan = query_plan(&conn, "SELECT COUNT(*) FROM downloads WHERE status NOT IN ('completed', 'cancelled')");
+        assert!(plan.contains("USING COVERING INDEX idx_downloads_status"), "{}", plan);
+        
+        let plan = query_plan(&conn, "SELECT id FROM downloads WHERE status IN ('downloading', 'paused', 'queued') ORDER BY started_at ASC");
+        assert!(plan.contains("USING INDEX idx_downloads_status"), "{}", plan);
+        
+        let plan = query_plan(&conn, "SELECT id FROM downloads WHERE status = 'seeding' AND type = 'torrent'");
+        assert!(plan.contains("USING INDEX idx_downloads_"), "{}", plan);
+        
+        let plan = query_plan(&conn, "SELECT id FROM download_history ORDER BY completed_at DESC LIMIT 100");
+        assert!(plan.contains("USING INDEX idx_history_completed_at"), "{}", plan);
+    }
+    
+    #[test]
+    fn test_recovers_from_file_that_is_not_a_database() {
+        let dir = std::env::temp_dir().join(format!("accelara-db-corrupt-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("accelara.db");
+        std::fs::write(&db_path, vec![0x5a; 8192]).unwrap();
+        std::fs::write(dir.join("accelara.db-wal"), b"garbage").unwrap();
+        
+        let error = open_connection(&db_path)
+            .and_then(|conn| conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get::<_, String>(0)))
+            .unwrap_err();
+        assert!(is_corruption(&error), "{:?}", error);
+        assert!(!is_corruption(&rusqlite::Error::QueryReturnedNoRows));
+        
+        let backup = set_aside_corrupt_database(&db_path, 1_700_000_000).unwrap();
+        assert_eq!(backup, dir.join("accelara.db.corrupt.1700000000"));
+        assert_eq!(std::fs::read(&backup).unwrap(), vec![0x5a; 8192]);
+        assert!(dir.join("accelara.db.corrupt.1700000000-wal").is_file());
+        assert!(!db_path.exists());
+        assert!(!dir.join("accelara.db-wal").exists());
+        
+        // A fresh database takes its place and is usable
+        let conn = open_connection(&db_path).unwrap();
+        init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('recovered', 'true')",
+            [],
+        ).unwrap();
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, CURRENT_SCHEMA_VERSION);
+        
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::checksum::{self, HashAlgorithm};
 use crate::commands::DOWNLOAD_PROCESSES;
 use crate::database;
+use crate::wrapper_exit::WrapperExit;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
@@ -10,7 +12,22 @@ use tokio::process::Child;
 use tokio::sync::Mutex;
 
 /// Set up download handlers to parse Go process output and emit events
-pub fn setup_download_handlers(_app: &mut tauri::App) {
+pub fn setup_download_handlers(app: &mut tauri::App) {
+    // Watch for downloads whose process is alive but no longer making progress
+    setup_stall_detection(app.handle().clone());
+    
+    // Switch rate limits as the day-of-week bandwidth schedule moves between rules
+    crate::schedule::setup_bandwidth_schedule(app.handle().clone());
+    
+    // Start downloads whose `schedule_download` time has come
+    setup_scheduled_starts(app.handle().clone());
+    
+    // Drop completed downloads older than `autoCleanupCompletedDays` from the list (history keeps them)
+    setup_completed_cleanup(app.handle().clone());
+    
+    // Emit the summed speed of all active downloads for the global throughput graph
+    setup_global_speed_events(app.handle().clone());
+    
     // Start periodic progress saving task
     tauri::async_runtime::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(5)); // Save every 5 seconds
@@ -34,12 +51,15 @@ pub fn setup_download_handlers(_app: &mut tauri::App) {
             // Remove stale entries
             for id in to_remove {
                 cache.remove(&id);
+                clear_speed_history(&id);
+                if let Ok(mut uploads) = UPLOAD_RATES.lock() {
+                    uploads.remove(&id);
+                }
             }
         }
     });
     
-    // Save all progress on app exit - use setup hook
-    // Note: Tauri doesn't have a direct shutdown hook, so we rely on periodic saves
+    // On a clean exit the rest is saved by flush_progress_cache (quit_app and RunEvent::ExitRequested)
     // The periodic save every 5 seconds ensures we don't lose much data on crash
 }
 
@@ -120,13 +140,519 @@ pub fn monitor_download_process(
     });
 }
 
+/// Progress tracking used to spot stalled downloads
+struct StallState {
+    downloaded: i64,
+    last_advanced: Instant,
+    reported: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref STALL_TRACKER: std::sync::Mutex<HashMap<String, StallState>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Start (or restart) stall tracking for a download whose process was just spawned
+fn track_stall_start(download_id: &str) {
+    if let Ok(mut tracker) = STALL_TRACKER.lock() {
+        tracker.insert(download_id.to_string(), StallState {
+            downloaded: 0,
+            last_advanced: Instant::now(),
+            reported: false,
+        });
+    }
+}
+
+/// Reset the stall timer whenever `downloaded` moves forward
+fn track_stall_progress(download_id: &str, downloaded: i64) {
+    if let Ok(mut tracker) = STALL_TRACKER.lock() {
+        let state = tracker.entry(download_id.to_string()).or_insert(StallState {
+            downloaded,
+            last_advanced: Instant::now(),
+            reported: false,
+        });
+        if downloaded > state.downloaded {
+            state.downloaded = downloaded;
+            state.last_advanced = Instant::now();
+            state.reported = false;
+        }
+    }
+}
+
+/// Periodically look for running downloads that haven't advanced within `stallTimeout`
+/// and report them (restarting them too when `autoRestartStalled` is on)
+fn setup_stall_detection(app: AppHandle) {
+    use crate::{commands, logger};
+    
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            
+            let stall_timeout = database::get_setting("stallTimeout")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(60);
+            if stall_timeout == 0 {
+                continue; // Disabled
+            }
+            let auto_restart = database::get_setting("autoRestartStalled")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            
+            let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+            
+            // Collect newly stalled downloads, dropping entries of downloads that stopped
+            let stalled: Vec<(String, u64)> = match STALL_TRACKER.lock() {
+                Ok(mut tracker) => {
+                    tracker.retain(|id, _| running.contains(id));
+                    tracker.iter_mut()
+                        .filter(|(_, state)| !state.reported && state.last_advanced.elapsed().as_secs() >= stall_timeout)
+                        .map(|(id, state)| {
+                            state.reported = true;
+                            (id.clone(), state.last_advanced.elapsed().as_secs())
+                        })
+                        .collect()
+                }
+                Err(_) => continue,
+            };
+            
+            for (download_id, stalled_for) in stalled {
+                // Only actively downloading rows count (seeding torrents legitimately sit still)
+                let status: Option<String> = database::get_connection().ok().and_then(|conn| {
+                    conn.query_row("SELECT status FROM downloads WHERE id = ?1", [&download_id], |row| row.get(0)).ok()
+                });
+                if status.as_deref() != Some("downloading") {
+                    continue;
+                }
+                
+                logger::log_warning("download", &format!("Download {} stalled (no progress for {}s)", download_id, stalled_for));
+                let _ = app.emit("download-stalled", serde_json::json!({
+                    "downloadId": download_id,
+                    "download_id": download_id,
+                    "stalledForSecs": stalled_for,
+                    "autoRestart": auto_restart,
+                }));
+                
+                if auto_restart {
+                    if let Err(e) = commands::restart_download_internal(download_id.clone(), app.clone()).await {
+                        logger::log_error("download", &format!("Failed to restart stalled download {}: {}", download_id, e));
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Scheduled downloads whose start time is at or before `now` (epoch seconds)
+/// They're moved back to paused with the schedule cleared, so a start that fails isn't retried every minute.
+pub fn take_due_scheduled_downloads(now: i64) -> Vec<String> {
+    let conn = match database::get_connection() {
+        Ok(conn) => conn,
+        Err(_) => return Vec::new(),
+    };
+    let scheduled: Vec<(String, Option<i64>)> = match conn.prepare(
+        "SELECT id, scheduled_at FROM downloads WHERE status = 'scheduled' ORDER BY scheduled_at ASC"
+    ) {
+        Ok(mut stmt) => stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default(),
+        Err(_) => return Vec::new(),
+    };
+    
+    scheduled
+        .into_iter()
+        .filter(|(_, scheduled_at)| crate::commands::is_due(*scheduled_at, now))
+        .filter(|(id, _)| {
+            // Only take it if it's still scheduled (it may have been paused or started meanwhile)
+            conn.execute(
+                "UPDATE downloads SET status = 'paused', scheduled_at = NULL WHERE id = ?1 AND status = 'scheduled'",
+                [id],
+            )
+            .map(|changed| changed > 0)
+            .unwrap_or(false)
+        })
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Every minute, start (or queue, if all slots are taken) the scheduled downloads that are due
+fn setup_scheduled_starts(app: AppHandle) {
+    use crate::{logger, queue};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+            for download_id in take_due_scheduled_downloads(now) {
+                logger::log_info("download", &format!("Scheduled start time reached for {}", download_id));
+                if let Err(e) = queue::enqueue(download_id.clone(), app.clone()).await {
+                    logger::log_error("download", &format!("Failed to start scheduled download {}: {}", download_id, e));
+                    let _ = app.emit("download-update", serde_json::json!({
+                        "downloadId": download_id,
+                        "download_id": download_id,
+                        "status": "paused",
+                        "error": e,
+                    }));
+                }
+            }
+        }
+    });
+}
+
+/// Default for the `autoCleanupCompletedDays` setting (0 keeps completed downloads in the list forever)
+pub const DEFAULT_AUTO_CLEANUP_COMPLETED_DAYS: u64 = 30;
+
+/// Delete the `completed` downloads rows that finished more than `days` days before `now` (epoch seconds)
+/// Their download_history entries stay. Downloads in `running` - a completed torrent still
+/// seeding - are never pruned. Returns the pruned ids.
+pub fn prune_completed_downloads(days: u64, now: i64, running: &[String]) -> Vec<String> {
+    let conn = match database::get_connection() {
+        Ok(conn) => conn,
+        Err(_) => return Vec::new(),
+    };
+    let cutoff = now - (days as i64) * 24 * 60 * 60;
+    // When it finished is recorded in the history; rows without one fall back to their last update
+    let stale: Vec<(String, String)> = match conn.prepare(
+        "SELECT d.id, d.source FROM downloads d LEFT JOIN download_history h ON h.id = d.id
+         WHERE d.status = 'completed' AND COALESCE(h.completed_at, d.updated_at, d.started_at) < ?1"
+    ) {
+        Ok(mut stmt) => stmt
+            .query_map([cutoff], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default(),
+        Err(_) => return Vec::new(),
+    };
+    
+    stale
+        .into_iter()
+        .filter(|(id, _)| !running.contains(id))
+        .filter(|(id, _)| {
+            conn.execute("DELETE FROM downloads WHERE id = ?1 AND status = 'completed'", [id])
+                .map(|deleted| deleted > 0)
+                .unwrap_or(false)
+        })
+        .map(|(id, source)| {
+            crate::commands::remove_saved_torrent_data(&conn, &source);
+            crate::download_log::clear(&id);
+            crate::credentials::forget(&id);
+            id
+        })
+        .collect()
+}
+
+/// At startup and then hourly, prune completed downloads past `autoCleanupCompletedDays`
+fn setup_completed_cleanup(app: AppHandle) {
+    use crate::logger;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            
+            let days = database::get_setting("autoCleanupCompletedDays")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_AUTO_CLEANUP_COMPLETED_DAYS);
+            if days == 0 {
+                continue; // Disabled
+            }
+            
+            let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+            let pruned = prune_completed_downloads(days, now, &running);
+            if !pruned.is_empty() {
+                logger::log_info("download", &format!("Removed {} completed downloads older than {} days from the list", pruned.len(), days));
+            }
+            for download_id in pruned {
+                let _ = app.emit("download-removed", serde_json::json!({
+                    "downloadId": download_id,
+                }));
+            }
+        }
+    });
+}
+
+/// Number of recent (downloaded, time) samples the speed estimate is based on
+const SPEED_WINDOW: usize = 10;
+
+/// Weight of the newest window rate in the moving average (higher reacts faster, jumps more)
+const SPEED_SMOOTHING: f64 = 0.3;
+
+/// Smoothed speed and ETA for one download, from its recent progress lines
+struct SpeedEstimator {
+    samples: VecDeque<(Instant, i64)>,
+    average: Option<f64>,
+}
+
+impl SpeedEstimator {
+    fn new() -> Self {
+        SpeedEstimator { samples: VecDeque::with_capacity(SPEED_WINDOW), average: None }
+    }
+
+    /// Add a sample and return the smoothed speed in bytes/sec (None until two samples exist)
+    fn update(&mut self, downloaded: i64, now: Instant) -> Option<f64> {
+        // Counter went backwards (restart from scratch) - old samples no longer apply
+        if self.samples.back().map(|(_, d)| downloaded < *d).unwrap_or(false) {
+            self.samples.clear();
+            self.average = None;
+        }
+        if self.samples.len() == SPEED_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, downloaded));
+
+        let (first_time, first_downloaded) = *self.samples.front()?;
+        let elapsed = now.duration_since(first_time).as_secs_f64();
+        if elapsed <= 0.0 {
+            return self.average;
+        }
+        let window_rate = (downloaded - first_downloaded) as f64 / elapsed;
+        let average = match self.average {
+            Some(average) => SPEED_SMOOTHING * window_rate + (1.0 - SPEED_SMOOTHING) * average,
+            None => window_rate,
+        };
+        self.average = Some(average);
+        self.average
+    }
+
+    /// Seconds left at the smoothed speed; None when the size is unknown or nothing is moving
+    fn eta_seconds(&self, downloaded: i64, total: i64) -> Option<u64> {
+        let speed = self.average?;
+        if total <= 0 || speed < 1.0 {
+            return None;
+        }
+        Some(((total - downloaded).max(0) as f64 / speed).ceil() as u64)
+    }
+}
+
+/// Minimum time between two progress events for the same download (~4 updates/sec)
+const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Coalesces a download's progress lines so the webview isn't flooded with events
+/// Status changes and final updates bypass the throttle.
+struct EmitThrottle {
+    last_emit: Option<Instant>,
+    last_status: Option<String>,
+    pending: Option<Value>,
+}
+
+impl EmitThrottle {
+    fn new() -> Self {
+        EmitThrottle { last_emit: None, last_status: None, pending: None }
+    }
+
+    /// Returns the update to emit now, or None if it was held back
+    fn offer(&mut self, update: Value) -> Option<Value> {
+        let status = update.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
+        let status_changed = status.is_some() && status != self.last_status;
+        // progress is a 0-1 fraction
+        let finished = update.get("progress").and_then(|v| v.as_f64()).map(|p| p >= 1.0).unwrap_or(false)
+            || update.get("error").map(|e| !e.is_null()).unwrap_or(false);
+        let due = self.last_emit.map(|t| t.elapsed() >= EMIT_INTERVAL).unwrap_or(true);
+
+        if status_changed || finished || due {
+            if status.is_some() {
+                self.last_status = status;
+            }
+            self.last_emit = Some(Instant::now());
+            self.pending = None;
+            Some(update)
+        } else {
+            self.pending = Some(update);
+            None
+        }
+    }
+
+    /// The last held-back update, if any
+    fn flush(&mut self) -> Option<Value> {
+        self.pending.take()
+    }
+}
+
 // Global map to store latest progress for periodic saving
 lazy_static::lazy_static! {
     static ref PROGRESS_CACHE: Arc<Mutex<HashMap<String, (f64, i64, i64, i64, Instant)>>> = 
         Arc::new(Mutex::new(HashMap::new()));
 }
 
-// Helper function to save progress to database
+/// Number of one-second speed samples kept per download
+const SPEED_HISTORY_LEN: usize = 60;
+
+// Recent speed samples per active download: (unix seconds, bytes/sec)
+// A plain std Mutex is enough here - it's only held for a push/copy, never across an await
+lazy_static::lazy_static! {
+    static ref SPEED_HISTORY: std::sync::Mutex<HashMap<String, VecDeque<(u64, i64)>>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Record a speed sample, keeping at most one sample per second (the latest wins)
+fn record_speed_sample(download_id: &str, speed: i64) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if let Ok(mut history) = SPEED_HISTORY.lock() {
+        let samples = history
+            .entry(download_id.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(SPEED_HISTORY_LEN));
+        match samples.back_mut() {
+            Some(last) if last.0 == now => last.1 = speed,
+            _ => {
+                if samples.len() == SPEED_HISTORY_LEN {
+                    samples.pop_front();
+                }
+                samples.push_back((now, speed));
+            }
+        }
+    }
+}
+
+/// Speed samples for a download, oldest first
+pub fn get_speed_history(download_id: &str) -> Vec<(u64, i64)> {
+    SPEED_HISTORY
+        .lock()
+        .ok()
+        .and_then(|history| history.get(download_id).map(|s| s.iter().copied().collect()))
+        .unwrap_or_default()
+}
+
+/// Drop the speed samples of a download that's no longer running
+pub fn clear_speed_history(download_id: &str) {
+    if let Ok(mut history) = SPEED_HISTORY.lock() {
+        history.remove(download_id);
+    }
+}
+
+// Latest upload rate (bytes/sec) reported by each torrent, with when it was reported
+lazy_static::lazy_static! {
+    static ref UPLOAD_RATES: std::sync::Mutex<HashMap<String, (i64, Instant)>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Default for the `globalSpeedEventIntervalMs` setting
+pub const DEFAULT_GLOBAL_SPEED_EVENT_INTERVAL_MS: u64 = 1000;
+
+/// Lower bound for `globalSpeedEventIntervalMs`, so a typo can't flood the frontend
+const MIN_GLOBAL_SPEED_EVENT_INTERVAL_MS: u64 = 100;
+
+/// A cached speed older than this belongs to a download that stopped reporting and isn't counted
+const ACTIVE_SPEED_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// The `global-speed` payload: summed download/upload speed of the downloads that reported recently
+fn aggregate_speeds(
+    progress: &HashMap<String, (f64, i64, i64, i64, Instant)>,
+    uploads: &HashMap<String, (i64, Instant)>,
+    now: Instant,
+) -> Value {
+    let is_fresh = |at: &Instant| now.saturating_duration_since(*at) <= ACTIVE_SPEED_MAX_AGE;
+    let active: Vec<&String> = progress
+        .iter()
+        .filter(|(_, (_, _, _, _, at))| is_fresh(at))
+        .map(|(id, _)| id)
+        .collect();
+    let total_download_speed: i64 = progress
+        .values()
+        .filter(|(_, _, _, _, at)| is_fresh(at))
+        .map(|(_, _, _, speed, _)| (*speed).max(0))
+        .sum();
+    let total_upload_speed: i64 = uploads
+        .values()
+        .filter(|(_, at)| is_fresh(at))
+        .map(|(rate, _)| (*rate).max(0))
+        .sum();
+    // A seeding torrent may report upload without progress lines
+    let upload_only = uploads
+        .iter()
+        .filter(|(id, (_, at))| is_fresh(at) && !active.contains(id))
+        .count();
+    
+    serde_json::json!({
+        "totalDownloadSpeed": total_download_speed,
+        "totalUploadSpeed": total_upload_speed,
+        "activeCount": active.len() + upload_only,
+    })
+}
+
+/// Emit `global-speed` every `globalSpeedEventIntervalMs`, so the frontend needn't sum per-download events
+fn setup_global_speed_events(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval_ms = database::get_setting("globalSpeedEventIntervalMs")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_GLOBAL_SPEED_EVENT_INTERVAL_MS)
+                .max(MIN_GLOBAL_SPEED_EVENT_INTERVAL_MS);
+            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            
+            let progress = PROGRESS_CACHE.lock().await.clone();
+            let uploads = UPLOAD_RATES.lock().map(|uploads| uploads.clone()).unwrap_or_default();
+            let _ = app.emit("global-speed", aggregate_speeds(&progress, &uploads, Instant::now()));
+        }
+    });
+}
+
+/// Write every cached progress entry to the database; returns how many downloads were updated
+/// The periodic save only runs every 5 seconds, so this is called on the way out.
+pub async fn flush_progress_cache() -> usize {
+    let entries = cached_progress_entries().await;
+    match database::get_connection() {
+        Ok(conn) => write_progress_entries(&conn, &entries),
+        Err(e) => {
+            crate::logger::log_error("download", &format!("Failed to flush progress cache: {}", e));
+            0
+        }
+    }
+}
+
+/// (id, progress, downloaded, total, speed) for every download in the progress cache
+async fn cached_progress_entries() -> Vec<(String, f64, i64, i64, i64)> {
+    PROGRESS_CACHE.lock().await
+        .iter()
+        .map(|(id, (progress, downloaded, total, speed, _))| (id.clone(), *progress, *downloaded, *total, *speed))
+        .collect()
+}
+
+/// Save progress entries in one transaction
+fn write_progress_entries(conn: &rusqlite::Connection, entries: &[(String, f64, i64, i64, i64)]) -> usize {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let write = || -> rusqlite::Result<usize> {
+        let tx = conn.unchecked_transaction()?;
+        let mut written = 0;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE downloads SET progress = ?, downloaded = ?, total = ?, speed = ?, updated_at = ? WHERE id = ?"
+            )?;
+            for (id, progress, downloaded, total, speed) in entries {
+                written += stmt.execute(rusqlite::params![progress, downloaded, total, speed, now, id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(written)
+    };
+    match write() {
+        Ok(written) => written,
+        Err(e) => {
+            crate::logger::log_error("download", &format!("Failed to flush progress cache: {}", e));
+            0
+        }
+    }
+}
+
+/// Write a download's latest cached progress to the database right away
+/// Used before restarting its process, so the resume starts from what the old process reached.
+pub async fn persist_cached_progress(download_id: &str) {
+    let cached = PROGRESS_CACHE.lock().await.get(download_id).cloned();
+    if let Some((progress, downloaded, total, speed, _)) = cached {
+        save_progress_to_db(download_id, progress, downloaded, total, speed);
+    }
+}
+
 fn save_progress_to_db(download_id: &str, progress: f64, downloaded: i64, total: i64, speed: i64) {
     if let Ok(conn) = database::get_connection() {
         let _ = conn.execute(
@@ -146,20 +672,60 @@ fn save_progress_to_db(download_id: &str, progress: f64, downloaded: i64, total:
     }
 }
 
+/// Log and forward a download's stderr until it closes
+/// Returns whether any line reported a full disk.
+async fn drain_stderr(app: AppHandle, download_id: String, stderr: Option<tokio::process::ChildStderr>) -> bool {
+    use crate::logger;
+    
+    let mut disk_full = false;
+    if let Some(stderr) = stderr {
+        logger::log_info("monitor_download", &format!("[{}] stderr stream available", download_id));
+        let mut reader = BufReader::new(stderr);
+        let mut line = String::new();
+        
+        while let Ok(n) = reader.read_line(&mut line).await {
+            if n == 0 {
+                logger::log_info("monitor_download", &format!("[{}] stderr stream closed (EOF)", download_id));
+                break;
+            }
+            
+            logger::log_error("monitor_download", &format!("[{}] stderr: {}", download_id, line.trim()));
+            if !line.trim().is_empty() {
+                let _ = app.emit("download-log", crate::download_log::record(&download_id, &line));
+            }
+            disk_full |= crate::wrapper_exit::is_disk_full_error(&line);
+            line.clear();
+        }
+    } else {
+        logger::log_warning("monitor_download", &format!("[{}] stderr stream not available", download_id));
+    }
+    disk_full
+}
+
 /// Monitor download process with pre-captured stdout/stderr
+/// `pid` identifies the monitored child in `DOWNLOAD_PROCESSES`: after a restart the entry
+/// under the same id belongs to a newer process, which this monitor must leave alone.
 pub async fn monitor_download_process_with_streams(
     app: AppHandle,
     download_id: String,
     stdout: Option<tokio::process::ChildStdout>,
     stderr: Option<tokio::process::ChildStderr>,
+    pid: Option<u32>,
 ) {
     use crate::logger;
     logger::log_info("monitor_download", &format!("Starting to monitor download: {}", download_id));
+    track_stall_start(&download_id);
+    
+    // Read alongside stdout: a wrapper blocked on a full stderr pipe would stop reporting progress
+    let stderr_task = tokio::spawn(drain_stderr(app.clone(), download_id.clone(), stderr));
     
     if let Some(stdout) = stdout {
         logger::log_info("monitor_download", &format!("[{}] stdout stream available", download_id));
         let mut reader = BufReader::new(stdout);
         let mut line = String::new();
+        let mut throttle = EmitThrottle::new();
+        let mut seeding_recorded = false;
+        let mut estimator = SpeedEstimator::new();
         
         while let Ok(n) = reader.read_line(&mut line).await {
             if n == 0 {
@@ -169,7 +735,32 @@ pub async fn monitor_download_process_with_streams(
             
             logger::log_info("monitor_download", &format!("[{}] Received line: {}", download_id, line.trim()));
             
-            if let Ok(json) = serde_json::from_str::<Value>(line.trim()) {
+            if let Ok(mut json) = serde_json::from_str::<Value>(line.trim()) {
+                // Steadier numbers for the UI; the raw `speed` is passed through untouched
+                if let Some(downloaded) = json.get("downloaded").and_then(|v| v.as_i64()) {
+                    let total = json.get("total").and_then(|v| v.as_i64()).unwrap_or(0);
+                    let smoothed = estimator.update(downloaded, Instant::now());
+                    json["smoothedSpeed"] = serde_json::json!(smoothed.map(|s| s.round() as i64));
+                    json["etaSeconds"] = serde_json::json!(estimator.eta_seconds(downloaded, total));
+                }
+                
+                // Persist the switch to seeding so it survives a restart (see auto_resume_downloads)
+                if !seeding_recorded && json.get("status").and_then(|v| v.as_str()) == Some("seeding") {
+                    seeding_recorded = true;
+                    if let Ok(conn) = database::get_connection() {
+                        let _ = conn.execute(
+                            "UPDATE downloads SET status = 'seeding' WHERE id = ?1 AND status = 'downloading'",
+                            [&download_id],
+                        );
+                    }
+                }
+                
+                if let Some(upload_rate) = json.get("upload_rate").and_then(|v| v.as_i64()) {
+                    if let Ok(mut uploads) = UPLOAD_RATES.lock() {
+                        uploads.insert(download_id.clone(), (upload_rate, Instant::now()));
+                    }
+                }
+                
                 // Extract progress data and cache it for periodic saving
                 if let (Some(download_id_val), Some(progress), Some(downloaded), Some(total), Some(speed)) = (
                     json.get("download_id").or_else(|| json.get("downloadId")),
@@ -208,6 +799,13 @@ pub async fn monitor_download_process_with_streams(
                         continue;
                     }
                     
+                    record_speed_sample(id_str, speed);
+                    track_stall_progress(id_str, downloaded);
+                    if let Some(peers) = json.get("peers").and_then(|v| v.as_u64()) {
+                        // Entries of stopped torrents are pruned by the peer rebalancing task
+                        crate::peer_budget::record_peers(id_str, peers);
+                    }
+                    
                     // Update cache with latest progress
                     let mut cache = PROGRESS_CACHE.lock().await;
                     let prev_progress = cache.get(id_str).map(|(p, _, _, _, _)| *p);
@@ -229,45 +827,54 @@ pub async fn monitor_download_process_with_streams(
                     }
                 }
                 
-                // Emit update event
-                let _ = app.emit("download-update", json);
+                // Emit update event (coalesced - the latest update always goes out eventually)
+                if let Some(update) = throttle.offer(json) {
+                    let _ = app.emit("download-update", update);
+                }
             }
             
             line.clear();
         }
-    }
-    
-    if let Some(stderr) = stderr {
-        logger::log_info("monitor_download", &format!("[{}] stderr stream available", download_id));
-        let mut reader = BufReader::new(stderr);
-        let mut line = String::new();
         
-        while let Ok(n) = reader.read_line(&mut line).await {
-            if n == 0 {
-                logger::log_info("monitor_download", &format!("[{}] stderr stream closed (EOF)", download_id));
-                break;
-            }
-            
-            logger::log_error("monitor_download", &format!("[{}] stderr: {}", download_id, line.trim()));
-            line.clear();
+        // Don't lose the last coalesced update when the stream ends
+        if let Some(update) = throttle.flush() {
+            let _ = app.emit("download-update", update);
         }
-    } else {
-        logger::log_warning("monitor_download", &format!("[{}] stderr stream not available", download_id));
     }
     
+    let disk_full = stderr_task.await.unwrap_or(false);
+    
     // Wait for process to complete
     let status = {
         let mut processes = DOWNLOAD_PROCESSES.lock().await;
-        if let Some(mut child) = processes.remove(&download_id) {
+        let ours = processes.get(&download_id).map(|c| c.id() == pid).unwrap_or(false);
+        if let Some(mut child) = if ours { processes.remove(&download_id) } else { None } {
             drop(processes);
             child.wait().await
         } else {
+            // Paused/stopped (or restarted) elsewhere - its slot is free for the next queued download
+            crate::queue::on_slot_freed(app.clone());
             return;
         }
     };
     
-    let success = status.as_ref().map(|s| s.success()).unwrap_or(false);
-    let final_status = if success { "completed" } else { "error" };
+    let mut success = status.as_ref().map(|s| s.success()).unwrap_or(false);
+    let mut final_status = if success { "completed" } else { "error" };
+    let mut exit = WrapperExit::from_code(status.as_ref().ok().and_then(|s| s.code()));
+    // Whatever the exit code, a disk-full message on stderr is the actionable cause
+    if !success && disk_full {
+        exit = WrapperExit::DiskFull;
+    }
+    if !success {
+        logger::log_error("monitor_download", &format!("[{}] Wrapper exited with {:?}", download_id, exit));
+    }
+    
+    // Another mirror may well work - the download keeps its queue slot while it switches
+    // (not for a full disk or arguments the wrapper rejects, which fail the same way anywhere)
+    if !success && !matches!(exit, WrapperExit::DiskFull | WrapperExit::Usage) && switch_to_next_mirror(&app, &download_id) {
+        return;
+    }
+    crate::queue::on_slot_freed(app.clone());
     
     // For HTTP downloads, verify the final file exists (not a .part file)
     // Note: Torrents don't use .part files - the torrent library writes directly to final locations
@@ -509,58 +1116,46 @@ pub async fn monitor_download_process_with_streams(
         }
     }
     
-    // Move completed download to history
+    // Torrents with a completeDir move out of their working folder now that the wrapper is done with them
     if success {
-        if let Ok(conn) = database::get_connection() {
-            // Get download info from database
-            if let Ok((source, output, download_type, downloaded, total, metadata)) = conn.query_row(
-                "SELECT source, output, type, downloaded, total, metadata FROM downloads WHERE id = ?1",
-                [&download_id],
-                |row| {
-                    Ok((
-                        row.get::<_, String>(0)?, // source
-                        row.get::<_, String>(1)?, // output
-                        row.get::<_, String>(2)?, // type
-                        row.get::<_, i64>(3)?,     // downloaded
-                        row.get::<_, i64>(4)?,     // total
-                        row.get::<_, Option<String>>(5)?, // metadata
-                    ))
-                },
-            ) {
-                // Use total if available, otherwise use downloaded
-                let file_size = if total > 0 { total } else { downloaded };
-                
-                // Check if already in history (avoid duplicates)
-                let exists = conn.query_row(
-                    "SELECT COUNT(*) FROM download_history WHERE id = ?1",
-                    [&download_id],
-                    |row| row.get::<_, i64>(0),
-                ).unwrap_or(0) > 0;
-                
-                if !exists {
-                    // Insert into download_history
-                    let completed_at = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64;
-                    
-                    let _ = conn.execute(
-                        "INSERT INTO download_history (id, source, output, type, size, completed_at, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                        rusqlite::params![
-                            download_id,
-                            source,
-                            output,
-                            download_type,
-                            file_size,
-                            completed_at,
-                            metadata.unwrap_or_default(),
-                        ],
-                    );
+        let id = download_id.clone();
+        match tokio::task::spawn_blocking(move || move_to_complete_dir(&id)).await {
+            Ok(Ok(Some(dest))) => logger::log_info("monitor_download", &format!("[{}] Moved to {}", download_id, dest.display())),
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => logger::log_error("monitor_download", &format!("[{}] Failed to move to completeDir: {}", download_id, e)),
+            Err(e) => logger::log_error("monitor_download", &format!("[{}] Move task failed: {}", download_id, e)),
+        }
+    }
+    
+    // md5/sha1 are never seen by the wrapper, so a finished file is only good once it's checked here
+    let mut verified_with = None;
+    if success {
+        let id = download_id.clone();
+        match tokio::task::spawn_blocking(move || verify_completed_checksum(&id)).await {
+            Ok(Ok(Some((algorithm, matched)))) => {
+                verified_with = Some(algorithm);
+                if !matched {
+                    logger::log_error("monitor_download", &format!("[{}] {} checksum mismatch", download_id, algorithm.as_str()));
+                    success = false;
+                    final_status = "error";
+                    exit = WrapperExit::ChecksumMismatch;
                 }
             }
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => logger::log_error("monitor_download", &format!("[{}] Checksum verification failed: {}", download_id, e)),
+            Err(e) => logger::log_error("monitor_download", &format!("[{}] Checksum task failed: {}", download_id, e)),
         }
     }
     
+    clear_speed_history(&download_id);
+    
+    // Move completed download to history
+    if success {
+        record_in_history(&download_id);
+        crate::notifications::play_completion_sound();
+        open_on_complete(&download_id);
+    }
+    
     if let Ok(conn) = database::get_connection() {
         // Update status in downloads table
         let _ = conn.execute(
@@ -568,6 +1163,25 @@ pub async fn monitor_download_process_with_streams(
             rusqlite::params![final_status, download_id],
         );
         
+        // Record why it failed so the UI can show an actionable error and decide whether to retry
+        if !success {
+            let metadata_str: Option<String> = conn.query_row(
+                "SELECT metadata FROM downloads WHERE id = ?1",
+                [&download_id],
+                |row| row.get(0),
+            ).ok().flatten();
+            let mut metadata: Value = metadata_str
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_else(|| serde_json::json!({}));
+            metadata["errorKind"] = serde_json::json!(exit.kind());
+            metadata["errorCode"] = serde_json::json!(exit.error_code());
+            metadata["retryable"] = serde_json::json!(exit.is_retryable());
+            let _ = conn.execute(
+                "UPDATE downloads SET error = ?, metadata = ? WHERE id = ?",
+                rusqlite::params![exit.message(), serde_json::to_string(&metadata).unwrap(), download_id],
+            );
+        }
+        
         // For completed downloads, update status but keep in downloads table for history
         // The history table is separate and tracks completed downloads
         // We keep completed downloads in the downloads table with "completed" status
@@ -579,11 +1193,392 @@ pub async fn monitor_download_process_with_streams(
         }
     }
     
-    let _ = app.emit("download-complete", serde_json::json!({
+    let mut event = serde_json::json!({
         "downloadId": download_id,
         "download_id": download_id,
         "success": success,
+    });
+    if let Some(algorithm) = verified_with {
+        event["checksumAlgorithm"] = serde_json::json!(algorithm.as_str());
+    }
+    if !success {
+        event["error"] = serde_json::json!(exit.message());
+        event["errorKind"] = serde_json::json!(exit.kind());
+        event["errorCode"] = serde_json::json!(exit.error_code());
+        event["retryable"] = serde_json::json!(exit.is_retryable());
+    }
+    let _ = app.emit("download-complete", event);
+}
+
+/// The URLs an HTTP download can be fetched from: its original source followed by its mirrors
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorState {
+    pub urls: Vec<String>,
+    /// Index into `urls` of the one currently in use
+    pub current: usize,
+}
+
+impl MirrorState {
+    /// Read the state kept in metadata (`mirrors`, `mirrorIndex` and, after a switch, `originalSource`)
+    pub fn from_metadata(source: &str, metadata: &Value) -> Self {
+        let original = metadata.get("originalSource").and_then(|v| v.as_str()).unwrap_or(source);
+        let mut urls = vec![original.to_string()];
+        if let Some(mirrors) = metadata.get("mirrors").and_then(|v| v.as_array()) {
+            for mirror in mirrors.iter().filter_map(|m| m.as_str()) {
+                if !urls.iter().any(|u| u == mirror) {
+                    urls.push(mirror.to_string());
+                }
+            }
+        }
+        let current = metadata.get("mirrorIndex")
+            .and_then(|v| v.as_u64())
+            .map(|i| (i as usize).min(urls.len() - 1))
+            .unwrap_or(0);
+        MirrorState { urls, current }
+    }
+    
+    /// Move on to the next URL; None once every mirror has been tried
+    pub fn advance(&mut self) -> Option<&str> {
+        if self.current + 1 >= self.urls.len() {
+            return None;
+        }
+        self.current += 1;
+        Some(&self.urls[self.current])
+    }
+    
+    /// Mirrors not tried yet
+    pub fn remaining(&self) -> usize {
+        self.urls.len() - 1 - self.current
+    }
+}
+
+/// Point a failed HTTP download at its next mirror and start it again under the same id and output
+/// Returns false when there's no mirror left, so the caller records the error.
+/// Not async: the relaunch is spawned, as awaiting it here would make the monitor's future contain itself.
+fn switch_to_next_mirror(app: &AppHandle, download_id: &str) -> bool {
+    use crate::logger;
+    
+    let (source, download_type, metadata_str): (String, String, Option<String>) = match database::get_connection()
+        .ok()
+        .and_then(|conn| conn.query_row(
+            "SELECT source, type, metadata FROM downloads WHERE id = ?1",
+            [download_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).ok())
+    {
+        Some(row) => row,
+        None => return false,
+    };
+    if download_type != "http" {
+        return false;
+    }
+    
+    let mut metadata: Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    let mut mirrors = MirrorState::from_metadata(&source, &metadata);
+    let next = match mirrors.advance() {
+        Some(next) => next.to_string(),
+        None => return false,
+    };
+    
+    metadata["originalSource"] = serde_json::json!(mirrors.urls[0]);
+    metadata["mirrorIndex"] = serde_json::json!(mirrors.current);
+    // ETags differ between servers, so the first mirror's validators would refuse the resume
+    metadata["validators"] = Value::Null;
+    if let Ok(conn) = database::get_connection() {
+        let _ = conn.execute(
+            "UPDATE downloads SET source = ?, error = NULL, metadata = ? WHERE id = ?",
+            rusqlite::params![next, serde_json::to_string(&metadata).unwrap(), download_id],
+        );
+    }
+    
+    logger::log_warning("monitor_download", &format!(
+        "[{}] Failed on {}, switching to mirror {} ({} left)",
+        download_id, source, next, mirrors.remaining()
+    ));
+    let _ = app.emit("download-mirror-switch", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "from": source,
+        "to": next,
+        "mirrorIndex": mirrors.current,
+        "remaining": mirrors.remaining(),
     }));
+    
+    let app = app.clone();
+    let download_id = download_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::commands::resume_download_internal(download_id.clone(), app.clone()).await {
+            logger::log_error("monitor_download", &format!("[{}] Failed to start mirror {}: {}", download_id, next, e));
+            if let Ok(conn) = database::get_connection() {
+                let _ = conn.execute(
+                    "UPDATE downloads SET status = 'error', error = ? WHERE id = ?",
+                    rusqlite::params![e, download_id],
+                );
+            }
+            let _ = app.emit("download-complete", serde_json::json!({
+                "downloadId": download_id,
+                "download_id": download_id,
+                "success": false,
+                "error": e,
+            }));
+            crate::queue::on_slot_freed(app);
+        }
+    });
+    true
+}
+
+/// Move a finished torrent's data from its working folder into `completeDir` and point `output` there
+/// Returns the new location, or None when the download has no completeDir.
+fn move_to_complete_dir(download_id: &str) -> Result<Option<std::path::PathBuf>, String> {
+    use crate::utils;
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let (output, metadata_str): (String, Option<String>) = conn.query_row(
+        "SELECT output, metadata FROM downloads WHERE id = ?1",
+        [download_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).map_err(|e| format!("Download not found: {}", e))?;
+    
+    let metadata: Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let complete_dir = match metadata.get("completeDir").and_then(|v| v.as_str()) {
+        Some(dir) => std::path::PathBuf::from(utils::expand_path(dir)),
+        None => return Ok(None),
+    };
+    
+    // The working folder may hold other downloads, so only the torrent's own folder/file moves
+    let name = utils::torrent_name(&metadata)
+        .ok_or_else(|| "Torrent name unknown, leaving data in place".to_string())?;
+    let src = std::path::PathBuf::from(utils::expand_path(&output)).join(&name);
+    if !src.exists() {
+        return Err(format!("Torrent data not found at {}", src.display()));
+    }
+    
+    let dest = utils::unique_path_in(&complete_dir, &name);
+    utils::move_path(&src, &dest)?;
+    
+    // Like the wrapper's output, `output` is the folder that contains the torrent's data
+    let new_output = dest.parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| complete_dir.to_string_lossy().to_string());
+    if dest.file_name().map(|n| n.to_string_lossy() != name).unwrap_or(false) {
+        // Renamed to avoid a collision - keep the metadata in sync so later lookups find it
+        let mut metadata = metadata;
+        metadata["name"] = serde_json::json!(dest.file_name().unwrap().to_string_lossy());
+        let _ = conn.execute(
+            "UPDATE downloads SET metadata = ? WHERE id = ?",
+            rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+        );
+    }
+    conn.execute(
+        "UPDATE downloads SET output = ? WHERE id = ?",
+        rusqlite::params![new_output, download_id],
+    ).map_err(|e| format!("Failed to update output: {}", e))?;
+    
+    Ok(Some(dest))
+}
+
+/// Check a finished file against the md5/sha1/sha256 in `options`
+/// Returns the algorithm used and whether it matched, or None when no hash was given.
+/// A sha256 was already checked by the wrapper (a mismatch exits with ChecksumMismatch), so it isn't hashed again.
+pub fn verify_file_checksum(path: &std::path::Path, options: &Value) -> Result<Option<(HashAlgorithm, bool)>, String> {
+    let (algorithm, expected) = match checksum::expected_hash(options) {
+        Some(expected) => expected,
+        None => return Ok(None),
+    };
+    if algorithm == HashAlgorithm::Sha256 {
+        return Ok(Some((algorithm, true)));
+    }
+    let computed = checksum::hash_file(path, algorithm, &std::sync::atomic::AtomicBool::new(false))?;
+    Ok(Some((algorithm, computed == expected)))
+}
+
+/// Verify a finished HTTP download against the checksum in its options
+fn verify_completed_checksum(download_id: &str) -> Result<Option<(HashAlgorithm, bool)>, String> {
+    use crate::utils;
+    
+    let conn = database::get_connection()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let (output, download_type, metadata_str): (String, String, Option<String>) = conn.query_row(
+        "SELECT output, type, metadata FROM downloads WHERE id = ?1",
+        [download_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).map_err(|e| format!("Download not found: {}", e))?;
+    
+    // Torrents are checked piece by piece by the wrapper; per-file hashes go through verify_download_checksum
+    if download_type != "http" {
+        return Ok(None);
+    }
+    let metadata: Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let options = metadata.get("options").cloned().unwrap_or_default();
+    verify_file_checksum(std::path::Path::new(&utils::expand_path(&output)), &options)
+}
+
+/// Open a finished download if it asked for it, `allowAutoOpen` is on and the file type is allowlisted
+fn open_on_complete(download_id: &str) {
+    use crate::{logger, utils};
+    
+    let allow_auto_open = database::get_setting("allowAutoOpen")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !allow_auto_open {
+        return;
+    }
+    
+    let row = database::get_connection().ok().and_then(|conn| conn.query_row(
+        "SELECT output, type, metadata FROM downloads WHERE id = ?1",
+        [download_id],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?)),
+    ).ok());
+    let (output, download_type, metadata_str) = match row {
+        Some(row) => row,
+        None => return,
+    };
+    
+    let metadata: Value = metadata_str
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    if !metadata.get("openOnComplete").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return;
+    }
+    
+    // Torrents land in <output>/<name>; only single files are opened
+    let mut path = std::path::PathBuf::from(utils::expand_path(&output));
+    if download_type == "torrent" || download_type == "magnet" {
+        match utils::torrent_name(&metadata) {
+            Some(name) => path = path.join(name),
+            None => return,
+        }
+    }
+    
+    if !utils::is_safe_to_auto_open(&path) {
+        logger::log_info("monitor_download", &format!("[{}] Not auto-opening {} (not an allowed file type)", download_id, path.display()));
+        return;
+    }
+    
+    if let Err(e) = utils::open_path(&path.to_string_lossy()) {
+        logger::log_warning("monitor_download", &format!("[{}] {}", download_id, e));
+    }
+}
+
+/// Copy a completed download into download_history (no-op if it's already there)
+pub fn record_in_history(download_id: &str) {
+    if let Ok(conn) = database::get_connection() {
+        // Get download info from database
+        if let Ok((source, output, download_type, downloaded, total, metadata, notes, category)) = conn.query_row(
+            "SELECT source, output, type, downloaded, total, metadata, notes, category FROM downloads WHERE id = ?1",
+            [download_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?, // source
+                    row.get::<_, String>(1)?, // output
+                    row.get::<_, String>(2)?, // type
+                    row.get::<_, i64>(3)?,     // downloaded
+                    row.get::<_, i64>(4)?,     // total
+                    row.get::<_, Option<String>>(5)?, // metadata
+                    row.get::<_, Option<String>>(6)?, // notes
+                    row.get::<_, Option<String>>(7)?, // category
+                ))
+            },
+        ) {
+            // Use total if available, otherwise use downloaded
+            let file_size = if total > 0 { total } else { downloaded };
+            
+            // Check if already in history (avoid duplicates)
+            let exists = conn.query_row(
+                "SELECT COUNT(*) FROM download_history WHERE id = ?1",
+                [download_id],
+                |row| row.get::<_, i64>(0),
+            ).unwrap_or(0) > 0;
+            
+            if !exists {
+                // Insert into download_history
+                let completed_at = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as i64;
+                
+                let _ = conn.execute(
+                    "INSERT INTO download_history (id, source, output, type, size, completed_at, metadata, notes, category) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    rusqlite::params![
+                        download_id,
+                        source,
+                        output,
+                        download_type,
+                        file_size,
+                        completed_at,
+                        metadata.unwrap_or_default(),
+                        notes,
+                        category,
+                    ],
+                );
+            }
+        }
+    }
+}
+
+/// Bytes in a kilobyte when converting iris's MB/s figures (`speedUnitBase` setting)
+pub const DEFAULT_SPEED_UNIT_BASE: u64 = 1024;
+
+/// How iris's MB/s figures become the bytes/s the rest of the app uses
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SpeedScale {
+    /// 1000 (SI) or 1024 (binary)
+    unit_base: f64,
+    /// Correction factor applied to every measured speed
+    calibration: f64,
+}
+
+impl SpeedScale {
+    /// Read `speedUnitBase` and `speedTestCalibration`, ignoring nonsensical values
+    fn from_settings() -> Self {
+        let unit_base = match database::get_setting("speedUnitBase").and_then(|v| v.as_u64()) {
+            Some(1000) => 1000.0,
+            _ => DEFAULT_SPEED_UNIT_BASE as f64,
+        };
+        let calibration = database::get_setting("speedTestCalibration")
+            .and_then(|v| v.as_f64())
+            .filter(|c| c.is_finite() && *c > 0.0)
+            .unwrap_or(1.0);
+        SpeedScale { unit_base, calibration }
+    }
+    
+    fn bytes_per_sec(&self, mbps: f64) -> f64 {
+        mbps * self.unit_base * self.unit_base * self.calibration
+    }
+}
+
+/// Speeds from an iris result in bytes/s, plus the raw iris figures
+struct IrisSpeeds {
+    download_bytes_per_sec: f64,
+    upload_bytes_per_sec: f64,
+    ping_ms: f64,
+    raw: serde_json::Value,
+}
+
+fn convert_iris_result(iris_json: &serde_json::Value, scale: SpeedScale) -> IrisSpeeds {
+    let download_mbps = iris_json.get("download_mbps").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let upload_mbps = iris_json.get("upload_mbps").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let ping_ms = iris_json.get("ping_ms").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    
+    IrisSpeeds {
+        download_bytes_per_sec: scale.bytes_per_sec(download_mbps),
+        upload_bytes_per_sec: scale.bytes_per_sec(upload_mbps),
+        ping_ms,
+        raw: serde_json::json!({
+            "download_mbps": download_mbps,
+            "upload_mbps": upload_mbps,
+            "ping_ms": ping_ms,
+            "unitBase": scale.unit_base,
+            "calibration": scale.calibration,
+        }),
+    }
 }
 
 /// Monitor speed test process
@@ -591,7 +1586,34 @@ pub async fn monitor_speed_test_process(
     app: AppHandle,
     test_id: String,
 ) {
+    watch_speed_test_process(test_id, move |event, payload| {
+        let _ = app.emit(event, payload);
+    }).await;
+}
+
+/// Mark an iris speed test as cancelled and kill it; its monitor then reports the cancellation
+pub async fn cancel_speed_test_process(test_id: &str) -> Result<(), String> {
+    use crate::commands::SPEED_TEST_PROCESSES;
+    use crate::speed_test;
+    
+    let mut processes = SPEED_TEST_PROCESSES.lock().await;
+    if let Some(child) = processes.get_mut(test_id) {
+        speed_test::mark_cancelled(test_id);
+        child.start_kill()
+            .map_err(|e| format!("Failed to kill process: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Wait for an iris process to finish and report its result through `emit`
+/// A test stopped by `cancel_speed_test_process` completes with `cancelled: true` instead
+/// of an error, and without a result for the frontend to save.
+async fn watch_speed_test_process<F>(test_id: String, emit: F)
+where
+    F: Fn(&str, serde_json::Value),
+{
     use crate::commands::SPEED_TEST_PROCESSES;
+    use crate::speed_test;
     use tokio::io::AsyncReadExt;
     
     // Get stdout/stderr from the stored process
@@ -604,196 +1626,479 @@ pub async fn monitor_speed_test_process(
         }
     };
     
-    tokio::spawn(async move {
-        // Read stderr for errors
-        let mut stderr_buf = String::new();
-        if let Some(mut stderr) = stderr {
-            let _ = stderr.read_to_string(&mut stderr_buf).await;
-            if !stderr_buf.trim().is_empty() {
-                eprintln!("[speed-test {}] Stderr: {}", test_id, stderr_buf.trim());
-                // Don't emit error immediately - wait to see if stdout has valid JSON
-                // Some tools output warnings to stderr but still produce valid JSON
-            }
+    // Read stderr for errors
+    let mut stderr_buf = String::new();
+    if let Some(mut stderr) = stderr {
+        let _ = stderr.read_to_string(&mut stderr_buf).await;
+        if !stderr_buf.trim().is_empty() {
+            eprintln!("[speed-test {}] Stderr: {}", test_id, stderr_buf.trim());
+            // Don't emit error immediately - wait to see if stdout has valid JSON
+            // Some tools output warnings to stderr but still produce valid JSON
         }
-        
-        // Read all stdout - iris outputs a single JSON object
-        let mut stdout_buf = String::new();
-        if let Some(mut stdout) = stdout {
-            let _ = stdout.read_to_string(&mut stdout_buf).await;
+    }
+    
+    // Read all stdout - iris outputs a single JSON object
+    let mut stdout_buf = String::new();
+    if let Some(mut stdout) = stdout {
+        let _ = stdout.read_to_string(&mut stdout_buf).await;
+    }
+    
+    // Wait for process to complete first
+    let status = {
+        let mut processes = SPEED_TEST_PROCESSES.lock().await;
+        if let Some(mut child) = processes.remove(&test_id) {
+            drop(processes);
+            child.wait().await
+        } else {
+            speed_test::take_cancelled(&test_id);
+            return;
         }
-        
-        // Wait for process to complete first
-        let status = {
-            let mut processes = SPEED_TEST_PROCESSES.lock().await;
-            if let Some(mut child) = processes.remove(&test_id) {
-                drop(processes);
-                child.wait().await
-            } else {
-                return;
-            }
+    };
+    
+    if speed_test::take_cancelled(&test_id) {
+        emit("speed-test-complete", serde_json::json!({
+            "testId": test_id,
+            "code": status.ok().and_then(|s| s.code()),
+            "cancelled": true,
+        }));
+        return;
+    }
+    
+    let success = status.as_ref().map(|s| s.success()).unwrap_or(false);
+    
+    if !success {
+        let error_msg = if !stderr_buf.trim().is_empty() {
+            format!("Speed test process failed: {}", stderr_buf.trim())
+        } else {
+            "Speed test process failed".to_string()
         };
+        emit("speed-test-error", serde_json::json!({
+            "testId": test_id,
+            "error": error_msg,
+        }));
+        emit("speed-test-complete", serde_json::json!({
+            "testId": test_id,
+            "code": status.map(|s| s.code().unwrap_or(1)).unwrap_or(1),
+        }));
+        return;
+    }
+    
+    // Parse iris JSON output
+    // Iris format: {"timestamp": "...", "download_mbps": 100.5, "upload_mbps": 50.2, "ping_ms": 25.3, ...}
+    let trimmed_output = stdout_buf.trim();
+    
+    // Check if output is empty
+    if trimmed_output.is_empty() {
+        eprintln!("[speed-test {}] Iris output is empty", test_id);
+        emit("speed-test-error", serde_json::json!({
+            "testId": test_id,
+            "error": "Speed test produced no output. The test may have failed or timed out.",
+        }));
+        emit("speed-test-complete", serde_json::json!({
+            "testId": test_id,
+            "code": 1,
+        }));
+        return;
+    }
+    
+    // Try to extract JSON from output (iris might output other text before/after JSON)
+    let json_start = trimmed_output.find('{');
+    let json_end = trimmed_output.rfind('}');
+    
+    let json_str = if let (Some(start), Some(end)) = (json_start, json_end) {
+        &trimmed_output[start..=end]
+    } else {
+        trimmed_output
+    };
+    
+    let iris_result: Result<serde_json::Value, _> = serde_json::from_str(json_str);
+    
+    if let Ok(iris_json) = iris_result {
+        // Convert iris format (MB/s) to ACCELARA format (bytes/s)
+        let speeds = convert_iris_result(&iris_json, SpeedScale::from_settings());
+        let download_bytes_per_sec = speeds.download_bytes_per_sec;
+        let upload_bytes_per_sec = speeds.upload_bytes_per_sec;
+        let ping_ms_int = speeds.ping_ms as i64;
         
-        let success = status.as_ref().map(|s| s.success()).unwrap_or(false);
+        // Build latency object - frontend expects google_ping (snake_case)
+        let latency = if ping_ms_int > 0 {
+            serde_json::json!({
+                "average": ping_ms_int,
+                "min": ping_ms_int,
+                "max": ping_ms_int,
+                "google_ping": ping_ms_int,
+                "googlePing": ping_ms_int, // Also include camelCase for compatibility
+            })
+        } else {
+            serde_json::Value::Null
+        };
         
-        if !success {
-            let error_msg = if !stderr_buf.trim().is_empty() {
-                format!("Speed test process failed: {}", stderr_buf.trim())
-            } else {
-                "Speed test process failed".to_string()
-            };
-            let _ = app.emit("speed-test-error", serde_json::json!({
-                "testId": test_id,
-                "error": error_msg,
-            }));
-            let _ = app.emit("speed-test-complete", serde_json::json!({
-                "testId": test_id,
-                "code": status.map(|s| s.code().unwrap_or(1)).unwrap_or(1),
-            }));
-            return;
-        }
+        // Build location object
+        let location = if let (Some(city), Some(country)) = (
+            iris_json.get("location").and_then(|l| l.get("city")).and_then(|v| v.as_str()),
+            iris_json.get("location").and_then(|l| l.get("country")).and_then(|v| v.as_str()),
+        ) {
+            serde_json::json!({
+                "city": city,
+                "country": country,
+            })
+        } else {
+            serde_json::Value::Null
+        };
         
-        // Parse iris JSON output
-        // Iris format: {"timestamp": "...", "download_mbps": 100.5, "upload_mbps": 50.2, "ping_ms": 25.3, ...}
-        let trimmed_output = stdout_buf.trim();
+        // Emit speed test update with results
+        // Frontend expects: download_speed, upload_speed, latency (with average/min/max/googlePing)
+        let result = serde_json::json!({
+            "type": "full",
+            "status": "completed",
+            "download_speed": download_bytes_per_sec,
+            "upload_speed": upload_bytes_per_sec,
+            "downloadSpeed": download_bytes_per_sec, // Also include camelCase for compatibility
+            "uploadSpeed": upload_bytes_per_sec,
+            "latency": latency,
+            "location": location,
+            "progress": 100.0,
+            "raw": speeds.raw,
+        });
         
-        // Check if output is empty
-        if trimmed_output.is_empty() {
-            eprintln!("[speed-test {}] Iris output is empty", test_id);
-            let _ = app.emit("speed-test-error", serde_json::json!({
-                "testId": test_id,
-                "error": "Speed test produced no output. The test may have failed or timed out.",
-            }));
-            let _ = app.emit("speed-test-complete", serde_json::json!({
-                "testId": test_id,
-                "code": 1,
+        // Emit updates for each test phase to match frontend expectations
+        // First latency
+        if let Some(_lat) = latency.as_object() {
+            emit("speed-test-update", serde_json::json!({
+                "type": "latency",
+                "latency": latency,
+                "progress": 33.0,
             }));
-            return;
         }
         
-        // Try to extract JSON from output (iris might output other text before/after JSON)
-        let json_start = trimmed_output.find('{');
-        let json_end = trimmed_output.rfind('}');
+        // Then download
+        emit("speed-test-update", serde_json::json!({
+            "type": "download",
+            "download_speed": download_bytes_per_sec,
+            "downloadSpeed": download_bytes_per_sec,
+            "progress": 66.0,
+        }));
         
-        let json_str = if let (Some(start), Some(end)) = (json_start, json_end) {
-            &trimmed_output[start..=end]
-        } else {
-            trimmed_output
-        };
+        // Then upload
+        emit("speed-test-update", serde_json::json!({
+            "type": "upload",
+            "upload_speed": upload_bytes_per_sec,
+            "uploadSpeed": upload_bytes_per_sec,
+            "progress": 100.0,
+        }));
         
-        let iris_result: Result<serde_json::Value, _> = serde_json::from_str(json_str);
+        // Final complete result
+        emit("speed-test-update", result.clone());
         
-        if let Ok(iris_json) = iris_result {
-            // Convert iris format to ACCELARA format
-            // Divide by 10 as per requirements, and convert MB/s to bytes/s
-            let download_mbps = iris_json.get("download_mbps").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            let upload_mbps = iris_json.get("upload_mbps").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            let ping_ms = iris_json.get("ping_ms").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            
-            // Convert MB/s to bytes/s, then divide by 10
-            let download_bytes_per_sec = (download_mbps * 1024.0 * 1024.0) / 10.0;
-            let upload_bytes_per_sec = (upload_mbps * 1024.0 * 1024.0) / 10.0;
-            let ping_ms_int = ping_ms as i64;
-            
-            // Build latency object - frontend expects google_ping (snake_case)
-            let latency = if ping_ms_int > 0 {
-                serde_json::json!({
-                    "average": ping_ms_int,
-                    "min": ping_ms_int,
-                    "max": ping_ms_int,
-                    "google_ping": ping_ms_int,
-                    "googlePing": ping_ms_int, // Also include camelCase for compatibility
-                })
-            } else {
-                serde_json::Value::Null
-            };
-            
-            // Build location object
-            let location = if let (Some(city), Some(country)) = (
-                iris_json.get("location").and_then(|l| l.get("city")).and_then(|v| v.as_str()),
-                iris_json.get("location").and_then(|l| l.get("country")).and_then(|v| v.as_str()),
-            ) {
-                serde_json::json!({
-                    "city": city,
-                    "country": country,
-                })
+        // Also emit completion event
+        emit("speed-test-complete", serde_json::json!({
+            "testId": test_id,
+            "code": 0,
+            "result": result,
+        }));
+    } else {
+        // Failed to parse JSON
+        eprintln!("[speed-test {}] Failed to parse iris output as JSON", test_id);
+        eprintln!("[speed-test {}] Raw stdout (first 500 chars): {}", test_id, 
+            if trimmed_output.len() > 500 { 
+                format!("{}...", &trimmed_output[..500])
             } else {
-                serde_json::Value::Null
-            };
-            
-            // Emit speed test update with results
-            // Frontend expects: download_speed, upload_speed, latency (with average/min/max/googlePing)
-            let result = serde_json::json!({
-                "type": "full",
-                "status": "completed",
-                "download_speed": download_bytes_per_sec,
-                "upload_speed": upload_bytes_per_sec,
-                "downloadSpeed": download_bytes_per_sec, // Also include camelCase for compatibility
-                "uploadSpeed": upload_bytes_per_sec,
-                "latency": latency,
-                "location": location,
-                "progress": 100.0,
+                trimmed_output.to_string()
             });
-            
-            // Emit updates for each test phase to match frontend expectations
-            // First latency
-            if let Some(_lat) = latency.as_object() {
-                let _ = app.emit("speed-test-update", serde_json::json!({
-                    "type": "latency",
-                    "latency": latency,
-                    "progress": 33.0,
-                }));
-            }
-            
-            // Then download
-            let _ = app.emit("speed-test-update", serde_json::json!({
-                "type": "download",
-                "download_speed": download_bytes_per_sec,
-                "downloadSpeed": download_bytes_per_sec,
-                "progress": 66.0,
-            }));
-            
-            // Then upload
-            let _ = app.emit("speed-test-update", serde_json::json!({
-                "type": "upload",
-                "upload_speed": upload_bytes_per_sec,
-                "uploadSpeed": upload_bytes_per_sec,
-                "progress": 100.0,
-            }));
-            
-            // Final complete result
-            let _ = app.emit("speed-test-update", result.clone());
-            
-            // Also emit completion event
-            let _ = app.emit("speed-test-complete", serde_json::json!({
-                "testId": test_id,
-                "code": 0,
-                "result": result,
-            }));
-        } else {
-            // Failed to parse JSON
-            eprintln!("[speed-test {}] Failed to parse iris output as JSON", test_id);
-            eprintln!("[speed-test {}] Raw stdout (first 500 chars): {}", test_id, 
-                if trimmed_output.len() > 500 { 
-                    format!("{}...", &trimmed_output[..500])
+        let parse_error = iris_result.err()
+            .map(|e| format!("JSON parse error: {}", e))
+            .unwrap_or_else(|| "Unknown parse error".to_string());
+        emit("speed-test-error", serde_json::json!({
+            "testId": test_id,
+            "error": format!("Failed to parse speed test results. {}. Output: {}", parse_error,
+                if trimmed_output.len() > 200 {
+                    format!("{}...", &trimmed_output[..200])
                 } else {
                     trimmed_output.to_string()
-                });
-            let parse_error = iris_result.err()
-                .map(|e| format!("JSON parse error: {}", e))
-                .unwrap_or_else(|| "Unknown parse error".to_string());
-            let _ = app.emit("speed-test-error", serde_json::json!({
-                "testId": test_id,
-                "error": format!("Failed to parse speed test results. {}. Output: {}", parse_error,
-                    if trimmed_output.len() > 200 {
-                        format!("{}...", &trimmed_output[..200])
-                    } else {
-                        trimmed_output.to_string()
-                    }),
-            }));
-            let _ = app.emit("speed-test-complete", serde_json::json!({
-                "testId": test_id,
-                "code": 1,
-            }));
+                }),
+        }));
+        emit("speed-test-complete", serde_json::json!({
+            "testId": test_id,
+            "code": 1,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smoothed_speed_and_eta() {
+        let mut estimator = SpeedEstimator::new();
+        let start = Instant::now();
+        assert_eq!(estimator.update(0, start), None);
+        
+        // Steady 1000 B/s
+        for i in 1..=5 {
+            estimator.update(i * 1000, start + Duration::from_secs(i as u64));
         }
-    });
+        let speed = estimator.average.unwrap();
+        assert!((speed - 1000.0).abs() < 1.0);
+        assert_eq!(estimator.eta_seconds(5000, 15000), Some(10));
+    }
+
+    #[test]
+    fn test_eta_unknown_size_or_stalled() {
+        let mut estimator = SpeedEstimator::new();
+        let start = Instant::now();
+        estimator.update(1000, start);
+        estimator.update(1000, start + Duration::from_secs(5));
+        // Not moving: no ETA rather than infinity
+        assert_eq!(estimator.eta_seconds(1000, 5000), None);
+        
+        estimator.update(6000, start + Duration::from_secs(10));
+        // Unknown total
+        assert_eq!(estimator.eta_seconds(6000, 0), None);
+    }
+
+    #[test]
+    fn test_reset_when_counter_goes_backwards() {
+        let mut estimator = SpeedEstimator::new();
+        let start = Instant::now();
+        estimator.update(0, start);
+        estimator.update(10_000, start + Duration::from_secs(1));
+        assert_eq!(estimator.update(0, start + Duration::from_secs(2)), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stopped_speed_test_reports_cancellation_not_error() {
+        use crate::commands::SPEED_TEST_PROCESSES;
+        use std::process::Stdio;
+        
+        let test_id = "test_stop_immediately".to_string();
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        SPEED_TEST_PROCESSES.lock().await.insert(test_id.clone(), child);
+        
+        let events: Arc<std::sync::Mutex<Vec<(String, Value)>>> = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let monitor = tokio::spawn(watch_speed_test_process(test_id.clone(), move |event, payload| {
+            recorded.lock().unwrap().push((event.to_string(), payload));
+        }));
+        
+        cancel_speed_test_process(&test_id).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(10), monitor).await.unwrap().unwrap();
+        
+        let events = events.lock().unwrap();
+        assert!(!events.iter().any(|(event, _)| event == "speed-test-error"), "{:?}", events);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "speed-test-complete");
+        assert_eq!(events[0].1["cancelled"], true);
+        assert!(events[0].1.get("result").is_none());
+        assert!(!SPEED_TEST_PROCESSES.lock().await.contains_key(&test_id));
+    }
+
+    #[test]
+    fn test_iris_result_conversion() {
+        let iris = serde_json::json!({
+            "timestamp": "2024-01-01T00:00:00Z",
+            "download_mbps": 12.5,
+            "upload_mbps": 2.0,
+            "ping_ms": 23.7,
+        });
+        
+        let binary = convert_iris_result(&iris, SpeedScale { unit_base: 1024.0, calibration: 1.0 });
+        assert_eq!(binary.download_bytes_per_sec, 12.5 * 1024.0 * 1024.0);
+        assert_eq!(binary.upload_bytes_per_sec, 2.0 * 1024.0 * 1024.0);
+        assert_eq!(binary.ping_ms, 23.7);
+        assert_eq!(binary.raw["download_mbps"], 12.5);
+        assert_eq!(binary.raw["upload_mbps"], 2.0);
+        
+        let si = convert_iris_result(&iris, SpeedScale { unit_base: 1000.0, calibration: 1.0 });
+        assert_eq!(si.download_bytes_per_sec, 12_500_000.0);
+        assert_eq!(si.upload_bytes_per_sec, 2_000_000.0);
+        
+        let calibrated = convert_iris_result(&iris, SpeedScale { unit_base: 1000.0, calibration: 0.5 });
+        assert_eq!(calibrated.download_bytes_per_sec, 6_250_000.0);
+        // Raw figures are never scaled
+        assert_eq!(calibrated.raw["download_mbps"], 12.5);
+    }
+    
+    #[test]
+    fn test_iris_result_missing_fields() {
+        let speeds = convert_iris_result(&serde_json::json!({ "download_mbps": 1.0 }), SpeedScale { unit_base: 1000.0, calibration: 1.0 });
+        assert_eq!(speeds.download_bytes_per_sec, 1_000_000.0);
+        assert_eq!(speeds.upload_bytes_per_sec, 0.0);
+        assert_eq!(speeds.ping_ms, 0.0);
+    }
+    
+    #[tokio::test]
+    async fn test_flush_writes_every_cached_entry() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        database::init_schema(&conn).unwrap();
+        
+        let ids = ["flush-a", "flush-b", "flush-c"];
+        {
+            let mut cache = PROGRESS_CACHE.lock().await;
+            for (i, id) in ids.iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO downloads (id, source, output, type, status) VALUES (?1, 'http://x', '/tmp/x', 'http', 'downloading')",
+                    [id],
+                ).unwrap();
+                let n = i as i64 + 1;
+                cache.insert(id.to_string(), (n as f64 * 10.0, n * 1000, 10_000, n * 100, Instant::now()));
+            }
+        }
+        
+        let written = write_progress_entries(&conn, &cached_progress_entries().await);
+        assert_eq!(written, ids.len());
+        
+        for (i, id) in ids.iter().enumerate() {
+            let n = i as i64 + 1;
+            let (progress, downloaded, speed, updated_at): (f64, i64, i64, Option<i64>) = conn.query_row(
+                "SELECT progress, downloaded, speed, updated_at FROM downloads WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            ).unwrap();
+            assert_eq!(progress, n as f64 * 10.0);
+            assert_eq!(downloaded, n * 1000);
+            assert_eq!(speed, n * 100);
+            assert!(updated_at.is_some());
+        }
+        
+        let mut cache = PROGRESS_CACHE.lock().await;
+        for id in ids {
+            cache.remove(id);
+        }
+    }
+    
+    #[test]
+    fn test_mirror_state_advances_until_exhausted() {
+        let metadata = serde_json::json!({
+            "mirrors": ["https://b.example/f.iso", "https://a.example/f.iso", "https://c.example/f.iso"],
+            "mirrorIndex": 0,
+        });
+        let mut state = MirrorState::from_metadata("https://a.example/f.iso", &metadata);
+        // The original source isn't listed twice
+        assert_eq!(state.urls.len(), 3);
+        assert_eq!(state.remaining(), 2);
+        
+        assert_eq!(state.advance(), Some("https://b.example/f.iso"));
+        assert_eq!(state.advance(), Some("https://c.example/f.iso"));
+        assert_eq!(state.remaining(), 0);
+        assert_eq!(state.advance(), None);
+        assert_eq!(state.current, 2);
+    }
+    
+    #[test]
+    fn test_mirror_state_resumes_from_metadata() {
+        // After one switch `source` is the mirror and the original is kept separately
+        let metadata = serde_json::json!({
+            "originalSource": "https://a.example/f.iso",
+            "mirrors": ["https://b.example/f.iso", "https://c.example/f.iso"],
+            "mirrorIndex": 1,
+        });
+        let mut state = MirrorState::from_metadata("https://b.example/f.iso", &metadata);
+        assert_eq!(state.urls[0], "https://a.example/f.iso");
+        assert_eq!(state.advance(), Some("https://c.example/f.iso"));
+        
+        // No mirrors at all: nothing to switch to
+        let mut state = MirrorState::from_metadata("https://a.example/f.iso", &serde_json::json!({}));
+        assert_eq!(state.advance(), None);
+        
+        // An out-of-range index is clamped rather than panicking
+        let state = MirrorState::from_metadata("https://a.example/f.iso", &serde_json::json!({ "mirrors": ["https://b.example/f.iso"], "mirrorIndex": 9 }));
+        assert_eq!(state.current, 1);
+    }
+
+    #[test]
+    fn test_verify_file_checksum_per_algorithm() {
+        let path = std::env::temp_dir().join(format!("accelara-verify-{}.bin", std::process::id()));
+        std::fs::write(&path, b"hello world").unwrap();
+        
+        let md5 = serde_json::json!({ "md5": "5EB63BBBE01EEED093CB22BB8F5ACDC3" });
+        assert_eq!(verify_file_checksum(&path, &md5), Ok(Some((HashAlgorithm::Md5, true))));
+        
+        let sha1 = serde_json::json!({ "sha1": "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed" });
+        assert_eq!(verify_file_checksum(&path, &sha1), Ok(Some((HashAlgorithm::Sha1, true))));
+        
+        let wrong = serde_json::json!({ "sha1": "0000000000000000000000000000000000000000" });
+        assert_eq!(verify_file_checksum(&path, &wrong), Ok(Some((HashAlgorithm::Sha1, false))));
+        
+        // The wrapper already checked sha256 before exiting successfully
+        let sha256 = serde_json::json!({ "sha256": "ignored", "md5": "0" });
+        assert_eq!(verify_file_checksum(&path, &sha256), Ok(Some((HashAlgorithm::Sha256, true))));
+        
+        assert_eq!(verify_file_checksum(&path, &serde_json::json!({})), Ok(None));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_prune_only_stale_completed_downloads() {
+        database::init_test_database();
+        let now = 1_700_000_000;
+        let day = 24 * 60 * 60;
+        let conn = database::get_connection().unwrap();
+        let rows = [
+            ("prune-old", "completed", now - 40 * day),
+            ("prune-recent", "completed", now - 2 * day),
+            ("prune-seeding", "seeding", now - 40 * day),
+            ("prune-running", "completed", now - 40 * day),
+            ("prune-paused", "paused", now - 40 * day),
+        ];
+        for (id, status, updated_at) in rows {
+            conn.execute(
+                "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at, updated_at)
+                 VALUES (?1, 'http://x/a', '/tmp/a', 'http', ?2, '{}', ?3, ?3)",
+                rusqlite::params![id, status, updated_at],
+            ).unwrap();
+        }
+        // Touched recently, but the history says it finished long ago
+        conn.execute(
+            "INSERT OR REPLACE INTO downloads (id, source, output, type, status, metadata, started_at, updated_at)
+             VALUES ('prune-history', 'http://x/a', '/tmp/a', 'http', 'completed', '{}', ?1, ?1)",
+            [now],
+        ).unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO download_history (id, source, output, type, completed_at) VALUES ('prune-history', 'http://x/a', '/tmp/a', 'http', ?1)",
+            [now - 31 * day],
+        ).unwrap();
+        
+        let mut pruned = prune_completed_downloads(30, now, &["prune-running".to_string()]);
+        pruned.retain(|id| id.starts_with("prune-"));
+        pruned.sort();
+        assert_eq!(pruned, vec!["prune-history", "prune-old"]);
+        
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM downloads WHERE id LIKE 'prune-%'", [], |row| row.get(0)).unwrap();
+        assert_eq!(remaining, 4);
+        let in_history: i64 = conn.query_row("SELECT COUNT(*) FROM download_history WHERE id = 'prune-history'", [], |row| row.get(0)).unwrap();
+        assert_eq!(in_history, 1);
+    }
+    
+    #[test]
+    fn test_aggregate_speeds() {
+        let now = Instant::now();
+        let old = now - Duration::from_secs(30);
+        let progress: HashMap<String, (f64, i64, i64, i64, Instant)> = [
+            ("http-a", (10.0, 100, 1_000, 2_000, now)),
+            ("torrent-b", (50.0, 500, 1_000, 3_000, now - Duration::from_secs(2))),
+            // Stopped reporting a while ago, so no longer counted
+            ("paused-c", (70.0, 700, 1_000, 9_999, old)),
+        ].into_iter().map(|(id, entry)| (id.to_string(), entry)).collect();
+        let uploads: HashMap<String, (i64, Instant)> = [
+            ("torrent-b", (400, now)),
+            ("seeding-d", (600, now)),
+            ("paused-c", (9_999, old)),
+        ].into_iter().map(|(id, entry)| (id.to_string(), entry)).collect();
+        
+        let summed = aggregate_speeds(&progress, &uploads, now);
+        assert_eq!(summed["totalDownloadSpeed"], 5_000);
+        assert_eq!(summed["totalUploadSpeed"], 1_000);
+        assert_eq!(summed["activeCount"], 3);
+        
+        let idle = aggregate_speeds(&HashMap::new(), &HashMap::new(), now);
+        assert_eq!(idle, serde_json::json!({ "totalDownloadSpeed": 0, "totalUploadSpeed": 0, "activeCount": 0 }));
+    }
 }
@@ -0,0 +1,98 @@
+use crate::logger::LogLevel;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Lines kept per download; older ones are dropped as new ones arrive
+const MAX_LINES_PER_DOWNLOAD: usize = 200;
+
+lazy_static::lazy_static! {
+    // Recent wrapper stderr lines of each download, oldest first
+    static ref DOWNLOAD_LOGS: Mutex<HashMap<String, VecDeque<serde_json::Value>>> = Mutex::new(HashMap::new());
+}
+
+/// Level of a wrapper stderr line; the wrapper writes plain text, so this goes by its wording
+pub fn stderr_level(line: &str) -> LogLevel {
+    let lower = line.to_lowercase();
+    if ["error", "fatal", "panic", "failed"].iter().any(|word| lower.contains(word)) {
+        LogLevel::Error
+    } else if lower.contains("warn") {
+        LogLevel::Warn
+    } else {
+        LogLevel::Info
+    }
+}
+
+/// Keep a stderr line of a download and return it as a `download-log` event payload
+pub fn record(download_id: &str, line: &str) -> serde_json::Value {
+    let entry = serde_json::json!({
+        "downloadId": download_id,
+        "level": stderr_level(line).as_str(),
+        "line": line.trim_end(),
+        "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+    });
+
+    let mut logs = DOWNLOAD_LOGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let lines = logs.entry(download_id.to_string()).or_default();
+    if lines.len() >= MAX_LINES_PER_DOWNLOAD {
+        lines.pop_front();
+    }
+    lines.push_back(entry.clone());
+    entry
+}
+
+/// The kept lines of a download, oldest first
+pub fn lines(download_id: &str) -> Vec<serde_json::Value> {
+    DOWNLOAD_LOGS
+        .lock()
+        .map(|logs| logs.get(download_id).map(|lines| lines.iter().cloned().collect()).unwrap_or_default())
+        .unwrap_or_default()
+}
+
+/// Forget a download's lines once it's removed
+pub fn clear(download_id: &str) {
+    if let Ok(mut logs) = DOWNLOAD_LOGS.lock() {
+        logs.remove(download_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_read_back() {
+        let id = "download-log-test";
+        clear(id);
+
+        let event = record(id, "HTTP download error: connection reset\n");
+        assert_eq!(event["downloadId"], id);
+        assert_eq!(event["level"], "ERROR");
+        assert_eq!(event["line"], "HTTP download error: connection reset");
+        record(id, "Connecting to 3 peers");
+
+        let kept = lines(id);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[1]["level"], "INFO");
+        assert!(lines("download-log-other").is_empty());
+
+        // Only the newest MAX_LINES_PER_DOWNLOAD are kept
+        for i in 0..MAX_LINES_PER_DOWNLOAD + 5 {
+            record(id, &format!("line {}", i));
+        }
+        let kept = lines(id);
+        assert_eq!(kept.len(), MAX_LINES_PER_DOWNLOAD);
+        assert_eq!(kept[0]["line"], "line 5");
+        assert_eq!(kept[MAX_LINES_PER_DOWNLOAD - 1]["line"], format!("line {}", MAX_LINES_PER_DOWNLOAD + 4));
+
+        clear(id);
+        assert!(lines(id).is_empty());
+    }
+
+    #[test]
+    fn test_stderr_level() {
+        assert_eq!(stderr_level("Error: source is required").as_str(), "ERROR");
+        assert_eq!(stderr_level("warning: tracker unreachable").as_str(), "WARN");
+        assert_eq!(stderr_level("Received signal: interrupt. Shutting down gracefully...").as_str(), "INFO");
+    }
+}
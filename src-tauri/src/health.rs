@@ -0,0 +1,39 @@
+use crate::commands::DOWNLOAD_PROCESSES;
+use crate::{browser_server, database, utils};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Free space below this in the download folder counts as unhealthy
+const MIN_FREE_SPACE: u64 = 1024 * 1024 * 1024; // 1GB
+
+// The wrapper binary doesn't move while the app runs, so only search until it's been found once
+static BINARIES_FOUND: AtomicBool = AtomicBool::new(false);
+
+/// Cheap status probe for polling: no network requests and no directory walks
+pub async fn health_report() -> serde_json::Value {
+    let db_ok = database::get_connection()
+        .and_then(|conn| conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0)))
+        .is_ok();
+    
+    let binaries_ok = BINARIES_FOUND.load(Ordering::Relaxed) || {
+        let found = utils::find_go_binary().is_some();
+        BINARIES_FOUND.store(found, Ordering::Relaxed);
+        found
+    };
+    
+    let active_downloads = DOWNLOAD_PROCESSES.lock().await.len();
+    
+    let download_path = database::get_setting("defaultDownloadPath")
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "~/Downloads".to_string());
+    let available = utils::available_space(std::path::Path::new(&utils::expand_path(&download_path)));
+    let disk_space_ok = available.map(|bytes| bytes >= MIN_FREE_SPACE).unwrap_or(false);
+    
+    serde_json::json!({
+        "dbOk": db_ok,
+        "binariesOk": binaries_ok,
+        "browserServerOk": browser_server::is_running(),
+        "activeDownloads": active_downloads,
+        "diskSpaceOk": disk_space_ok,
+        "diskSpaceAvailable": available,
+    })
+}
@@ -10,8 +10,32 @@ mod browser_server;
 mod logger;
 mod updater;
 mod update_manager;
+mod power;
+mod workspace;
+mod rate_schedule;
+mod schedule;
+mod process_cleanup;
+mod paths;
+mod peer_budget;
+mod quiet_hours;
+mod notifications;
+mod speed_test;
+mod wrapper_exit;
+mod health;
+mod backup;
+mod queue;
+mod checksum;
+mod stats;
+mod search;
+mod categories;
+mod log_stream;
+mod bandwidth;
+mod tray;
+mod binaries;
+mod download_log;
+mod credentials;
 
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -55,6 +79,72 @@ pub fn run() {
             commands::download_update,
             commands::install_update,
             commands::restart_app,
+            commands::relocate_paths,
+            commands::scan_and_fix_paths,
+            commands::assemble_download,
+            commands::load_workspace,
+            commands::run_workspace,
+            commands::is_resumable,
+            commands::find_orphaned_processes,
+            commands::kill_orphaned_processes,
+            commands::get_app_paths,
+            commands::get_download_speed_history,
+            commands::restart_download_from_scratch,
+            commands::duplicate_download,
+            commands::query_downloads,
+            commands::ensure_download_folder,
+            commands::add_downloads_batch,
+            commands::pause_batch,
+            commands::resume_batch,
+            commands::remove_batch,
+            commands::get_torrent_peer_stats,
+            commands::export_torrent,
+            commands::get_magnet_link,
+            commands::ping_browser_server,
+            commands::set_download_notes,
+            commands::get_infohash,
+            commands::get_settings_with_sources,
+            commands::reset_setting,
+            commands::reset_all_settings,
+            commands::remove_downloads_by_status,
+            commands::start_custom_speed_test,
+            commands::check_for_updates_now,
+            commands::get_update_status,
+            commands::hide_window,
+            commands::get_release_notes_html,
+            commands::health_check,
+            commands::relocate_completed_downloads,
+            commands::preview_download,
+            commands::export_state,
+            commands::import_state,
+            commands::get_queue_state,
+            commands::verify_download_checksum,
+            commands::cancel_checksum_verification,
+            commands::set_torrent_file_selection,
+            commands::get_browser_server_status,
+            commands::get_browser_token,
+            commands::cancel_update_download,
+            commands::get_download_stats,
+            commands::search_downloads,
+            commands::set_download_category,
+            commands::get_categories,
+            commands::relocate_download,
+            commands::export_data,
+            commands::import_data,
+            commands::set_download_rate_limit,
+            commands::check_disk_space,
+            commands::pause_all_downloads,
+            commands::resume_all_downloads,
+            commands::get_download_disk_usage,
+            commands::start_log_streaming,
+            commands::stop_log_streaming,
+            commands::validate_source,
+            commands::open_file,
+            commands::schedule_download,
+            commands::get_binary_versions,
+            commands::refresh_binary_paths,
+            commands::get_download_logs,
+            commands::rename_download_output
         ])
         .setup(|app| {
             // Initialize logger
@@ -62,7 +152,34 @@ pub fn run() {
             logger::log_info("app", "ACCELARA starting up");
             
             // Initialize database
-            database::init().expect("Failed to initialize database");
+            match database::init() {
+                Ok(database::DatabaseInit::Opened) => {}
+                Ok(database::DatabaseInit::Recovered { backup, reason }) => {
+                    // Starting with an empty list beats not starting at all; the old file is kept for recovery
+                    let message = format!(
+                        "The download database was damaged ({}) and has been reset. The old file was saved as {}",
+                        reason,
+                        backup.display(),
+                    );
+                    logger::log_warning("app", &message);
+                    notifications::notify("ACCELARA database reset", &message);
+                    let app_handle = app.handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        // Give the frontend time to register its listeners
+                        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+                        let _ = app_handle.emit("startup-warning", serde_json::json!({
+                            "kind": "databaseRecovered",
+                            "message": message,
+                            "backupPath": backup.to_string_lossy(),
+                        }));
+                    });
+                }
+                Err(e) => {
+                    logger::log_error("app", &format!("Failed to initialize database: {}", e));
+                    return Err(format!("Failed to initialize database: {}", e).into());
+                }
+            }
+            logger::apply_settings();
             
             // Set up event listeners for downloads
             download::setup_download_handlers(app);
@@ -73,6 +190,12 @@ pub fn run() {
             // Start browser integration server for browser extensions
             browser_server::start_browser_server(app.handle().clone());
             
+            // Pause downloads before the OS sleeps and resume them on wake
+            power::setup_power_monitoring(app.handle().clone());
+            
+            // Tray icon with download status and quick actions, since closing only hides the window
+            tray::setup_tray(app.handle());
+            
             // Handle window close event - hide window instead of closing (daemon mode)
             // On macOS, this keeps the app running in the dock
             // Get the main window and set up close event handler
@@ -86,10 +209,28 @@ pub fn run() {
                     let app_handle_clone = app_handle.clone();
                     window.on_window_event(move |event| {
                         if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                            // Prevent closing - hide the window instead
+                            // Never let the window close itself - closeBehavior decides what happens
                             api.prevent_close();
-                            if let Some(w) = app_handle_clone.get_webview_window("main") {
-                                let _ = w.hide();
+                            let close_behavior = database::get_setting("closeBehavior")
+                                .and_then(|v| v.as_str().map(|s| s.to_string()))
+                                .unwrap_or_else(|| "minimizeToTray".to_string());
+                            match close_behavior.as_str() {
+                                "quit" => {
+                                    // Stop downloads cleanly and mark them paused before exiting
+                                    let app_handle = app_handle_clone.clone();
+                                    tauri::async_runtime::spawn(async move {
+                                        let _ = commands::quit_app(app_handle).await;
+                                    });
+                                }
+                                "ask" => {
+                                    // The UI answers with quit_app or hide_window
+                                    let _ = app_handle_clone.emit("confirm-close", serde_json::json!({}));
+                                }
+                                _ => {
+                                    if let Some(w) = app_handle_clone.get_webview_window("main") {
+                                        let _ = w.hide();
+                                    }
+                                }
                             }
                         }
                     });
@@ -129,14 +270,36 @@ pub fn run() {
             tauri::async_runtime::spawn(async move {
                 // Wait a bit for the app to fully initialize
                 tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+                // Clean up wrapper processes left behind by a crashed session first,
+                // so they don't fight the resumed downloads over the same files
+                let killed = process_cleanup::kill_orphaned_processes().await;
+                if !killed.is_empty() {
+                    logger::log_info("app", &format!("Killed {} orphaned wrapper process(es)", killed.len()));
+                }
                 commands::auto_resume_downloads(app_handle).await;
             });
             
+            // Keep torrent peer limits within the global connection budget
+            peer_budget::setup_peer_rebalancing(app.handle().clone());
+            
+            // Divide globalRateLimit between running downloads
+            bandwidth::setup_bandwidth_rebalancing(app.handle().clone());
+            
+            // Track quiet hours (mutes notifications, not downloads)
+            quiet_hours::setup_quiet_hours(app.handle().clone());
+            
             // Set up automatic update checking
             update_manager::setup_update_checking(app.handle().clone());
             
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            // Periodic saves run every few seconds; don't lose the progress since the last one
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                let flushed = tauri::async_runtime::block_on(download::flush_progress_cache());
+                logger::log_info("app", &format!("Saved progress of {} download(s) before exit", flushed));
+            }
+        });
 }
@@ -0,0 +1,177 @@
+use crate::logger;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often the log file is checked for new lines
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+lazy_static::lazy_static! {
+    // The one running watcher; every debug window listens to the same `log-line` events
+    static ref WATCHER: Mutex<Option<tokio::task::AbortHandle>> = Mutex::new(None);
+}
+
+/// Follows a log file from where it ended when the tail was created
+/// A file that shrinks (rotated to an archive) is read again from the start.
+struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    // Text after the last newline, waiting for the rest of its line
+    partial: String,
+}
+
+impl LogTail {
+    fn new(path: PathBuf) -> Self {
+        let offset = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        LogTail { path, offset, partial: String::new() }
+    }
+
+    /// Complete lines appended since the last poll
+    fn poll(&mut self) -> Vec<String> {
+        let len = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            // Between a rotation and the next write there's no file at all
+            Err(_) => {
+                self.offset = 0;
+                self.partial.clear();
+                return Vec::new();
+            }
+        };
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Vec::new();
+        }
+
+        let mut bytes = Vec::new();
+        let read = fs::File::open(&self.path).and_then(|mut file| {
+            file.seek(SeekFrom::Start(self.offset))?;
+            file.take(len - self.offset).read_to_end(&mut bytes)
+        });
+        if read.is_err() {
+            return Vec::new();
+        }
+        self.offset += bytes.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+
+        let mut lines: Vec<String> = self.partial.split('\n').map(|l| l.trim_end_matches('\r').to_string()).collect();
+        self.partial = lines.pop().unwrap_or_default();
+        lines.retain(|l| !l.is_empty());
+        lines
+    }
+}
+
+/// Payload of a `log-line` event; `level` is null for session headers and other unlevelled lines
+fn line_event(line: &str) -> serde_json::Value {
+    serde_json::json!({
+        "line": line,
+        "level": logger::line_level(line).map(|level| level.as_str()),
+    })
+}
+
+/// Poll the log file forever, handing each new line to `emit`
+async fn watch<F>(path: PathBuf, emit: F)
+where
+    F: Fn(serde_json::Value),
+{
+    let mut tail = LogTail::new(path);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        for line in tail.poll() {
+            emit(line_event(&line));
+        }
+    }
+}
+
+/// Start emitting `log-line` events for new log lines; false if a watcher is already running
+pub fn start(app: AppHandle) -> bool {
+    let mut watcher = WATCHER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if watcher.as_ref().map(|handle| !handle.is_finished()).unwrap_or(false) {
+        return false;
+    }
+
+    let task = tauri::async_runtime::spawn(watch(crate::paths::log_path(), move |event| {
+        let _ = app.emit("log-line", event);
+    }));
+    *watcher = Some(task.inner().abort_handle());
+    true
+}
+
+/// Stop the watcher started by `start`; false if none was running
+pub fn stop() -> bool {
+    let mut watcher = WATCHER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match watcher.take() {
+        Some(handle) => {
+            handle.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    fn append(path: &PathBuf, text: &str) {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_tail_reads_new_lines_and_follows_rotation() {
+        let path = std::env::temp_dir().join(format!("accelara-log-tail-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        append(&path, "old line\n");
+
+        let mut tail = LogTail::new(path.clone());
+        assert!(tail.poll().is_empty());
+
+        // A half-written line waits for its newline
+        append(&path, "[2026-01-01 10:00:00.000] [INFO] a: first\n[2026-01-01 10:00:00.000] [WARN] b: sec");
+        assert_eq!(tail.poll(), vec!["[2026-01-01 10:00:00.000] [INFO] a: first"]);
+        append(&path, "ond\n");
+        assert_eq!(tail.poll(), vec!["[2026-01-01 10:00:00.000] [WARN] b: second"]);
+
+        // Rotated away and recreated smaller
+        fs::remove_file(&path).unwrap();
+        assert!(tail.poll().is_empty());
+        append(&path, "fresh\n");
+        assert_eq!(tail.poll(), vec!["fresh"]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_watch_emits_appended_lines() {
+        let path = std::env::temp_dir().join(format!("accelara-log-watch-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        append(&path, "before the watcher\n");
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let task = tokio::spawn(watch(path.clone(), move |event| sink.lock().unwrap().push(event)));
+
+        // Let the watcher take its starting offset before appending
+        tokio::time::sleep(POLL_INTERVAL).await;
+        append(&path, "[2026-01-01 10:00:00.000] [ERROR] db: locked\n=== ACCELARA Log Session Started ===\n");
+        tokio::time::sleep(POLL_INTERVAL * 3).await;
+        task.abort();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["line"], "[2026-01-01 10:00:00.000] [ERROR] db: locked");
+        assert_eq!(events[0]["level"], "ERROR");
+        assert!(events[1]["level"].is_null());
+
+        let _ = fs::remove_file(&path);
+    }
+}
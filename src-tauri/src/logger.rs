@@ -1,9 +1,137 @@
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::sync::atomic::{AtomicU32, Ordering};
-use dirs::home_dir;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+// Serializes appends and rotation so a rotation can't cut off a line another thread is writing
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+// Appends since start-up; the size check runs every ROTATION_CHECK_INTERVAL writes
+static WRITE_COUNT: AtomicU32 = AtomicU32::new(0);
+const ROTATION_CHECK_INTERVAL: u32 = 100;
+
+/// Severity of a log message, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+    
+    /// Parse a level name as used in settings and log lines (case-insensitive)
+    pub fn parse(name: &str) -> Option<LogLevel> {
+        match name.trim().to_lowercase().as_str() {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+    
+    fn from_u8(value: u8) -> LogLevel {
+        match value {
+            0 => LogLevel::Debug,
+            1 => LogLevel::Info,
+            2 => LogLevel::Warn,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
+// Messages below this level are dropped (settings key `logLevel`)
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+// Write JSON lines instead of plain text (settings key `logFormat = "json"`)
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+/// Set the minimum level that gets logged
+pub fn set_min_level(level: LogLevel) {
+    MIN_LEVEL.store(level as u8, Ordering::SeqCst);
+}
+
+pub fn min_level() -> LogLevel {
+    LogLevel::from_u8(MIN_LEVEL.load(Ordering::SeqCst))
+}
+
+/// Switch between plain text and JSON-lines output
+pub fn set_json_format(json: bool) {
+    JSON_FORMAT.store(json, Ordering::SeqCst);
+}
+
+pub const DEFAULT_LOG_MAX_SIZE_MB: u64 = 10;
+pub const DEFAULT_LOG_KEEP_ARCHIVES: u32 = 3;
+
+// The log is rotated to `accelara.log.1` once it grows past this (settings key `logMaxSizeMb`)
+static MAX_LOG_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_LOG_MAX_SIZE_MB * 1024 * 1024);
+
+// Number of rotated archives kept, oldest deleted first (settings key `logKeepArchives`)
+static KEEP_ARCHIVES: AtomicU32 = AtomicU32::new(DEFAULT_LOG_KEEP_ARCHIVES);
+
+/// Apply the logging settings (call after the database is ready and when settings change)
+pub fn apply_settings() {
+    let level = crate::database::get_setting("logLevel")
+        .and_then(|v| v.as_str().and_then(LogLevel::parse))
+        .unwrap_or(LogLevel::Info);
+    let json = crate::database::get_setting("logFormat")
+        .and_then(|v| v.as_str().map(|f| f.eq_ignore_ascii_case("json")))
+        .unwrap_or(false);
+    let max_size_mb = crate::database::get_setting("logMaxSizeMb")
+        .and_then(|v| v.as_u64())
+        .filter(|mb| *mb > 0)
+        .unwrap_or(DEFAULT_LOG_MAX_SIZE_MB);
+    let keep_archives = crate::database::get_setting("logKeepArchives")
+        .and_then(|v| v.as_u64())
+        .map(|n| n.min(u32::MAX as u64) as u32)
+        .unwrap_or(DEFAULT_LOG_KEEP_ARCHIVES);
+    set_min_level(level);
+    set_json_format(json);
+    MAX_LOG_SIZE.store(max_size_mb * 1024 * 1024, Ordering::SeqCst);
+    KEEP_ARCHIVES.store(keep_archives, Ordering::SeqCst);
+}
+
+/// Whether a message at `level` passes the `min` filter
+fn is_enabled(level: LogLevel, min: LogLevel) -> bool {
+    level >= min
+}
+
+/// Format one log line (without the trailing newline)
+fn format_line(timestamp: &str, level: LogLevel, context: &str, message: &str, json: bool) -> String {
+    if json {
+        serde_json::json!({
+            "ts": timestamp,
+            "level": level.as_str(),
+            "context": context,
+            "message": message,
+        }).to_string()
+    } else {
+        format!("[{}] [{}] {}: {}", timestamp, level.as_str(), context, message)
+    }
+}
+
+/// Level of a line written by this logger, in either format; None for headers and foreign lines
+pub fn line_level(line: &str) -> Option<LogLevel> {
+    if line.starts_with('{') {
+        return serde_json::from_str::<serde_json::Value>(line).ok()
+            .and_then(|v| v.get("level").and_then(|l| l.as_str()).and_then(LogLevel::parse));
+    }
+    // "[<timestamp>] [LEVEL] context: message"
+    let rest = line.strip_prefix('[')?.split_once("] [")?.1;
+    LogLevel::parse(rest.split_once(']')?.0)
+}
 
 /// Initialize logging to a file in production
 pub fn init_logger() {
@@ -23,101 +151,216 @@ pub fn init_logger() {
 
 /// Get the log file path
 fn get_log_path() -> Option<PathBuf> {
-    if let Some(home) = home_dir() {
-        let log_dir = home.join(".accelara");
-        // Create directory if it doesn't exist
-        let _ = std::fs::create_dir_all(&log_dir);
-        Some(log_dir.join("accelara.log"))
-    } else {
-        None
-    }
+    Some(crate::paths::log_path())
 }
 
-/// Check and clean log file if it exceeds 10MB
-fn check_and_clean_logs() {
-    if let Some(log_path) = get_log_path() {
-        if log_path.exists() {
-            if let Ok(metadata) = fs::metadata(&log_path) {
-                const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024; // 10MB
-                if metadata.len() > MAX_LOG_SIZE {
-                    // Rotate: keep last 5MB of logs
-                    const KEEP_SIZE: u64 = 5 * 1024 * 1024; // 5MB
-                    
-                    // Read the file
-                    if let Ok(content) = fs::read_to_string(&log_path) {
-                        let total_size = content.len() as u64;
-                        if total_size > KEEP_SIZE {
-                            // Keep only the last portion
-                            let skip_bytes = (total_size - KEEP_SIZE) as usize;
-                            // Find the next newline to avoid cutting in the middle of a line
-                            let start_pos = if skip_bytes < content.len() {
-                                content[skip_bytes..]
-                                    .find('\n')
-                                    .map(|pos| skip_bytes + pos + 1)
-                                    .unwrap_or(skip_bytes)
-                            } else {
-                                skip_bytes
-                            };
-                            
-                            let kept_content = &content[start_pos..];
-                            
-                            // Write the kept content back to the file with a rotation header
-                            if let Ok(mut file) = fs::File::create(&log_path) {
-                                let _ = writeln!(file, "\n=== ACCELARA Log Session (Rotated) ===");
-                                let _ = writeln!(file, "Previous log file exceeded 10MB, kept last 5MB");
-                                let _ = writeln!(file, "Rotation time: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"));
-                                let _ = writeln!(file, "{}", kept_content);
-                                let _ = file.flush();
-                            }
-                        }
-                    }
-                }
-            }
+/// Path of the `n`th rotated archive, e.g. `accelara.log.2` (1 is the most recent)
+pub fn archive_path(log_path: &Path, n: u32) -> PathBuf {
+    let mut name = log_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", n));
+    log_path.with_file_name(name)
+}
+
+/// Rotate the log to numbered archives once it exceeds `max_size`, keeping at most `keep_archives`
+/// Callers must hold WRITE_LOCK.
+fn check_and_clean_logs(log_path: &Path, max_size: u64, keep_archives: u32) {
+    let size = match fs::metadata(log_path) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return,
+    };
+    if size <= max_size {
+        return;
+    }
+    
+    // Drop the oldest archive and anything left over from a higher `logKeepArchives`
+    let mut n = keep_archives.max(1);
+    while archive_path(log_path, n).exists() {
+        let _ = fs::remove_file(archive_path(log_path, n));
+        n += 1;
+    }
+    
+    if keep_archives == 0 {
+        let _ = fs::remove_file(log_path);
+        return;
+    }
+    
+    // accelara.log.(n-1) -> accelara.log.n, ..., accelara.log -> accelara.log.1
+    for n in (1..keep_archives).rev() {
+        let from = archive_path(log_path, n);
+        if from.exists() {
+            let _ = fs::rename(&from, archive_path(log_path, n + 1));
         }
     }
+    let _ = fs::rename(log_path, archive_path(log_path, 1));
 }
 
-/// Write a log message to file
-pub fn log_to_file(message: &str) {
+/// Write a log message at `level` to file, if it passes the configured minimum level
+pub fn log(level: LogLevel, context: &str, message: &str) {
+    if !is_enabled(level, min_level()) {
+        return;
+    }
+    
+    let timestamp = chrono::Local::now();
+    let json = JSON_FORMAT.load(Ordering::SeqCst);
+    let line = if json {
+        format_line(&timestamp.to_rfc3339(), level, context, message, true)
+    } else {
+        format_line(&timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string(), level, context, message, false)
+    };
+    
     if let Some(log_path) = get_log_path() {
-        // Check and clean logs before writing (only check periodically to avoid overhead)
-        // We'll check every 100 writes or so - use an atomic counter
-        static WRITE_COUNT: AtomicU32 = AtomicU32::new(0);
-        let count = WRITE_COUNT.fetch_add(1, Ordering::Relaxed);
-        if count % 100 == 0 {
-            check_and_clean_logs();
-        }
-        
-        if let Ok(mut file) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)
-        {
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-            let _ = writeln!(file, "[{}] {}", timestamp, message);
-            let _ = file.flush();
-        }
+        append_line(&log_path, &line);
     }
     // Also print to stderr (visible in console if available)
-    eprintln!("{}", message);
+    eprintln!("[{}] {}: {}", level.as_str(), context, message);
+}
+
+/// Append one line to the log file, rotating it first every so often
+fn append_line(log_path: &Path, line: &str) {
+    // A panic while logging elsewhere shouldn't stop all further logging
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    
+    // Check and clean logs before writing (only check periodically to avoid overhead)
+    let count = WRITE_COUNT.fetch_add(1, Ordering::Relaxed);
+    if count % ROTATION_CHECK_INTERVAL == 0 {
+        check_and_clean_logs(log_path, MAX_LOG_SIZE.load(Ordering::SeqCst), KEEP_ARCHIVES.load(Ordering::SeqCst));
+    }
+    
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+    {
+        // One write call per line so the line lands in the file whole
+        let _ = file.write_all(format!("{}\n", line).as_bytes());
+        let _ = file.flush();
+    }
 }
 
 /// Log an error with context
 pub fn log_error(context: &str, error: &str) {
-    let message = format!("[ERROR] {}: {}", context, error);
-    log_to_file(&message);
+    log(LogLevel::Error, context, error);
 }
 
 /// Log a warning with context
 #[allow(dead_code)]
 pub fn log_warning(context: &str, warning: &str) {
-    let message = format!("[WARN] {}: {}", context, warning);
-    log_to_file(&message);
+    log(LogLevel::Warn, context, warning);
 }
 
 /// Log info with context
 pub fn log_info(context: &str, info: &str) {
-    let message = format!("[INFO] {}: {}", context, info);
-    log_to_file(&message);
+    log(LogLevel::Info, context, info);
 }
 
+/// Log debugging detail with context (dropped unless `logLevel` is "debug")
+#[allow(dead_code)]
+pub fn log_debug(context: &str, detail: &str) {
+    log(LogLevel::Debug, context, detail);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_level_filtering_suppresses_lower_levels() {
+        assert!(!is_enabled(LogLevel::Debug, LogLevel::Info));
+        assert!(is_enabled(LogLevel::Info, LogLevel::Info));
+        assert!(is_enabled(LogLevel::Error, LogLevel::Info));
+        assert!(!is_enabled(LogLevel::Info, LogLevel::Warn));
+        assert!(!is_enabled(LogLevel::Warn, LogLevel::Error));
+        assert!(is_enabled(LogLevel::Debug, LogLevel::Debug));
+    }
+    
+    #[test]
+    fn test_level_parsing() {
+        assert_eq!(LogLevel::parse("WARNING"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse(" debug "), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::parse("verbose"), None);
+    }
+    
+    #[test]
+    fn test_line_level_in_both_formats() {
+        let text = format_line("2026-01-01 10:00:00.000", LogLevel::Warn, "updater", "slow [mirror]", false);
+        assert_eq!(text, "[2026-01-01 10:00:00.000] [WARN] updater: slow [mirror]");
+        assert_eq!(line_level(&text), Some(LogLevel::Warn));
+        
+        let json = format_line("2026-01-01T10:00:00+00:00", LogLevel::Error, "db", "locked", true);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["context"], "db");
+        assert_eq!(parsed["message"], "locked");
+        assert_eq!(line_level(&json), Some(LogLevel::Error));
+        
+        assert_eq!(line_level("=== ACCELARA Log Session Started ==="), None);
+    }
+    
+    #[test]
+    fn test_concurrent_writers_do_not_interleave() {
+        const THREADS: usize = 32;
+        const LINES: usize = 200;
+        
+        let path = std::env::temp_dir().join(format!("accelara-log-stress-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        
+        let handles: Vec<_> = (0..THREADS).map(|t| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for i in 0..LINES {
+                    let line = format_line("2026-01-01 10:00:00.000", LogLevel::Info, &format!("thread-{}", t), &format!("line {} {}", i, "x".repeat(200)), false);
+                    append_line(&path, &line);
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), THREADS * LINES);
+        for line in lines {
+            assert_eq!(line_level(line), Some(LogLevel::Info), "malformed line: {}", line);
+            assert!(line.ends_with(&"x".repeat(200)), "truncated line: {}", line);
+        }
+        
+        let _ = fs::remove_file(&path);
+    }
+    
+    #[test]
+    fn test_rotation_creates_and_prunes_archives() {
+        let dir = std::env::temp_dir().join(format!("accelara-log-rotate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("accelara.log");
+        let line = "x".repeat(99);
+        
+        // Each round writes past the 1KB cap and rotates
+        for round in 0..4 {
+            for _ in 0..20 {
+                append_line(&path, &format!("{} {}", round, line));
+            }
+            check_and_clean_logs(&path, 1024, 2);
+            assert!(!path.exists());
+        }
+        
+        // Only the two newest rounds survive, newest first
+        assert!(fs::read_to_string(archive_path(&path, 1)).unwrap().starts_with("3 "));
+        assert!(fs::read_to_string(archive_path(&path, 2)).unwrap().starts_with("2 "));
+        assert!(!archive_path(&path, 3).exists());
+        
+        // Under the cap nothing moves
+        append_line(&path, "small");
+        check_and_clean_logs(&path, 1024, 2);
+        assert!(path.exists());
+        
+        // Lowering the archive count prunes the extras on the next rotation
+        for _ in 0..20 {
+            append_line(&path, &line);
+        }
+        check_and_clean_logs(&path, 1024, 1);
+        assert!(archive_path(&path, 1).exists());
+        assert!(!archive_path(&path, 2).exists());
+        
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
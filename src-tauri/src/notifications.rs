@@ -0,0 +1,158 @@
+use crate::database;
+use crate::logger;
+use crate::quiet_hours;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    // Notifications held back during quiet hours, as (title, body)
+    static ref DEFERRED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+}
+
+/// Show a system notification, or hold it until quiet hours end
+/// Only the latest deferred notification per title is kept.
+pub fn notify(title: &str, body: &str) {
+    if quiet_hours::is_quiet_now() {
+        logger::log_info("notifications", &format!("Quiet hours, deferring notification: {}", title));
+        if let Ok(mut deferred) = DEFERRED.lock() {
+            deferred.retain(|(t, _)| t != title);
+            deferred.push((title.to_string(), body.to_string()));
+        }
+        return;
+    }
+
+    show_system_notification(title, body);
+}
+
+/// Show the notifications that were deferred during quiet hours
+pub fn flush_deferred() {
+    let pending: Vec<(String, String)> = match DEFERRED.lock() {
+        Ok(mut deferred) => deferred.drain(..).collect(),
+        Err(_) => return,
+    };
+    for (title, body) in pending {
+        show_system_notification(&title, &body);
+    }
+}
+
+fn show_system_notification(title: &str, body: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+        let _ = Command::new("osascript")
+            .arg("-e")
+            .arg(format!(r#"display notification "{}" with title "{}""#, body, title))
+            .output();
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Windows notifications require additional setup
+        // For now, callers also emit an event the UI can show
+        let _ = (title, body);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::Command;
+        let _ = Command::new("notify-send")
+            .arg(title)
+            .arg(body)
+            .output();
+    }
+}
+
+/// Sound files the platform player can handle
+#[cfg(target_os = "windows")]
+const SUPPORTED_SOUND_EXTENSIONS: &[&str] = &["wav"];
+#[cfg(not(target_os = "windows"))]
+const SUPPORTED_SOUND_EXTENSIONS: &[&str] = &["wav", "mp3", "aiff", "aif", "ogg", "oga"];
+
+/// Play the `completionSound` setting (`none`, `default` or a file path)
+/// Skipped when `notifyOnComplete` is off or during quiet hours; sounds aren't deferred.
+pub fn play_completion_sound() {
+    let notify_on_complete = database::get_setting("notifyOnComplete")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    if !notify_on_complete || quiet_hours::is_quiet_now() {
+        return;
+    }
+
+    let setting = database::get_setting("completionSound")
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "default".to_string());
+
+    let sound = match setting.as_str() {
+        "none" => return,
+        "default" | "" => None,
+        path => match validate_sound_file(path) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                logger::log_warning("notifications", &format!("{}, using the default sound", e));
+                None
+            }
+        },
+    };
+
+    if let Err(e) = play_sound(sound.as_deref()) {
+        logger::log_warning("notifications", &format!("Failed to play completion sound: {}", e));
+    }
+}
+
+/// Check that a custom sound exists and is a format we can play
+fn validate_sound_file(path: &str) -> Result<String, String> {
+    let expanded = crate::utils::expand_path(path);
+    let path = std::path::Path::new(&expanded);
+    if !path.is_file() {
+        return Err(format!("Completion sound not found: {}", expanded));
+    }
+
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !SUPPORTED_SOUND_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("Unsupported completion sound format: {}", expanded));
+    }
+
+    Ok(expanded)
+}
+
+/// Play a sound file (or the platform default) without waiting for it to finish
+fn play_sound(path: Option<&str>) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("afplay");
+        command.arg(path.unwrap_or("/System/Library/Sounds/Glass.aiff"));
+        command
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let file = path.unwrap_or(r"C:\Windows\Media\tada.wav").replace('\'', "''");
+        let mut command = Command::new("powershell");
+        command
+            .arg("-NoProfile")
+            .arg("-Command")
+            .arg(format!("(New-Object Media.SoundPlayer '{}').PlaySync()", file));
+        command
+    };
+
+    #[cfg(target_os = "linux")]
+    let mut command = {
+        let mut command = Command::new("paplay");
+        command.arg(path.unwrap_or("/usr/share/sounds/freedesktop/stereo/complete.oga"));
+        command
+    };
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|mut child| {
+            // Reap the player when it exits so it doesn't linger as a zombie
+            std::thread::spawn(move || child.wait());
+        })
+        .map_err(|e| e.to_string())
+}
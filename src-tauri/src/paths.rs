@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use dirs::home_dir;
+
+/// Environment variable that overrides the app data directory (portable installs, testing)
+pub const DATA_DIR_ENV: &str = "ACCELARA_DATA_DIR";
+
+/// Name of the app's directory under the platform data and cache directories
+const APP_DIR_NAME: &str = "accelara";
+
+/// Where older versions kept everything, in the home directory
+const LEGACY_DIR_NAME: &str = ".accelara";
+
+/// The default data directory, resolved (and migrated to) once per run
+static DEFAULT_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// `~/.accelara`, where the database and logs lived before the platform data directory was used
+pub fn legacy_data_dir() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(LEGACY_DIR_NAME))
+}
+
+/// Whether `dir` is missing or holds nothing
+fn is_empty_dir(dir: &Path) -> bool {
+    match std::fs::read_dir(dir) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => !dir.exists(),
+    }
+}
+
+/// Move the legacy data directory to `target` if only the legacy one has data
+/// Returns the directory to use: `target`, or `legacy` when moving it failed so nothing is lost.
+/// When both hold data `target` wins and `legacy` is left untouched.
+pub fn migrate_legacy_dir(legacy: &Path, target: &Path) -> PathBuf {
+    if legacy == target || !legacy.is_dir() || is_empty_dir(legacy) || !is_empty_dir(target) {
+        return target.to_path_buf();
+    }
+
+    // An empty target is in the way of the move; it has nothing worth keeping
+    let _ = std::fs::remove_dir(target);
+    // The logger writes into this directory, so report through stderr
+    match crate::utils::move_path(legacy, target) {
+        Ok(()) => {
+            eprintln!("[paths] Moved app data from {} to {}", legacy.display(), target.display());
+            target.to_path_buf()
+        }
+        Err(e) => {
+            eprintln!("[paths] Failed to move app data to {}, still using {}: {}", target.display(), legacy.display(), e);
+            legacy.to_path_buf()
+        }
+    }
+}
+
+/// The data directory to use when `ACCELARA_DATA_DIR` isn't set
+/// `platform` is the OS data directory (e.g. `~/.local/share`, `~/Library/Application Support`,
+/// `%APPDATA%`); without one the legacy `~/.accelara` is used as is.
+pub fn resolve_data_dir(platform: Option<PathBuf>, legacy: Option<PathBuf>) -> PathBuf {
+    match (platform, legacy) {
+        (Some(platform), Some(legacy)) => migrate_legacy_dir(&legacy, &platform.join(APP_DIR_NAME)),
+        (Some(platform), None) => platform.join(APP_DIR_NAME),
+        (None, Some(legacy)) => legacy,
+        (None, None) => panic!("Failed to get home directory"),
+    }
+}
+
+/// Directory holding the database, logs and other app state
+pub fn app_data_dir() -> PathBuf {
+    let dir = match std::env::var(DATA_DIR_ENV) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => DEFAULT_DATA_DIR
+            .get_or_init(|| resolve_data_dir(dirs::data_dir(), legacy_data_dir()))
+            .clone(),
+    };
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Path of the SQLite database
+pub fn db_path() -> PathBuf {
+    app_data_dir().join("accelara.db")
+}
+
+/// Path of the main log file
+pub fn log_path() -> PathBuf {
+    app_data_dir().join("accelara.log")
+}
+
+/// Pids of the wrapper processes we've spawned, for orphan cleanup on Windows
+#[cfg(target_os = "windows")]
+pub fn spawned_pids_path() -> PathBuf {
+    app_data_dir().join("spawned-pids.json")
+}
+
+/// Scratch directory for temporary files the app creates itself
+pub fn temp_dir() -> PathBuf {
+    let dir = app_data_dir().join("tmp");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Where downloaded app updates are kept until they're installed
+/// The platform cache directory, or the data directory when it's overridden or there's none.
+pub fn update_cache_dir() -> PathBuf {
+    let overridden = std::env::var(DATA_DIR_ENV).map(|dir| !dir.is_empty()).unwrap_or(false);
+    let cache = match dirs::cache_dir() {
+        Some(cache) if !overridden => cache.join(APP_DIR_NAME),
+        _ => app_data_dir().join("cache"),
+    };
+    let dir = cache.join("updates");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("accelara-paths-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resolve_data_dir() {
+        let root = scratch_dir("resolve");
+        let platform = root.join("share");
+        let missing_legacy = root.join(".accelara");
+
+        // Nothing to migrate: the platform directory is used
+        assert_eq!(resolve_data_dir(Some(platform.clone()), Some(missing_legacy.clone())), platform.join("accelara"));
+        assert_eq!(resolve_data_dir(Some(platform.clone()), None), platform.join("accelara"));
+        // No platform data directory: fall back to ~/.accelara
+        assert_eq!(resolve_data_dir(None, Some(missing_legacy.clone())), missing_legacy);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_migrate_legacy_dir() {
+        let root = scratch_dir("migrate");
+        let legacy = root.join(".accelara");
+        let target = root.join("share").join("accelara");
+        std::fs::create_dir_all(legacy.join("tmp")).unwrap();
+        std::fs::write(legacy.join("accelara.db"), b"db").unwrap();
+        std::fs::write(legacy.join("tmp").join("a.torrent"), b"d1:ae").unwrap();
+        // An empty target (e.g. created by an earlier run) doesn't block the move
+        std::fs::create_dir_all(&target).unwrap();
+
+        assert_eq!(migrate_legacy_dir(&legacy, &target), target);
+        assert_eq!(std::fs::read(target.join("accelara.db")).unwrap(), b"db");
+        assert!(target.join("tmp").join("a.torrent").is_file());
+        assert!(!legacy.exists());
+
+        // Once the target has data, a reappearing legacy directory is left alone
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("accelara.db"), b"old").unwrap();
+        assert_eq!(migrate_legacy_dir(&legacy, &target), target);
+        assert_eq!(std::fs::read(target.join("accelara.db")).unwrap(), b"db");
+        assert_eq!(std::fs::read(legacy.join("accelara.db")).unwrap(), b"old");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::binaries;
+use crate::commands::{self, DOWNLOAD_PROCESSES};
+use crate::database;
+use crate::logger;
+use crate::utils;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Total peer connections shared by all running torrents when `maxTorrentConnections` isn't set
+pub const DEFAULT_MAX_TORRENT_CONNECTIONS: u64 = 200;
+
+/// No torrent gets fewer peers than this, however many are running
+const MIN_PEERS_PER_TORRENT: u64 = 10;
+
+lazy_static::lazy_static! {
+    // Peer limit each running torrent was started with
+    static ref ASSIGNED_BUDGETS: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+    // Latest active peer count reported by each running torrent
+    static ref PEER_COUNTS: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// The global connection budget from settings
+pub fn max_torrent_connections() -> u64 {
+    database::get_setting("maxTorrentConnections")
+        .and_then(|v| v.as_u64())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_MAX_TORRENT_CONNECTIONS)
+}
+
+/// Split `total` connections evenly across `torrents` running torrents
+pub fn per_torrent_budget(total: u64, torrents: usize) -> u64 {
+    (total / torrents.max(1) as u64).max(MIN_PEERS_PER_TORRENT)
+}
+
+/// Whether a torrent's limit is far enough from the target to be worth a restart
+/// (more than 1.5x or less than 2/3 of it)
+pub fn needs_rebalance(assigned: u64, target: u64) -> bool {
+    assigned * 2 > target * 3 || assigned * 3 < target * 2
+}
+
+/// IDs of the running downloads (from `running_ids`) that are torrents or magnets
+pub fn running_torrents(running_ids: &[String]) -> Vec<String> {
+    let conn = match database::get_connection() {
+        Ok(conn) => conn,
+        Err(_) => return Vec::new(),
+    };
+    running_ids
+        .iter()
+        .filter(|id| {
+            conn.query_row("SELECT type FROM downloads WHERE id = ?1", [id.as_str()], |row| row.get::<_, String>(0))
+                .map(|t| t == "torrent" || t == "magnet")
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether the helper binary honours `--max-peers`; an older one would exit on it,
+/// and without it a rebalancing restart changes nothing
+pub async fn wrapper_limits_peers() -> bool {
+    match utils::find_go_binary() {
+        Some(binary) => binaries::supported_flags(&binary).await.contains("max-peers"),
+        None => false,
+    }
+}
+
+/// Remember the peer limit a torrent was started with
+pub fn assign_budget(download_id: &str, budget: u64) {
+    if let Ok(mut budgets) = ASSIGNED_BUDGETS.lock() {
+        budgets.insert(download_id.to_string(), budget);
+    }
+}
+
+/// Record the active peer count from a torrent progress update
+pub fn record_peers(download_id: &str, peers: u64) {
+    if let Ok(mut counts) = PEER_COUNTS.lock() {
+        counts.insert(download_id.to_string(), peers);
+    }
+}
+
+/// Forget torrents that are no longer running
+/// (done here rather than when a monitor exits, since a restart's old monitor can outlive the new start)
+fn retain_running(running: &[String]) {
+    if let Ok(mut budgets) = ASSIGNED_BUDGETS.lock() {
+        budgets.retain(|id, _| running.contains(id));
+    }
+    if let Ok(mut counts) = PEER_COUNTS.lock() {
+        counts.retain(|id, _| running.contains(id));
+    }
+}
+
+/// Current peers and peer limit of each running torrent
+pub fn peer_stats() -> serde_json::Value {
+    let budgets = ASSIGNED_BUDGETS.lock().map(|b| b.clone()).unwrap_or_default();
+    let counts = PEER_COUNTS.lock().map(|c| c.clone()).unwrap_or_default();
+
+    let torrents: Vec<serde_json::Value> = budgets
+        .iter()
+        .map(|(id, budget)| serde_json::json!({
+            "downloadId": id,
+            "peers": counts.get(id).copied().unwrap_or(0),
+            "maxPeers": budget,
+        }))
+        .collect();
+
+    serde_json::json!({
+        "maxTorrentConnections": max_torrent_connections(),
+        "totalPeers": counts.values().sum::<u64>(),
+        "torrents": torrents,
+    })
+}
+
+/// Set up the background task that rebalances peer limits as torrents start and finish
+pub fn setup_peer_rebalancing(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+
+            let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+            retain_running(&running);
+            let torrents = running_torrents(&running);
+            if torrents.is_empty() || !wrapper_limits_peers().await {
+                continue;
+            }
+
+            let target = per_torrent_budget(max_torrent_connections(), torrents.len());
+            let budgets = ASSIGNED_BUDGETS.lock().map(|b| b.clone()).unwrap_or_default();
+
+            // Only restart torrents whose limit is well off target - a restart costs a reconnect
+            let off_target: Vec<String> = torrents
+                .into_iter()
+                .filter(|id| budgets.get(id).map(|&assigned| needs_rebalance(assigned, target)).unwrap_or(false))
+                .collect();
+
+            for download_id in off_target {
+                logger::log_info("peer_budget", &format!("Rebalancing {} to {} peers", download_id, target));
+                if let Err(e) = commands::restart_download_internal(download_id.clone(), app.clone()).await {
+                    logger::log_error("peer_budget", &format!("Failed to rebalance {}: {}", download_id, e));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_torrent_budget() {
+        assert_eq!(per_torrent_budget(200, 1), 200);
+        assert_eq!(per_torrent_budget(200, 4), 50);
+        assert_eq!(per_torrent_budget(200, 0), 200);
+        assert_eq!(per_torrent_budget(200, 100), MIN_PEERS_PER_TORRENT);
+    }
+
+    #[test]
+    fn test_needs_rebalance() {
+        assert!(!needs_rebalance(100, 100));
+        assert!(!needs_rebalance(66, 50));
+        assert!(needs_rebalance(200, 100));
+        assert!(needs_rebalance(50, 100));
+    }
+}
@@ -0,0 +1,444 @@
+use crate::commands;
+use crate::database;
+use crate::logger;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+const SLEEP_PAUSE_REASON: &str = "Paused for system sleep";
+
+/// Set up OS sleep/wake monitoring
+/// Active downloads are paused before the machine sleeps (their TCP connections
+/// would die anyway) and the ones we paused are resumed once it wakes up.
+/// Must be called on the main thread on macOS, whose run loop delivers the notifications.
+pub fn setup_power_monitoring(app: AppHandle) {
+    #[cfg(target_os = "linux")]
+    {
+        let app_clone = app.clone();
+        tauri::async_runtime::spawn(async move {
+            // logind announces PrepareForSleep(true) before suspend and PrepareForSleep(false) on resume
+            if let Err(e) = watch_logind_sleep_signals(&app_clone).await {
+                logger::log_warning("power", &format!("logind sleep monitoring unavailable ({}), falling back to clock-jump detection", e));
+                watch_clock_jumps(&app_clone).await;
+            }
+        });
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // NSWorkspace posts willSleep before the machine sleeps and didWake after
+        if let Err(e) = macos::observe_sleep_notifications(app.clone()) {
+            logger::log_warning("power", &format!("Sleep notifications unavailable ({}), falling back to clock-jump detection", e));
+            let app_clone = app.clone();
+            tauri::async_runtime::spawn(async move {
+                watch_clock_jumps(&app_clone).await;
+            });
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // WM_POWERBROADCAST carries PBT_APMSUSPEND before sleep and PBT_APMRESUMEAUTOMATIC after
+        if let Err(e) = windows::watch_power_broadcasts(app.clone()) {
+            logger::log_warning("power", &format!("Power broadcasts unavailable ({}), falling back to clock-jump detection", e));
+            let app_clone = app.clone();
+            tauri::async_runtime::spawn(async move {
+                watch_clock_jumps(&app_clone).await;
+            });
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        // Detect wake by watching for a wall-clock jump while our timer was suspended.
+        // We can't pause ahead of sleep here, so dead connections are restarted on wake instead.
+        let app_clone = app.clone();
+        tauri::async_runtime::spawn(async move {
+            watch_clock_jumps(&app_clone).await;
+        });
+    }
+}
+
+/// How long a sleep callback may spend pausing downloads
+/// The OS only waits a few seconds before suspending anyway.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const SLEEP_PAUSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause downloads from an OS sleep callback, which has to return before the machine suspends
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn pause_before_sleep(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::block_on(async move {
+        if tokio::time::timeout(SLEEP_PAUSE_TIMEOUT, handle_sleep(&app)).await.is_err() {
+            logger::log_warning("power", "Timed out pausing downloads before sleep");
+        }
+    });
+}
+
+/// Resume sleep-paused downloads from an OS wake callback
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn resume_after_wake(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        handle_wake(&app).await;
+    });
+}
+
+/// Take a logind "delay" inhibitor lock so suspend waits for us to pause downloads
+/// The lock is held by `systemd-inhibit` for as long as `cat` keeps reading its stdin,
+/// so dropping the child's stdin (or the child itself) releases it.
+#[cfg(target_os = "linux")]
+fn take_sleep_delay_lock() -> Option<tokio::process::Child> {
+    use std::process::Stdio;
+    use tokio::process::Command;
+
+    match Command::new("systemd-inhibit")
+        .args(&[
+            "--what=sleep",
+            "--mode=delay",
+            "--who=ACCELARA",
+            "--why=Pausing downloads before sleep",
+            "cat",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => Some(child),
+        Err(e) => {
+            logger::log_warning("power", &format!("Failed to take sleep inhibitor lock, downloads may not pause before sleep: {}", e));
+            None
+        }
+    }
+}
+
+/// Release a lock from `take_sleep_delay_lock`, letting the pending suspend go ahead
+#[cfg(target_os = "linux")]
+async fn release_sleep_delay_lock(lock: Option<tokio::process::Child>) {
+    if let Some(mut child) = lock {
+        drop(child.stdin.take());
+        let _ = child.wait().await;
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn watch_logind_sleep_signals(app: &AppHandle) -> Result<(), String> {
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::process::Command;
+
+    let mut child = Command::new("gdbus")
+        .args(&[
+            "monitor",
+            "--system",
+            "--dest", "org.freedesktop.login1",
+            "--object-path", "/org/freedesktop/login1",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn gdbus: {}", e))?;
+
+    let stdout = child.stdout.take()
+        .ok_or_else(|| "gdbus stdout not available".to_string())?;
+
+    logger::log_info("power", "Listening for logind PrepareForSleep signals");
+
+    // Without the lock logind suspends right after signalling, before the pause goes through
+    let mut lock = take_sleep_delay_lock();
+
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        // e.g. "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)"
+        if !line.contains("PrepareForSleep") {
+            continue;
+        }
+        if line.contains("true") {
+            handle_sleep(app).await;
+            release_sleep_delay_lock(lock.take()).await;
+        } else if line.contains("false") {
+            lock = take_sleep_delay_lock();
+            handle_wake(app).await;
+        }
+    }
+
+    release_sleep_delay_lock(lock).await;
+    Err("gdbus monitor exited".to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::sync::OnceLock;
+    use tauri::AppHandle;
+
+    const WILL_SLEEP_NOTIFICATION: &str = "NSWorkspaceWillSleepNotification";
+    const DID_WAKE_NOTIFICATION: &str = "NSWorkspaceDidWakeNotification";
+
+    // Handle used by the Objective-C callbacks, which can't capture state
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    // The system waits for willSleep observers to return before sleeping
+    extern "C" fn will_sleep(_this: &Object, _cmd: Sel, _notification: id) {
+        if let Some(app) = APP.get() {
+            super::pause_before_sleep(app);
+        }
+    }
+
+    extern "C" fn did_wake(_this: &Object, _cmd: Sel, _notification: id) {
+        if let Some(app) = APP.get() {
+            super::resume_after_wake(app);
+        }
+    }
+
+    /// Register observers on NSWorkspace's notification center for sleep and wake
+    pub fn observe_sleep_notifications(app: AppHandle) -> Result<(), String> {
+        if APP.set(app).is_err() {
+            return Err("Sleep observer already registered".to_string());
+        }
+
+        let mut decl = ClassDecl::new("AccelaraSleepObserver", class!(NSObject))
+            .ok_or_else(|| "Failed to declare observer class".to_string())?;
+        unsafe {
+            decl.add_method(sel!(willSleep:), will_sleep as extern "C" fn(&Object, Sel, id));
+            decl.add_method(sel!(didWake:), did_wake as extern "C" fn(&Object, Sel, id));
+        }
+        let observer_class = decl.register();
+
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let center: id = msg_send![workspace, notificationCenter];
+            if center == nil {
+                return Err("NSWorkspace notification center unavailable".to_string());
+            }
+            // Kept for the lifetime of the app, so it is never released
+            let observer: id = msg_send![observer_class, new];
+            let will_sleep_name = NSString::alloc(nil).init_str(WILL_SLEEP_NOTIFICATION);
+            let did_wake_name = NSString::alloc(nil).init_str(DID_WAKE_NOTIFICATION);
+            let _: () = msg_send![center,
+                addObserver: observer
+                selector: sel!(willSleep:)
+                name: will_sleep_name
+                object: nil];
+            let _: () = msg_send![center,
+                addObserver: observer
+                selector: sel!(didWake:)
+                name: did_wake_name
+                object: nil];
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::sync::OnceLock;
+    use tauri::AppHandle;
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage, MSG,
+        PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, WM_POWERBROADCAST, WNDCLASSW,
+    };
+
+    // Handle used by the window procedure, which can't capture state
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg == WM_POWERBROADCAST {
+            if let Some(app) = APP.get() {
+                match wparam as u32 {
+                    // Windows gives us about two seconds before suspending
+                    PBT_APMSUSPEND => super::pause_before_sleep(app),
+                    PBT_APMRESUMEAUTOMATIC => super::resume_after_wake(app),
+                    _ => {}
+                }
+            }
+            return 1;
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    /// Create a hidden window on its own thread to receive WM_POWERBROADCAST
+    /// It has to be a top-level window: message-only windows don't get broadcasts.
+    pub fn watch_power_broadcasts(app: AppHandle) -> Result<(), String> {
+        if APP.set(app).is_err() {
+            return Err("Power broadcast window already created".to_string());
+        }
+
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || unsafe {
+            let class_name: Vec<u16> = "AccelaraPowerWindow\0".encode_utf16().collect();
+            let instance = GetModuleHandleW(std::ptr::null());
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance,
+                lpszClassName: class_name.as_ptr(),
+                ..std::mem::zeroed()
+            };
+            if RegisterClassW(&class) == 0 {
+                let _ = ready_tx.send(Err("Failed to register power window class".to_string()));
+                return;
+            }
+
+            // Never shown, so it doesn't appear anywhere
+            let hwnd = CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                class_name.as_ptr(),
+                0,
+                0, 0, 0, 0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null(),
+            );
+            if hwnd.is_null() {
+                let _ = ready_tx.send(Err("Failed to create power window".to_string()));
+                return;
+            }
+            let _ = ready_tx.send(Ok(()));
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        });
+
+        ready_rx.recv().map_err(|_| "Power window thread exited".to_string())?
+    }
+}
+
+/// Fallback wake detection: if far more wall-clock time passed than we slept for,
+/// the machine was suspended in between
+async fn watch_clock_jumps(app: &AppHandle) {
+    const TICK: Duration = Duration::from_secs(5);
+    const JUMP_THRESHOLD: Duration = Duration::from_secs(30);
+
+    loop {
+        let before = SystemTime::now();
+        tokio::time::sleep(TICK).await;
+        let elapsed = SystemTime::now().duration_since(before).unwrap_or_default();
+
+        if elapsed > JUMP_THRESHOLD {
+            logger::log_info("power", &format!("Detected system wake ({}s gap)", elapsed.as_secs()));
+            // Connections held across the sleep are dead - cycle the downloads that were running
+            handle_sleep(app).await;
+            handle_wake(app).await;
+        }
+    }
+}
+
+/// Pause all running downloads and mark them as sleep-paused
+async fn handle_sleep(app: &AppHandle) {
+    let download_ids: Vec<String> = match database::get_connection() {
+        Ok(conn) => {
+            let mut ids = Vec::new();
+            if let Ok(mut stmt) = conn.prepare("SELECT id FROM downloads WHERE status = 'downloading'") {
+                if let Ok(rows) = stmt.query_map([], |row| row.get::<_, String>(0)) {
+                    ids = rows.filter_map(|r| r.ok()).collect();
+                }
+            }
+            ids
+        }
+        Err(e) => {
+            logger::log_error("power", &format!("Database error: {}", e));
+            return;
+        }
+    };
+
+    logger::log_info("power", &format!("System going to sleep, pausing {} download(s)", download_ids.len()));
+
+    let mut paused = Vec::new();
+    for download_id in download_ids {
+        match commands::pause_download_internal(download_id.clone(), app.clone(), SLEEP_PAUSE_REASON).await {
+            Ok(_) => {
+                mark_sleep_paused(&download_id);
+                paused.push(download_id);
+            }
+            Err(e) => {
+                logger::log_error("power", &format!("Failed to pause {} before sleep: {}", download_id, e));
+            }
+        }
+    }
+
+    let _ = app.emit("power-sleep", serde_json::json!({
+        "pausedIds": paused,
+    }));
+}
+
+/// Resume the downloads that were paused by `handle_sleep`
+async fn handle_wake(app: &AppHandle) {
+    let download_ids: Vec<String> = match database::get_connection() {
+        Ok(conn) => {
+            let mut ids = Vec::new();
+            if let Ok(mut stmt) = conn.prepare("SELECT id, metadata FROM downloads WHERE status = 'paused'") {
+                if let Ok(rows) = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+                }) {
+                    for (id, metadata_str) in rows.flatten() {
+                        let sleep_paused = metadata_str
+                            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+                            .and_then(|m| m.get("sleep_paused").and_then(|v| v.as_bool()))
+                            .unwrap_or(false);
+                        if sleep_paused {
+                            ids.push(id);
+                        }
+                    }
+                }
+            }
+            ids
+        }
+        Err(e) => {
+            logger::log_error("power", &format!("Database error: {}", e));
+            return;
+        }
+    };
+
+    logger::log_info("power", &format!("System woke up, resuming {} download(s)", download_ids.len()));
+
+    // Give the network a moment to come back before reconnecting
+    if !download_ids.is_empty() {
+        tokio::time::sleep(Duration::from_secs(3)).await;
+    }
+
+    let mut resumed = Vec::new();
+    for download_id in download_ids {
+        match crate::queue::enqueue(download_id.clone(), app.clone()).await {
+            Ok(_) => resumed.push(download_id),
+            Err(e) => {
+                logger::log_error("power", &format!("Failed to resume {} after wake: {}", download_id, e));
+            }
+        }
+    }
+
+    let _ = app.emit("power-wake", serde_json::json!({
+        "resumedIds": resumed,
+    }));
+}
+
+fn mark_sleep_paused(download_id: &str) {
+    if let Ok(conn) = database::get_connection() {
+        if let Ok(Some(metadata_str)) = conn.query_row(
+            "SELECT metadata FROM downloads WHERE id = ?1",
+            [download_id],
+            |row| row.get::<_, Option<String>>(0),
+        ) {
+            let mut metadata: serde_json::Value = serde_json::from_str(&metadata_str)
+                .unwrap_or_else(|_| serde_json::json!({}));
+            metadata["sleep_paused"] = serde_json::json!(true);
+            let _ = conn.execute(
+                "UPDATE downloads SET metadata = ? WHERE id = ?",
+                rusqlite::params![serde_json::to_string(&metadata).unwrap(), download_id],
+            );
+        }
+    }
+}
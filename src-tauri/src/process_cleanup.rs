@@ -0,0 +1,230 @@
+use crate::commands::{DOWNLOAD_PROCESSES, SPEED_TEST_PROCESSES};
+use crate::logger;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Environment variable set on every api-wrapper/iris child we spawn,
+/// so processes left behind by a crashed session can be recognised later
+pub const DOWNLOAD_ID_ENV: &str = "ACCELARA_DOWNLOAD_ID";
+
+// Serialises read-modify-write of the spawned pid file between concurrent spawns
+#[cfg(target_os = "windows")]
+static SPAWNED_PIDS_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanProcess {
+    pub pid: u32,
+    pub name: String,
+    pub download_id: Option<String>,
+}
+
+/// Find wrapper processes started by ACCELARA that aren't tracked by this session
+pub async fn find_orphaned_processes() -> Vec<OrphanProcess> {
+    let mut tracked: HashSet<u32> = HashSet::new();
+    tracked.extend(DOWNLOAD_PROCESSES.lock().await.values().filter_map(|c| c.id()));
+    tracked.extend(SPEED_TEST_PROCESSES.lock().await.values().filter_map(|c| c.id()));
+
+    list_marked_processes()
+        .into_iter()
+        .filter(|p| !tracked.contains(&p.pid) && p.pid != std::process::id())
+        .collect()
+}
+
+/// Remember a wrapper process we just spawned, so a later session can tell it apart
+/// from api-wrapper/iris processes that aren't ours
+pub fn record_spawn(pid: Option<u32>, id: &str) {
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(pid) = pid {
+            let _guard = SPAWNED_PIDS_LOCK.lock();
+            let mut pids = spawned_pids();
+            pids.insert(pid.to_string(), serde_json::json!(id));
+            if let Err(e) = save_spawned_pids(pids) {
+                logger::log_warning("process_cleanup", &format!("Failed to record pid {}: {}", pid, e));
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (pid, id);
+    }
+}
+
+/// Recorded pids mapped to their download/test id
+/// Kept in a file of its own, since the frontend writes back whole settings snapshots.
+#[cfg(target_os = "windows")]
+fn spawned_pids() -> serde_json::Map<String, serde_json::Value> {
+    std::fs::read(crate::paths::spawned_pids_path())
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "windows")]
+fn save_spawned_pids(pids: serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    let data = serde_json::to_vec(&pids).map_err(|e| e.to_string())?;
+    std::fs::write(crate::paths::spawned_pids_path(), data).map_err(|e| e.to_string())
+}
+
+/// Kill all orphaned wrapper processes, returning the ones that were killed
+pub async fn kill_orphaned_processes() -> Vec<OrphanProcess> {
+    let orphans = find_orphaned_processes().await;
+    let mut killed = Vec::new();
+
+    for orphan in orphans {
+        logger::log_info("process_cleanup", &format!("Killing orphaned {} (pid {}, download {})",
+            orphan.name, orphan.pid, orphan.download_id.as_deref().unwrap_or("unknown")));
+        if kill_process(orphan.pid) {
+            killed.push(orphan);
+        } else {
+            logger::log_warning("process_cleanup", &format!("Failed to kill pid {}", orphan.pid));
+        }
+    }
+
+    killed
+}
+
+#[cfg(target_os = "linux")]
+fn list_marked_processes() -> Vec<OrphanProcess> {
+    // /proc/<pid>/environ holds the NUL-separated environment (readable for our own processes)
+    let mut processes = Vec::new();
+    let marker = format!("{}=", DOWNLOAD_ID_ENV);
+
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            let pid = match entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) {
+                Some(pid) => pid,
+                None => continue,
+            };
+            let environ = match std::fs::read(entry.path().join("environ")) {
+                Ok(environ) => environ,
+                Err(_) => continue,
+            };
+            let download_id = environ
+                .split(|b| *b == 0)
+                .filter_map(|var| std::str::from_utf8(var).ok())
+                .find_map(|var| var.strip_prefix(marker.as_str()).map(|id| id.to_string()));
+
+            if let Some(download_id) = download_id {
+                let name = std::fs::read_to_string(entry.path().join("comm"))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default();
+                processes.push(OrphanProcess {
+                    pid,
+                    name,
+                    download_id: Some(download_id),
+                });
+            }
+        }
+    }
+
+    processes
+}
+
+#[cfg(target_os = "macos")]
+fn list_marked_processes() -> Vec<OrphanProcess> {
+    use std::process::Command;
+
+    // -E appends the environment to the command column
+    let output = match Command::new("ps").args(&["-axwwE", "-o", "pid=,command="]).output() {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    let marker = format!("{}=", DOWNLOAD_ID_ENV);
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(2, ' ');
+            let pid = parts.next()?.parse::<u32>().ok()?;
+            let command = parts.next()?.trim();
+            let download_id = command
+                .split_whitespace()
+                .find_map(|token| token.strip_prefix(marker.as_str()))?;
+            let name = command
+                .split_whitespace()
+                .next()
+                .and_then(|exe| std::path::Path::new(exe).file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            Some(OrphanProcess {
+                pid,
+                name,
+                download_id: Some(download_id.to_string()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn list_marked_processes() -> Vec<OrphanProcess> {
+    use std::process::Command;
+
+    // Another process's environment isn't readily available on Windows, so only running
+    // wrapper images whose pid we recorded at spawn count (see `record_spawn`)
+    let _guard = SPAWNED_PIDS_LOCK.lock();
+    let recorded = spawned_pids();
+    let mut processes = Vec::new();
+    for image in ["api-wrapper.exe", "iris.exe"] {
+        let output = match Command::new("tasklist")
+            .args(&["/FI", &format!("IMAGENAME eq {}", image), "/FO", "CSV", "/NH"])
+            .output()
+        {
+            Ok(output) if output.status.success() => output,
+            _ => return Vec::new(),
+        };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim_matches('"')).collect();
+            if fields.len() > 1 {
+                if let Some(id) = recorded.get(fields[1]) {
+                    if let Ok(pid) = fields[1].parse::<u32>() {
+                        processes.push(OrphanProcess {
+                            pid,
+                            name: fields[0].to_string(),
+                            download_id: id.as_str().map(|s| s.to_string()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Forget pids that have exited, so one Windows hands out again later isn't taken for ours
+    let running: serde_json::Map<String, serde_json::Value> = recorded
+        .into_iter()
+        .filter(|(pid, _)| processes.iter().any(|p| p.pid.to_string() == *pid))
+        .collect();
+    let _ = save_spawned_pids(running);
+
+    processes
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn list_marked_processes() -> Vec<OrphanProcess> {
+    Vec::new()
+}
+
+fn kill_process(pid: u32) -> bool {
+    use std::process::Command;
+
+    #[cfg(unix)]
+    {
+        Command::new("kill")
+            .arg("-KILL")
+            .arg(pid.to_string())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    #[cfg(windows)]
+    {
+        Command::new("taskkill")
+            .args(&["/PID", &pid.to_string(), "/F"])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,234 @@
+use crate::commands::{self, DOWNLOAD_PROCESSES};
+use crate::{database, logger, utils};
+use std::collections::VecDeque;
+use tauri::{AppHandle, Emitter};
+
+/// Default for the `maxConcurrentDownloads` setting
+pub const DEFAULT_MAX_CONCURRENT: usize = 3;
+
+/// Downloads waiting for a free slot, in start order
+pub struct DownloadQueue {
+    pub max_concurrent: usize,
+    queued: VecDeque<String>,
+}
+
+impl DownloadQueue {
+    fn new() -> Self {
+        DownloadQueue { max_concurrent: DEFAULT_MAX_CONCURRENT, queued: VecDeque::new() }
+    }
+
+    /// Add to the back of the queue; a download is only queued once
+    pub fn push(&mut self, download_id: &str) {
+        if !self.queued.iter().any(|id| id == download_id) {
+            self.queued.push_back(download_id.to_string());
+        }
+    }
+
+    pub fn remove(&mut self, download_id: &str) -> bool {
+        let before = self.queued.len();
+        self.queued.retain(|id| id != download_id);
+        self.queued.len() != before
+    }
+
+    /// Next download to start if fewer than `max_concurrent` are running (0 = no limit)
+    pub fn next_to_start(&mut self, running: usize) -> Option<String> {
+        if self.max_concurrent == 0 || running < self.max_concurrent {
+            self.queued.pop_front()
+        } else {
+            None
+        }
+    }
+
+    pub fn has_free_slot(&self, running: usize) -> bool {
+        self.max_concurrent == 0 || running < self.max_concurrent
+    }
+
+    pub fn queued(&self) -> Vec<String> {
+        self.queued.iter().cloned().collect()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref QUEUE: std::sync::Mutex<DownloadQueue> = std::sync::Mutex::new(DownloadQueue::new());
+    // Held while deciding whether to start and spawning, so two starts can't both take the last slot
+    static ref START_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+fn max_concurrent() -> usize {
+    database::get_setting("maxConcurrentDownloads")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_MAX_CONCURRENT)
+}
+
+/// Running downloads that take a slot; seeding torrents only upload, so they don't count
+async fn running_downloads() -> Vec<String> {
+    let ids: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+    let conn = match database::get_connection() {
+        Ok(conn) => conn,
+        Err(_) => return ids,
+    };
+    ids.into_iter()
+        .filter(|id| {
+            conn.query_row("SELECT status FROM downloads WHERE id = ?1", [id], |row| row.get::<_, String>(0))
+                .map(|status| status != "seeding")
+                .unwrap_or(true)
+        })
+        .collect()
+}
+
+/// Start a download now if a slot is free, otherwise queue it
+/// Returns true if it started, false if it was queued.
+pub async fn enqueue(download_id: String, app: AppHandle) -> Result<bool, String> {
+    let guard = START_LOCK.lock().await;
+    
+    let running = running_downloads().await;
+    if running.contains(&download_id) {
+        return Ok(true);
+    }
+    
+    let (has_slot, queue_empty) = match QUEUE.lock() {
+        Ok(mut queue) => {
+            queue.max_concurrent = max_concurrent();
+            (queue.has_free_slot(running.len()), queue.queued.is_empty())
+        }
+        Err(_) => (true, true),
+    };
+    
+    // Start right away (so errors reach the caller) unless others are already waiting
+    if has_slot && queue_empty {
+        commands::resume_download_internal(download_id, app.clone()).await?;
+        drop(guard);
+        emit_queue_state(&app).await;
+        return Ok(true);
+    }
+    
+    if let Ok(mut queue) = QUEUE.lock() {
+        queue.push(&download_id);
+    }
+    if let Ok(conn) = database::get_connection() {
+        let _ = conn.execute(
+            "UPDATE downloads SET status = 'queued' WHERE id = ?1",
+            [&download_id],
+        );
+    }
+    let _ = app.emit("download-update", serde_json::json!({
+        "downloadId": download_id,
+        "download_id": download_id,
+        "status": "queued",
+    }));
+    logger::log_info("queue", &format!("Queued {} ({} running)", download_id, running.len()));
+    drop(guard);
+    
+    // Free slots with a backlog: start from the front of the queue
+    if has_slot {
+        promote_queued(&app).await;
+    } else {
+        emit_queue_state(&app).await;
+    }
+    Ok(false)
+}
+
+/// Drop a download from the queue (paused or removed while waiting)
+pub fn dequeue(download_id: &str) -> bool {
+    QUEUE.lock().map(|mut queue| queue.remove(download_id)).unwrap_or(false)
+}
+
+/// Start queued downloads while slots are free; called whenever a download process exits
+pub fn on_slot_freed(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        promote_queued(&app).await;
+    });
+}
+
+async fn promote_queued(app: &AppHandle) {
+    let mut started_any = false;
+    loop {
+        let _guard = START_LOCK.lock().await;
+        let running = running_downloads().await.len();
+        let next = match QUEUE.lock() {
+            Ok(mut queue) => {
+                queue.max_concurrent = max_concurrent();
+                queue.next_to_start(running)
+            }
+            Err(_) => None,
+        };
+        let download_id = match next {
+            Some(id) => id,
+            None => break,
+        };
+        
+        if started_any {
+            tokio::time::sleep(utils::start_stagger()).await;
+        }
+        logger::log_info("queue", &format!("Starting queued download {}", download_id));
+        if let Err(e) = commands::resume_download_internal(download_id.clone(), app.clone()).await {
+            logger::log_error("queue", &format!("Failed to start queued download {}: {}", download_id, e));
+            if let Ok(conn) = database::get_connection() {
+                let _ = conn.execute(
+                    "UPDATE downloads SET status = 'paused' WHERE id = ?1 AND status = 'queued'",
+                    [&download_id],
+                );
+            }
+        }
+        started_any = true;
+    }
+    emit_queue_state(app).await;
+}
+
+/// Which downloads are running and which are waiting, in order
+pub async fn queue_state() -> serde_json::Value {
+    let running = running_downloads().await;
+    let (max_concurrent, queued) = match QUEUE.lock() {
+        Ok(queue) => (queue.max_concurrent, queue.queued()),
+        Err(_) => (max_concurrent(), Vec::new()),
+    };
+    serde_json::json!({
+        "maxConcurrent": max_concurrent,
+        "running": running,
+        "queued": queued,
+    })
+}
+
+async fn emit_queue_state(app: &AppHandle) {
+    let _ = app.emit("queue-update", queue_state().await);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_respects_limit() {
+        let mut queue = DownloadQueue::new();
+        queue.max_concurrent = 2;
+        queue.push("a");
+        queue.push("b");
+        queue.push("a");
+        assert_eq!(queue.queued(), vec!["a", "b"]);
+        
+        assert_eq!(queue.next_to_start(2), None);
+        assert_eq!(queue.next_to_start(1), Some("a".to_string()));
+        assert_eq!(queue.next_to_start(1), Some("b".to_string()));
+        assert_eq!(queue.next_to_start(0), None);
+    }
+
+    #[test]
+    fn test_zero_means_unlimited() {
+        let mut queue = DownloadQueue::new();
+        queue.max_concurrent = 0;
+        queue.push("a");
+        assert!(queue.has_free_slot(100));
+        assert_eq!(queue.next_to_start(100), Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut queue = DownloadQueue::new();
+        queue.push("a");
+        queue.push("b");
+        assert!(queue.remove("a"));
+        assert!(!queue.remove("a"));
+        assert_eq!(queue.queued(), vec!["b"]);
+    }
+}
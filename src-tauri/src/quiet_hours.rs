@@ -0,0 +1,98 @@
+use crate::database;
+use crate::logger;
+use crate::notifications;
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Hours during which user-facing interruptions (system notifications, scheduled
+/// checks) are held back. Downloads are not affected.
+/// `start_hour > end_hour` wraps past midnight (e.g. 22 -> 7); equal hours disable it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl QuietHours {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start_hour == self.end_hour {
+            false
+        } else if self.start_hour < self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            // Overnight window
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+static QUIET_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+fn load_quiet_hours() -> Option<QuietHours> {
+    database::get_setting("quietHours").and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Whether quiet hours are in effect right now
+pub fn is_quiet_now() -> bool {
+    load_quiet_hours()
+        .map(|q| q.contains(chrono::Local::now().hour()))
+        .unwrap_or(false)
+}
+
+/// Set up the background task that tracks quiet hours, tells the UI about it and
+/// delivers notifications that were held back once the window ends
+pub fn setup_quiet_hours(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+
+            let quiet_hours = load_quiet_hours();
+            let active = quiet_hours
+                .as_ref()
+                .map(|q| q.contains(chrono::Local::now().hour()))
+                .unwrap_or(false);
+
+            if QUIET_ACTIVE.swap(active, Ordering::SeqCst) == active {
+                continue;
+            }
+
+            logger::log_info("quiet_hours", if active { "Quiet hours started" } else { "Quiet hours ended" });
+            let _ = app.emit("quiet-hours-active", serde_json::json!({
+                "active": active,
+                "startHour": quiet_hours.as_ref().map(|q| q.start_hour),
+                "endHour": quiet_hours.as_ref().map(|q| q.end_hour),
+            }));
+
+            if !active {
+                notifications::flush_deferred();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours_ranges() {
+        let day = QuietHours { start_hour: 13, end_hour: 15 };
+        assert!(day.contains(13));
+        assert!(day.contains(14));
+        assert!(!day.contains(15));
+
+        let night = QuietHours { start_hour: 22, end_hour: 7 };
+        assert!(night.contains(23));
+        assert!(night.contains(3));
+        assert!(!night.contains(7));
+        assert!(!night.contains(12));
+
+        let disabled = QuietHours { start_hour: 0, end_hour: 0 };
+        assert!(!disabled.contains(0));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::database;
+use crate::schedule::{self, ScheduleRule};
+use serde::{Deserialize, Serialize};
+
+/// A time-of-day window with its own download speed cap (the `rateSchedule` setting)
+/// Applied as an every-day bandwidth schedule rule (see `schedule::effective_schedule`).
+/// `limit` uses the same format as the `rateLimit` setting; empty/null means unlimited.
+/// Windows where `start_hour > end_hour` wrap past midnight (e.g. 22 -> 6).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    #[serde(default)]
+    pub limit: Option<String>,
+}
+
+impl RateWindow {
+    /// The same window as an every-day bandwidth schedule rule
+    /// Hour 24 is midnight, so a 22 -> 24 window becomes 22:00-00:00.
+    pub fn to_rule(&self) -> ScheduleRule {
+        ScheduleRule {
+            days: schedule::ALL_DAYS,
+            start: format!("{:02}:00", self.start_hour % 24),
+            end: format!("{:02}:00", self.end_hour % 24),
+            rate_limit: self.limit.clone().unwrap_or_default(),
+            upload_limit: String::new(),
+        }
+    }
+}
+
+/// The `rateSchedule` setting
+pub fn load_windows() -> Vec<RateWindow> {
+    database::get_setting("rateSchedule")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Rules with `windows` put ahead of `rules`, so bandwidth schedule rules win where they overlap
+pub fn merge_windows(windows: &[RateWindow], rules: Vec<ScheduleRule>) -> Vec<ScheduleRule> {
+    windows.iter().map(RateWindow::to_rule).chain(rules).collect()
+}
+
+/// The window that applies on `weekday` (0 = Monday) at `minute` past midnight; the last match wins
+pub fn find_window(windows: &[RateWindow], weekday: u32, minute: u32) -> Option<&RateWindow> {
+    windows.iter().rev().find(|w| w.to_rule().matches(weekday, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::test_support::{at, MON, TUE};
+
+    fn window(start_hour: u32, end_hour: u32, limit: &str) -> RateWindow {
+        RateWindow { start_hour, end_hour, limit: Some(limit.to_string()) }
+    }
+
+    #[test]
+    fn test_window_matching() {
+        let rule = window(9, 17, "1MB").to_rule();
+        assert_eq!(rule.rate_limit, "1MB");
+        assert!(rule.matches(MON, at(9, 0)));
+        assert!(rule.matches(MON, at(16, 59)));
+        assert!(!rule.matches(MON, at(17, 0)));
+        assert!(!rule.matches(MON, at(3, 0)));
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let rule = window(22, 6, "500KB").to_rule();
+        assert!(rule.matches(MON, at(23, 0)));
+        assert!(rule.matches(TUE, at(0, 0)));
+        assert!(rule.matches(TUE, at(5, 59)));
+        assert!(!rule.matches(TUE, at(6, 0)));
+        assert!(!rule.matches(TUE, at(12, 0)));
+        assert!(window(22, 24, "1MB").to_rule().matches(MON, at(23, 30)));
+        assert_eq!(RateWindow { start_hour: 1, end_hour: 2, limit: None }.to_rule().rate_limit, "");
+    }
+
+    #[test]
+    fn test_bandwidth_rules_win_over_windows() {
+        let existing = vec![window(12, 14, "256KB").to_rule()];
+        let schedule = schedule::BandwidthSchedule { rules: merge_windows(&[window(8, 20, "2MB")], existing) };
+        assert_eq!(schedule.find_rule(MON, at(13, 0)).unwrap().rate_limit, "256KB");
+        assert_eq!(schedule.find_rule(MON, at(10, 0)).unwrap().rate_limit, "2MB");
+    }
+
+    #[test]
+    fn test_find_window() {
+        let windows = [window(8, 20, "2MB"), window(12, 14, "256KB")];
+        assert_eq!(find_window(&windows, MON, at(13, 0)).unwrap().limit.as_deref(), Some("256KB"));
+        assert_eq!(find_window(&windows, MON, at(9, 0)).unwrap().limit.as_deref(), Some("2MB"));
+        assert!(find_window(&windows, MON, at(21, 0)).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::commands::{self, DOWNLOAD_PROCESSES};
+use crate::database;
+use crate::logger;
+use crate::rate_schedule::{self, RateWindow};
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Every day of the week (bit 0 = Monday ... bit 6 = Sunday)
+pub const ALL_DAYS: u8 = 0b111_1111;
+
+fn all_days() -> u8 {
+    ALL_DAYS
+}
+
+/// A day-of-week/time-of-day rule with its own download and upload caps
+/// `days` is a bitmask with bit 0 = Monday. `start`/`end` are local "HH:MM" times;
+/// when `end` is earlier than `start` the rule runs past midnight and the part after
+/// midnight belongs to the day it started on. Empty limits mean unlimited.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRule {
+    #[serde(default = "all_days")]
+    pub days: u8,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub rate_limit: String,
+    #[serde(default)]
+    pub upload_limit: String,
+}
+
+/// Parse "HH:MM" into minutes since midnight
+fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    if hours < 24 && minutes < 60 {
+        Some(hours * 60 + minutes)
+    } else {
+        None
+    }
+}
+
+fn has_day(days: u8, weekday: u32) -> bool {
+    days & (1 << weekday) != 0
+}
+
+impl ScheduleRule {
+    /// Whether the rule applies on `weekday` (0 = Monday) at `minute` past midnight
+    /// Rules with an unparseable start or end never match.
+    pub fn matches(&self, weekday: u32, minute: u32) -> bool {
+        let (start, end) = match (parse_time(&self.start), parse_time(&self.end)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return false,
+        };
+        let yesterday = (weekday + 6) % 7;
+
+        if start == end {
+            // Same start and end covers the whole day
+            has_day(self.days, weekday)
+        } else if start < end {
+            has_day(self.days, weekday) && minute >= start && minute < end
+        } else {
+            // Overnight rule: the evening part runs on its own day, the morning part on the next
+            (has_day(self.days, weekday) && minute >= start)
+                || (has_day(self.days, yesterday) && minute < end)
+        }
+    }
+}
+
+/// The `bandwidthSchedule` setting: a list of rules where the last match wins
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct BandwidthSchedule {
+    pub rules: Vec<ScheduleRule>,
+}
+
+impl BandwidthSchedule {
+    /// Find the rule that applies on `weekday` (0 = Monday) at `minute` past midnight
+    pub fn find_rule(&self, weekday: u32, minute: u32) -> Option<&ScheduleRule> {
+        self.rules.iter().rev().find(|r| r.matches(weekday, minute))
+    }
+}
+
+// Currently applied rule (None = no rule matches, downloads use their own limits)
+lazy_static::lazy_static! {
+    static ref ACTIVE_RULE: RwLock<Option<ScheduleRule>> = RwLock::new(None);
+}
+
+/// The bandwidth schedule rule currently in effect, if any
+pub fn active_rule() -> Option<ScheduleRule> {
+    ACTIVE_RULE.read().ok().and_then(|r| r.clone())
+}
+
+fn load_schedule() -> BandwidthSchedule {
+    database::get_setting("bandwidthSchedule")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// The bandwidth schedule with the `rateSchedule` windows folded in as every-day rules
+/// Both settings are read on every tick, so edits apply without a restart.
+fn effective_schedule(windows: &[RateWindow]) -> BandwidthSchedule {
+    BandwidthSchedule { rules: rate_schedule::merge_windows(windows, load_schedule().rules) }
+}
+
+/// Set up the background task that applies the `bandwidthSchedule` setting
+pub fn setup_bandwidth_schedule(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+
+            let now = chrono::Local::now();
+            let weekday = now.weekday().num_days_from_monday();
+            let minute = now.hour() * 60 + now.minute();
+            let windows = rate_schedule::load_windows();
+            let rule = effective_schedule(&windows).find_rule(weekday, minute).cloned();
+
+            // Only the limits matter - a different rule with the same caps needs no restart
+            let limits = |r: &Option<ScheduleRule>| r.as_ref().map(|r| (r.rate_limit.clone(), r.upload_limit.clone()));
+            let changed = {
+                let mut active = match ACTIVE_RULE.write() {
+                    Ok(active) => active,
+                    Err(_) => continue,
+                };
+                let changed = limits(&active) != limits(&rule);
+                *active = rule.clone();
+                changed
+            };
+            if !changed {
+                continue;
+            }
+
+            let reason = match &rule {
+                Some(r) => format!("Bandwidth rule {}-{}", r.start, r.end),
+                None => "No bandwidth rule active".to_string(),
+            };
+            logger::log_info("schedule", &format!(
+                "{} (download: {}, upload: {})",
+                reason,
+                rule.as_ref().map(|r| r.rate_limit.as_str()).filter(|l| !l.is_empty()).unwrap_or("unlimited"),
+                rule.as_ref().map(|r| r.upload_limit.as_str()).filter(|l| !l.is_empty()).unwrap_or("unlimited"),
+            ));
+
+            let _ = app.emit("bandwidth-schedule-applied", serde_json::json!({
+                "rule": rule,
+                "reason": reason,
+            }));
+            // The download cap now in effect, whether it came from a rate window or a bandwidth rule
+            let _ = app.emit("rate-schedule-applied", serde_json::json!({
+                "limit": rule.as_ref().map(|r| r.rate_limit.clone()).filter(|l| !l.is_empty()),
+                "window": rate_schedule::find_window(&windows, weekday, minute),
+                "reason": reason,
+            }));
+
+            // The Go binary only reads --limit/--bt-upload-limit at startup, so restart running downloads
+            let running: Vec<String> = DOWNLOAD_PROCESSES.lock().await.keys().cloned().collect();
+            for download_id in running {
+                if let Err(e) = commands::restart_download_internal(download_id.clone(), app.clone()).await {
+                    logger::log_error("schedule", &format!("Failed to apply limits to {}: {}", download_id, e));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    pub const MON: u32 = 0;
+    pub const TUE: u32 = 1;
+    pub const SUN: u32 = 6;
+
+    /// Minutes past midnight
+    pub fn at(hours: u32, minutes: u32) -> u32 {
+        hours * 60 + minutes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{at, MON, SUN, TUE};
+    use super::*;
+
+    fn rule(days: u8, start: &str, end: &str, rate_limit: &str) -> ScheduleRule {
+        ScheduleRule {
+            days,
+            start: start.to_string(),
+            end: end.to_string(),
+            rate_limit: rate_limit.to_string(),
+            upload_limit: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_daytime_rule() {
+        let schedule = BandwidthSchedule { rules: vec![rule(ALL_DAYS, "09:00", "17:30", "1MB")] };
+        assert!(schedule.find_rule(MON, at(9, 0)).is_some());
+        assert!(schedule.find_rule(MON, at(17, 29)).is_some());
+        assert!(schedule.find_rule(MON, at(17, 30)).is_none());
+        assert!(schedule.find_rule(MON, at(8, 59)).is_none());
+    }
+
+    #[test]
+    fn test_weekday_mask() {
+        // Monday to Friday only
+        let schedule = BandwidthSchedule { rules: vec![rule(0b001_1111, "09:00", "17:00", "1MB")] };
+        assert!(schedule.find_rule(MON, at(12, 0)).is_some());
+        assert!(schedule.find_rule(SUN, at(12, 0)).is_none());
+    }
+
+    #[test]
+    fn test_rule_across_midnight() {
+        // Monday night only: 22:00 Monday until 06:00 Tuesday
+        let schedule = BandwidthSchedule { rules: vec![rule(1 << MON, "22:00", "06:00", "10MB")] };
+        assert!(schedule.find_rule(MON, at(22, 0)).is_some());
+        assert!(schedule.find_rule(MON, at(23, 59)).is_some());
+        assert!(schedule.find_rule(TUE, at(0, 0)).is_some());
+        assert!(schedule.find_rule(TUE, at(5, 59)).is_some());
+        assert!(schedule.find_rule(TUE, at(6, 0)).is_none());
+        // The morning part belongs to the previous day, not the current one
+        assert!(schedule.find_rule(MON, at(3, 0)).is_none());
+        assert!(schedule.find_rule(TUE, at(22, 0)).is_none());
+    }
+
+    #[test]
+    fn test_sunday_night_wraps_to_monday() {
+        let schedule = BandwidthSchedule { rules: vec![rule(1 << SUN, "23:00", "01:00", "5MB")] };
+        assert!(schedule.find_rule(SUN, at(23, 30)).is_some());
+        assert!(schedule.find_rule(MON, at(0, 30)).is_some());
+        assert!(schedule.find_rule(MON, at(23, 30)).is_none());
+    }
+
+    #[test]
+    fn test_last_match_wins_and_invalid_rules_skipped() {
+        let schedule = BandwidthSchedule { rules: vec![
+            rule(ALL_DAYS, "08:00", "20:00", "2MB"),
+            rule(ALL_DAYS, "12:00", "14:00", "256KB"),
+            rule(ALL_DAYS, "25:00", "14:00", "1KB"),
+        ] };
+        assert_eq!(schedule.find_rule(MON, at(13, 0)).unwrap().rate_limit, "256KB");
+        assert_eq!(schedule.find_rule(MON, at(10, 0)).unwrap().rate_limit, "2MB");
+    }
+
+    #[test]
+    fn test_deserialize_setting() {
+        let value = serde_json::json!([
+            { "days": 31, "start": "09:00", "end": "17:00", "rateLimit": "1MB", "uploadLimit": "100KB" },
+            { "start": "00:00", "end": "00:00" }
+        ]);
+        let schedule: BandwidthSchedule = serde_json::from_value(value).unwrap();
+        assert_eq!(schedule.rules.len(), 2);
+        assert_eq!(schedule.rules[0].upload_limit, "100KB");
+        assert_eq!(schedule.rules[1].days, ALL_DAYS);
+        assert!(schedule.rules[1].rate_limit.is_empty());
+    }
+}
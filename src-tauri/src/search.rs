@@ -0,0 +1,130 @@
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Results returned when the caller doesn't pass a limit
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Escape `%`, `_` and the escape character itself for a `LIKE ... ESCAPE '\'` pattern
+fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Case-insensitive substring search over source and output (and so the file name) of
+/// active downloads and history, newest first. An ID present in both tables is returned once,
+/// from whichever row is more recent.
+/// LIKE only folds ASCII case, which covers URLs, paths and magnet info hashes.
+pub fn search_downloads(conn: &Connection, query: &str, limit: usize) -> rusqlite::Result<Vec<serde_json::Value>> {
+    let query = query.trim();
+    if query.is_empty() || limit == 0 {
+        return Ok(Vec::new());
+    }
+    
+    let mut stmt = conn.prepare(
+        "SELECT id, source, output, type, status, total, COALESCE(updated_at, started_at, 0) AS ts, 0 AS in_history
+         FROM downloads
+         WHERE source LIKE ?1 ESCAPE '\\' OR output LIKE ?1 ESCAPE '\\'
+         UNION ALL
+         SELECT id, source, output, type, 'completed', size, COALESCE(completed_at, 0) AS ts, 1 AS in_history
+         FROM download_history
+         WHERE source LIKE ?1 ESCAPE '\\' OR output LIKE ?1 ESCAPE '\\'
+         ORDER BY ts DESC, in_history ASC"
+    )?;
+    
+    let rows = stmt.query_map([like_pattern(query)], |row| {
+        let output: String = row.get(2)?;
+        let file_name = Path::new(&output).file_name().map(|n| n.to_string_lossy().to_string());
+        Ok(serde_json::json!({
+            "id": row.get::<_, String>(0)?,
+            "source": row.get::<_, String>(1)?,
+            "output": output,
+            "fileName": file_name,
+            "type": row.get::<_, String>(3)?,
+            "status": row.get::<_, String>(4)?,
+            "size": row.get::<_, Option<i64>>(5)?,
+            "updatedAt": row.get::<_, i64>(6)?,
+            "inHistory": row.get::<_, i64>(7)? != 0,
+        }))
+    })?;
+    
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    for row in rows {
+        let item = row?;
+        let id = item["id"].as_str().unwrap_or_default().to_string();
+        if seen.insert(id) {
+            results.push(item);
+            if results.len() >= limit {
+                break;
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+    
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        database::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO downloads (id, source, output, type, status, total, started_at, updated_at) VALUES
+                ('a', 'https://example.com/files/Ubuntu-24.04.iso', '/home/me/Downloads', 'http', 'downloading', 100, 10, 50),
+                ('b', 'magnet:?xt=urn:btih:C12FE1C06BBA254A9DC9F519B335AA7C1367A88A&dn=Season', '/home/me/Videos', 'magnet', 'paused', 0, 20, 20),
+                ('both', 'https://example.com/report.pdf', '/home/me/Docs/report.pdf', 'http', 'completed', 10, 5, 5);
+             INSERT INTO download_history (id, source, output, type, size, completed_at) VALUES
+                ('h1', 'https://mirror.org/archive_2023.tar.gz', '/home/me/Downloads/archive_2023.tar.gz', 'http', 500, 40),
+                ('both', 'https://example.com/report.pdf', '/home/me/Docs/report.pdf', 'http', 10, 30);"
+        ).unwrap();
+        conn
+    }
+    
+    fn ids(results: &[serde_json::Value]) -> Vec<&str> {
+        results.iter().map(|r| r["id"].as_str().unwrap()).collect()
+    }
+    
+    #[test]
+    fn test_partial_case_insensitive_match() {
+        let conn = test_db();
+        assert_eq!(ids(&search_downloads(&conn, "ubuntu", 10).unwrap()), vec!["a"]);
+        assert_eq!(ids(&search_downloads(&conn, "ARCHIVE_20", 10).unwrap()), vec!["h1"]);
+        assert!(search_downloads(&conn, "nothing-like-this", 10).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_magnet_hash_match() {
+        let conn = test_db();
+        let results = search_downloads(&conn, "c12fe1c06bba254a9dc9f519b335aa7c1367a88a", 10).unwrap();
+        assert_eq!(ids(&results), vec!["b"]);
+    }
+    
+    #[test]
+    fn test_merged_deduplicated_by_recency() {
+        let conn = test_db();
+        let results = search_downloads(&conn, "example.com", 10).unwrap();
+        assert_eq!(ids(&results), vec!["a", "both"]);
+        // The history row is newer than the stale downloads row
+        assert_eq!(results[1]["inHistory"], true);
+        assert_eq!(ids(&search_downloads(&conn, "/home/me", 2).unwrap()), vec!["a", "h1"]);
+    }
+    
+    #[test]
+    fn test_wildcards_are_literal() {
+        let conn = test_db();
+        // `_` would match any character if it weren't escaped
+        assert_eq!(ids(&search_downloads(&conn, "archive_", 10).unwrap()), vec!["h1"]);
+        assert!(search_downloads(&conn, "%", 10).unwrap().is_empty());
+    }
+}
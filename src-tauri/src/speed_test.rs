@@ -0,0 +1,274 @@
+use crate::logger;
+use futures_util::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Endpoints used when the iris binary isn't available
+pub const DEFAULT_DOWNLOAD_URL: &str = "https://speed.cloudflare.com/__down?bytes=100000000";
+pub const DEFAULT_UPLOAD_URL: &str = "https://speed.cloudflare.com/__up";
+const DEFAULT_LATENCY_URL: &str = "https://speed.cloudflare.com/__down?bytes=0";
+
+/// How long the download phase runs before the measured rate is taken
+const DOWNLOAD_DURATION: Duration = Duration::from_secs(10);
+/// Payload posted during the upload phase
+const UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+const LATENCY_SAMPLES: usize = 5;
+
+/// Where a Rust-side speed test downloads from and uploads to
+#[derive(Debug, Clone)]
+pub struct SpeedTestTargets {
+    pub download_url: String,
+    pub upload_url: Option<String>,
+    pub latency_url: String,
+    /// Host being measured, recorded with the result (None for the general internet test)
+    pub target: Option<String>,
+}
+
+impl SpeedTestTargets {
+    /// Targets for measuring throughput to a specific endpoint
+    pub fn custom(download_url: &str, upload_url: Option<&str>) -> Result<Self, String> {
+        let parsed = reqwest::Url::parse(download_url)
+            .map_err(|e| format!("Invalid download URL: {}", e))?;
+        if parsed.scheme() != "http" && parsed.scheme() != "https" {
+            return Err("Download URL must be http(s)".to_string());
+        }
+        if let Some(upload_url) = upload_url {
+            reqwest::Url::parse(upload_url).map_err(|e| format!("Invalid upload URL: {}", e))?;
+        }
+
+        Ok(SpeedTestTargets {
+            download_url: download_url.to_string(),
+            upload_url: upload_url.map(|u| u.to_string()),
+            latency_url: download_url.to_string(),
+            target: parsed.host_str().map(|h| h.to_string()),
+        })
+    }
+}
+
+impl Default for SpeedTestTargets {
+    fn default() -> Self {
+        SpeedTestTargets {
+            download_url: DEFAULT_DOWNLOAD_URL.to_string(),
+            upload_url: Some(DEFAULT_UPLOAD_URL.to_string()),
+            latency_url: DEFAULT_LATENCY_URL.to_string(),
+            target: None,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    // Running Rust-side tests, so stop_speed_test can cancel them like iris processes
+    static ref RUNNING_TESTS: Mutex<HashMap<String, tokio::task::AbortHandle>> = Mutex::new(HashMap::new());
+    // Iris tests stopped by the user, so their monitor reports a cancellation rather than a failure
+    static ref CANCELLED_TESTS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// Record that `test_id` was stopped on purpose
+pub fn mark_cancelled(test_id: &str) {
+    if let Ok(mut cancelled) = CANCELLED_TESTS.lock() {
+        cancelled.insert(test_id.to_string());
+    }
+}
+
+/// Whether `test_id` was stopped on purpose, clearing the mark
+pub fn take_cancelled(test_id: &str) -> bool {
+    CANCELLED_TESTS.lock().map(|mut cancelled| cancelled.remove(test_id)).unwrap_or(false)
+}
+
+/// Report a stopped test: `speed-test-complete` with `cancelled: true` and no result to save
+pub fn emit_cancelled(app: &AppHandle, test_id: &str) {
+    let _ = app.emit("speed-test-complete", serde_json::json!({
+        "testId": test_id,
+        "code": null,
+        "cancelled": true,
+    }));
+}
+
+/// Start a speed test in the background, emitting the same events as the iris monitor
+/// `fallback` marks results of the built-in test standing in for iris (less accurate).
+pub fn spawn(app: AppHandle, test_id: String, targets: SpeedTestTargets, fallback: bool) {
+    let id = test_id.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let result = run(&app, &test_id, &targets, fallback).await;
+        if let Ok(mut running) = RUNNING_TESTS.lock() {
+            running.remove(&test_id);
+        }
+
+        match result {
+            Ok(result) => {
+                let _ = app.emit("speed-test-update", result.clone());
+                let _ = app.emit("speed-test-complete", serde_json::json!({
+                    "testId": test_id,
+                    "code": 0,
+                    "result": result,
+                    "fallback": fallback,
+                }));
+            }
+            Err(e) => {
+                logger::log_error("speed_test", &format!("Speed test {} failed: {}", test_id, e));
+                let _ = app.emit("speed-test-error", serde_json::json!({
+                    "testId": test_id,
+                    "error": e,
+                }));
+                let _ = app.emit("speed-test-complete", serde_json::json!({
+                    "testId": test_id,
+                    "code": 1,
+                    "fallback": fallback,
+                }));
+            }
+        }
+    });
+
+    if let Ok(mut running) = RUNNING_TESTS.lock() {
+        running.insert(id, task.inner().abort_handle());
+    }
+}
+
+/// Cancel a running Rust-side test; returns false if no such test is running
+pub fn cancel(test_id: &str) -> bool {
+    match RUNNING_TESTS.lock().ok().and_then(|mut running| running.remove(test_id)) {
+        Some(handle) => {
+            handle.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+async fn run(app: &AppHandle, test_id: &str, targets: &SpeedTestTargets, fallback: bool) -> Result<serde_json::Value, String> {
+    let client = reqwest::Client::builder()
+        .user_agent("ACCELARA-SpeedTest/1.0")
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    logger::log_info("speed_test", &format!("Running built-in speed test {} against {}", test_id, targets.download_url));
+
+    // Latency
+    let latency_ms = measure_latency(&client, &targets.latency_url).await;
+    let latency = match &latency_ms {
+        Some(samples) if !samples.is_empty() => {
+            let average = samples.iter().sum::<f64>() / samples.len() as f64;
+            let min = samples.iter().cloned().fold(f64::MAX, f64::min);
+            let max = samples.iter().cloned().fold(0.0, f64::max);
+            serde_json::json!({
+                "average": average.round() as i64,
+                "min": min.round() as i64,
+                "max": max.round() as i64,
+                "google_ping": average.round() as i64,
+                "googlePing": average.round() as i64,
+            })
+        }
+        _ => serde_json::Value::Null,
+    };
+    let _ = app.emit("speed-test-update", serde_json::json!({
+        "type": "latency",
+        "latency": latency,
+        "progress": 33.0,
+        "fallback": fallback,
+    }));
+
+    // Download
+    let download_speed = measure_download(&client, &targets.download_url).await?;
+    let _ = app.emit("speed-test-update", serde_json::json!({
+        "type": "download",
+        "download_speed": download_speed,
+        "downloadSpeed": download_speed,
+        "progress": 66.0,
+        "fallback": fallback,
+    }));
+
+    // Upload (optional for custom targets)
+    let upload_speed = match &targets.upload_url {
+        Some(url) => measure_upload(&client, url).await?,
+        None => 0.0,
+    };
+    let _ = app.emit("speed-test-update", serde_json::json!({
+        "type": "upload",
+        "upload_speed": upload_speed,
+        "uploadSpeed": upload_speed,
+        "progress": 100.0,
+        "fallback": fallback,
+    }));
+
+    Ok(serde_json::json!({
+        "type": "full",
+        "status": "completed",
+        "download_speed": download_speed,
+        "upload_speed": upload_speed,
+        "downloadSpeed": download_speed,
+        "uploadSpeed": upload_speed,
+        "latency": latency,
+        "location": serde_json::Value::Null,
+        "progress": 100.0,
+        "fallback": fallback,
+        "target": targets.target,
+    }))
+}
+
+/// Round-trip times (ms) of a few HEAD requests; the first one also pays for the TLS handshake
+async fn measure_latency(client: &reqwest::Client, url: &str) -> Option<Vec<f64>> {
+    let mut samples = Vec::new();
+    for _ in 0..LATENCY_SAMPLES {
+        let start = Instant::now();
+        // Any response counts - some servers reject HEAD but still answer promptly
+        if client.head(url).send().await.is_ok() {
+            samples.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+    // Drop the handshake-inflated first sample when we have others
+    if samples.len() > 1 {
+        samples.remove(0);
+    }
+    if samples.is_empty() { None } else { Some(samples) }
+}
+
+/// Download for up to DOWNLOAD_DURATION and return the average rate in bytes/sec
+async fn measure_download(client: &reqwest::Client, url: &str) -> Result<f64, String> {
+    let response = client.get(url)
+        .timeout(DOWNLOAD_DURATION + Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Download test failed: {}", e))?;
+
+    let start = Instant::now();
+    let mut received: u64 = 0;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => received += chunk.len() as u64,
+            // A timeout after plenty of data is still a usable measurement
+            Err(e) if received == 0 => return Err(format!("Download test failed: {}", e)),
+            Err(_) => break,
+        }
+        if start.elapsed() >= DOWNLOAD_DURATION {
+            break;
+        }
+    }
+
+    Ok(rate(received, start.elapsed()))
+}
+
+/// Post a fixed payload and return the rate in bytes/sec
+async fn measure_upload(client: &reqwest::Client, url: &str) -> Result<f64, String> {
+    let payload = vec![0u8; UPLOAD_BYTES];
+    let start = Instant::now();
+    client.post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .body(payload)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Upload test failed: {}", e))?;
+
+    Ok(rate(UPLOAD_BYTES as u64, start.elapsed()))
+}
+
+fn rate(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 { bytes as f64 / secs } else { 0.0 }
+}
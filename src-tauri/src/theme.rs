@@ -14,35 +14,26 @@ pub fn setup_theme_monitoring(app: AppHandle) {
     // Monitor theme changes
     #[cfg(target_os = "macos")]
     {
-        let app_clone = app.clone();
-        tauri::async_runtime::spawn(async move {
-            use std::process::Command;
-            
-            loop {
-                tokio::time::sleep(Duration::from_secs(1)).await;
+        // The system posts a distributed notification whenever the appearance flips;
+        // only poll if we couldn't subscribe to it
+        if let Err(e) = macos::observe_theme_changes(app.clone()) {
+            crate::logger::log_warning("theme", &format!("Theme change observer unavailable ({}), falling back to polling", e));
+            let app_clone = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut last_theme = String::new();
                 
-                let output = Command::new("defaults")
-                    .args(&["read", "-g", "AppleInterfaceStyle"])
-                    .output();
-                
-                let current_theme = match output {
-                    Ok(result) if result.status.success() => {
-                        let theme = String::from_utf8_lossy(&result.stdout).trim().to_lowercase();
-                        if theme == "dark" {
-                            "dark"
-                        } else {
-                            "light"
+                loop {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    
+                    if let Ok(current_theme) = crate::commands::get_system_theme().await {
+                        if current_theme != last_theme {
+                            let _ = app_clone.emit("system-theme-changed", current_theme.as_str());
+                            last_theme = current_theme;
                         }
                     }
-                    _ => "light",
-                };
-                
-                // Emit if theme changed
-                // Note: This is a simple polling approach
-                // For better performance, we could use FSEvents on macOS
-                let _ = app_clone.emit("system-theme-changed", current_theme);
-            }
-        });
+                }
+            });
+        }
     }
     
     #[cfg(target_os = "windows")]
@@ -118,3 +109,62 @@ pub fn setup_theme_monitoring(app: AppHandle) {
     }
 }
 
+#[cfg(target_os = "macos")]
+mod macos {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::sync::OnceLock;
+    use tauri::{AppHandle, Emitter};
+    
+    const THEME_CHANGED_NOTIFICATION: &str = "AppleInterfaceThemeChangedNotification";
+    
+    // Handle used by the Objective-C callback, which can't capture state
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+    
+    extern "C" fn theme_changed(_this: &Object, _cmd: Sel, _notification: id) {
+        if let Some(app) = APP.get() {
+            let app = app.clone();
+            // Delivered on the main thread - read the new value off it
+            tauri::async_runtime::spawn(async move {
+                if let Ok(theme) = crate::commands::get_system_theme().await {
+                    let _ = app.emit("system-theme-changed", theme);
+                }
+            });
+        }
+    }
+    
+    /// Register an NSDistributedNotificationCenter observer for appearance changes
+    /// Must be called on the main thread, whose run loop delivers the notifications.
+    pub fn observe_theme_changes(app: AppHandle) -> Result<(), String> {
+        if APP.set(app).is_err() {
+            return Err("Theme observer already registered".to_string());
+        }
+        
+        let mut decl = ClassDecl::new("AccelaraThemeObserver", class!(NSObject))
+            .ok_or_else(|| "Failed to declare observer class".to_string())?;
+        unsafe {
+            decl.add_method(sel!(themeChanged:), theme_changed as extern "C" fn(&Object, Sel, id));
+        }
+        let observer_class = decl.register();
+        
+        unsafe {
+            let center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+            if center == nil {
+                return Err("NSDistributedNotificationCenter unavailable".to_string());
+            }
+            // Kept for the lifetime of the app, so it is never released
+            let observer: id = msg_send![observer_class, new];
+            let name = NSString::alloc(nil).init_str(THEME_CHANGED_NOTIFICATION);
+            let _: () = msg_send![center,
+                addObserver: observer
+                selector: sel!(themeChanged:)
+                name: name
+                object: nil];
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use crate::commands;
+use crate::database;
+use crate::logger;
+use std::time::Duration;
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::AppHandle;
+
+const TRAY_ID: &str = "main";
+
+/// How often the tooltip is refreshed with the active downloads
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Tooltip text for `active` running downloads at a combined `speed` (bytes/s)
+fn tray_summary(active: u64, speed: u64) -> String {
+    match active {
+        0 => "ACCELARA - no active downloads".to_string(),
+        1 => format!("ACCELARA - 1 download at {}/s", commands::format_bytes(speed)),
+        n => format!("ACCELARA - {} downloads at {}/s", n, commands::format_bytes(speed)),
+    }
+}
+
+/// (count, combined speed) of the downloads currently downloading
+fn active_downloads() -> (u64, u64) {
+    database::get_connection()
+        .ok()
+        .and_then(|conn| conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(speed), 0) FROM downloads WHERE status = 'downloading'",
+            [],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        ).ok())
+        .map(|(count, speed)| (count.max(0) as u64, speed.max(0) as u64))
+        .unwrap_or((0, 0))
+}
+
+/// Set up the tray icon and its menu (Show, Pause All, Resume All, Quit)
+/// Some Linux desktops have no tray; the app then just runs without one.
+pub fn setup_tray(app: &AppHandle) {
+    if let Err(e) = build_tray(app) {
+        logger::log_warning("tray", &format!("System tray unavailable: {}", e));
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let tray = match app.tray_by_id(TRAY_ID) {
+                Some(tray) => tray,
+                None => return,
+            };
+            let (active, speed) = active_downloads();
+            let _ = tray.set_tooltip(Some(tray_summary(active, speed)));
+            // Only shown next to the icon on macOS
+            let title = if active > 0 { Some(format!("{}/s", commands::format_bytes(speed))) } else { None };
+            let _ = tray.set_title(title);
+        }
+    });
+}
+
+fn build_tray(app: &AppHandle) -> tauri::Result<()> {
+    let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
+    let pause_all = MenuItem::with_id(app, "pause_all", "Pause All", true, None::<&str>)?;
+    let resume_all = MenuItem::with_id(app, "resume_all", "Resume All", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show, &pause_all, &resume_all, &quit])?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tray_summary(0, 0))
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| {
+            let app = app.clone();
+            let id = event.id.as_ref().to_string();
+            tauri::async_runtime::spawn(async move {
+                let result = match id.as_str() {
+                    "show" => commands::show_window(app).await,
+                    "pause_all" => commands::pause_all_downloads(app).await.map(|_| ()),
+                    "resume_all" => commands::resume_all_downloads(app).await.map(|_| ()),
+                    "quit" => commands::quit_app(app).await,
+                    _ => Ok(()),
+                };
+                if let Err(e) = result {
+                    logger::log_error("tray", &format!("Tray action {} failed: {}", id, e));
+                }
+            });
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                let app = tray.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = commands::show_window(app).await;
+                });
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tray_summary() {
+        assert_eq!(tray_summary(0, 0), "ACCELARA - no active downloads");
+        assert_eq!(tray_summary(1, 2048), "ACCELARA - 1 download at 2.00 KB/s");
+        assert_eq!(tray_summary(3, 5 * 1024 * 1024), "ACCELARA - 3 downloads at 5.00 MB/s");
+    }
+}
@@ -1,9 +1,18 @@
 use crate::updater;
 use crate::database;
 use crate::logger;
+use crate::notifications;
+use crate::quiet_hours;
 use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+// Set while an update check is running, so overlapping requests don't each hit GitHub
+static CHECK_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Name of the file (in the app data dir) holding the last update check result
+const UPDATE_STATUS_FILE: &str = "update_status.json";
+
 /// Set up automatic update checking
 pub fn setup_update_checking(app: AppHandle) {
     let app_clone = app.clone();
@@ -14,6 +23,7 @@ pub fn setup_update_checking(app: AppHandle) {
         tokio::time::sleep(Duration::from_secs(5)).await;
         
         if should_auto_check() {
+            wait_for_quiet_hours_end().await;
             logger::log_info("update_manager", "Auto-checking for updates on startup...");
             check_and_notify(&app_clone).await;
         }
@@ -30,6 +40,7 @@ pub fn setup_update_checking(app: AppHandle) {
             tokio::time::sleep(Duration::from_secs(interval_secs)).await;
             
             if should_auto_check() {
+                wait_for_quiet_hours_end().await;
                 logger::log_info("update_manager", "Periodic update check...");
                 check_and_notify(&app_clone).await;
             }
@@ -37,6 +48,16 @@ pub fn setup_update_checking(app: AppHandle) {
     });
 }
 
+/// Defer scheduled checks (and the update prompt they can raise) until quiet hours are over
+async fn wait_for_quiet_hours_end() {
+    if quiet_hours::is_quiet_now() {
+        logger::log_info("update_manager", "Quiet hours active, deferring update check");
+        while quiet_hours::is_quiet_now() {
+            tokio::time::sleep(Duration::from_secs(300)).await;
+        }
+    }
+}
+
 /// Check if auto-check is enabled
 fn should_auto_check() -> bool {
     if let Ok(conn) = database::get_connection() {
@@ -71,9 +92,54 @@ fn get_check_interval() -> u64 {
     24
 }
 
+/// Run an update check now, unless one is already in progress (then returns None)
+/// Emits `update-check-started`/`update-check-complete` and records the result for `get_update_status`.
+pub async fn check_now(app: &AppHandle) -> Option<updater::UpdateCheckResult> {
+    if CHECK_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        logger::log_info("update_manager", "Update check already in progress, skipping");
+        return None;
+    }
+    
+    let _ = app.emit("update-check-started", serde_json::json!({}));
+    let result = updater::check_for_updates().await;
+    save_update_status(&result);
+    CHECK_IN_PROGRESS.store(false, Ordering::SeqCst);
+    
+    let _ = app.emit("update-check-complete", &result);
+    Some(result)
+}
+
+/// Whether a check is running plus the last recorded result
+pub fn get_update_status() -> serde_json::Value {
+    let saved: serde_json::Value = std::fs::read_to_string(crate::paths::app_data_dir().join(UPDATE_STATUS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    
+    serde_json::json!({
+        "checking": CHECK_IN_PROGRESS.load(Ordering::SeqCst),
+        "lastCheckedAt": saved.get("checkedAt").cloned().unwrap_or(serde_json::Value::Null),
+        "result": saved.get("result").cloned().unwrap_or(serde_json::Value::Null),
+    })
+}
+
+fn save_update_status(result: &updater::UpdateCheckResult) {
+    let status = serde_json::json!({
+        "checkedAt": chrono::Utc::now().timestamp(),
+        "result": result,
+    });
+    let path = crate::paths::app_data_dir().join(UPDATE_STATUS_FILE);
+    if let Err(e) = std::fs::write(&path, status.to_string()) {
+        logger::log_warning("update_manager", &format!("Failed to save update status: {}", e));
+    }
+}
+
 /// Check for updates and emit notification if available
 async fn check_and_notify(app: &AppHandle) {
-    let result = updater::check_for_updates().await;
+    let result = match check_now(app).await {
+        Some(result) => result,
+        None => return,
+    };
     
     if let Some(error) = &result.error {
         logger::log_error("update_manager", &format!("Update check failed: {}", error));
@@ -91,38 +157,10 @@ async fn check_and_notify(app: &AppHandle) {
             "release_info": result.release_info,
         }));
         
-        // Show system notification
-        #[cfg(target_os = "macos")]
-        {
-            use std::process::Command;
-            let title = format!("ACCELARA Update Available");
-            let body = format!("Version {} is now available (you have {})", 
-                result.latest_version, result.current_version);
-            
-            let _ = Command::new("osascript")
-                .arg("-e")
-                .arg(format!(r#"display notification "{}" with title "{}""#, body, title))
-                .output();
-        }
-        
-        #[cfg(target_os = "windows")]
-        {
-            // Windows notifications require additional setup
-            // For now, we'll just emit the event
-        }
-        
-        #[cfg(target_os = "linux")]
-        {
-            use std::process::Command;
-            let title = "ACCELARA Update Available";
-            let body = format!("Version {} is now available (you have {})", 
-                result.latest_version, result.current_version);
-            
-            let _ = Command::new("notify-send")
-                .arg(title)
-                .arg(&body)
-                .output();
-        }
+        // Show system notification (held back during quiet hours)
+        let body = format!("Version {} is now available (you have {})", 
+            result.latest_version, result.current_version);
+        notifications::notify("ACCELARA Update Available", &body);
     } else {
         logger::log_info("update_manager", "No updates available");
     }
@@ -1,7 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::path::PathBuf;
-use dirs::home_dir;
 
 const GITHUB_REPO: &str = "mwangiiharun/accelara";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -14,6 +13,10 @@ pub struct ReleaseInfo {
     pub published_at: String,
     pub html_url: String,
     pub assets: Vec<ReleaseAsset>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -24,13 +27,126 @@ pub struct ReleaseAsset {
     pub content_type: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateCheckResult {
     pub has_update: bool,
     pub current_version: String,
     pub latest_version: String,
     pub release_info: Option<ReleaseInfo>,
     pub error: Option<String>,
+    /// Seconds until GitHub's rate limit resets, when that's why the check failed
+    #[serde(default)]
+    pub retry_after: Option<u64>,
+}
+
+/// Retries for a release check after a transient failure (waits 1s, 2s, 4s)
+const CHECK_RETRIES: u32 = 3;
+const CHECK_BACKOFF_BASE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Why a release check failed, which decides whether it's worth trying again
+#[derive(Debug, Clone, PartialEq)]
+enum CheckError {
+    /// Connection problems, timeouts and 5xx responses
+    Network(String),
+    /// GitHub's API rate limit is exhausted; seconds until it resets
+    RateLimited(u64),
+    /// Other error responses and unparseable bodies - retrying won't change the answer
+    Fatal(String),
+}
+
+impl CheckError {
+    fn message(&self) -> String {
+        match self {
+            CheckError::Network(e) | CheckError::Fatal(e) => e.clone(),
+            CheckError::RateLimited(secs) => format!("GitHub API rate limit exceeded, try again in {}s", secs),
+        }
+    }
+}
+
+/// How long to wait before retrying after the `failures`-th failed attempt, or None to give up
+fn retry_delay(error: &CheckError, failures: u32) -> Option<std::time::Duration> {
+    match error {
+        CheckError::Network(_) if (1..=CHECK_RETRIES).contains(&failures) => {
+            Some(CHECK_BACKOFF_BASE * 2u32.pow(failures - 1))
+        }
+        _ => None,
+    }
+}
+
+/// Run `attempt` until it succeeds or `retry_delay` says to stop, sleeping between tries
+async fn with_retries<T, F, Fut, S, SFut>(mut attempt: F, mut sleep: S) -> Result<T, CheckError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, CheckError>>,
+    S: FnMut(std::time::Duration) -> SFut,
+    SFut: std::future::Future<Output = ()>,
+{
+    use crate::logger;
+    
+    let mut failures = 0;
+    loop {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                failures += 1;
+                match retry_delay(&e, failures) {
+                    Some(delay) => {
+                        logger::log_warning("updater", &format!("Update check failed ({}), retry {}/{} in {}s",
+                            e.message(), failures, CHECK_RETRIES, delay.as_secs()));
+                        sleep(delay).await;
+                    }
+                    None => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+/// Seconds until the rate limit resets if `status`/`headers` say we've been rate limited
+/// GitHub answers 403 or 429 with `X-RateLimit-Remaining: 0` and `X-RateLimit-Reset` (epoch
+/// seconds) for the primary limit, and `Retry-After` for secondary limits.
+fn rate_limit_retry_after(status: reqwest::StatusCode, headers: &reqwest::header::HeaderMap, now: u64) -> Option<u64> {
+    if status != reqwest::StatusCode::FORBIDDEN && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let header = |name: &str| headers.get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    
+    if let Some(secs) = header("retry-after") {
+        return Some(secs);
+    }
+    if header("x-ratelimit-remaining") == Some(0) {
+        if let Some(reset) = header("x-ratelimit-reset") {
+            return Some(reset.saturating_sub(now));
+        }
+    }
+    None
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Build an HTTP client for the update server, honouring the `updateCaCertPath` setting
+/// (for enterprises that mirror the update server). Certificates are always verified:
+/// the sha256 check on downloaded updates relies on the checksum file arriving intact.
+fn update_client(timeout_secs: u64) -> Result<reqwest::Client, String> {
+    use crate::{database, utils};
+    
+    let ca_cert = database::get_setting("updateCaCertPath")
+        .and_then(|v| v.as_str().map(|s| s.to_string()));
+    
+    let builder = reqwest::Client::builder()
+        .user_agent("ACCELARA-Updater/1.0")
+        .timeout(std::time::Duration::from_secs(timeout_secs));
+    
+    utils::apply_tls_options(builder, ca_cert.as_deref(), false, "updater")?
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
 }
 
 /// Check for updates by querying GitHub Releases API
@@ -39,15 +155,10 @@ pub async fn check_for_updates() -> UpdateCheckResult {
     
     logger::log_info("updater", &format!("Checking for updates. Current version: {}", CURRENT_VERSION));
     
-    let client = reqwest::Client::builder()
-        .user_agent("ACCELARA-Updater/1.0")
-        .timeout(std::time::Duration::from_secs(10))
-        .build();
-    
-    let client = match client {
+    let client = match update_client(10) {
         Ok(c) => c,
         Err(e) => {
-            let error_msg = format!("Failed to create HTTP client: {}", e);
+            let error_msg = e;
             logger::log_error("updater", &error_msg);
             return UpdateCheckResult {
                 has_update: false,
@@ -55,68 +166,48 @@ pub async fn check_for_updates() -> UpdateCheckResult {
                 latest_version: CURRENT_VERSION.to_string(),
                 release_info: None,
                 error: Some(error_msg),
+                retry_after: None,
             };
         }
     };
     
-    let url = format!("https://api.github.com/repos/{}/releases/latest", GITHUB_REPO);
+    let allow_prerelease = crate::database::get_setting("allowPrerelease")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     
-    logger::log_info("updater", &format!("Fetching latest release from: {}", url));
-    
-    match client.get(&url).send().await {
-        Ok(response) => {
-            if !response.status().is_success() {
-                let status = response.status();
-                let error_msg = format!("GitHub API returned error: {}", status);
-                logger::log_error("updater", &error_msg);
-                return UpdateCheckResult {
-                    has_update: false,
-                    current_version: CURRENT_VERSION.to_string(),
-                    latest_version: CURRENT_VERSION.to_string(),
-                    release_info: None,
-                    error: Some(error_msg),
-                };
+    let fetched = with_retries(|| fetch_latest_release(&client, allow_prerelease), tokio::time::sleep).await;
+    match fetched {
+        Ok(release) => {
+            let latest_version = release.tag_name.trim_start_matches('v').to_string();
+            let current_version = CURRENT_VERSION.trim_start_matches('v').to_string();
+            
+            logger::log_info("updater", &format!("Latest release: {} (current: {})", latest_version, current_version));
+            
+            // Pre-release tags are only offered to users who opted in
+            let skipped_prerelease = !allow_prerelease && is_prerelease(&release);
+            if skipped_prerelease {
+                logger::log_info("updater", &format!("Ignoring pre-release {}", release.tag_name));
             }
+            let has_update = !skipped_prerelease
+                && compare_versions(&current_version, &latest_version) == Ordering::Less;
             
-            match response.json::<ReleaseInfo>().await {
-                Ok(release) => {
-                    let latest_version = release.tag_name.trim_start_matches('v').to_string();
-                    let current_version = CURRENT_VERSION.trim_start_matches('v').to_string();
-                    
-                    logger::log_info("updater", &format!("Latest release: {} (current: {})", latest_version, current_version));
-                    
-                    // Compare versions (simple string comparison, can be improved with semver)
-                    let has_update = compare_versions(&current_version, &latest_version) == Ordering::Less;
-                    
-                    if has_update {
-                        logger::log_info("updater", "Update available!");
-                    } else {
-                        logger::log_info("updater", "Already on latest version");
-                    }
-                    
-                    UpdateCheckResult {
-                        has_update,
-                        current_version: CURRENT_VERSION.to_string(),
-                        latest_version: release.tag_name.clone(),
-                        release_info: Some(release),
-                        error: None,
-                    }
-                }
-                Err(e) => {
-                    let error_msg = format!("Failed to parse release info: {}", e);
-                    logger::log_error("updater", &error_msg);
-                    UpdateCheckResult {
-                        has_update: false,
-                        current_version: CURRENT_VERSION.to_string(),
-                        latest_version: CURRENT_VERSION.to_string(),
-                        release_info: None,
-                        error: Some(error_msg),
-                    }
-                }
+            if has_update {
+                logger::log_info("updater", "Update available!");
+            } else {
+                logger::log_info("updater", "Already on latest version");
+            }
+            
+            UpdateCheckResult {
+                has_update,
+                current_version: CURRENT_VERSION.to_string(),
+                latest_version: release.tag_name.clone(),
+                release_info: Some(release),
+                error: None,
+                retry_after: None,
             }
         }
         Err(e) => {
-            let error_msg = format!("Failed to fetch release info: {}", e);
+            let error_msg = e.message();
             logger::log_error("updater", &error_msg);
             UpdateCheckResult {
                 has_update: false,
@@ -124,22 +215,82 @@ pub async fn check_for_updates() -> UpdateCheckResult {
                 latest_version: CURRENT_VERSION.to_string(),
                 release_info: None,
                 error: Some(error_msg),
+                retry_after: match e {
+                    CheckError::RateLimited(secs) => Some(secs),
+                    _ => None,
+                },
             }
         }
     }
 }
 
-/// Simple version comparison (handles semantic versioning)
+/// Fetch the newest release. `/releases/latest` never includes pre-releases, so when they're
+/// allowed we list recent releases and take the highest version instead.
+async fn fetch_latest_release(client: &reqwest::Client, allow_prerelease: bool) -> Result<ReleaseInfo, CheckError> {
+    use crate::logger;
+    
+    let url = if allow_prerelease {
+        format!("https://api.github.com/repos/{}/releases?per_page=20", GITHUB_REPO)
+    } else {
+        format!("https://api.github.com/repos/{}/releases/latest", GITHUB_REPO)
+    };
+    
+    logger::log_info("updater", &format!("Fetching latest release from: {}", url));
+    
+    let response = client.get(&url).send().await
+        .map_err(|e| CheckError::Network(format!("Failed to fetch release info: {}", e)))?;
+    let status = response.status();
+    if let Some(secs) = rate_limit_retry_after(status, response.headers(), unix_now()) {
+        return Err(CheckError::RateLimited(secs));
+    }
+    if status.is_server_error() {
+        return Err(CheckError::Network(format!("GitHub API returned error: {}", status)));
+    }
+    if !status.is_success() {
+        return Err(CheckError::Fatal(format!("GitHub API returned error: {}", status)));
+    }
+    
+    // A body cut off mid-transfer is a network problem; valid JSON of the wrong shape isn't
+    let body = response.bytes().await
+        .map_err(|e| CheckError::Network(format!("Failed to read release info: {}", e)))?;
+    
+    if !allow_prerelease {
+        return serde_json::from_slice::<ReleaseInfo>(&body)
+            .map_err(|e| CheckError::Fatal(format!("Failed to parse release info: {}", e)));
+    }
+    
+    let releases = serde_json::from_slice::<Vec<ReleaseInfo>>(&body)
+        .map_err(|e| CheckError::Fatal(format!("Failed to parse release info: {}", e)))?;
+    releases.into_iter()
+        .filter(|r| !r.draft)
+        .max_by(|a, b| compare_versions(a.tag_name.trim_start_matches('v'), b.tag_name.trim_start_matches('v')))
+        .ok_or_else(|| CheckError::Fatal("No releases found".to_string()))
+}
+
+/// Whether a release is a pre-release, either flagged on GitHub or by its tag
+fn is_prerelease(release: &ReleaseInfo) -> bool {
+    release.prerelease
+        || semver::Version::parse(release.tag_name.trim_start_matches('v'))
+            .map(|v| !v.pre.is_empty())
+            .unwrap_or(false)
+}
+
+/// Semantic version comparison: pre-releases sort below their release and build metadata is ignored
 /// Returns Ordering::Less if v1 < v2, Ordering::Greater if v1 > v2, Ordering::Equal if v1 == v2
+/// Versions that aren't valid semver (e.g. "3.1") fall back to comparing the numeric components.
 fn compare_versions(v1: &str, v2: &str) -> Ordering {
-    let v1_parts: Vec<u32> = v1
-        .split('.')
-        .map(|s| s.parse::<u32>().unwrap_or(0))
-        .collect();
-    let v2_parts: Vec<u32> = v2
-        .split('.')
-        .map(|s| s.parse::<u32>().unwrap_or(0))
-        .collect();
+    if let (Ok(a), Ok(b)) = (semver::Version::parse(v1), semver::Version::parse(v2)) {
+        return a.cmp_precedence(&b);
+    }
+    
+    let numeric_parts = |v: &str| -> Vec<u32> {
+        v.split(['-', '+']).next().unwrap_or("")
+            .split('.')
+            .map(|s| s.parse::<u32>().unwrap_or(0))
+            .collect()
+    };
+    let v1_parts = numeric_parts(v1);
+    let v2_parts = numeric_parts(v2);
     
     let max_len = v1_parts.len().max(v2_parts.len());
     
@@ -367,47 +518,249 @@ pub async fn install_update(file_path: &PathBuf) -> Result<(), String> {
     Ok(())
 }
 
+/// Number of times a failed update download is retried before giving up
+const UPDATE_DOWNLOAD_RETRIES: u32 = 3;
+
+/// Minimum time between `update-download-progress` events
+const UPDATE_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+// Set by cancel_update_download; checked between chunks of the in-flight transfer
+static UPDATE_DOWNLOAD_CANCELLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Stop the in-flight update download, keeping its `.part` file so the next attempt resumes
+pub fn cancel_update_download() {
+    UPDATE_DOWNLOAD_CANCELLED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn update_download_cancelled() -> bool {
+    UPDATE_DOWNLOAD_CANCELLED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// A downloaded update, and whether its SHA-256 was checked against the release's `.sha256` sidecar
+#[derive(Debug, Serialize, Clone)]
+pub struct UpdateDownload {
+    pub path: PathBuf,
+    pub verified: bool,
+}
+
+/// What the release says an asset should look like
+#[derive(Debug, Default)]
+struct ExpectedAsset {
+    size: Option<u64>,
+    sha256: Option<String>,
+}
+
+/// Look up the size of `asset_url` and its `<name>.sha256` sidecar in the recent releases
+/// Missing information is left as None; verification then only checks what is known.
+async fn expected_asset(client: &reqwest::Client, asset_url: &str) -> ExpectedAsset {
+    use crate::logger;
+    
+    let url = format!("https://api.github.com/repos/{}/releases?per_page=20", GITHUB_REPO);
+    let releases: Vec<ReleaseInfo> = match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => response.json().await.unwrap_or_default(),
+        Ok(response) => {
+            logger::log_warning("updater", &format!("Couldn't look up update asset: GitHub API returned {}", response.status()));
+            return ExpectedAsset::default();
+        }
+        Err(e) => {
+            logger::log_warning("updater", &format!("Couldn't look up update asset: {}", e));
+            return ExpectedAsset::default();
+        }
+    };
+    
+    let found = releases.iter().find_map(|release| {
+        release.assets.iter()
+            .find(|a| a.browser_download_url == asset_url)
+            .map(|asset| (release, asset))
+    });
+    let (release, asset) = match found {
+        Some(found) => found,
+        None => {
+            logger::log_warning("updater", &format!("Update asset not found in recent releases: {}", asset_url));
+            return ExpectedAsset::default();
+        }
+    };
+    
+    let sidecar_name = format!("{}.sha256", asset.name);
+    let sha256 = match release.assets.iter().find(|a| a.name == sidecar_name) {
+        Some(sidecar) => match client.get(&sidecar.browser_download_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                response.text().await.ok().and_then(|text| parse_sha256_sidecar(&text))
+            }
+            _ => None,
+        },
+        None => None,
+    };
+    
+    ExpectedAsset { size: Some(asset.size), sha256 }
+}
+
+/// Pull the hash out of a sidecar file, either bare or in `sha256sum` format (`<hash>  <name>`)
+fn parse_sha256_sidecar(text: &str) -> Option<String> {
+    let hash = text.split_whitespace().next()?.to_lowercase();
+    if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(hash)
+    } else {
+        None
+    }
+}
+
+/// Check a downloaded update against its expected size and hash
+/// Returns Ok(true) when the hash was checked, Ok(false) when there was no hash to check against.
+fn verify_update_file(path: &std::path::Path, expected: &ExpectedAsset) -> Result<bool, String> {
+    let size = std::fs::metadata(path)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read downloaded update: {}", e))?;
+    if let Some(expected_size) = expected.size {
+        if size != expected_size {
+            return Err(format!("Update size mismatch: got {} bytes, expected {}", size, expected_size));
+        }
+    }
+    
+    match &expected.sha256 {
+        Some(expected_hash) => {
+            let computed = crate::checksum::sha256_file(path, &std::sync::atomic::AtomicBool::new(false))?;
+            if computed != *expected_hash {
+                return Err(format!("Update checksum mismatch: got {}, expected {}", computed, expected_hash));
+            }
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
 /// Download update file to a temporary location
-pub async fn download_update(asset_url: &str, filename: &str) -> Result<PathBuf, String> {
+/// Data is written to `<filename>.part` first, so an interrupted download (or a retry after
+/// a stream error) continues from where it stopped with a Range request.
+pub async fn download_update(asset_url: &str, filename: &str, app: &tauri::AppHandle) -> Result<UpdateDownload, String> {
     use crate::logger;
-    use std::fs::File;
-    use std::io::Write;
+    
+    UPDATE_DOWNLOAD_CANCELLED.store(false, std::sync::atomic::Ordering::SeqCst);
     
     logger::log_info("updater", &format!("Downloading update from: {}", asset_url));
     
-    let client = reqwest::Client::builder()
-        .user_agent("ACCELARA-Updater/1.0")
-        .timeout(std::time::Duration::from_secs(300)) // 5 minutes for large files
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let client = update_client(300)?; // 5 minutes for large files
+    
+    // Kept in the app's cache directory rather than cluttering the user's Downloads
+    let downloads_dir = crate::paths::update_cache_dir();
+    
+    let file_path = downloads_dir.join(filename);
+    let part_path = downloads_dir.join(format!("{}.part", filename));
+    
+    let mut attempt = 0;
+    let total_size = loop {
+        match download_update_attempt(&client, asset_url, &part_path, app).await {
+            Ok(total_size) => break total_size,
+            Err(e) if attempt < UPDATE_DOWNLOAD_RETRIES && !update_download_cancelled() => {
+                attempt += 1;
+                let delay = crate::utils::backoff_with_jitter(std::time::Duration::from_secs(1), attempt);
+                logger::log_warning("updater", &format!("Update download failed ({}), retry {}/{} in {:.1}s",
+                    e, attempt, UPDATE_DOWNLOAD_RETRIES, delay.as_secs_f64()));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    
+    // Verify we got the whole file before handing it to the installer
+    let downloaded = std::fs::metadata(&part_path)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read downloaded update: {}", e))?;
+    if let Some(total_size) = total_size {
+        if downloaded != total_size {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(format!("Update download incomplete: got {} of {} bytes", downloaded, total_size));
+        }
+    }
+    
+    // Never hand the installer something that doesn't match what was published
+    let expected = expected_asset(&client, asset_url).await;
+    let verify_path = part_path.clone();
+    let verified = tokio::task::spawn_blocking(move || verify_update_file(&verify_path, &expected))
+        .await
+        .map_err(|e| format!("Update verification failed: {}", e))
+        .and_then(|r| r);
+    let verified = match verified {
+        Ok(verified) => verified,
+        Err(e) => {
+            logger::log_error("updater", &e);
+            let _ = std::fs::remove_file(&part_path);
+            return Err(e);
+        }
+    };
+    if !verified {
+        logger::log_warning("updater", "No .sha256 sidecar published for this update, only the size was checked");
+    }
+    
+    std::fs::rename(&part_path, &file_path)
+        .map_err(|e| format!("Failed to move downloaded update into place: {}", e))?;
+    
+    logger::log_info("updater", &format!("Update downloaded successfully to: {}", file_path.display()));
+    
+    Ok(UpdateDownload { path: file_path, verified })
+}
+
+/// One attempt at fetching the update into `part_path`, resuming from its current size
+/// Returns the full size of the asset when the server reported it
+async fn download_update_attempt(
+    client: &reqwest::Client,
+    asset_url: &str,
+    part_path: &std::path::Path,
+    app: &tauri::AppHandle,
+) -> Result<Option<u64>, String> {
+    use crate::logger;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tauri::Emitter;
+    
+    let existing = std::fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+    
+    let mut request = client.get(asset_url);
+    if existing > 0 {
+        logger::log_info("updater", &format!("Resuming update download from byte {}", existing));
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
     
-    let response = client
-        .get(asset_url)
+    let response = request
         .send()
         .await
         .map_err(|e| format!("Failed to download update: {}", e))?;
     
-    if !response.status().is_success() {
-        return Err(format!("Download failed with status: {}", response.status()));
-    }
+    let status = response.status();
     
-    let total_size = response.content_length().unwrap_or(0);
-    logger::log_info("updater", &format!("Update file size: {} bytes", total_size));
+    // Range past the end: the partial file is already complete
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(format!("Download failed with status: {}", status));
+    }
     
-    // Create downloads directory in home folder
-    let downloads_dir = if let Some(home) = home_dir() {
-        home.join("Downloads")
+    // 206 means the server honoured the Range request; anything else restarts from zero
+    let resuming = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let total_size = if resuming {
+        // Content-Range: bytes <start>-<end>/<total>
+        response.headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit('/').next())
+            .and_then(|v| v.parse::<u64>().ok())
     } else {
-        return Err("Could not determine home directory".to_string());
+        response.content_length()
     };
-    
-    let file_path = downloads_dir.join(filename);
-    
-    // Download file
-    let mut file = File::create(&file_path)
+    logger::log_info("updater", &format!("Update file size: {} bytes", total_size.unwrap_or(0)));
+    
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resuming)
+        .truncate(!resuming)
+        .open(part_path)
         .map_err(|e| format!("Failed to create file: {}", e))?;
     
-    let mut downloaded: u64 = 0;
+    let mut downloaded: u64 = if resuming { existing } else { 0 };
+    let mut next_log: u64 = downloaded + 10_485_760;
+    let mut last_progress: Option<std::time::Instant> = None;
     let mut stream = response.bytes_stream();
     
     use futures_util::StreamExt;
@@ -417,21 +770,103 @@ pub async fn download_update(asset_url: &str, filename: &str) -> Result<PathBuf,
             .map_err(|e| format!("Failed to write chunk: {}", e))?;
         downloaded += chunk.len() as u64;
         
+        // Stop between whole chunks so the partial file stays valid to resume from
+        if update_download_cancelled() {
+            file.flush().map_err(|e| format!("Failed to write chunk: {}", e))?;
+            logger::log_info("updater", &format!("Update download cancelled at {} bytes", downloaded));
+            return Err("Update download cancelled".to_string());
+        }
+        
+        if last_progress.map(|t| t.elapsed() >= UPDATE_PROGRESS_INTERVAL).unwrap_or(true) {
+            last_progress = Some(std::time::Instant::now());
+            let percent = match total_size {
+                Some(total) if total > 0 => (downloaded as f64 / total as f64) * 100.0,
+                _ => 0.0,
+            };
+            let _ = app.emit("update-download-progress", serde_json::json!({
+                "downloaded": downloaded,
+                "total": total_size,
+                "percent": percent,
+            }));
+        }
+        
         // Log progress every 10MB
-        if downloaded % 10_485_760 == 0 {
-            let progress = if total_size > 0 {
-                (downloaded as f64 / total_size as f64) * 100.0
-            } else {
-                0.0
+        if downloaded >= next_log {
+            next_log += 10_485_760;
+            let progress = match total_size {
+                Some(total) if total > 0 => (downloaded as f64 / total as f64) * 100.0,
+                _ => 0.0,
             };
             logger::log_info("updater", &format!("Download progress: {:.1}% ({} / {} bytes)", 
-                progress, downloaded, total_size));
+                progress, downloaded, total_size.unwrap_or(0)));
         }
     }
     
-    logger::log_info("updater", &format!("Update downloaded successfully to: {}", file_path.display()));
+    file.flush().map_err(|e| format!("Failed to write chunk: {}", e))?;
+    
+    Ok(total_size)
+}
+
+lazy_static::lazy_static! {
+    // Rendered release notes HTML, keyed by release tag
+    static ref RELEASE_NOTES_CACHE: std::sync::Mutex<std::collections::HashMap<String, String>> =
+        std::sync::Mutex::new(std::collections::HashMap::new());
+}
+
+/// Fetch a single release by tag from GitHub Releases API
+async fn fetch_release(tag: &str) -> Result<ReleaseInfo, String> {
+    let client = update_client(10)?;
+    let url = format!("https://api.github.com/repos/{}/releases/tags/{}", GITHUB_REPO, tag);
+    
+    let response = client.get(&url).send().await
+        .map_err(|e| format!("Failed to fetch release info: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub API returned error: {}", response.status()));
+    }
+    response.json::<ReleaseInfo>().await
+        .map_err(|e| format!("Failed to parse release info: {}", e))
+}
+
+/// Release notes for `tag` as sanitized HTML, fetched once and then served from cache
+pub async fn get_release_notes_html(tag: &str) -> Result<String, String> {
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')) {
+        return Err(format!("Invalid release tag: {}", tag));
+    }
     
-    Ok(file_path)
+    if let Some(html) = RELEASE_NOTES_CACHE.lock().ok().and_then(|cache| cache.get(tag).cloned()) {
+        return Ok(html);
+    }
+    
+    let release = fetch_release(tag).await?;
+    let html = render_release_notes(&release.body);
+    if let Ok(mut cache) = RELEASE_NOTES_CACHE.lock() {
+        cache.insert(tag.to_string(), html.clone());
+    }
+    Ok(html)
+}
+
+/// Render release markdown to HTML and strip anything unsafe (scripts, event handlers, ...)
+/// Falls back to escaped plain text if rendering panics or produces nothing.
+fn render_release_notes(markdown: &str) -> String {
+    let rendered = std::panic::catch_unwind(|| {
+        let parser = pulldown_cmark::Parser::new_ext(markdown, pulldown_cmark::Options::all());
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, parser);
+        ammonia::clean(&html)
+    });
+    
+    match rendered {
+        Ok(html) if !html.trim().is_empty() || markdown.trim().is_empty() => html,
+        _ => format!("<pre>{}</pre>", escape_html(markdown)),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
 
 #[cfg(test)]
@@ -445,6 +880,130 @@ mod tests {
         assert_eq!(compare_versions("3.0.0", "3.0.0"), Ordering::Equal);
         assert_eq!(compare_versions("2.9.9", "3.0.0"), Ordering::Less);
         assert_eq!(compare_versions("3.1.0", "3.0.9"), Ordering::Greater);
+        assert_eq!(compare_versions("3.0.0-rc.1", "3.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("3.0.0", "3.0.0-beta.1"), Ordering::Greater);
+        assert_eq!(compare_versions("3.0.0-beta.1", "3.0.0-beta.2"), Ordering::Less);
+        assert_eq!(compare_versions("3.0.0-alpha", "3.0.0-beta"), Ordering::Less);
+        assert_eq!(compare_versions("3.0.0+build", "3.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("3.0.0+build.5", "3.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("3.1", "3.0.9"), Ordering::Greater);
+    }
+    
+    #[test]
+    fn test_truncated_update_fails_verification() {
+        let path = std::env::temp_dir().join(format!("accelara-update-test-{}.bin", std::process::id()));
+        // "hello world" with its last byte missing
+        std::fs::write(&path, b"hello worl").unwrap();
+        let expected = ExpectedAsset {
+            size: Some(11),
+            sha256: Some("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string()),
+        };
+        assert!(verify_update_file(&path, &expected).is_err());
+        
+        // Right size but wrong content still fails on the hash
+        std::fs::write(&path, b"hello worle").unwrap();
+        assert!(verify_update_file(&path, &expected).is_err());
+        
+        std::fs::write(&path, b"hello world").unwrap();
+        assert_eq!(verify_update_file(&path, &expected), Ok(true));
+        assert_eq!(verify_update_file(&path, &ExpectedAsset { size: Some(11), sha256: None }), Ok(false));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[test]
+    fn test_parse_sha256_sidecar() {
+        let hash = "B94D27B9934D3E08A52E52D7DA7DABFAC484EFE37A5380EE9088F7ACE2EFCDE9";
+        assert_eq!(parse_sha256_sidecar(&format!("{}  ACCELARA.dmg\n", hash)), Some(hash.to_lowercase()));
+        assert_eq!(parse_sha256_sidecar(hash), Some(hash.to_lowercase()));
+        assert_eq!(parse_sha256_sidecar("not-a-hash"), None);
+    }
+    
+    #[test]
+    fn test_release_notes_are_sanitized() {
+        let html = render_release_notes("## Fixes\n\n- **Faster** resume\n\n<script>alert(1)</script>");
+        assert!(html.contains("<h2>Fixes</h2>"));
+        assert!(html.contains("<strong>Faster</strong>"));
+        assert!(!html.contains("<script>"));
+    }
+    
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+    
+    /// Stand-in for the GitHub client: hands out scripted results and records the waits
+    async fn run_scripted(script: Vec<Result<u32, CheckError>>) -> (Result<u32, CheckError>, Vec<u64>, usize) {
+        let responses = std::cell::RefCell::new(std::collections::VecDeque::from(script));
+        let calls = std::cell::Cell::new(0);
+        let sleeps = std::cell::RefCell::new(Vec::new());
+        let result = with_retries(
+            || {
+                calls.set(calls.get() + 1);
+                let next = responses.borrow_mut().pop_front().expect("unexpected extra request");
+                async move { next }
+            },
+            |delay| {
+                sleeps.borrow_mut().push(delay.as_secs());
+                async {}
+            },
+        ).await;
+        (result, sleeps.into_inner(), calls.get())
+    }
+    
+    fn network() -> Result<u32, CheckError> {
+        Err(CheckError::Network("connection reset".to_string()))
+    }
+    
+    #[tokio::test]
+    async fn test_check_retries_network_errors_with_backoff() {
+        let (result, sleeps, calls) = run_scripted(vec![network(), network(), Ok(7)]).await;
+        assert_eq!(result, Ok(7));
+        assert_eq!(sleeps, vec![1, 2]);
+        assert_eq!(calls, 3);
+        
+        let (result, sleeps, calls) = run_scripted(vec![network(), network(), network(), network()]).await;
+        assert!(matches!(result, Err(CheckError::Network(_))));
+        assert_eq!(sleeps, vec![1, 2, 4]);
+        assert_eq!(calls, 4);
+    }
+    
+    #[tokio::test]
+    async fn test_check_does_not_retry_client_errors_or_rate_limits() {
+        let (result, sleeps, calls) = run_scripted(vec![Err(CheckError::Fatal("404 Not Found".to_string()))]).await;
+        assert!(matches!(result, Err(CheckError::Fatal(_))));
+        assert!(sleeps.is_empty());
+        assert_eq!(calls, 1);
+        
+        let (result, sleeps, calls) = run_scripted(vec![network(), Err(CheckError::RateLimited(120))]).await;
+        assert_eq!(result, Err(CheckError::RateLimited(120)));
+        assert_eq!(sleeps, vec![1]);
+        assert_eq!(calls, 2);
+    }
+    
+    #[test]
+    fn test_rate_limit_retry_after() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        use reqwest::StatusCode;
+        
+        let mut primary = HeaderMap::new();
+        primary.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        primary.insert("x-ratelimit-reset", HeaderValue::from_static("1700000300"));
+        assert_eq!(rate_limit_retry_after(StatusCode::FORBIDDEN, &primary, 1_700_000_000), Some(300));
+        // Reset already passed
+        assert_eq!(rate_limit_retry_after(StatusCode::FORBIDDEN, &primary, 1_700_000_500), Some(0));
+        // Only error responses count
+        assert_eq!(rate_limit_retry_after(StatusCode::OK, &primary, 1_700_000_000), None);
+        
+        let mut secondary = HeaderMap::new();
+        secondary.insert("retry-after", HeaderValue::from_static("60"));
+        assert_eq!(rate_limit_retry_after(StatusCode::TOO_MANY_REQUESTS, &secondary, 0), Some(60));
+        
+        // A 403 with quota left is a plain client error
+        let mut forbidden = HeaderMap::new();
+        forbidden.insert("x-ratelimit-remaining", HeaderValue::from_static("42"));
+        forbidden.insert("x-ratelimit-reset", HeaderValue::from_static("1700000300"));
+        assert_eq!(rate_limit_retry_after(StatusCode::FORBIDDEN, &forbidden, 1_700_000_000), None);
     }
 }
 
@@ -1,9 +1,58 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use dirs::home_dir;
+use std::sync::Mutex;
 use which::which;
 
+/// A binary's location, found once and reused until `clear`
+/// Only a found path is kept, so a missing binary is looked for again on the next call.
+pub struct BinaryPathCache {
+    path: Mutex<Option<PathBuf>>,
+}
+
+impl BinaryPathCache {
+    pub const fn new() -> Self {
+        BinaryPathCache { path: Mutex::new(None) }
+    }
+
+    /// The cached path, or the result of `discover` when there is none (or it has since disappeared)
+    pub fn get_or_discover<F>(&self, discover: F) -> Option<PathBuf>
+    where
+        F: FnOnce() -> Option<PathBuf>,
+    {
+        let mut cached = self.path.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(path) = cached.as_ref().filter(|path| path.exists()) {
+            return Some(path.clone());
+        }
+        *cached = discover();
+        cached.clone()
+    }
+
+    pub fn clear(&self) {
+        *self.path.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+}
+
+static GO_BINARY: BinaryPathCache = BinaryPathCache::new();
+static IRIS_BINARY: BinaryPathCache = BinaryPathCache::new();
+
+/// Forget the cached binary locations, e.g. after an update put new ones in place
+pub fn clear_binary_paths() {
+    GO_BINARY.clear();
+    IRIS_BINARY.clear();
+}
+
+/// Location of the api-wrapper binary; searched for (with logging) on the first call only
 pub fn find_go_binary() -> Option<PathBuf> {
+    GO_BINARY.get_or_discover(discover_go_binary)
+}
+
+/// Location of the iris binary; searched for (with logging) on the first call only
+pub fn find_iris_binary() -> Option<PathBuf> {
+    IRIS_BINARY.get_or_discover(discover_iris_binary)
+}
+
+fn discover_go_binary() -> Option<PathBuf> {
     use crate::logger;
     // Try to find api-wrapper binary
     // Priority: bundled location > project bin (current dir) > project bin (parent dir) > PATH
@@ -102,7 +151,7 @@ pub fn find_go_binary() -> Option<PathBuf> {
     None
 }
 
-pub fn find_iris_binary() -> Option<PathBuf> {
+fn discover_iris_binary() -> Option<PathBuf> {
     use crate::logger;
     // Try to find iris binary
     // Priority: bundled location > project bin (current dir) > project bin (parent dir) > PATH
@@ -249,3 +298,371 @@ pub fn expand_path(path: &str) -> String {
     }
 }
 
+
+/// Get the `.accelara-temp-<filename>` directory the Go binary stores HTTP chunks in
+pub fn chunk_temp_dir(output_path: &Path) -> Option<PathBuf> {
+    let file_name = output_path.file_name()?;
+    let parent = output_path.parent()?;
+    Some(parent.join(format!(".accelara-temp-{}", file_name.to_string_lossy())))
+}
+
+/// List the `.part.` chunk files inside an HTTP download's temp directory
+pub fn list_chunk_files(temp_dir: &Path) -> Vec<PathBuf> {
+    let mut chunks: Vec<PathBuf> = match fs::read_dir(temp_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.to_string_lossy().contains(".part."))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    chunks.sort();
+    chunks
+}
+
+/// Check that a custom CA certificate exists and is readable, returning its expanded path
+pub fn validate_ca_cert(path: &str) -> Result<PathBuf, String> {
+    let expanded = PathBuf::from(expand_path(path));
+    if !expanded.is_file() {
+        return Err(format!("CA certificate not found: {}", expanded.display()));
+    }
+    fs::File::open(&expanded)
+        .map_err(|e| format!("CA certificate is not readable ({}): {}", expanded.display(), e))?;
+    Ok(expanded)
+}
+
+/// Apply a custom CA certificate and/or disabled certificate verification to a reqwest client
+/// `context` is only used for log messages
+pub fn apply_tls_options(
+    mut builder: reqwest::ClientBuilder,
+    ca_cert_path: Option<&str>,
+    insecure_skip_verify: bool,
+    context: &str,
+) -> Result<reqwest::ClientBuilder, String> {
+    use crate::logger;
+    
+    if let Some(path) = ca_cert_path.filter(|p| !p.is_empty()) {
+        let cert_path = validate_ca_cert(path)?;
+        let pem = fs::read(&cert_path)
+            .map_err(|e| format!("Failed to read CA certificate {}: {}", cert_path.display(), e))?;
+        let cert = reqwest::Certificate::from_pem(&pem)
+            .map_err(|e| format!("Invalid CA certificate {}: {}", cert_path.display(), e))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    
+    if insecure_skip_verify {
+        logger::log_warning(context, "TLS certificate verification is DISABLED for this connection");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    
+    Ok(builder)
+}
+
+/// Name of a torrent's top-level file or folder, from metadata stored at start or learned later
+pub fn torrent_name(metadata: &serde_json::Value) -> Option<String> {
+    // A top-level "name" wins: it's updated when the data is renamed after completion
+    metadata.get("name")
+        .or_else(|| metadata.get("torrentMeta").and_then(|meta| meta.get("name")))
+        .or_else(|| metadata.get("torrentInfo").and_then(|info| info.get("name")))
+        .or_else(|| metadata.get("options").and_then(|opts| opts.get("torrentInfo")).and_then(|info| info.get("name")))
+        .and_then(|v| v.as_str())
+        // Never let a name escape the download folder
+        .filter(|n| !n.is_empty() && !n.contains("..") && !n.contains('/') && !n.contains('\\'))
+        .map(|n| n.to_string())
+}
+
+/// First free path for `name` inside `dir`: "name.ext", then "name (2).ext", ...
+pub fn unique_path_in(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    
+    let path = Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let extension = path.extension().and_then(|e| e.to_str());
+    for n in 2.. {
+        let candidate = match extension {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        };
+        if !candidate.exists() {
+            return candidate;
+        }
+    }
+    unreachable!()
+}
+
+/// Move a file or folder, falling back to copy + delete when a rename isn't possible
+/// (e.g. across devices). The copy goes to a hidden sibling of `dest` first and is renamed
+/// into place, so `dest` never holds a half-copied file.
+pub fn move_path(src: &Path, dest: &Path) -> Result<(), String> {
+    if dest.exists() {
+        return Err(format!("Destination already exists: {}", dest.display()));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    
+    let file_name = dest.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "download".to_string());
+    let staging = dest.with_file_name(format!(".accelara-moving-{}", file_name));
+    
+    let copied = if src.is_dir() {
+        copy_dir_recursive(src, &staging)
+    } else {
+        fs::copy(src, &staging).map(|_| ())
+    };
+    if let Err(e) = copied {
+        let _ = if staging.is_dir() { fs::remove_dir_all(&staging) } else { fs::remove_file(&staging) };
+        return Err(format!("Failed to copy {} to {}: {}", src.display(), dest.display(), e));
+    }
+    
+    fs::rename(&staging, dest)
+        .map_err(|e| format!("Failed to move {} into place: {}", dest.display(), e))?;
+    
+    let removed = if src.is_dir() { fs::remove_dir_all(src) } else { fs::remove_file(src) };
+    removed.map_err(|e| format!("Moved to {} but failed to remove {}: {}", dest.display(), src.display(), e))
+}
+
+fn copy_dir_recursive(src: &Path, dest: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Free bytes on the filesystem holding `path`
+/// A path that doesn't exist yet is measured at its nearest existing parent.
+pub fn available_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    fs2::available_space(existing).ok()
+}
+
+/// Default gap between starting downloads one after another (setting `startStaggerMs`)
+pub const DEFAULT_START_STAGGER_MS: u64 = 200;
+
+/// A random duration in `0..=max`
+/// Only used to spread out timers, so std's randomly-keyed hasher is random enough.
+pub fn jitter(max: std::time::Duration) -> std::time::Duration {
+    use std::hash::{BuildHasher, Hasher};
+    
+    let max_ms = max.as_millis() as u64;
+    if max_ms == 0 {
+        return std::time::Duration::ZERO;
+    }
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0));
+    std::time::Duration::from_millis(hasher.finish() % (max_ms + 1))
+}
+
+/// Delay between consecutive download starts: the configured base plus up to 50% jitter,
+/// so a batch against one host doesn't start (and later retry) in lockstep
+pub fn start_stagger() -> std::time::Duration {
+    let base_ms = crate::database::get_setting("startStaggerMs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_START_STAGGER_MS);
+    let base = std::time::Duration::from_millis(base_ms);
+    base + jitter(base / 2)
+}
+
+/// Exponential backoff (`base * 2^attempt`) plus up to 25% jitter
+pub fn backoff_with_jitter(base: std::time::Duration, attempt: u32) -> std::time::Duration {
+    let delay = base * 2u32.saturating_pow(attempt.min(16));
+    delay + jitter(delay / 4)
+}
+
+/// File types that may be opened automatically when a download completes
+/// Media, documents and archives only - never executables or scripts.
+const AUTO_OPEN_EXTENSIONS: &[&str] = &[
+    // Media
+    "mp4", "mkv", "webm", "avi", "mov", "m4v", "mp3", "flac", "wav", "ogg", "m4a", "aac", "opus",
+    "jpg", "jpeg", "png", "gif", "webp", "bmp", "svg",
+    // Documents
+    "pdf", "txt", "md", "epub", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "csv", "ppt", "pptx", "odp",
+    // Archives
+    "zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar",
+];
+
+/// Whether a completed file is on the auto-open allowlist
+pub fn is_safe_to_auto_open(path: &Path) -> bool {
+    path.is_file()
+        && path.extension()
+            .map(|e| AUTO_OPEN_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+            .unwrap_or(false)
+}
+
+/// Open a file or folder with the system's default application
+pub fn open_path(path: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(path)
+            .output()
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        // explorer only understands backslashes; the path goes as one argument, so no shell quoting is needed
+        std::process::Command::new("explorer")
+            .arg(path.replace('/', "\\"))
+            .output()
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(path)
+            .output()
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    }
+    
+    Ok(())
+}
+
+/// Check that files can be created in `dir` by creating and removing a probe file
+pub fn check_dir_writable(dir: &Path) -> Result<(), String> {
+    if !dir.exists() {
+        return Err(format!("Download folder does not exist: {}", dir.display()));
+    }
+    if !dir.is_dir() {
+        return Err(format!("Download folder is not a directory: {}", dir.display()));
+    }
+    
+    let probe = dir.join(format!(".accelara-write-test-{}", std::process::id()));
+    fs::File::create(&probe)
+        .map_err(|e| format!("Download folder is not writable: {} ({})", dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Folder a download writes into: the output itself for torrents, its parent for HTTP files
+pub fn download_folder(output: &str, download_type: &str) -> PathBuf {
+    let expanded = PathBuf::from(expand_path(output));
+    if download_type == "torrent" || download_type == "magnet" {
+        expanded
+    } else {
+        expanded.parent().map(|p| p.to_path_buf()).unwrap_or(expanded)
+    }
+}
+
+/// Info hashes and trackers carried by a magnet link
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MagnetInfo {
+    /// v1 (SHA-1) info hash as lowercase hex
+    pub info_hash_v1: Option<String>,
+    /// v2 (SHA-256) info hash as lowercase hex
+    pub info_hash_v2: Option<String>,
+    pub name: Option<String>,
+    pub trackers: Vec<String>,
+}
+
+/// Parse the `xt`, `dn` and `tr` parameters of a magnet link
+/// Handles hex and base32 `btih` hashes and `btmh` multihashes (hybrid links carry both).
+pub fn parse_magnet(link: &str) -> Option<MagnetInfo> {
+    let url = reqwest::Url::parse(link).ok()?;
+    if url.scheme() != "magnet" {
+        return None;
+    }
+    
+    let mut info = MagnetInfo::default();
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "xt" => {
+                if let Some(hash) = value.strip_prefix("urn:btih:") {
+                    info.info_hash_v1 = match hash.len() {
+                        40 => Some(hash.to_ascii_lowercase()),
+                        32 => base32_to_hex(hash),
+                        _ => None,
+                    };
+                } else if let Some(multihash) = value.strip_prefix("urn:btmh:") {
+                    // 0x12 = sha2-256, 0x20 = 32 byte digest
+                    info.info_hash_v2 = multihash
+                        .strip_prefix("1220")
+                        .filter(|h| h.len() == 64)
+                        .map(|h| h.to_ascii_lowercase());
+                }
+            }
+            "dn" => info.name = Some(value.to_string()),
+            "tr" => info.trackers.push(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(info)
+}
+
+/// Decode an RFC 4648 base32 string (as used by older magnet links) to lowercase hex
+fn base32_to_hex(input: &str) -> Option<String> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    
+    let mut bits: u64 = 0;
+    let mut bit_count = 0;
+    let mut hex = String::new();
+    for c in input.to_ascii_uppercase().bytes() {
+        let value = ALPHABET.iter().position(|&a| a == c)? as u64;
+        bits = (bits << 5) | value;
+        bit_count += 5;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            hex.push_str(&format!("{:02x}", (bits >> bit_count) & 0xff));
+        }
+    }
+    Some(hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_binary_path_discovered_once() {
+        let binary = std::env::temp_dir().join(format!("accelara-binary-cache-{}", std::process::id()));
+        std::fs::write(&binary, b"").unwrap();
+        let probes = AtomicUsize::new(0);
+        let discover = || {
+            probes.fetch_add(1, Ordering::SeqCst);
+            Some(binary.clone())
+        };
+
+        let cache = BinaryPathCache::new();
+        for _ in 0..5 {
+            assert_eq!(cache.get_or_discover(discover), Some(binary.clone()));
+        }
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+
+        // After a refresh (or once the cached file is gone) it's searched for again
+        cache.clear();
+        cache.get_or_discover(discover);
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+        std::fs::remove_file(&binary).unwrap();
+        assert_eq!(cache.get_or_discover(discover), Some(binary.clone()));
+        assert_eq!(probes.load(Ordering::SeqCst), 3);
+
+        // Not found isn't cached
+        let missing = BinaryPathCache::new();
+        let misses = AtomicUsize::new(0);
+        for _ in 0..2 {
+            assert_eq!(missing.get_or_discover(|| { misses.fetch_add(1, Ordering::SeqCst); None }), None);
+        }
+        assert_eq!(misses.load(Ordering::SeqCst), 2);
+    }
+}
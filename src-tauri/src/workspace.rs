@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the workspace file looked up when a directory is given
+pub const WORKSPACE_FILE_NAME: &str = ".accelara.toml";
+
+/// Keys understood at the top level of a workspace file
+const KNOWN_KEYS: &[&str] = &["output", "concurrency", "chunk_size", "chunkSize", "rate_limit", "rateLimit", "sources"];
+
+/// Keys understood on a `[[sources]]` table entry
+const KNOWN_SOURCE_KEYS: &[&str] = &["url", "output"];
+
+/// Shared download defaults loaded from an `.accelara.toml`
+///
+/// ```toml
+/// output = "~/Downloads/datasets"
+/// concurrency = 8
+/// chunk_size = "8MB"
+///
+/// [[sources]]
+/// url = "https://example.com/a.zip"
+///
+/// [[sources]]
+/// url = "magnet:?xt=urn:btih:..."
+/// output = "~/Downloads/isos"
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceConfig {
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub concurrency: Option<u64>,
+    #[serde(default, alias = "chunk_size")]
+    pub chunk_size: Option<String>,
+    #[serde(default, alias = "rate_limit")]
+    pub rate_limit: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<WorkspaceSource>,
+}
+
+/// A source entry, either a bare URL string or a table with a per-source output
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum WorkspaceSource {
+    Url(String),
+    Detailed {
+        url: String,
+        #[serde(default)]
+        output: Option<String>,
+    },
+}
+
+impl WorkspaceSource {
+    pub fn url(&self) -> &str {
+        match self {
+            WorkspaceSource::Url(url) => url,
+            WorkspaceSource::Detailed { url, .. } => url,
+        }
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        match self {
+            WorkspaceSource::Url(_) => None,
+            WorkspaceSource::Detailed { output, .. } => output.as_deref(),
+        }
+    }
+}
+
+impl WorkspaceConfig {
+    /// Download options in the same shape the frontend passes to `start_download`
+    pub fn download_options(&self) -> serde_json::Value {
+        let mut options = serde_json::json!({});
+        if let Some(concurrency) = self.concurrency {
+            options["concurrency"] = serde_json::json!(concurrency);
+        }
+        if let Some(chunk_size) = &self.chunk_size {
+            options["chunkSize"] = serde_json::json!(chunk_size);
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            options["rateLimit"] = serde_json::json!(rate_limit);
+        }
+        options
+    }
+}
+
+/// Resolve a user-supplied path to the workspace file (a directory means `<dir>/.accelara.toml`)
+pub fn resolve_workspace_path(path: &str) -> PathBuf {
+    let expanded = PathBuf::from(crate::utils::expand_path(path));
+    if expanded.is_dir() {
+        expanded.join(WORKSPACE_FILE_NAME)
+    } else {
+        expanded
+    }
+}
+
+/// Parse workspace file contents, returning the config and warnings for unknown keys
+pub fn parse_workspace(content: &str) -> Result<(WorkspaceConfig, Vec<String>), String> {
+    let raw: toml::Value = toml::from_str(content)
+        .map_err(|e| format!("Invalid workspace file: {}", e))?;
+
+    let mut warnings = Vec::new();
+    if let Some(table) = raw.as_table() {
+        for key in table.keys() {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                warnings.push(format!("Unknown key '{}' ignored", key));
+            }
+        }
+        if let Some(sources) = table.get("sources").and_then(|v| v.as_array()) {
+            for (index, source) in sources.iter().enumerate() {
+                if let Some(source_table) = source.as_table() {
+                    for key in source_table.keys() {
+                        if !KNOWN_SOURCE_KEYS.contains(&key.as_str()) {
+                            warnings.push(format!("Unknown key '{}' in sources[{}] ignored", key, index));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let config: WorkspaceConfig = raw.try_into()
+        .map_err(|e| format!("Invalid workspace file: {}", e))?;
+
+    Ok((config, warnings))
+}
+
+/// Load and parse a workspace file from disk
+pub fn load_workspace(path: &Path) -> Result<(WorkspaceConfig, Vec<String>), String> {
+    use crate::logger;
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read workspace file {}: {}", path.display(), e))?;
+
+    let (config, warnings) = parse_workspace(&content)?;
+    for warning in &warnings {
+        logger::log_warning("workspace", &format!("{}: {}", path.display(), warning));
+    }
+
+    Ok((config, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_workspace() {
+        let content = r#"
+            output = "~/Downloads/team"
+            concurrency = 4
+            chunk_size = "8MB"
+            colour = "blue"
+            sources = ["https://example.com/a.zip", { url = "magnet:?xt=urn:btih:abc", output = "/tmp/isos", tag = "x" }]
+        "#;
+
+        let (config, warnings) = parse_workspace(content).unwrap();
+        assert_eq!(config.output.as_deref(), Some("~/Downloads/team"));
+        assert_eq!(config.concurrency, Some(4));
+        assert_eq!(config.chunk_size.as_deref(), Some("8MB"));
+        assert_eq!(config.sources.len(), 2);
+        assert_eq!(config.sources[0].url(), "https://example.com/a.zip");
+        assert_eq!(config.sources[1].output(), Some("/tmp/isos"));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_workspace_invalid() {
+        assert!(parse_workspace("concurrency = \"lots\"").is_err());
+        assert!(parse_workspace("not toml at all [").is_err());
+    }
+}
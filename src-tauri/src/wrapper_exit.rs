@@ -0,0 +1,144 @@
+/// Why the Go wrapper exited, from its exit code
+/// Contract with the wrapper: 0 = success, 2 = network, 3 = disk full,
+/// 4 = authentication required, 5 = checksum mismatch, 64 = unknown flag or bad usage;
+/// anything else is a generic failure.
+/// Wrappers from before this contract exit 2 on an unknown flag, which reads as a network error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapperExit {
+    Success,
+    Network,
+    DiskFull,
+    AuthRequired,
+    ChecksumMismatch,
+    /// The wrapper rejected its arguments, e.g. a flag it's too old to know
+    Usage,
+    /// Killed by a signal, so there is no exit code
+    Terminated,
+    Other(i32),
+}
+
+impl WrapperExit {
+    /// Classify a process exit code (`None` when the process was killed by a signal)
+    pub fn from_code(code: Option<i32>) -> Self {
+        match code {
+            Some(0) => WrapperExit::Success,
+            Some(2) => WrapperExit::Network,
+            Some(3) => WrapperExit::DiskFull,
+            Some(4) => WrapperExit::AuthRequired,
+            Some(5) => WrapperExit::ChecksumMismatch,
+            Some(64) => WrapperExit::Usage,
+            Some(code) => WrapperExit::Other(code),
+            None => WrapperExit::Terminated,
+        }
+    }
+
+    /// Short machine-readable kind, stored in metadata and sent to the UI
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WrapperExit::Success => "success",
+            WrapperExit::Network => "network",
+            WrapperExit::DiskFull => "diskFull",
+            WrapperExit::AuthRequired => "authRequired",
+            WrapperExit::ChecksumMismatch => "checksumMismatch",
+            WrapperExit::Usage => "usage",
+            WrapperExit::Terminated => "terminated",
+            WrapperExit::Other(_) => "unknown",
+        }
+    }
+
+    /// Stable error code for the UI, e.g. "DISK_FULL"
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            WrapperExit::Success => "OK",
+            WrapperExit::Network => "NETWORK",
+            WrapperExit::DiskFull => "DISK_FULL",
+            WrapperExit::AuthRequired => "AUTH_REQUIRED",
+            WrapperExit::ChecksumMismatch => "CHECKSUM_MISMATCH",
+            WrapperExit::Usage => "HELPER_OUTDATED",
+            WrapperExit::Terminated => "TERMINATED",
+            WrapperExit::Other(_) => "UNKNOWN",
+        }
+    }
+
+    /// Actionable message shown to the user
+    pub fn message(&self) -> String {
+        match self {
+            WrapperExit::Success => String::new(),
+            WrapperExit::Network => "Network error - check your connection and try again".to_string(),
+            WrapperExit::DiskFull => "Disk full - free up space and resume".to_string(),
+            WrapperExit::AuthRequired => "Authentication required - the server rejected the request".to_string(),
+            WrapperExit::ChecksumMismatch => "Checksum mismatch - the downloaded file is corrupt".to_string(),
+            WrapperExit::Usage => "The download helper didn't accept its options - reinstall or update the app".to_string(),
+            WrapperExit::Terminated => "Download process was terminated".to_string(),
+            WrapperExit::Other(code) => format!("Download failed (exit code {})", code),
+        }
+    }
+
+    /// Whether retrying the same download can reasonably succeed without user action
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, WrapperExit::Network | WrapperExit::Terminated | WrapperExit::Other(_))
+    }
+}
+
+/// Whether a line of wrapper stderr reports that the disk is full
+/// Older wrappers exit with a generic code on ENOSPC, so the message is the only hint.
+/// Covers Unix ENOSPC ("no space left on device") and Windows ERROR_DISK_FULL (0x70).
+pub fn is_disk_full_error(line: &str) -> bool {
+    let line = line.to_lowercase();
+    line.contains("no space left")
+        || line.contains("enospc")
+        || line.contains("not enough space on the disk")
+        || line
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|token| token == "0x70" || token == "0x00000070")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_codes() {
+        assert_eq!(WrapperExit::from_code(Some(0)), WrapperExit::Success);
+        assert_eq!(WrapperExit::from_code(Some(2)), WrapperExit::Network);
+        assert_eq!(WrapperExit::from_code(Some(3)), WrapperExit::DiskFull);
+        assert_eq!(WrapperExit::from_code(Some(4)), WrapperExit::AuthRequired);
+        assert_eq!(WrapperExit::from_code(Some(5)), WrapperExit::ChecksumMismatch);
+        assert_eq!(WrapperExit::from_code(Some(64)), WrapperExit::Usage);
+    }
+
+    #[test]
+    fn test_unknown_and_signal_exits() {
+        assert_eq!(WrapperExit::from_code(Some(1)), WrapperExit::Other(1));
+        assert_eq!(WrapperExit::from_code(Some(42)).message(), "Download failed (exit code 42)");
+        assert_eq!(WrapperExit::from_code(None), WrapperExit::Terminated);
+    }
+
+    #[test]
+    fn test_messages() {
+        assert!(WrapperExit::DiskFull.message().starts_with("Disk full"));
+        assert!(WrapperExit::AuthRequired.message().starts_with("Authentication required"));
+        assert!(WrapperExit::ChecksumMismatch.message().starts_with("Checksum mismatch"));
+        assert_eq!(WrapperExit::DiskFull.kind(), "diskFull");
+    }
+
+    #[test]
+    fn test_retry_decisions() {
+        assert!(WrapperExit::Network.is_retryable());
+        assert!(!WrapperExit::AuthRequired.is_retryable());
+        assert!(!WrapperExit::DiskFull.is_retryable());
+        assert!(!WrapperExit::ChecksumMismatch.is_retryable());
+        assert!(!WrapperExit::Usage.is_retryable());
+    }
+
+    #[test]
+    fn test_disk_full_signatures() {
+        assert!(is_disk_full_error("write /data/file.part.3: no space left on device"));
+        assert!(is_disk_full_error("Error: ENOSPC while writing chunk"));
+        assert!(is_disk_full_error("write C:\\Downloads\\a.iso: There is not enough space on the disk."));
+        assert!(is_disk_full_error("WriteFile failed with error 0x70"));
+        assert!(!is_disk_full_error("connection reset by peer"));
+        assert!(!is_disk_full_error("offset 0x7000 out of range"));
+        assert_eq!(WrapperExit::DiskFull.error_code(), "DISK_FULL");
+    }
+}
@@ -11,6 +11,8 @@ function AppContent({ startDownload }) {
   const { showToast } = useToast();
   const [showAddModal, setShowAddModal] = useState(false);
   const [modalInitialSource, setModalInitialSource] = useState('');
+  // Cookies/headers the browser extension sent with the download (never logged)
+  const [modalRequestHeaders, setModalRequestHeaders] = useState(null);
 
   useEffect(() => {
     // Remove all theme classes
@@ -34,6 +36,15 @@ function AppContent({ startDownload }) {
 
   // Removed debug logging to prevent re-renders
 
+  useEffect(() => {
+    // Problems the backend recovered from at startup, e.g. a corrupt database that was reset
+    if (window.electronAPI?.onStartupWarning) {
+      return window.electronAPI.onStartupWarning((warning) => {
+        showToast(warning.message, 'error', 15000);
+      });
+    }
+  }, [showToast]);
+
   useEffect(() => {
     // Listen for external downloads (magnet links, torrent files)
     // Open the download modal with the source pre-filled and auto-start
@@ -41,6 +52,7 @@ function AppContent({ startDownload }) {
       const handleExternalDownload = async (data) => {
         // Set the initial source and open the modal with auto-start enabled
         setModalInitialSource(data.source);
+        setModalRequestHeaders(data.headers || null);
         setShowAddModal(true);
         // Ensure window is visible and focused
         if (window.electronAPI.focusWindow) {
@@ -69,8 +81,10 @@ function AppContent({ startDownload }) {
           onClose={() => {
             setShowAddModal(false);
             setModalInitialSource(''); // Clear initial source when closing
+            setModalRequestHeaders(null);
           }} 
           initialSource={modalInitialSource}
+          requestHeaders={modalRequestHeaders}
           autoStart={!!modalInitialSource} // Auto-start if source came from extension
         />
       )}
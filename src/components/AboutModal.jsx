@@ -102,14 +102,14 @@ export default function AboutModal({ onClose }) {
       }, 500);
       
       // Download update
-      const path = await window.electronAPI.downloadUpdate(asset.browser_download_url, asset.name);
+      const { path, verified } = await window.electronAPI.downloadUpdate(asset.browser_download_url, asset.name);
       
       clearInterval(progressInterval);
       setDownloadProgress(90);
       
       // Ask user if they want to install now
       const shouldInstall = window.confirm(
-        `Update downloaded successfully!\n\nLocation: ${path}\n\n` +
+        `Update downloaded successfully${verified ? ' (checksum verified)' : ''}!\n\nLocation: ${path}\n\n` +
         `Would you like to install it now? The app will restart automatically.`
       );
       
@@ -1,10 +1,10 @@
 import { useState, useEffect } from 'react';
 import { useDownloads } from '../context/DownloadContext';
 import { useSettings } from '../context/SettingsContext';
-import { X, File, Folder, Loader2, AlertCircle } from 'lucide-react';
+import { X, File, Folder, Loader2, AlertCircle, Lock } from 'lucide-react';
 import { formatBytes } from '../utils/format';
 
-export default function AddDownloadModal({ onClose, initialSource = '', autoStart = false }) {
+export default function AddDownloadModal({ onClose, initialSource = '', requestHeaders = null, autoStart = false }) {
   const { startDownload, resumeDownload } = useDownloads();
   const { settings } = useSettings();
   const [source, setSource] = useState(initialSource);
@@ -86,7 +86,7 @@ export default function AddDownloadModal({ onClose, initialSource = '', autoStar
           }
         } else if (source.startsWith('http://') || source.startsWith('https://')) {
           // Get HTTP file info
-          const info = await globalThis.electronAPI.getHTTPInfo(source);
+          const info = await globalThis.electronAPI.getHTTPInfo(source, requestHeaders && source === initialSource ? { headers: requestHeaders } : null);
           setHttpInfo(info);
           // Auto-set output filename if not set
           if (!output && info.fileName) {
@@ -107,6 +107,10 @@ export default function AddDownloadModal({ onClose, initialSource = '', autoStar
     return () => clearTimeout(timeoutId);
   }, [source, settings.defaultDownloadPath]);
 
+  // Only the header names are looked at; the values (cookies, tokens) are never shown or logged
+  const attachedHeaderNames = requestHeaders && source === initialSource ? Object.keys(requestHeaders) : [];
+  const cookiesAttached = attachedHeaderNames.some((name) => name.toLowerCase() === 'cookie');
+
   // Auto-start download if:
   // 1. autoStart is true (from extension), OR
   // 2. source is a valid HTTP/HTTPS URL (pasted link)
@@ -159,7 +163,16 @@ export default function AddDownloadModal({ onClose, initialSource = '', autoStar
         downloadOptions.httpInfo = httpInfo;
       }
       
+      // Forward the browser's cookies/headers so downloads behind a login work
+      if (requestHeaders && source === initialSource) {
+        downloadOptions.headers = requestHeaders;
+      }
+      
       const id = await startDownload(source, output || undefined, downloadOptions);
+      // Declined the large download prompt - leave the dialog open
+      if (!id) {
+        return;
+      }
       
       setDownloadId(id);
       
@@ -292,6 +305,15 @@ export default function AddDownloadModal({ onClose, initialSource = '', autoStar
             </div>
           )}
 
+          {attachedHeaderNames.length > 0 && (
+            <div className="flex items-center gap-2 text-sm theme-text-secondary">
+              <Lock className="w-4 h-4 flex-shrink-0" />
+              <span>
+                {cookiesAttached ? 'Browser cookies attached' : 'Browser request headers attached'}
+              </span>
+            </div>
+          )}
+
           {inspectError && (
             <div className="flex items-start gap-2 p-3 theme-bg-tertiary rounded-lg border theme-border">
               <AlertCircle className="w-4 h-4 theme-text-tertiary flex-shrink-0 mt-0.5" />
@@ -1,6 +1,8 @@
 import { useState, useEffect, useRef } from 'react';
 import { X, RefreshCw, FileText, Copy, Check, GripVertical } from 'lucide-react';
 
+const MAX_LOG_LINES = 1000;
+
 export default function DebugLogViewer({ onClose }) {
   const [logs, setLogs] = useState([]);
   const [logPath, setLogPath] = useState('');
@@ -14,9 +16,17 @@ export default function DebugLogViewer({ onClose }) {
 
   useEffect(() => {
     loadLogs();
-    // Auto-refresh every 5 seconds
-    const interval = setInterval(loadLogs, 5000);
-    return () => clearInterval(interval);
+    if (!window.electronAPI) return;
+
+    // New lines arrive as log-line events instead of polling
+    const unlisten = window.electronAPI.onLogLine(({ line }) => {
+      setLogs((prev) => [...prev, line].slice(-MAX_LOG_LINES));
+    });
+    window.electronAPI.startLogStreaming().catch(() => {});
+    return () => {
+      unlisten();
+      window.electronAPI.stopLogStreaming().catch(() => {});
+    };
   }, []);
 
   // Center the modal on mount
@@ -221,7 +231,7 @@ export default function DebugLogViewer({ onClose }) {
 
         {/* Footer */}
         <div className="p-3 border-t theme-border text-xs theme-text-tertiary">
-          <p>Logs auto-refresh every 5 seconds. Log file: <code className="theme-text-secondary">~/.accelara/accelara.log</code></p>
+          <p>Logs auto-refresh every 5 seconds. Log file: <code className="theme-text-secondary">{logPath || 'accelara.log in the app data directory'}</code></p>
         </div>
       </div>
     </div>
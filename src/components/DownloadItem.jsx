@@ -73,6 +73,9 @@ export default function DownloadItem({ download }) {
                 if (download.type === 'http' && download.fileName) {
                   return download.fileName;
                 }
+                if (download.displayName) {
+                  return download.displayName;
+                }
                 // Fallback to source URL (truncated)
                 if (download.source && download.source.length > 50) {
                   return download.source.substring(0, 50) + '...';
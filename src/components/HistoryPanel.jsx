@@ -99,7 +99,7 @@ export default function HistoryPanel() {
                 <div className="flex items-start gap-2 mb-2">
                   {getIcon(item.type)}
                   <div className="flex-1 min-w-0">
-                    <p className="text-sm theme-text-primary truncate">{item.source}</p>
+                    <p className="text-sm theme-text-primary truncate" title={item.source}>{item.displayName || item.source}</p>
                     <p className="text-xs theme-text-tertiary truncate">{item.output}</p>
                   </div>
                   {item.output && (
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { useSettings } from '../context/SettingsContext';
-import { Sun, Monitor, Sparkles, Folder, Check, Trash2, Loader2, Power, Bug, RefreshCw, Bell } from 'lucide-react';
+import { Sun, Monitor, Sparkles, Folder, Check, Trash2, Loader2, Power, Bug, RefreshCw, Bell, Globe, Copy } from 'lucide-react';
 
 export default function SettingsPanel() {
   const { settings, updateSettings } = useSettings();
@@ -8,6 +8,8 @@ export default function SettingsPanel() {
   const [junkDataSize, setJunkDataSize] = useState(null);
   const [isLoadingJunk, setIsLoadingJunk] = useState(false);
   const [isClearingJunk, setIsClearingJunk] = useState(false);
+  const [browserToken, setBrowserToken] = useState('');
+  const [tokenCopied, setTokenCopied] = useState(false);
   
   // Local state for text inputs that require Apply button
   const [localSettings, setLocalSettings] = useState({
@@ -38,6 +40,24 @@ export default function SettingsPanel() {
     loadJunkDataSize();
   }, [settings]);
   
+  useEffect(() => {
+    if (window.electronAPI && window.electronAPI.getBrowserToken) {
+      window.electronAPI.getBrowserToken()
+        .then(setBrowserToken)
+        .catch((error) => console.error('Failed to load browser extension token:', error));
+    }
+  }, []);
+  
+  const handleCopyToken = async () => {
+    try {
+      await navigator.clipboard.writeText(browserToken);
+      setTokenCopied(true);
+      setTimeout(() => setTokenCopied(false), 2000);
+    } catch (error) {
+      console.error('Failed to copy token:', error);
+    }
+  };
+  
   const loadJunkDataSize = async () => {
     if (window.electronAPI) {
       setIsLoadingJunk(true);
@@ -451,6 +471,39 @@ export default function SettingsPanel() {
         </div>
       </div>
 
+      {/* Browser Extension */}
+      <div className="pt-4 border-t theme-border">
+        <h4 className="text-sm font-semibold theme-text-primary mb-3 flex items-center gap-2">
+          <Globe className="w-4 h-4" />
+          Browser Extension
+        </h4>
+        
+        <label className="block text-sm font-medium theme-text-secondary mb-2">
+          Extension token
+        </label>
+        <div className="flex gap-2">
+          <input
+            type="text"
+            readOnly
+            value={browserToken}
+            onFocus={(e) => e.target.select()}
+            className="input-field flex-1 font-mono text-xs"
+          />
+          <button
+            onClick={handleCopyToken}
+            disabled={!browserToken}
+            className="px-3 py-2 rounded-lg transition-colors theme-bg-tertiary hover:theme-bg-primary theme-text-secondary hover:theme-text-primary disabled:opacity-50"
+            type="button"
+            title="Copy token"
+          >
+            {tokenCopied ? <Check className="w-4 h-4" /> : <Copy className="w-4 h-4" />}
+          </button>
+        </div>
+        <p className="text-xs theme-text-tertiary mt-1">
+          Paste this into the ACCELARA extension popup so it can send downloads to the app
+        </p>
+      </div>
+
       {/* Debug Logs */}
       <div className="pt-4 border-t theme-border">
         <button
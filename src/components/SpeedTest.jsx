@@ -81,6 +81,12 @@ export default function SpeedTest() {
       const handleComplete = async (data) => {
         setIsRunning(false);
         setTestType(null);
+        
+        // Stopped tests have nothing worth saving
+        if (data.cancelled) {
+          setProgress(0);
+          return;
+        }
         setProgress(100);
         
         // If result is included in the completion event, use it
@@ -1,4 +1,5 @@
 import { createContext, useContext, useState, useEffect, useCallback } from 'react';
+import { formatBytes } from '../utils/format';
 
 const DownloadContext = createContext();
 
@@ -394,12 +395,25 @@ export function DownloadProvider({ children }) {
       return existingDownloadId;
     }
 
-    const result = await window.electronAPI.startDownload({
+    let result = await window.electronAPI.startDownload({
       source,
       output,
       options,
     });
 
+    // Downloads above the large download threshold need the user's go-ahead first
+    if (result.needsConfirmation) {
+      if (!confirm(`This download is ${formatBytes(result.size)}, above your ${formatBytes(result.threshold)} confirmation threshold. Download it anyway?`)) {
+        return null;
+      }
+      result = await window.electronAPI.startDownload({
+        source,
+        output,
+        options,
+        confirmed: true,
+      });
+    }
+
     // Check again after starting to prevent duplicate in state
     setDownloads((prev) => {
       // If download already exists (from updateHandler or getActiveDownloads), update it instead of adding
@@ -582,10 +596,12 @@ export function DownloadProvider({ children }) {
       setDownloads((prev) => prev.filter((d) => d.id !== downloadId));
 
       // Start a new download with the same source and output
+      // (the original was already confirmed or below the large download threshold)
       const result = await window.electronAPI.startDownload({
         source: download.source,
         output: download.output,
         options,
+        confirmed: true,
       });
 
       // Update highlighted download ID if the retried download was highlighted
@@ -89,8 +89,8 @@ export const tauriAPI = {
 
   // Download operations
   async startDownload(config) {
-    const downloadId = await invoke('start_download', { config });
-    return { downloadId };
+    // Returns { downloadId } or { needsConfirmation, size, threshold } for huge downloads
+    return await invoke('start_download', { config });
   },
 
   async stopDownload(downloadId) {
@@ -117,8 +117,8 @@ export const tauriAPI = {
     return await invoke('get_active_downloads');
   },
 
-  async getDownloadHistory() {
-    return await invoke('get_download_history');
+  async getDownloadHistory(limit) {
+    return await invoke('get_download_history', { limit });
   },
 
   async clearDownloadHistory() {
@@ -130,8 +130,21 @@ export const tauriAPI = {
     return await invoke('inspect_torrent', { source });
   },
 
-  async getHTTPInfo(source) {
-    return await invoke('get_http_info', { source });
+  async checkDiskSpace(path, requiredBytes) {
+    return await invoke('check_disk_space', { path, requiredBytes });
+  },
+
+  async getDownloadDiskUsage(downloadId) {
+    return await invoke('get_download_disk_usage', { downloadId });
+  },
+
+  async validateSource(source, probe = false) {
+    return await invoke('validate_source', { source, probe });
+  },
+
+  // options may carry the download's headers and auth ({ headers, auth }) so protected URLs can be probed
+  async getHTTPInfo(source, options = null) {
+    return await invoke('get_http_info', { source, options });
   },
 
   // Settings
@@ -158,11 +171,34 @@ export const tauriAPI = {
     return { success: true };
   },
 
+  async openFile(downloadId) {
+    return await invoke('open_file', { downloadId });
+  },
+
+  // timestamp is epoch seconds; a time in the past starts the download now
+  async scheduleDownload(downloadId, timestamp) {
+    return await invoke('schedule_download', { downloadId, timestamp });
+  },
+
+  // Only for downloads that aren't running; newName is a bare file name, not a path
+  async renameDownloadOutput(downloadId, newName) {
+    return await invoke('rename_download_output', { downloadId, newName });
+  },
+
   // System
   async getSystemTheme() {
     return await invoke('get_system_theme');
   },
 
+  // { apiWrapper: {path, version}, iris: {path, version} }; version is "unknown" if it can't be read
+  async getBinaryVersions() {
+    return await invoke('get_binary_versions');
+  },
+
+  async refreshBinaryPaths() {
+    return await invoke('refresh_binary_paths');
+  },
+
   async showWindow() {
     return await invoke('show_window');
   },
@@ -176,8 +212,20 @@ export const tauriAPI = {
     return await invoke('get_log_path');
   },
 
-  async getRecentLogs(lines) {
-    return await invoke('get_recent_logs', { lines });
+  async getRecentLogs(lines, includeArchive = false) {
+    return await invoke('get_recent_logs', { lines, includeArchive });
+  },
+
+  async startLogStreaming() {
+    return await invoke('start_log_streaming');
+  },
+
+  async stopLogStreaming() {
+    return await invoke('stop_log_streaming');
+  },
+
+  async getDownloadLogs(downloadId) {
+    return await invoke('get_download_logs', { downloadId });
   },
 
   async openDebugLogWindow() {
@@ -250,6 +298,11 @@ export const tauriAPI = {
     return await invoke('clear_junk_data');
   },
 
+  // Browser extension
+  async getBrowserToken() {
+    return await invoke('get_browser_token');
+  },
+
   // Event listeners
   onDownloadUpdate(callback) {
     const key = 'download-update';
@@ -269,6 +322,76 @@ export const tauriAPI = {
     };
   },
 
+  onLogLine(callback) {
+    const key = 'log-line';
+    listen('log-line', (event) => {
+      callback(event.payload);
+    }).then((unlisten) => {
+      this._unlistenFunctions[key] = unlisten;
+    }).catch((err) => {
+      console.error('Failed to set up log-line listener:', err);
+    });
+    return () => {
+      if (this._unlistenFunctions[key]) {
+        this._unlistenFunctions[key]();
+        delete this._unlistenFunctions[key];
+      }
+    };
+  },
+
+  // { kind, message, ... } for problems the app recovered from while starting (e.g. a reset database)
+  onStartupWarning(callback) {
+    const key = 'startup-warning';
+    listen('startup-warning', (event) => {
+      callback(event.payload);
+    }).then((unlisten) => {
+      this._unlistenFunctions[key] = unlisten;
+    }).catch((err) => {
+      console.error('Failed to set up startup-warning listener:', err);
+    });
+    return () => {
+      if (this._unlistenFunctions[key]) {
+        this._unlistenFunctions[key]();
+        delete this._unlistenFunctions[key];
+      }
+    };
+  },
+
+  // { totalDownloadSpeed, totalUploadSpeed, activeCount }, every globalSpeedEventIntervalMs
+  onGlobalSpeed(callback) {
+    const key = 'global-speed';
+    listen('global-speed', (event) => {
+      callback(event.payload);
+    }).then((unlisten) => {
+      this._unlistenFunctions[key] = unlisten;
+    }).catch((err) => {
+      console.error('Failed to set up global-speed listener:', err);
+    });
+    return () => {
+      if (this._unlistenFunctions[key]) {
+        this._unlistenFunctions[key]();
+        delete this._unlistenFunctions[key];
+      }
+    };
+  },
+
+  onDownloadLog(callback) {
+    const key = 'download-log';
+    listen('download-log', (event) => {
+      callback(event.payload);
+    }).then((unlisten) => {
+      this._unlistenFunctions[key] = unlisten;
+    }).catch((err) => {
+      console.error('Failed to set up download-log listener:', err);
+    });
+    return () => {
+      if (this._unlistenFunctions[key]) {
+        this._unlistenFunctions[key]();
+        delete this._unlistenFunctions[key];
+      }
+    };
+  },
+
   onDownloadComplete(callback) {
     const key = 'download-complete';
     listen('download-complete', (event) => {